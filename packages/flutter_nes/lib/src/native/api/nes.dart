@@ -9,6 +9,9 @@ import 'package:flutter_rust_bridge/flutter_rust_bridge_for_generated.dart';
 import 'texture.dart';
 
 // Rust type: RustOpaqueMoi<flutter_rust_bridge::for_generated::rust_async::RwLock<NesEmulator>>
+/// The emulation loop takes ownership of the [`ControlDeck`] while running and hands it back once
+/// stopped. Input and config changes reach the running deck through the input queue and command
+/// channel.
 @sealed
 class NesEmulator extends RustOpaque {
   NesEmulator.dcoDecode(List<dynamic> wire)
@@ -26,10 +29,55 @@ class NesEmulator extends RustOpaque {
         RustLib.instance.api.rust_arc_decrement_strong_count_NesEmulatorPtr,
   );
 
+  void aimZapper({required int x, required int y, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorAimZapper(
+        that: this,
+        x: x,
+        y: y,
+      );
+
+  /// Report the number of samples played back from the audio stream.
+  void audioConsumed({required int samples, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorAudioConsumed(
+        that: this,
+        samples: samples,
+      );
+
+  /// Stream resampled audio as one chunk of f32 samples per frame. While attached, the run loop
+  /// is paced by audio consumption reported through `audio_consumed`.
+  Stream<Float32List> audioStream({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorAudioStream(
+        that: this,
+      );
+
+  /// Whether the loaded cart has battery-backed SRAM that should be persisted.
+  bool batteryBacked({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorBatteryBacked(
+        that: this,
+      );
+
   static NesEmulator create({dynamic hint}) =>
       RustLib.instance.api.nesEmulatorCreate(hint: hint);
 
-  Future<void> handleButton(
+  /// Export battery-backed SRAM, e.g. to save it to disk.
+  Future<Uint8List> exportSram({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorExportSram(
+        that: this,
+      );
+
+  /// Run a single frame and pause.
+  void frameAdvance({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorFrameAdvance(
+        that: this,
+      );
+
+  /// Frame number of the next frame to run.
+  int frameNumber({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorFrameNumber(
+        that: this,
+      );
+
+  void handleButton(
           {required Player player,
           required NesButton button,
           required bool pressed,
@@ -41,12 +89,33 @@ class NesEmulator extends RustOpaque {
         pressed: pressed,
       );
 
+  /// Import battery-backed SRAM previously exported with `export_sram`. Ignored if the loaded
+  /// cart isn't battery-backed.
+  Future<void> importSram({required List<int> sram, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorImportSram(
+        that: this,
+        sram: sram,
+      );
+
+  /// Load a ROM, applying IPS, UPS or BPS `patches` in order first.
   Future<void> loadRom(
-          {required String name, required List<int> data, dynamic hint}) =>
+          {required String name,
+          required List<int> data,
+          required List<Uint8List> patches,
+          dynamic hint}) =>
       RustLib.instance.api.nesEmulatorLoadRom(
         that: this,
         name: name,
         data: data,
+        patches: patches,
+      );
+
+  void pause({dynamic hint}) => RustLib.instance.api.nesEmulatorPause(
+        that: this,
+      );
+
+  void resume({dynamic hint}) => RustLib.instance.api.nesEmulatorResume(
+        that: this,
       );
 
   Future<void> runLoopForCallback(
@@ -68,14 +137,143 @@ class NesEmulator extends RustOpaque {
         texture: texture,
       );
 
+  /// Queue input for the next frame.
+  void sendInput({required NesInput input, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSendInput(
+        that: this,
+        input: input,
+      );
+
+  /// Queue input for the given frame. Input for frames that already ran is applied before the
+  /// next one.
+  void sendInputAt(
+          {required int frame, required NesInput input, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSendInputAt(
+        that: this,
+        frame: frame,
+        input: input,
+      );
+
+  /// Set the audio sample rate and buffer size.
+  void setAudioConfig({required NesAudioConfig config, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSetAudioConfig(
+        that: this,
+        config: config,
+      );
+
+  void setFilter({required VideoFilter filter, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSetFilter(
+        that: this,
+        filter: filter,
+      );
+
+  void setFourPlayer({required FourPlayer fourPlayer, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSetFourPlayer(
+        that: this,
+        fourPlayer: fourPlayer,
+      );
+
+  void setRegion({required NesRegion region, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSetRegion(
+        that: this,
+        region: region,
+      );
+
+  /// Set the emulation speed multiplier, clamped to `0.25..=8.0`. Audio keeps streaming with its
+  /// pitch shifted by the speed.
+  void setSpeed({required double speed, dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSetSpeed(
+        that: this,
+        speed: speed,
+      );
+
+  /// Stream a copy of battery-backed SRAM whenever the game writes to it, checked about once a
+  /// second, and once more when the emulation loop stops.
+  Stream<Uint8List> sramStream({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorSramStream(
+        that: this,
+      );
+
   void stopLoop({dynamic hint}) => RustLib.instance.api.nesEmulatorStopLoop(
         that: this,
       );
 
+  void triggerZapper({dynamic hint}) =>
+      RustLib.instance.api.nesEmulatorTriggerZapper(
+        that: this,
+      );
+
   static NesEmulator withConfig({required NesConfig config, dynamic hint}) =>
       RustLib.instance.api.nesEmulatorWithConfig(config: config, hint: hint);
 }
 
+// Rust type: RustOpaqueMoi<flutter_rust_bridge::for_generated::rust_async::RwLock<NesInput>>
+/// Input sent to the running emulation.
+@sealed
+class NesInput extends RustOpaque {
+  NesInput.dcoDecode(List<dynamic> wire)
+      : super.dcoDecode(wire, _kStaticData);
+
+  NesInput.sseDecode(int ptr, int externalSizeOnNative)
+      : super.sseDecode(ptr, externalSizeOnNative, _kStaticData);
+
+  static final _kStaticData = RustArcStaticData(
+    rustArcIncrementStrongCount:
+        RustLib.instance.api.rust_arc_increment_strong_count_NesInput,
+    rustArcDecrementStrongCount:
+        RustLib.instance.api.rust_arc_decrement_strong_count_NesInput,
+    rustArcDecrementStrongCountPtr:
+        RustLib.instance.api.rust_arc_decrement_strong_count_NesInputPtr,
+  );
+
+  static NesInput button(
+          {required Player player,
+          required NesButton button,
+          required bool pressed,
+          dynamic hint}) =>
+      RustLib.instance.api.nesInputButton(
+          player: player, button: button, pressed: pressed, hint: hint);
+
+  static NesInput fourPlayer({required FourPlayer fourPlayer, dynamic hint}) =>
+      RustLib.instance.api.nesInputFourPlayer(
+          fourPlayer: fourPlayer, hint: hint);
+
+  static NesInput zapperAim({required int x, required int y, dynamic hint}) =>
+      RustLib.instance.api.nesInputZapperAim(x: x, y: y, hint: hint);
+
+  static NesInput zapperTrigger({dynamic hint}) =>
+      RustLib.instance.api.nesInputZapperTrigger(hint: hint);
+}
+
+class NesAudioConfig {
+  /// Output sample rate, e.g. 44100 or 48000.
+  final int sampleRate;
+  /// Size of the Dart side audio buffer in samples. Emulation waits for queued audio to drop
+  /// below half of this before running the next frame.
+  final int bufferSize;
+
+  const NesAudioConfig({
+    required this.sampleRate,
+    required this.bufferSize,
+  });
+
+  static NesAudioConfig create(
+          {required int sampleRate, required int bufferSize, dynamic hint}) =>
+      RustLib.instance.api.nesAudioConfigCreate(
+          sampleRate: sampleRate, bufferSize: bufferSize, hint: hint);
+
+  @override
+  int get hashCode => sampleRate.hashCode ^ bufferSize.hashCode;
+
+  @override
+  bool operator ==(Object other) =>
+      identical(this, other) ||
+      other is NesAudioConfig &&
+          runtimeType == other.runtimeType &&
+          sampleRate == other.sampleRate &&
+          bufferSize == other.bufferSize;
+}
+
 enum NesButton {
   start,
   select,
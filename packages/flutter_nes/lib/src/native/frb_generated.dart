@@ -64,6 +64,9 @@ class RustLib extends BaseEntrypoint<RustLibApi, RustLibApiImpl, RustLibWire> {
 }
 
 abstract class RustLibApi extends BaseApi {
+  NesAudioConfig nesAudioConfigCreate(
+      {required int sampleRate, required int bufferSize, dynamic hint});
+
   NesConfig nesConfigCreate(
       {required VideoFilter filter,
       required NesRegion region,
@@ -73,21 +76,50 @@ abstract class RustLibApi extends BaseApi {
       required List<String> genieCodes,
       dynamic hint});
 
+  void nesEmulatorAimZapper(
+      {required NesEmulator that,
+      required int x,
+      required int y,
+      dynamic hint});
+
+  void nesEmulatorAudioConsumed(
+      {required NesEmulator that, required int samples, dynamic hint});
+
+  Stream<Float32List> nesEmulatorAudioStream(
+      {required NesEmulator that, dynamic hint});
+
+  bool nesEmulatorBatteryBacked({required NesEmulator that, dynamic hint});
+
   NesEmulator nesEmulatorCreate({dynamic hint});
 
-  Future<void> nesEmulatorHandleButton(
+  Future<Uint8List> nesEmulatorExportSram(
+      {required NesEmulator that, dynamic hint});
+
+  void nesEmulatorFrameAdvance({required NesEmulator that, dynamic hint});
+
+  int nesEmulatorFrameNumber({required NesEmulator that, dynamic hint});
+
+  void nesEmulatorHandleButton(
       {required NesEmulator that,
       required Player player,
       required NesButton button,
       required bool pressed,
       dynamic hint});
 
+  Future<void> nesEmulatorImportSram(
+      {required NesEmulator that, required List<int> sram, dynamic hint});
+
   Future<void> nesEmulatorLoadRom(
       {required NesEmulator that,
       required String name,
       required List<int> data,
+      required List<Uint8List> patches,
       dynamic hint});
 
+  void nesEmulatorPause({required NesEmulator that, dynamic hint});
+
+  void nesEmulatorResume({required NesEmulator that, dynamic hint});
+
   Future<void> nesEmulatorRunLoopForCallback(
       {required NesEmulator that,
       required FutureOr<void> Function(Uint8List) callback,
@@ -99,10 +131,55 @@ abstract class RustLibApi extends BaseApi {
   Future<void> nesEmulatorRunLoopForTexture(
       {required NesEmulator that, required NesTexture texture, dynamic hint});
 
+  void nesEmulatorSendInput(
+      {required NesEmulator that, required NesInput input, dynamic hint});
+
+  void nesEmulatorSendInputAt(
+      {required NesEmulator that,
+      required int frame,
+      required NesInput input,
+      dynamic hint});
+
+  void nesEmulatorSetAudioConfig(
+      {required NesEmulator that,
+      required NesAudioConfig config,
+      dynamic hint});
+
+  void nesEmulatorSetFilter(
+      {required NesEmulator that, required VideoFilter filter, dynamic hint});
+
+  void nesEmulatorSetFourPlayer(
+      {required NesEmulator that,
+      required FourPlayer fourPlayer,
+      dynamic hint});
+
+  void nesEmulatorSetRegion(
+      {required NesEmulator that, required NesRegion region, dynamic hint});
+
+  void nesEmulatorSetSpeed(
+      {required NesEmulator that, required double speed, dynamic hint});
+
+  Stream<Uint8List> nesEmulatorSramStream(
+      {required NesEmulator that, dynamic hint});
+
   void nesEmulatorStopLoop({required NesEmulator that, dynamic hint});
 
+  void nesEmulatorTriggerZapper({required NesEmulator that, dynamic hint});
+
   NesEmulator nesEmulatorWithConfig({required NesConfig config, dynamic hint});
 
+  NesInput nesInputButton(
+      {required Player player,
+      required NesButton button,
+      required bool pressed,
+      dynamic hint});
+
+  NesInput nesInputFourPlayer({required FourPlayer fourPlayer, dynamic hint});
+
+  NesInput nesInputZapperAim({required int x, required int y, dynamic hint});
+
+  NesInput nesInputZapperTrigger({dynamic hint});
+
   Future<NesTexture?> nesTextureCreate({required int handle, dynamic hint});
 
   int nesTextureId({required NesTexture that, dynamic hint});
@@ -118,6 +195,14 @@ abstract class RustLibApi extends BaseApi {
 
   CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_NesEmulatorPtr;
 
+  RustArcIncrementStrongCountFnType
+      get rust_arc_increment_strong_count_NesInput;
+
+  RustArcDecrementStrongCountFnType
+      get rust_arc_decrement_strong_count_NesInput;
+
+  CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_NesInputPtr;
+
   RustArcIncrementStrongCountFnType
       get rust_arc_increment_strong_count_NesTexture;
 
@@ -135,6 +220,32 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     required super.portManager,
   });
 
+  @override
+  NesAudioConfig nesAudioConfigCreate(
+      {required int sampleRate, required int bufferSize, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_u_32(sampleRate, serializer);
+        sse_encode_u_32(bufferSize, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 6)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_nes_audio_config,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesAudioConfigCreateConstMeta,
+      argValues: [sampleRate, bufferSize],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesAudioConfigCreateConstMeta => const TaskConstMeta(
+        debugName: "NesAudioConfig_create",
+        argNames: ["sampleRate", "bufferSize"],
+      );
+
   @override
   NesConfig nesConfigCreate(
       {required VideoFilter filter,
@@ -153,7 +264,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         sse_encode_four_player(fourPlayer, serializer);
         sse_encode_bool(zapper, serializer);
         sse_encode_list_String(genieCodes, serializer);
-        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 1)!;
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 5)!;
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_nes_config,
@@ -178,12 +289,122 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         ],
       );
 
+  @override
+  void nesEmulatorAimZapper(
+      {required NesEmulator that,
+      required int x,
+      required int y,
+      dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_i_32(x, serializer);
+        sse_encode_i_32(y, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 24)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorAimZapperConstMeta,
+      argValues: [that, x, y],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorAimZapperConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_aim_zapper",
+        argNames: ["that", "x", "y"],
+      );
+
+  @override
+  void nesEmulatorAudioConsumed(
+      {required NesEmulator that, required int samples, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_u_32(samples, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 15)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorAudioConsumedConstMeta,
+      argValues: [that, samples],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorAudioConsumedConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_audio_consumed",
+        argNames: ["that", "samples"],
+      );
+
+  @override
+  Stream<Float32List> nesEmulatorAudioStream(
+      {required NesEmulator that, dynamic hint}) {
+    return handler.executeStream(StreamTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 14, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_prim_f_32_strict,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorAudioStreamConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorAudioStreamConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_audio_stream",
+        argNames: ["that"],
+      );
+
+  @override
+  bool nesEmulatorBatteryBacked({required NesEmulator that, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 16)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_bool,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorBatteryBackedConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorBatteryBackedConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_battery_backed",
+        argNames: ["that"],
+      );
+
   @override
   NesEmulator nesEmulatorCreate({dynamic hint}) {
     return handler.executeSync(SyncTask(
       callFfi: () {
         final serializer = SseSerializer(generalizedFrbRustBinding);
-        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 2)!;
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 7)!;
       },
       codec: SseCodec(
         decodeSuccessData:
@@ -203,22 +424,98 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
       );
 
   @override
-  Future<void> nesEmulatorHandleButton(
+  Future<Uint8List> nesEmulatorExportSram(
+      {required NesEmulator that, dynamic hint}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 17, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_prim_u_8_strict,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kNesEmulatorExportSramConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorExportSramConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_export_sram",
+        argNames: ["that"],
+      );
+
+  @override
+  void nesEmulatorFrameAdvance({required NesEmulator that, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 30)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorFrameAdvanceConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorFrameAdvanceConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_frame_advance",
+        argNames: ["that"],
+      );
+
+  @override
+  int nesEmulatorFrameNumber({required NesEmulator that, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 20)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_u_32,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorFrameNumberConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorFrameNumberConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_frame_number",
+        argNames: ["that"],
+      );
+
+  @override
+  void nesEmulatorHandleButton(
       {required NesEmulator that,
       required Player player,
       required NesButton button,
       required bool pressed,
       dynamic hint}) {
-    return handler.executeNormal(NormalTask(
-      callFfi: (port_) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
         final serializer = SseSerializer(generalizedFrbRustBinding);
-        sse_encode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
             that, serializer);
         sse_encode_player(player, serializer);
         sse_encode_nes_button(button, serializer);
         sse_encode_bool(pressed, serializer);
-        pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 8, port: port_);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 23)!;
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
@@ -236,28 +533,58 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         argNames: ["that", "player", "button", "pressed"],
       );
 
+  @override
+  Future<void> nesEmulatorImportSram(
+      {required NesEmulator that, required List<int> sram, dynamic hint}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_list_prim_u_8_loose(sram, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 18, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kNesEmulatorImportSramConstMeta,
+      argValues: [that, sram],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorImportSramConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_import_sram",
+        argNames: ["that", "sram"],
+      );
+
   @override
   Future<void> nesEmulatorLoadRom(
       {required NesEmulator that,
       required String name,
       required List<int> data,
+      required List<Uint8List> patches,
       dynamic hint}) {
     return handler.executeNormal(NormalTask(
       callFfi: (port_) {
         final serializer = SseSerializer(generalizedFrbRustBinding);
-        sse_encode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
             that, serializer);
         sse_encode_String(name, serializer);
         sse_encode_list_prim_u_8_loose(data, serializer);
+        sse_encode_list_list_prim_u_8_strict(patches, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 4, port: port_);
+            funcId: 9, port: port_);
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
         decodeErrorData: sse_decode_AnyhowException,
       ),
       constMeta: kNesEmulatorLoadRomConstMeta,
-      argValues: [that, name, data],
+      argValues: [that, name, data, patches],
       apiImpl: this,
       hint: hint,
     ));
@@ -265,7 +592,57 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
 
   TaskConstMeta get kNesEmulatorLoadRomConstMeta => const TaskConstMeta(
         debugName: "NesEmulator_load_rom",
-        argNames: ["that", "name", "data"],
+        argNames: ["that", "name", "data", "patches"],
+      );
+
+  @override
+  void nesEmulatorPause({required NesEmulator that, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 28)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorPauseConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorPauseConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_pause",
+        argNames: ["that"],
+      );
+
+  @override
+  void nesEmulatorResume({required NesEmulator that, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 29)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorResumeConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorResumeConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_resume",
+        argNames: ["that"],
       );
 
   @override
@@ -278,30 +655,287 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
             that, serializer);
-        sse_encode_DartFn_Inputs_list_prim_u_8_strict_Output_unit(
-            callback, serializer);
-        pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 5, port: port_);
+        sse_encode_DartFn_Inputs_list_prim_u_8_strict_Output_unit(
+            callback, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 10, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kNesEmulatorRunLoopForCallbackConstMeta,
+      argValues: [that, callback],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorRunLoopForCallbackConstMeta =>
+      const TaskConstMeta(
+        debugName: "NesEmulator_run_loop_for_callback",
+        argNames: ["that", "callback"],
+      );
+
+  @override
+  Stream<Uint8List> nesEmulatorRunLoopForPainter(
+      {required NesEmulator that, dynamic hint}) {
+    return handler.executeStream(StreamTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 11, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_list_prim_u_8_strict,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kNesEmulatorRunLoopForPainterConstMeta,
+      argValues: [that],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorRunLoopForPainterConstMeta =>
+      const TaskConstMeta(
+        debugName: "NesEmulator_run_loop_for_painter",
+        argNames: ["that"],
+      );
+
+  @override
+  Future<void> nesEmulatorRunLoopForTexture(
+      {required NesEmulator that, required NesTexture texture, dynamic hint}) {
+    return handler.executeNormal(NormalTask(
+      callFfi: (port_) {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+            texture, serializer);
+        pdeCallFfi(generalizedFrbRustBinding, serializer,
+            funcId: 12, port: port_);
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: sse_decode_AnyhowException,
+      ),
+      constMeta: kNesEmulatorRunLoopForTextureConstMeta,
+      argValues: [that, texture],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorRunLoopForTextureConstMeta =>
+      const TaskConstMeta(
+        debugName: "NesEmulator_run_loop_for_texture",
+        argNames: ["that", "texture"],
+      );
+
+  @override
+  void nesEmulatorSendInput(
+      {required NesEmulator that, required NesInput input, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+            input, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 21)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorSendInputConstMeta,
+      argValues: [that, input],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorSendInputConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_send_input",
+        argNames: ["that", "input"],
+      );
+
+  @override
+  void nesEmulatorSendInputAt(
+      {required NesEmulator that,
+      required int frame,
+      required NesInput input,
+      dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_u_32(frame, serializer);
+        sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+            input, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 22)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorSendInputAtConstMeta,
+      argValues: [that, frame, input],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorSendInputAtConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_send_input_at",
+        argNames: ["that", "frame", "input"],
+      );
+
+  @override
+  void nesEmulatorSetAudioConfig(
+      {required NesEmulator that,
+      required NesAudioConfig config,
+      dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_box_autoadd_nes_audio_config(config, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 13)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorSetAudioConfigConstMeta,
+      argValues: [that, config],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorSetAudioConfigConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_set_audio_config",
+        argNames: ["that", "config"],
+      );
+
+  @override
+  void nesEmulatorSetFilter(
+      {required NesEmulator that, required VideoFilter filter, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_video_filter(filter, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 32)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorSetFilterConstMeta,
+      argValues: [that, filter],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorSetFilterConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_set_filter",
+        argNames: ["that", "filter"],
+      );
+
+  @override
+  void nesEmulatorSetFourPlayer(
+      {required NesEmulator that,
+      required FourPlayer fourPlayer,
+      dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_four_player(fourPlayer, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 26)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorSetFourPlayerConstMeta,
+      argValues: [that, fourPlayer],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorSetFourPlayerConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_set_four_player",
+        argNames: ["that", "fourPlayer"],
+      );
+
+  @override
+  void nesEmulatorSetRegion(
+      {required NesEmulator that, required NesRegion region, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_nes_region(region, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 33)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData: sse_decode_unit,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesEmulatorSetRegionConstMeta,
+      argValues: [that, region],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesEmulatorSetRegionConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_set_region",
+        argNames: ["that", "region"],
+      );
+
+  @override
+  void nesEmulatorSetSpeed(
+      {required NesEmulator that, required double speed, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+            that, serializer);
+        sse_encode_f_32(speed, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 31)!;
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
-        decodeErrorData: sse_decode_AnyhowException,
+        decodeErrorData: null,
       ),
-      constMeta: kNesEmulatorRunLoopForCallbackConstMeta,
-      argValues: [that, callback],
+      constMeta: kNesEmulatorSetSpeedConstMeta,
+      argValues: [that, speed],
       apiImpl: this,
       hint: hint,
     ));
   }
 
-  TaskConstMeta get kNesEmulatorRunLoopForCallbackConstMeta =>
-      const TaskConstMeta(
-        debugName: "NesEmulator_run_loop_for_callback",
-        argNames: ["that", "callback"],
+  TaskConstMeta get kNesEmulatorSetSpeedConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_set_speed",
+        argNames: ["that", "speed"],
       );
 
   @override
-  Stream<Uint8List> nesEmulatorRunLoopForPainter(
+  Stream<Uint8List> nesEmulatorSramStream(
       {required NesEmulator that, dynamic hint}) {
     return handler.executeStream(StreamTask(
       callFfi: (port_) {
@@ -309,77 +943,71 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
             that, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 6, port: port_);
+            funcId: 19, port: port_);
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_list_prim_u_8_strict,
-        decodeErrorData: sse_decode_AnyhowException,
+        decodeErrorData: null,
       ),
-      constMeta: kNesEmulatorRunLoopForPainterConstMeta,
+      constMeta: kNesEmulatorSramStreamConstMeta,
       argValues: [that],
       apiImpl: this,
       hint: hint,
     ));
   }
 
-  TaskConstMeta get kNesEmulatorRunLoopForPainterConstMeta =>
-      const TaskConstMeta(
-        debugName: "NesEmulator_run_loop_for_painter",
+  TaskConstMeta get kNesEmulatorSramStreamConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_sram_stream",
         argNames: ["that"],
       );
 
   @override
-  Future<void> nesEmulatorRunLoopForTexture(
-      {required NesEmulator that, required NesTexture texture, dynamic hint}) {
-    return handler.executeNormal(NormalTask(
-      callFfi: (port_) {
+  void nesEmulatorStopLoop({required NesEmulator that, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
             that, serializer);
-        sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
-            texture, serializer);
-        pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 7, port: port_);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 27)!;
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
-        decodeErrorData: sse_decode_AnyhowException,
+        decodeErrorData: null,
       ),
-      constMeta: kNesEmulatorRunLoopForTextureConstMeta,
-      argValues: [that, texture],
+      constMeta: kNesEmulatorStopLoopConstMeta,
+      argValues: [that],
       apiImpl: this,
       hint: hint,
     ));
   }
 
-  TaskConstMeta get kNesEmulatorRunLoopForTextureConstMeta =>
-      const TaskConstMeta(
-        debugName: "NesEmulator_run_loop_for_texture",
-        argNames: ["that", "texture"],
+  TaskConstMeta get kNesEmulatorStopLoopConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_stop_loop",
+        argNames: ["that"],
       );
 
   @override
-  void nesEmulatorStopLoop({required NesEmulator that, dynamic hint}) {
+  void nesEmulatorTriggerZapper({required NesEmulator that, dynamic hint}) {
     return handler.executeSync(SyncTask(
       callFfi: () {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
             that, serializer);
-        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 9)!;
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 25)!;
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
         decodeErrorData: null,
       ),
-      constMeta: kNesEmulatorStopLoopConstMeta,
+      constMeta: kNesEmulatorTriggerZapperConstMeta,
       argValues: [that],
       apiImpl: this,
       hint: hint,
     ));
   }
 
-  TaskConstMeta get kNesEmulatorStopLoopConstMeta => const TaskConstMeta(
-        debugName: "NesEmulator_stop_loop",
+  TaskConstMeta get kNesEmulatorTriggerZapperConstMeta => const TaskConstMeta(
+        debugName: "NesEmulator_trigger_zapper",
         argNames: ["that"],
       );
 
@@ -389,7 +1017,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
       callFfi: () {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_box_autoadd_nes_config(config, serializer);
-        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 3)!;
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 8)!;
       },
       codec: SseCodec(
         decodeSuccessData:
@@ -408,6 +1036,112 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         argNames: ["config"],
       );
 
+  @override
+  NesInput nesInputButton(
+      {required Player player,
+      required NesButton button,
+      required bool pressed,
+      dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_player(player, serializer);
+        sse_encode_nes_button(button, serializer);
+        sse_encode_bool(pressed, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 1)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData:
+            sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesInputButtonConstMeta,
+      argValues: [player, button, pressed],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesInputButtonConstMeta => const TaskConstMeta(
+        debugName: "NesInput_button",
+        argNames: ["player", "button", "pressed"],
+      );
+
+  @override
+  NesInput nesInputFourPlayer({required FourPlayer fourPlayer, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_four_player(fourPlayer, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 4)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData:
+            sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesInputFourPlayerConstMeta,
+      argValues: [fourPlayer],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesInputFourPlayerConstMeta => const TaskConstMeta(
+        debugName: "NesInput_four_player",
+        argNames: ["fourPlayer"],
+      );
+
+  @override
+  NesInput nesInputZapperAim({required int x, required int y, dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        sse_encode_i_32(x, serializer);
+        sse_encode_i_32(y, serializer);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 2)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData:
+            sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesInputZapperAimConstMeta,
+      argValues: [x, y],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesInputZapperAimConstMeta => const TaskConstMeta(
+        debugName: "NesInput_zapper_aim",
+        argNames: ["x", "y"],
+      );
+
+  @override
+  NesInput nesInputZapperTrigger({dynamic hint}) {
+    return handler.executeSync(SyncTask(
+      callFfi: () {
+        final serializer = SseSerializer(generalizedFrbRustBinding);
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 3)!;
+      },
+      codec: SseCodec(
+        decodeSuccessData:
+            sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput,
+        decodeErrorData: null,
+      ),
+      constMeta: kNesInputZapperTriggerConstMeta,
+      argValues: [],
+      apiImpl: this,
+      hint: hint,
+    ));
+  }
+
+  TaskConstMeta get kNesInputZapperTriggerConstMeta => const TaskConstMeta(
+        debugName: "NesInput_zapper_trigger",
+        argNames: [],
+      );
+
   @override
   Future<NesTexture?> nesTextureCreate({required int handle, dynamic hint}) {
     return handler.executeNormal(NormalTask(
@@ -415,7 +1149,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_i_64(handle, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 10, port: port_);
+            funcId: 34, port: port_);
       },
       codec: SseCodec(
         decodeSuccessData:
@@ -441,7 +1175,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         final serializer = SseSerializer(generalizedFrbRustBinding);
         sse_encode_Auto_Ref_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
             that, serializer);
-        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 12)!;
+        return pdeCallFfi(generalizedFrbRustBinding, serializer, funcId: 36)!;
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_i_64,
@@ -469,7 +1203,7 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
             that, serializer);
         sse_encode_list_prim_u_8_loose(data, serializer);
         pdeCallFfi(generalizedFrbRustBinding, serializer,
-            funcId: 11, port: port_);
+            funcId: 35, port: port_);
       },
       codec: SseCodec(
         decodeSuccessData: sse_decode_unit,
@@ -515,6 +1249,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
       get rust_arc_decrement_strong_count_NesEmulator => wire
           .rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator;
 
+  RustArcIncrementStrongCountFnType
+      get rust_arc_increment_strong_count_NesInput => wire
+          .rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput;
+
+  RustArcDecrementStrongCountFnType
+      get rust_arc_decrement_strong_count_NesInput => wire
+          .rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput;
+
   RustArcIncrementStrongCountFnType
       get rust_arc_increment_strong_count_NesTexture => wire
           .rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture;
@@ -538,19 +1280,19 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   }
 
   @protected
-  NesTexture
-      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+  NesInput
+      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
           dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
-    return NesTexture.dcoDecode(raw as List<dynamic>);
+    return NesInput.dcoDecode(raw as List<dynamic>);
   }
 
   @protected
-  NesEmulator
-      dco_decode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+  NesTexture
+      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
-    return NesEmulator.dcoDecode(raw as List<dynamic>);
+    return NesTexture.dcoDecode(raw as List<dynamic>);
   }
 
   @protected
@@ -590,6 +1332,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return NesEmulator.dcoDecode(raw as List<dynamic>);
   }
 
+  @protected
+  NesInput
+      dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return NesInput.dcoDecode(raw as List<dynamic>);
+  }
+
   @protected
   NesTexture
       dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -618,12 +1368,24 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return raw as NesTexture;
   }
 
+  @protected
+  NesAudioConfig dco_decode_box_autoadd_nes_audio_config(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return dco_decode_nes_audio_config(raw);
+  }
+
   @protected
   NesConfig dco_decode_box_autoadd_nes_config(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
     return dco_decode_nes_config(raw);
   }
 
+  @protected
+  double dco_decode_f_32(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as double;
+  }
+
   @protected
   FourPlayer dco_decode_four_player(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -648,6 +1410,18 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return (raw as List<dynamic>).map(dco_decode_String).toList();
   }
 
+  @protected
+  List<Uint8List> dco_decode_list_list_prim_u_8_strict(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return (raw as List<dynamic>).map(dco_decode_list_prim_u_8_strict).toList();
+  }
+
+  @protected
+  Float32List dco_decode_list_prim_f_32_strict(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as Float32List;
+  }
+
   @protected
   List<int> dco_decode_list_prim_u_8_loose(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -660,6 +1434,18 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return raw as Uint8List;
   }
 
+  @protected
+  NesAudioConfig dco_decode_nes_audio_config(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    final arr = raw as List<dynamic>;
+    if (arr.length != 2)
+      throw Exception('unexpected arr length: expect 2 but see ${arr.length}');
+    return NesAudioConfig(
+      sampleRate: dco_decode_u_32(arr[0]),
+      bufferSize: dco_decode_u_32(arr[1]),
+    );
+  }
+
   @protected
   NesButton dco_decode_nes_button(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -711,6 +1497,12 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return RamState.values[raw as int];
   }
 
+  @protected
+  int dco_decode_u_32(dynamic raw) {
+    // Codec=Dco (DartCObject based), see doc to use other codecs
+    return raw as int;
+  }
+
   @protected
   int dco_decode_u_8(dynamic raw) {
     // Codec=Dco (DartCObject based), see doc to use other codecs
@@ -752,20 +1544,20 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
   }
 
   @protected
-  NesTexture
-      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+  NesInput
+      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
           SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    return NesTexture.sseDecode(
+    return NesInput.sseDecode(
         sse_decode_usize(deserializer), sse_decode_i_32(deserializer));
   }
 
   @protected
-  NesEmulator
-      sse_decode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+  NesTexture
+      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    return NesEmulator.sseDecode(
+    return NesTexture.sseDecode(
         sse_decode_usize(deserializer), sse_decode_i_32(deserializer));
   }
 
@@ -803,6 +1595,15 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         sse_decode_usize(deserializer), sse_decode_i_32(deserializer));
   }
 
+  @protected
+  NesInput
+      sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return NesInput.sseDecode(
+        sse_decode_usize(deserializer), sse_decode_i_32(deserializer));
+  }
+
   @protected
   NesTexture
       sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -834,12 +1635,25 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         deserializer));
   }
 
+  @protected
+  NesAudioConfig sse_decode_box_autoadd_nes_audio_config(
+      SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return (sse_decode_nes_audio_config(deserializer));
+  }
+
   @protected
   NesConfig sse_decode_box_autoadd_nes_config(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
     return (sse_decode_nes_config(deserializer));
   }
 
+  @protected
+  double sse_decode_f_32(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getFloat32();
+  }
+
   @protected
   FourPlayer sse_decode_four_player(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -871,6 +1685,26 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return ans_;
   }
 
+  @protected
+  List<Uint8List> sse_decode_list_list_prim_u_8_strict(
+      SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+
+    var len_ = sse_decode_i_32(deserializer);
+    var ans_ = <Uint8List>[];
+    for (var idx_ = 0; idx_ < len_; ++idx_) {
+      ans_.add(sse_decode_list_prim_u_8_strict(deserializer));
+    }
+    return ans_;
+  }
+
+  @protected
+  Float32List sse_decode_list_prim_f_32_strict(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var len_ = sse_decode_i_32(deserializer);
+    return deserializer.buffer.getFloat32List(len_);
+  }
+
   @protected
   List<int> sse_decode_list_prim_u_8_loose(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -885,6 +1719,15 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return deserializer.buffer.getUint8List(len_);
   }
 
+  @protected
+  NesAudioConfig sse_decode_nes_audio_config(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    var var_sampleRate = sse_decode_u_32(deserializer);
+    var var_bufferSize = sse_decode_u_32(deserializer);
+    return NesAudioConfig(
+        sampleRate: var_sampleRate, bufferSize: var_bufferSize);
+  }
+
   @protected
   NesButton sse_decode_nes_button(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -945,6 +1788,12 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     return RamState.values[inner];
   }
 
+  @protected
+  int sse_decode_u_32(SseDeserializer deserializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    return deserializer.buffer.getUint32();
+  }
+
   @protected
   int sse_decode_u_8(SseDeserializer deserializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -986,18 +1835,18 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
 
   @protected
   void
-      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
-          NesTexture self, SseSerializer serializer) {
+      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          NesInput self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
     sse_encode_usize(self.sseEncode(move: true), serializer);
   }
 
   @protected
   void
-      sse_encode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
-          NesEmulator self, SseSerializer serializer) {
+      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+          NesTexture self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
-    sse_encode_usize(self.sseEncode(move: false), serializer);
+    sse_encode_usize(self.sseEncode(move: true), serializer);
   }
 
   @protected
@@ -1042,6 +1891,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_usize(self.sseEncode(move: null), serializer);
   }
 
+  @protected
+  void
+      sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          NesInput self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_usize(self.sseEncode(move: null), serializer);
+  }
+
   @protected
   void
       sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -1071,6 +1928,13 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
         self, serializer);
   }
 
+  @protected
+  void sse_encode_box_autoadd_nes_audio_config(
+      NesAudioConfig self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_nes_audio_config(self, serializer);
+  }
+
   @protected
   void sse_encode_box_autoadd_nes_config(
       NesConfig self, SseSerializer serializer) {
@@ -1078,6 +1942,12 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_nes_config(self, serializer);
   }
 
+  @protected
+  void sse_encode_f_32(double self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    serializer.buffer.putFloat32(self);
+  }
+
   @protected
   void sse_encode_four_player(FourPlayer self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -1105,6 +1975,24 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     }
   }
 
+  @protected
+  void sse_encode_list_list_prim_u_8_strict(
+      List<Uint8List> self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    for (final item in self) {
+      sse_encode_list_prim_u_8_strict(item, serializer);
+    }
+  }
+
+  @protected
+  void sse_encode_list_prim_f_32_strict(
+      Float32List self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_i_32(self.length, serializer);
+    serializer.buffer.putFloat32List(self);
+  }
+
   @protected
   void sse_encode_list_prim_u_8_loose(
       List<int> self, SseSerializer serializer) {
@@ -1122,6 +2010,14 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     serializer.buffer.putUint8List(self);
   }
 
+  @protected
+  void sse_encode_nes_audio_config(
+      NesAudioConfig self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    sse_encode_u_32(self.sampleRate, serializer);
+    sse_encode_u_32(self.bufferSize, serializer);
+  }
+
   @protected
   void sse_encode_nes_button(NesButton self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -1170,6 +2066,12 @@ class RustLibApiImpl extends RustLibApiImplPlatform implements RustLibApi {
     sse_encode_i_32(self.index, serializer);
   }
 
+  @protected
+  void sse_encode_u_32(int self, SseSerializer serializer) {
+    // Codec=Sse (Serialization based), see doc to use other codecs
+    serializer.buffer.putUint32(self);
+  }
+
   @protected
   void sse_encode_u_8(int self, SseSerializer serializer) {
     // Codec=Sse (Serialization based), see doc to use other codecs
@@ -24,6 +24,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       get rust_arc_decrement_strong_count_NesEmulatorPtr => wire
           ._rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulatorPtr;
 
+  CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_NesInputPtr =>
+      wire._rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInputPtr;
+
   CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_NesTexturePtr =>
       wire._rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexturePtr;
 
@@ -36,13 +39,13 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
           dynamic raw);
 
   @protected
-  NesTexture
-      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+  NesInput
+      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
           dynamic raw);
 
   @protected
-  NesEmulator
-      dco_decode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+  NesTexture
+      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic raw);
 
   @protected
@@ -67,6 +70,11 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           dynamic raw);
 
+  @protected
+  NesInput
+      dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic raw);
+
   @protected
   NesTexture
       dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -83,9 +91,15 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       dco_decode_box_autoadd_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic raw);
 
+  @protected
+  NesAudioConfig dco_decode_box_autoadd_nes_audio_config(dynamic raw);
+
   @protected
   NesConfig dco_decode_box_autoadd_nes_config(dynamic raw);
 
+  @protected
+  double dco_decode_f_32(dynamic raw);
+
   @protected
   FourPlayer dco_decode_four_player(dynamic raw);
 
@@ -98,12 +112,21 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   List<String> dco_decode_list_String(dynamic raw);
 
+  @protected
+  List<Uint8List> dco_decode_list_list_prim_u_8_strict(dynamic raw);
+
+  @protected
+  Float32List dco_decode_list_prim_f_32_strict(dynamic raw);
+
   @protected
   List<int> dco_decode_list_prim_u_8_loose(dynamic raw);
 
   @protected
   Uint8List dco_decode_list_prim_u_8_strict(dynamic raw);
 
+  @protected
+  NesAudioConfig dco_decode_nes_audio_config(dynamic raw);
+
   @protected
   NesButton dco_decode_nes_button(dynamic raw);
 
@@ -124,6 +147,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   RamState dco_decode_ram_state(dynamic raw);
 
+  @protected
+  int dco_decode_u_32(dynamic raw);
+
   @protected
   int dco_decode_u_8(dynamic raw);
 
@@ -145,13 +171,13 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
           SseDeserializer deserializer);
 
   @protected
-  NesTexture
-      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+  NesInput
+      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
           SseDeserializer deserializer);
 
   @protected
-  NesEmulator
-      sse_decode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+  NesTexture
+      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           SseDeserializer deserializer);
 
   @protected
@@ -172,6 +198,11 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           SseDeserializer deserializer);
 
+  @protected
+  NesInput
+      sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          SseDeserializer deserializer);
+
   @protected
   NesTexture
       sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -188,9 +219,16 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_decode_box_autoadd_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           SseDeserializer deserializer);
 
+  @protected
+  NesAudioConfig sse_decode_box_autoadd_nes_audio_config(
+      SseDeserializer deserializer);
+
   @protected
   NesConfig sse_decode_box_autoadd_nes_config(SseDeserializer deserializer);
 
+  @protected
+  double sse_decode_f_32(SseDeserializer deserializer);
+
   @protected
   FourPlayer sse_decode_four_player(SseDeserializer deserializer);
 
@@ -203,12 +241,22 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   List<String> sse_decode_list_String(SseDeserializer deserializer);
 
+  @protected
+  List<Uint8List> sse_decode_list_list_prim_u_8_strict(
+      SseDeserializer deserializer);
+
+  @protected
+  Float32List sse_decode_list_prim_f_32_strict(SseDeserializer deserializer);
+
   @protected
   List<int> sse_decode_list_prim_u_8_loose(SseDeserializer deserializer);
 
   @protected
   Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer);
 
+  @protected
+  NesAudioConfig sse_decode_nes_audio_config(SseDeserializer deserializer);
+
   @protected
   NesButton sse_decode_nes_button(SseDeserializer deserializer);
 
@@ -229,6 +277,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   RamState sse_decode_ram_state(SseDeserializer deserializer);
 
+  @protected
+  int sse_decode_u_32(SseDeserializer deserializer);
+
   @protected
   int sse_decode_u_8(SseDeserializer deserializer);
 
@@ -252,13 +303,13 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
 
   @protected
   void
-      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
-          NesTexture self, SseSerializer serializer);
+      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          NesInput self, SseSerializer serializer);
 
   @protected
   void
-      sse_encode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
-          NesEmulator self, SseSerializer serializer);
+      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+          NesTexture self, SseSerializer serializer);
 
   @protected
   void
@@ -282,6 +333,11 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           NesEmulator self, SseSerializer serializer);
 
+  @protected
+  void
+      sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          NesInput self, SseSerializer serializer);
+
   @protected
   void
       sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -298,10 +354,17 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_encode_box_autoadd_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           NesTexture self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_box_autoadd_nes_audio_config(
+      NesAudioConfig self, SseSerializer serializer);
+
   @protected
   void sse_encode_box_autoadd_nes_config(
       NesConfig self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_f_32(double self, SseSerializer serializer);
+
   @protected
   void sse_encode_four_player(FourPlayer self, SseSerializer serializer);
 
@@ -314,6 +377,14 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_list_String(List<String> self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_list_list_prim_u_8_strict(
+      List<Uint8List> self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_list_prim_f_32_strict(
+      Float32List self, SseSerializer serializer);
+
   @protected
   void sse_encode_list_prim_u_8_loose(List<int> self, SseSerializer serializer);
 
@@ -321,6 +392,10 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   void sse_encode_list_prim_u_8_strict(
       Uint8List self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_nes_audio_config(
+      NesAudioConfig self, SseSerializer serializer);
+
   @protected
   void sse_encode_nes_button(NesButton self, SseSerializer serializer);
 
@@ -341,6 +416,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_ram_state(RamState self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_u_32(int self, SseSerializer serializer);
+
   @protected
   void sse_encode_u_8(int self, SseSerializer serializer);
 
@@ -400,6 +478,38 @@ class RustLibWire implements BaseWire {
       _rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulatorPtr
           .asFunction<void Function(ffi.Pointer<ffi.Void>)>();
 
+  void
+      rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+    ffi.Pointer<ffi.Void> ptr,
+  ) {
+    return _rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+      ptr,
+    );
+  }
+
+  late final _rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInputPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Pointer<ffi.Void>)>>(
+          'frbgen_flutter_nes_rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput');
+  late final _rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput =
+      _rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInputPtr
+          .asFunction<void Function(ffi.Pointer<ffi.Void>)>();
+
+  void
+      rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+    ffi.Pointer<ffi.Void> ptr,
+  ) {
+    return _rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+      ptr,
+    );
+  }
+
+  late final _rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInputPtr =
+      _lookup<ffi.NativeFunction<ffi.Void Function(ffi.Pointer<ffi.Void>)>>(
+          'frbgen_flutter_nes_rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput');
+  late final _rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput =
+      _rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInputPtr
+          .asFunction<void Function(ffi.Pointer<ffi.Void>)>();
+
   void
       rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
     ffi.Pointer<ffi.Void> ptr,
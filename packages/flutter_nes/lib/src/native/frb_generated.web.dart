@@ -23,6 +23,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       get rust_arc_decrement_strong_count_NesEmulatorPtr => wire
           .rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator;
 
+  CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_NesInputPtr =>
+      wire.rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput;
+
   CrossPlatformFinalizerArg get rust_arc_decrement_strong_count_NesTexturePtr =>
       wire.rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture;
 
@@ -35,13 +38,13 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
           dynamic raw);
 
   @protected
-  NesTexture
-      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+  NesInput
+      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
           dynamic raw);
 
   @protected
-  NesEmulator
-      dco_decode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+  NesTexture
+      dco_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic raw);
 
   @protected
@@ -66,6 +69,11 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           dynamic raw);
 
+  @protected
+  NesInput
+      dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic raw);
+
   @protected
   NesTexture
       dco_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -82,9 +90,15 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       dco_decode_box_autoadd_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic raw);
 
+  @protected
+  NesAudioConfig dco_decode_box_autoadd_nes_audio_config(dynamic raw);
+
   @protected
   NesConfig dco_decode_box_autoadd_nes_config(dynamic raw);
 
+  @protected
+  double dco_decode_f_32(dynamic raw);
+
   @protected
   FourPlayer dco_decode_four_player(dynamic raw);
 
@@ -97,12 +111,21 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   List<String> dco_decode_list_String(dynamic raw);
 
+  @protected
+  List<Uint8List> dco_decode_list_list_prim_u_8_strict(dynamic raw);
+
+  @protected
+  Float32List dco_decode_list_prim_f_32_strict(dynamic raw);
+
   @protected
   List<int> dco_decode_list_prim_u_8_loose(dynamic raw);
 
   @protected
   Uint8List dco_decode_list_prim_u_8_strict(dynamic raw);
 
+  @protected
+  NesAudioConfig dco_decode_nes_audio_config(dynamic raw);
+
   @protected
   NesButton dco_decode_nes_button(dynamic raw);
 
@@ -123,6 +146,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   RamState dco_decode_ram_state(dynamic raw);
 
+  @protected
+  int dco_decode_u_32(dynamic raw);
+
   @protected
   int dco_decode_u_8(dynamic raw);
 
@@ -144,13 +170,13 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
           SseDeserializer deserializer);
 
   @protected
-  NesTexture
-      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+  NesInput
+      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
           SseDeserializer deserializer);
 
   @protected
-  NesEmulator
-      sse_decode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
+  NesTexture
+      sse_decode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           SseDeserializer deserializer);
 
   @protected
@@ -171,6 +197,11 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           SseDeserializer deserializer);
 
+  @protected
+  NesInput
+      sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          SseDeserializer deserializer);
+
   @protected
   NesTexture
       sse_decode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -187,9 +218,16 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_decode_box_autoadd_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           SseDeserializer deserializer);
 
+  @protected
+  NesAudioConfig sse_decode_box_autoadd_nes_audio_config(
+      SseDeserializer deserializer);
+
   @protected
   NesConfig sse_decode_box_autoadd_nes_config(SseDeserializer deserializer);
 
+  @protected
+  double sse_decode_f_32(SseDeserializer deserializer);
+
   @protected
   FourPlayer sse_decode_four_player(SseDeserializer deserializer);
 
@@ -202,12 +240,22 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   List<String> sse_decode_list_String(SseDeserializer deserializer);
 
+  @protected
+  List<Uint8List> sse_decode_list_list_prim_u_8_strict(
+      SseDeserializer deserializer);
+
+  @protected
+  Float32List sse_decode_list_prim_f_32_strict(SseDeserializer deserializer);
+
   @protected
   List<int> sse_decode_list_prim_u_8_loose(SseDeserializer deserializer);
 
   @protected
   Uint8List sse_decode_list_prim_u_8_strict(SseDeserializer deserializer);
 
+  @protected
+  NesAudioConfig sse_decode_nes_audio_config(SseDeserializer deserializer);
+
   @protected
   NesButton sse_decode_nes_button(SseDeserializer deserializer);
 
@@ -228,6 +276,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   RamState sse_decode_ram_state(SseDeserializer deserializer);
 
+  @protected
+  int sse_decode_u_32(SseDeserializer deserializer);
+
   @protected
   int sse_decode_u_8(SseDeserializer deserializer);
 
@@ -251,13 +302,13 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
 
   @protected
   void
-      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
-          NesTexture self, SseSerializer serializer);
+      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          NesInput self, SseSerializer serializer);
 
   @protected
   void
-      sse_encode_Auto_RefMut_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
-          NesEmulator self, SseSerializer serializer);
+      sse_encode_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
+          NesTexture self, SseSerializer serializer);
 
   @protected
   void
@@ -281,6 +332,11 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           NesEmulator self, SseSerializer serializer);
 
+  @protected
+  void
+      sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          NesInput self, SseSerializer serializer);
+
   @protected
   void
       sse_encode_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
@@ -297,10 +353,17 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
       sse_encode_box_autoadd_Auto_Owned_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           NesTexture self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_box_autoadd_nes_audio_config(
+      NesAudioConfig self, SseSerializer serializer);
+
   @protected
   void sse_encode_box_autoadd_nes_config(
       NesConfig self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_f_32(double self, SseSerializer serializer);
+
   @protected
   void sse_encode_four_player(FourPlayer self, SseSerializer serializer);
 
@@ -313,6 +376,14 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_list_String(List<String> self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_list_list_prim_u_8_strict(
+      List<Uint8List> self, SseSerializer serializer);
+
+  @protected
+  void sse_encode_list_prim_f_32_strict(
+      Float32List self, SseSerializer serializer);
+
   @protected
   void sse_encode_list_prim_u_8_loose(List<int> self, SseSerializer serializer);
 
@@ -320,6 +391,10 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   void sse_encode_list_prim_u_8_strict(
       Uint8List self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_nes_audio_config(
+      NesAudioConfig self, SseSerializer serializer);
+
   @protected
   void sse_encode_nes_button(NesButton self, SseSerializer serializer);
 
@@ -340,6 +415,9 @@ abstract class RustLibApiImplPlatform extends BaseApiImpl<RustLibWire> {
   @protected
   void sse_encode_ram_state(RamState self, SseSerializer serializer);
 
+  @protected
+  void sse_encode_u_32(int self, SseSerializer serializer);
+
   @protected
   void sse_encode_u_8(int self, SseSerializer serializer);
 
@@ -370,6 +448,18 @@ class RustLibWire implements BaseWire {
           .rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
               ptr);
 
+  void rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic ptr) =>
+      wasmModule
+          .rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+              ptr);
+
+  void rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic ptr) =>
+      wasmModule
+          .rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+              ptr);
+
   void rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic ptr) =>
       wasmModule
@@ -403,6 +493,14 @@ class RustLibWasmModule implements WasmModule {
       rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesEmulator(
           dynamic ptr);
 
+  external void
+      rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic ptr);
+
+  external void
+      rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+          dynamic ptr);
+
   external void
       rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
           dynamic ptr);
@@ -108,7 +108,7 @@ class _NesWidgetState extends State<NesWidget> {
   Future<void> loadRom() async {
     final name = widget.dataProvider.name;
     final data = await widget.dataProvider.resolveData();
-    await emulator.loadRom(name: name, data: data);
+    await emulator.loadRom(name: name, data: data, patches: const []);
   }
 
   @override
@@ -1,13 +1,20 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use std::thread;
-use crossbeam_channel::{bounded, Receiver, Sender};
+use anyhow::bail;
+use crossbeam_channel::{bounded, unbounded, Receiver, Sender, TryRecvError};
 use flutter_rust_bridge::{DartFnFuture, frb, spawn};
-use nes_core::common::NesRegion;
+use nes_core::common::{NesRegion, Regional};
 use nes_core::control_deck::{Config, ControlDeck};
 use nes_core::input::{FourPlayer, JoypadBtnState, Player};
 use nes_core::mem::RamState;
 use nes_core::video::VideoFilter;
 use crate::api::texture::NesTexture;
+use crate::audio::AudioPacer;
 use crate::fps::Fps;
+use crate::input::InputQueue;
+use crate::sram::SramWatcher;
 use crate::frb_generated::StreamSink;
 
 impl Into<JoypadBtnState> for NesButton {
@@ -40,6 +47,61 @@ pub enum NesButton {
     Right,
 }
 
+/// Input sent to the running emulation.
+#[frb(opaque)]
+pub enum NesInput {
+    Button {
+        player: Player,
+        button: NesButton,
+        pressed: bool,
+    },
+    ZapperAim {
+        x: i32,
+        y: i32,
+    },
+    ZapperTrigger,
+    FourPlayer(FourPlayer),
+}
+
+impl NesInput {
+    #[frb(sync)]
+    pub fn button(player: Player, button: NesButton, pressed: bool) -> NesInput {
+        NesInput::Button {
+            player,
+            button,
+            pressed,
+        }
+    }
+
+    #[frb(sync)]
+    pub fn zapper_aim(x: i32, y: i32) -> NesInput {
+        NesInput::ZapperAim { x, y }
+    }
+
+    #[frb(sync)]
+    pub fn zapper_trigger() -> NesInput {
+        NesInput::ZapperTrigger
+    }
+
+    #[frb(sync)]
+    pub fn four_player(four_player: FourPlayer) -> NesInput {
+        NesInput::FourPlayer(four_player)
+    }
+
+    pub(crate) fn apply(self, control: &mut ControlDeck) {
+        match self {
+            NesInput::Button {
+                player,
+                button,
+                pressed,
+            } => control.joypad_mut(player).set_button(button.into(), pressed),
+            NesInput::ZapperAim { x, y } => control.aim_zapper(x, y),
+            NesInput::ZapperTrigger => control.trigger_zapper(),
+            NesInput::FourPlayer(four_player) => control.set_four_player(four_player),
+        }
+    }
+}
+
 pub struct NesConfig {
     pub filter: VideoFilter,
     pub region: NesRegion,
@@ -83,67 +145,289 @@ impl Into<Config> for NesConfig {
     }
 }
 
+pub struct NesAudioConfig {
+    /// Output sample rate, e.g. 44100 or 48000.
+    pub sample_rate: u32,
+    /// Size of the Dart side audio buffer in samples. Emulation waits for queued audio to drop
+    /// below half of this before running the next frame.
+    pub buffer_size: u32,
+}
+
+impl NesAudioConfig {
+    #[frb(sync)]
+    pub fn create(sample_rate: u32, buffer_size: u32) -> NesAudioConfig {
+        NesAudioConfig {
+            sample_rate,
+            buffer_size,
+        }
+    }
+}
+
+/// Commands sent to a running emulation loop.
+enum NesCommand {
+    Stop,
+    Pause,
+    Resume,
+    FrameAdvance,
+    SetSpeed(f32),
+    SetFilter(VideoFilter),
+    SetRegion(NesRegion),
+    SetSampleRate(f32),
+    Sram(Sender<Vec<u8>>),
+    LoadSram(Vec<u8>),
+}
+
+/// Run loop state controlled through [`NesCommand`]s.
+struct RunState {
+    paused: bool,
+    // Run a single frame while paused
+    step: bool,
+    speed: f32,
+    // Output sample rate at normal speed
+    sample_rate: f32,
+    fps: Fps,
+    // Input queued for future frames
+    pending_input: Vec<(u32, NesInput)>,
+}
+
+impl RunState {
+    fn new(control: &ControlDeck) -> RunState {
+        RunState {
+            paused: false,
+            step: false,
+            speed: 1.0,
+            sample_rate: control.sample_rate(),
+            fps: Fps::for_region(control.region(), 1.0),
+            pending_input: Vec::new(),
+        }
+    }
+
+    fn update_fps(&mut self, control: &ControlDeck) {
+        self.fps
+            .set_fps(Fps::refresh_rate(control.region()) * self.speed);
+    }
+
+    /// Generate fewer samples per emulated second when running fast, and more when running slow,
+    /// so audio keeps playing in real time with its pitch shifted by the speed.
+    fn update_sample_rate(&self, control: &mut ControlDeck) {
+        control.set_sample_rate(self.sample_rate / self.speed);
+    }
+}
+
+/// The emulation loop takes ownership of the [`ControlDeck`] while running and hands it back once
+/// stopped. Input and config changes reach the running deck through the input queue and command
+/// channel.
 #[frb(opaque)]
 pub struct NesEmulator {
-    control: ControlDeck,
-    tx: Sender<()>,
-    rx: Receiver<()>,
+    // `None` while the emulation loop is running
+    control: Mutex<Option<ControlDeck>>,
+    tx: Sender<NesCommand>,
+    rx: Receiver<NesCommand>,
+    input: InputQueue,
+    audio: Arc<AudioPacer>,
+    sram: SramWatcher,
+    battery_backed: AtomicBool,
 }
 
 impl NesEmulator {
+    pub const MIN_SPEED: f32 = 0.25;
+    pub const MAX_SPEED: f32 = 8.0;
+    /// Longest time to wait for the running emulation loop to answer a request.
+    const REPLY_TIMEOUT: Duration = Duration::from_secs(1);
+
     #[frb(sync)]
     pub fn create() -> NesEmulator {
-        let (tx, rx) = bounded(1);
+        let (tx, rx) = unbounded();
         NesEmulator {
-            control: ControlDeck::new(),
+            control: Mutex::new(Some(ControlDeck::new())),
             tx,
             rx,
+            input: InputQueue::new(),
+            audio: Arc::new(AudioPacer::new()),
+            sram: SramWatcher::new(),
+            battery_backed: AtomicBool::new(false),
         }
     }
     #[frb(sync)]
     pub fn with_config(config: NesConfig) -> NesEmulator {
-        let (tx, rx) = bounded(1);
+        let (tx, rx) = unbounded();
         NesEmulator {
-            control: ControlDeck::with_config(config.into()),
+            control: Mutex::new(Some(ControlDeck::with_config(config.into()))),
             tx,
             rx,
+            input: InputQueue::new(),
+            audio: Arc::new(AudioPacer::new()),
+            sram: SramWatcher::new(),
+            battery_backed: AtomicBool::new(false),
         }
     }
 
-    pub fn load_rom(&mut self, name: String, data: Vec<u8>) -> anyhow::Result<()> {
-        self.control.load_rom(name, data)?;
+    fn control(&self) -> MutexGuard<'_, Option<ControlDeck>> {
+        self.control.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Take the deck for the emulation loop.
+    fn start(&self) -> anyhow::Result<ControlDeck> {
+        match self.control().take() {
+            Some(control) => Ok(control),
+            None => bail!("emulation loop is already running"),
+        }
+    }
+
+    /// Hand the deck back once the emulation loop stops, saving any pending SRAM changes.
+    fn stop(&self, mut control: ControlDeck, state: &RunState) {
+        control.set_sample_rate(state.sample_rate);
+        self.sram.flush(&mut control);
+        *self.control() = Some(control);
+    }
+
+    /// Load a ROM, applying IPS, UPS or BPS `patches` in order first.
+    pub fn load_rom(
+        &self,
+        name: String,
+        data: Vec<u8>,
+        patches: Vec<Vec<u8>>,
+    ) -> anyhow::Result<()> {
+        let mut control = self.control();
+        let Some(control) = control.as_mut() else {
+            bail!("can't load a ROM while the emulation loop is running");
+        };
+        control.load_rom_with_patches(name, data, &patches)?;
+        self.input.set_frame(control.frame_number());
+        self.battery_backed
+            .store(control.cart_battery_backed(), Ordering::Release);
         Ok(())
     }
 
-    fn run_loop(&self, render: impl NesRender) -> anyhow::Result<()> {
-        let mut control = self.control.clone();
-        let rx = self.rx.clone();
-        let mut fps = Fps::new(60.0);
+    /// Apply pending commands, blocking while paused. Returns `false` once the loop should stop.
+    fn poll_commands(&self, control: &mut ControlDeck, state: &mut RunState) -> bool {
         loop {
-            if rx.try_recv().ok().is_some() {
-                break;
+            let command = if state.paused && !state.step {
+                match self.rx.recv() {
+                    Ok(command) => command,
+                    Err(_) => return false,
+                }
+            } else {
+                match self.rx.try_recv() {
+                    Ok(command) => command,
+                    Err(TryRecvError::Empty) => return true,
+                    Err(TryRecvError::Disconnected) => return false,
+                }
+            };
+            if !self.apply_command(command, control, state) {
+                return false;
             }
-            control.clock_frame()?;
-            let data = control.frame_buffer();
-            render.render(data.to_vec());
-            fps.tick();
         }
-        Ok(())
+    }
+
+    /// Drain commands left over from a previous run. Stop, pause and frame advance requests only
+    /// apply to the run they were sent to, while config changes still take effect.
+    fn drain_commands(&self, control: &mut ControlDeck, state: &mut RunState) {
+        while let Ok(command) = self.rx.try_recv() {
+            match command {
+                NesCommand::Stop
+                | NesCommand::Pause
+                | NesCommand::Resume
+                | NesCommand::FrameAdvance => {}
+                command => {
+                    self.apply_command(command, control, state);
+                }
+            }
+        }
+    }
+
+    /// Returns `false` if the loop should stop.
+    fn apply_command(
+        &self,
+        command: NesCommand,
+        control: &mut ControlDeck,
+        state: &mut RunState,
+    ) -> bool {
+        match command {
+            NesCommand::Stop => return false,
+            NesCommand::Pause => state.paused = true,
+            NesCommand::Resume => {
+                state.paused = false;
+                state.fps.tick();
+            }
+            NesCommand::FrameAdvance => {
+                state.paused = true;
+                state.step = true;
+            }
+            NesCommand::SetSpeed(speed) => {
+                state.speed = speed;
+                state.update_fps(control);
+                state.update_sample_rate(control);
+            }
+            NesCommand::SetFilter(filter) => control.set_filter(filter),
+            NesCommand::SetRegion(region) => {
+                control.set_region(region);
+                state.update_fps(control);
+            }
+            NesCommand::SetSampleRate(sample_rate) => {
+                state.sample_rate = sample_rate;
+                state.update_sample_rate(control);
+            }
+            NesCommand::Sram(reply) => {
+                let _ = reply.send(control.sram().to_vec());
+            }
+            NesCommand::LoadSram(sram) => control.load_sram(sram),
+        }
+        true
+    }
+
+    /// Run a single frame, returning its frame buffer.
+    fn run_frame(&self, control: &mut ControlDeck, state: &mut RunState) -> anyhow::Result<Vec<u8>> {
+        self.input.apply(control, &mut state.pending_input);
+        control.clock_frame()?;
+        self.sram.poll(control);
+        state.step = false;
+        Ok(control.frame_buffer().to_vec())
+    }
+
+    fn pace(&self, control: &mut ControlDeck, state: &mut RunState) {
+        if state.paused {
+            control.clear_audio_samples();
+        } else {
+            self.audio.pace(control, &mut state.fps);
+        }
+    }
+
+    fn run_loop(&self, render: impl NesRender) -> anyhow::Result<()> {
+        let mut control = self.start()?;
+        let mut state = RunState::new(&control);
+        self.drain_commands(&mut control, &mut state);
+        let mut result = Ok(());
+        while self.poll_commands(&mut control, &mut state) {
+            match self.run_frame(&mut control, &mut state) {
+                Ok(data) => render.render(data),
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
+            }
+            self.pace(&mut control, &mut state);
+        }
+        self.stop(control, &state);
+        result
     }
     pub async fn run_loop_for_callback(&self, callback: impl Fn(Vec<u8>) -> DartFnFuture<()>) -> anyhow::Result<()> {
-        let mut control = self.control.clone();
-        let rx = self.rx.clone();
-        let mut fps = Fps::new(60.0);
-        loop {
-            if rx.try_recv().ok().is_some() {
-                break;
+        let mut control = self.start()?;
+        let mut state = RunState::new(&control);
+        self.drain_commands(&mut control, &mut state);
+        let mut result = Ok(());
+        while self.poll_commands(&mut control, &mut state) {
+            match self.run_frame(&mut control, &mut state) {
+                Ok(data) => callback(data).await,
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                }
             }
-            control.clock_frame()?;
-            let data = control.frame_buffer();
-            callback(data.to_vec()).await;
-            fps.tick();
+            self.pace(&mut control, &mut state);
         }
-        Ok(())
+        self.stop(control, &state);
+        result
     }
     pub fn run_loop_for_painter(&self, sink: StreamSink<Vec<u8>>) -> anyhow::Result<()> {
         self.run_loop(sink)?;
@@ -154,15 +438,149 @@ impl NesEmulator {
         Ok(())
     }
 
-    pub fn handle_button(&mut self, player: Player, button: NesButton, pressed: bool) {
-        let joypad = &mut self.control.joypad_mut(player.into());
-        joypad.set_button(button.into(), pressed);
+    /// Set the audio sample rate and buffer size.
+    #[frb(sync)]
+    pub fn set_audio_config(&self, config: NesAudioConfig) {
+        let sample_rate = config.sample_rate as f32;
+        match self.control().as_mut() {
+            Some(control) => control.set_sample_rate(sample_rate),
+            None => {
+                let _ = self.tx.send(NesCommand::SetSampleRate(sample_rate));
+            }
+        }
+        self.audio.set_buffer_size(config.buffer_size as usize);
+    }
+
+    /// Stream resampled audio as one chunk of f32 samples per frame. While attached, the run loop
+    /// is paced by audio consumption reported through `audio_consumed`.
+    pub fn audio_stream(&self, sink: StreamSink<Vec<f32>>) {
+        self.audio.set_sink(Some(sink));
+    }
+
+    /// Report the number of samples played back from the audio stream.
+    #[frb(sync)]
+    pub fn audio_consumed(&self, samples: u32) {
+        self.audio.consumed(samples as usize);
+    }
+
+    /// Whether the loaded cart has battery-backed SRAM that should be persisted.
+    #[frb(sync)]
+    pub fn battery_backed(&self) -> bool {
+        self.battery_backed.load(Ordering::Acquire)
+    }
+
+    /// Export battery-backed SRAM, e.g. to save it to disk.
+    pub fn export_sram(&self) -> anyhow::Result<Vec<u8>> {
+        if let Some(control) = self.control().as_ref() {
+            return Ok(control.sram().to_vec());
+        }
+        let (reply_tx, reply_rx) = bounded(1);
+        self.tx.send(NesCommand::Sram(reply_tx))?;
+        Ok(reply_rx.recv_timeout(Self::REPLY_TIMEOUT)?)
+    }
+
+    /// Import battery-backed SRAM previously exported with `export_sram`. Ignored if the loaded
+    /// cart isn't battery-backed.
+    pub fn import_sram(&self, sram: Vec<u8>) -> anyhow::Result<()> {
+        match self.control().as_mut() {
+            Some(control) => control.load_sram(sram),
+            None => self.tx.send(NesCommand::LoadSram(sram))?,
+        }
+        Ok(())
+    }
+
+    /// Stream a copy of battery-backed SRAM whenever the game writes to it, checked about once a
+    /// second, and once more when the emulation loop stops.
+    pub fn sram_stream(&self, sink: StreamSink<Vec<u8>>) {
+        self.sram.set_sink(Some(sink));
+    }
+
+    /// Frame number of the next frame to run.
+    #[frb(sync)]
+    pub fn frame_number(&self) -> u32 {
+        self.input.frame()
+    }
+
+    /// Queue input for the next frame.
+    #[frb(sync)]
+    pub fn send_input(&self, input: NesInput) {
+        self.input.push(input);
+    }
+
+    /// Queue input for the given frame. Input for frames that already ran is applied before the
+    /// next one.
+    #[frb(sync)]
+    pub fn send_input_at(&self, frame: u32, input: NesInput) {
+        self.input.push_at(frame, input);
+    }
+
+    #[frb(sync)]
+    pub fn handle_button(&self, player: Player, button: NesButton, pressed: bool) {
+        self.send_input(NesInput::button(player, button, pressed));
+    }
+
+    #[frb(sync)]
+    pub fn aim_zapper(&self, x: i32, y: i32) {
+        self.send_input(NesInput::zapper_aim(x, y));
+    }
+
+    #[frb(sync)]
+    pub fn trigger_zapper(&self) {
+        self.send_input(NesInput::zapper_trigger());
+    }
+
+    #[frb(sync)]
+    pub fn set_four_player(&self, four_player: FourPlayer) {
+        self.send_input(NesInput::four_player(four_player));
     }
 
     #[frb(sync)]
     pub fn stop_loop(&self) {
-        let tx = self.tx.clone();
-        let _ = tx.send(());
+        let _ = self.tx.send(NesCommand::Stop);
+    }
+
+    #[frb(sync)]
+    pub fn pause(&self) {
+        let _ = self.tx.send(NesCommand::Pause);
+    }
+
+    #[frb(sync)]
+    pub fn resume(&self) {
+        let _ = self.tx.send(NesCommand::Resume);
+    }
+
+    /// Run a single frame and pause.
+    #[frb(sync)]
+    pub fn frame_advance(&self) {
+        let _ = self.tx.send(NesCommand::FrameAdvance);
+    }
+
+    /// Set the emulation speed multiplier, clamped to `0.25..=8.0`. Audio keeps streaming with its
+    /// pitch shifted by the speed.
+    #[frb(sync)]
+    pub fn set_speed(&self, speed: f32) {
+        let speed = speed.clamp(Self::MIN_SPEED, Self::MAX_SPEED);
+        let _ = self.tx.send(NesCommand::SetSpeed(speed));
+    }
+
+    #[frb(sync)]
+    pub fn set_filter(&self, filter: VideoFilter) {
+        match self.control().as_mut() {
+            Some(control) => control.set_filter(filter),
+            None => {
+                let _ = self.tx.send(NesCommand::SetFilter(filter));
+            }
+        }
+    }
+
+    #[frb(sync)]
+    pub fn set_region(&self, region: NesRegion) {
+        match self.control().as_mut() {
+            Some(control) => control.set_region(region),
+            None => {
+                let _ = self.tx.send(NesCommand::SetRegion(region));
+            }
+        }
     }
 }
 
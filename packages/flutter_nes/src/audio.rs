@@ -0,0 +1,88 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+use nes_core::control_deck::ControlDeck;
+use crate::fps::Fps;
+use crate::frb_generated::StreamSink;
+
+/// Streams per-frame audio chunks to Dart and paces emulation by how much of that audio is still
+/// waiting to be played.
+pub struct AudioPacer {
+    sink: Mutex<Option<StreamSink<Vec<f32>>>>,
+    queued: AtomicUsize,
+    buffer_size: AtomicUsize,
+}
+
+impl AudioPacer {
+    pub const DEFAULT_BUFFER_SIZE: usize = 2048;
+    /// Longest time to wait for Dart to consume audio before running the next frame anyway.
+    const MAX_WAIT: Duration = Duration::from_millis(100);
+
+    pub fn new() -> AudioPacer {
+        AudioPacer {
+            sink: Mutex::new(None),
+            queued: AtomicUsize::new(0),
+            buffer_size: AtomicUsize::new(Self::DEFAULT_BUFFER_SIZE),
+        }
+    }
+
+    pub fn set_sink(&self, sink: Option<StreamSink<Vec<f32>>>) {
+        if let Ok(mut current) = self.sink.lock() {
+            *current = sink;
+        }
+        self.queued.store(0, Ordering::Release);
+    }
+
+    pub fn set_buffer_size(&self, buffer_size: usize) {
+        self.buffer_size.store(buffer_size.max(1), Ordering::Release);
+    }
+
+    /// Called as Dart plays back audio samples.
+    pub fn consumed(&self, samples: usize) {
+        let _ = self
+            .queued
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |queued| {
+                Some(queued.saturating_sub(samples))
+            });
+    }
+
+    /// Send the audio for the last frame and wait until it's time to run the next one. While an
+    /// audio stream is attached, emulation runs as fast as Dart consumes audio, otherwise it's
+    /// paced by `fps`.
+    pub fn pace(&self, control: &mut ControlDeck, fps: &mut Fps) {
+        let samples = control.audio_samples().to_vec();
+        control.clear_audio_samples();
+        let streaming = match self.sink.lock() {
+            Ok(mut sink) => match sink.as_ref() {
+                Some(stream) => {
+                    let len = samples.len();
+                    if stream.add(samples).is_ok() {
+                        self.queued.fetch_add(len, Ordering::AcqRel);
+                        true
+                    } else {
+                        *sink = None;
+                        false
+                    }
+                }
+                None => false,
+            },
+            Err(_) => false,
+        };
+        if !streaming {
+            fps.tick();
+            return;
+        }
+
+        // Measured before waiting, since waiting caps the queue at half of `buffer_size`
+        let buffer_size = self.buffer_size.load(Ordering::Acquire);
+        let queued = self.queued.load(Ordering::Acquire);
+        control.set_audio_buffer_fill(queued as f32 / buffer_size as f32);
+
+        let target = buffer_size / 2;
+        let deadline = Instant::now() + Self::MAX_WAIT;
+        while self.queued.load(Ordering::Acquire) > target && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+}
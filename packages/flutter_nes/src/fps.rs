@@ -1,4 +1,5 @@
 use std::{thread, time};
+use nes_core::common::NesRegion;
 
 pub struct Fps {
     last_tick_time: time::Instant,
@@ -15,10 +16,28 @@ impl Fps {
             fps_in_nanos: (1. / fps) * 1_000_000_000.,
         }
     }
+    /// Frame rate of the given region, scaled by `speed`.
+    pub fn for_region(region: NesRegion, speed: f32) -> Fps {
+        Fps::new(Self::refresh_rate(region) * speed)
+    }
+
+    pub fn refresh_rate(region: NesRegion) -> f32 {
+        match region {
+            NesRegion::Ntsc => 60.0988,
+            NesRegion::Pal => 50.007,
+            NesRegion::Dendy => 50.0,
+        }
+    }
+
     pub fn fps(&self) -> f32 {
         self.fps
     }
 
+    pub fn set_fps(&mut self, fps: f32) {
+        self.fps = fps;
+        self.fps_in_nanos = (1. / fps) * 1_000_000_000.;
+    }
+
     pub fn tick(&mut self) -> f32 {
         let t = self.last_tick_time.elapsed();
         let total_nanos = t.as_secs() * 1_000_000_000 + t.subsec_nanos() as u64;
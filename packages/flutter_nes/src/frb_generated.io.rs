@@ -28,6 +28,20 @@ pub extern "C" fn frbgen_flutter_nes_rust_arc_decrement_strong_count_RustOpaque_
     MoiArc::<flutter_rust_bridge::for_generated::rust_async::RwLock<NesEmulator>>::decrement_strong_count(ptr as _);
 }
 
+#[no_mangle]
+pub extern "C" fn frbgen_flutter_nes_rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+    ptr: *const std::ffi::c_void,
+) {
+    MoiArc::<flutter_rust_bridge::for_generated::rust_async::RwLock<NesInput>>::increment_strong_count(ptr as _);
+}
+
+#[no_mangle]
+pub extern "C" fn frbgen_flutter_nes_rust_arc_decrement_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesInput(
+    ptr: *const std::ffi::c_void,
+) {
+    MoiArc::<flutter_rust_bridge::for_generated::rust_async::RwLock<NesInput>>::decrement_strong_count(ptr as _);
+}
+
 #[no_mangle]
 pub extern "C" fn frbgen_flutter_nes_rust_arc_increment_strong_count_RustOpaque_flutter_rust_bridgefor_generatedrust_asyncRwLockNesTexture(
     ptr: *const std::ffi::c_void,
@@ -0,0 +1,64 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use crossbeam_queue::SegQueue;
+use nes_core::control_deck::ControlDeck;
+use crate::api::nes::NesInput;
+
+/// An input event and the frame it applies to.
+struct TimedInput {
+    frame: u32,
+    input: NesInput,
+}
+
+/// Lock-free queue of input events sent from Dart to the emulation thread.
+///
+/// Events are timestamped with the frame they apply to and applied right before that frame runs.
+/// Events for frames that already ran are applied before the next one.
+pub struct InputQueue {
+    queue: SegQueue<TimedInput>,
+    // Frame number of the next frame to run
+    frame: AtomicU32,
+}
+
+impl InputQueue {
+    pub fn new() -> InputQueue {
+        InputQueue {
+            queue: SegQueue::new(),
+            frame: AtomicU32::new(0),
+        }
+    }
+
+    /// Frame number of the next frame to run.
+    pub fn frame(&self) -> u32 {
+        self.frame.load(Ordering::Acquire)
+    }
+
+    pub fn set_frame(&self, frame: u32) {
+        self.frame.store(frame, Ordering::Release);
+    }
+
+    /// Queue `input` for the next frame.
+    pub fn push(&self, input: NesInput) {
+        self.push_at(self.frame(), input);
+    }
+
+    /// Queue `input` for the given frame.
+    pub fn push_at(&self, frame: u32, input: NesInput) {
+        self.queue.push(TimedInput { frame, input });
+    }
+
+    /// Apply queued input up to the next frame of `control`. Input for later frames is moved to
+    /// `pending`, which is owned by the emulation thread.
+    pub fn apply(&self, control: &mut ControlDeck, pending: &mut Vec<(u32, NesInput)>) {
+        let frame = control.frame_number();
+        self.set_frame(frame);
+        while let Some(TimedInput { frame, input }) = self.queue.pop() {
+            pending.push((frame, input));
+        }
+        // Stable sort keeps the order of input within the same frame
+        pending.sort_by_key(|(frame, _)| *frame);
+        let due = pending.partition_point(|(input_frame, _)| *input_frame <= frame);
+        for (_, input) in pending.drain(..due) {
+            input.apply(control);
+        }
+    }
+}
@@ -1,4 +1,7 @@
 mod frb_generated; /* AUTO INJECTED BY flutter_rust_bridge. This line may not be accurate, and you can change it according to your needs. */
 pub mod api;
 mod payload;
-mod fps;
\ No newline at end of file
+mod fps;
+mod audio;
+mod input;
+mod sram;
\ No newline at end of file
@@ -0,0 +1,49 @@
+use std::sync::Mutex;
+use nes_core::control_deck::ControlDeck;
+use crate::frb_generated::StreamSink;
+
+/// Notifies Dart with a copy of battery-backed SRAM after the game writes to it, so saves can be
+/// persisted without polling.
+pub struct SramWatcher {
+    sink: Mutex<Option<StreamSink<Vec<u8>>>>,
+}
+
+impl SramWatcher {
+    /// Number of frames between checks, so a burst of writes results in a single notification.
+    const CHECK_INTERVAL: u32 = 60;
+
+    pub fn new() -> SramWatcher {
+        SramWatcher {
+            sink: Mutex::new(None),
+        }
+    }
+
+    pub fn set_sink(&self, sink: Option<StreamSink<Vec<u8>>>) {
+        if let Ok(mut current) = self.sink.lock() {
+            *current = sink;
+        }
+    }
+
+    /// Called after every frame. Sends SRAM if it changed since the last notification.
+    pub fn poll(&self, control: &mut ControlDeck) {
+        if control.frame_number().is_multiple_of(Self::CHECK_INTERVAL) {
+            self.flush(control);
+        }
+    }
+
+    /// Send SRAM right away if it changed since the last notification.
+    pub fn flush(&self, control: &mut ControlDeck) {
+        if !control.sram_dirty() {
+            return;
+        }
+        if let Ok(mut sink) = self.sink.lock() {
+            if let Some(stream) = sink.as_ref() {
+                if stream.add(control.sram().to_vec()).is_ok() {
+                    control.clear_sram_dirty();
+                } else {
+                    *sink = None;
+                }
+            }
+        }
+    }
+}
@@ -1,29 +1,54 @@
-use crate::audio::window_sinc::WindowSinc;
+use core::f32::consts::PI;
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum FilterKind {
+    LowPass,
+    HighPass,
+}
+
+/// First-order RC filter, as found in the NES analog output chain.
+///
+/// <https://www.nesdev.org/wiki/APU_Mixer>
+#[derive(Debug, Clone)]
 #[must_use]
 pub struct Filter {
-    sinc: WindowSinc,
+    kind: FilterKind,
+    alpha: f32,
+    prev_input: f32,
+    prev_output: f32,
 }
 
 impl Filter {
-    pub fn low_pass(sample_rate: f32, cutoff: f32, bandwidth: f32) -> Self {
-        let sinc = WindowSinc::new(sample_rate, cutoff, bandwidth);
-        Self { sinc }
+    pub fn low_pass(sample_rate: f32, cutoff: f32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff);
+        let dt = 1.0 / sample_rate;
+        Self::new(FilterKind::LowPass, dt / (rc + dt))
     }
 
-    pub fn high_pass(sample_rate: f32, cutoff: f32, bandwidth: f32) -> Self {
-        let mut sinc = WindowSinc::new(sample_rate, cutoff, bandwidth);
-        sinc.spectral_invert();
-        Self { sinc }
+    pub fn high_pass(sample_rate: f32, cutoff: f32) -> Self {
+        let rc = 1.0 / (2.0 * PI * cutoff);
+        let dt = 1.0 / sample_rate;
+        Self::new(FilterKind::HighPass, rc / (rc + dt))
     }
 
-    #[must_use]
-    pub fn apply(&self, sample: f32) -> f32 {
-        let mut out = 0.0;
-        for h in self.sinc.taps() {
-            out += sample * h;
+    const fn new(kind: FilterKind, alpha: f32) -> Self {
+        Self {
+            kind,
+            alpha,
+            prev_input: 0.0,
+            prev_output: 0.0,
         }
-        out
+    }
+
+    #[must_use]
+    pub fn apply(&mut self, sample: f32) -> f32 {
+        let output = match self.kind {
+            FilterKind::LowPass => self.prev_output + self.alpha * (sample - self.prev_output),
+            FilterKind::HighPass => self.alpha * (self.prev_output + sample - self.prev_input),
+        };
+        self.prev_input = sample;
+        self.prev_output = output;
+        output
     }
 }
@@ -1,6 +1,10 @@
 mod filter;
+mod output;
+mod resampler;
 mod window_sinc;
 
+pub use output::AudioOutput;
+
 pub trait Audio {
     fn output(&self) -> f32;
-}
\ No newline at end of file
+}
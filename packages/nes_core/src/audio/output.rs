@@ -0,0 +1,204 @@
+use alloc::vec::Vec;
+use crate::{
+    audio::{filter::Filter, resampler::Resampler},
+    common::NesRegion,
+    cpu::Cpu,
+};
+
+/// NES audio output stage.
+///
+/// Takes one mixed sample per CPU cycle, runs it through the analog filter chain of the console
+/// (two high-pass filters at 90Hz and 440Hz and a low-pass filter at 14kHz) and resamples it to
+/// the selected output sample rate.
+///
+/// Dynamic rate control nudges the output rate by up to [`AudioOutput::MAX_RATE_DELTA`] based on
+/// how full the consumer's buffer is, so that it neither starves nor overflows when the emulation
+/// and audio device clocks drift apart.
+///
+/// <https://www.nesdev.org/wiki/APU_Mixer>
+/// <https://docs.libretro.com/development/cores/dynamic-rate-control/>
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct AudioOutput {
+    clock_rate: f32,
+    sample_rate: f32,
+    rate_delta: f32,
+    filters: [Filter; 3],
+    // Samples averaged together before resampling, to reduce the cost of resampling from the
+    // CPU clock rate
+    decimation: usize,
+    decimation_counter: usize,
+    decimation_sum: f32,
+    resampler: Resampler,
+    samples: Vec<f32>,
+}
+
+impl Default for AudioOutput {
+    fn default() -> Self {
+        Self::new(
+            Cpu::region_clock_rate(NesRegion::default()),
+            Self::DEFAULT_SAMPLE_RATE,
+        )
+    }
+}
+
+impl AudioOutput {
+    pub const DEFAULT_SAMPLE_RATE: f32 = 44_100.0;
+    /// Maximum output rate adjustment applied by dynamic rate control.
+    pub const MAX_RATE_DELTA: f32 = 0.005;
+
+    pub fn new(clock_rate: f32, sample_rate: f32) -> Self {
+        let decimation = ((clock_rate / (sample_rate * 4.0)) as usize).max(1);
+        let resampler = Resampler::new(clock_rate / decimation as f32, sample_rate);
+        Self {
+            clock_rate,
+            sample_rate,
+            rate_delta: 0.0,
+            filters: [
+                Filter::high_pass(clock_rate, 90.0),
+                Filter::high_pass(clock_rate, 440.0),
+                Filter::low_pass(clock_rate, 14_000.0),
+            ],
+            decimation,
+            decimation_counter: 0,
+            decimation_sum: 0.0,
+            resampler,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Output sample rate.
+    #[inline]
+    #[must_use]
+    pub const fn sample_rate(&self) -> f32 {
+        self.sample_rate
+    }
+
+    /// Set the output sample rate, resetting the filter chain.
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        if sample_rate != self.sample_rate {
+            let samples = core::mem::take(&mut self.samples);
+            *self = Self::new(self.clock_rate, sample_rate);
+            self.samples = samples;
+        }
+    }
+
+    /// Set the input clock rate, resetting the filter chain.
+    pub fn set_clock_rate(&mut self, clock_rate: f32) {
+        if clock_rate != self.clock_rate {
+            let samples = core::mem::take(&mut self.samples);
+            *self = Self::new(clock_rate, self.sample_rate);
+            self.samples = samples;
+        }
+    }
+
+    /// Dynamic rate control. `fill` is how full the consumer's buffer is, from `0.0` (empty) to
+    /// `1.0` (full). Output is sped up when the buffer is less than half full, and slowed down
+    /// when it's more than half full.
+    pub fn set_buffer_fill(&mut self, fill: f32) {
+        let fill = fill.clamp(0.0, 1.0);
+        self.rate_delta = (1.0 - 2.0 * fill) * Self::MAX_RATE_DELTA;
+        self.resampler.set_ratio(
+            self.clock_rate / self.decimation as f32,
+            self.sample_rate * (1.0 + self.rate_delta),
+        );
+    }
+
+    /// Add a sample at the CPU clock rate.
+    pub fn push(&mut self, sample: f32) {
+        let sample = self
+            .filters
+            .iter_mut()
+            .fold(sample, |sample, filter| filter.apply(sample));
+        self.decimation_sum += sample;
+        self.decimation_counter += 1;
+        if self.decimation_counter == self.decimation {
+            let sample = self.decimation_sum / self.decimation as f32;
+            self.decimation_counter = 0;
+            self.decimation_sum = 0.0;
+            self.resampler.push(sample, &mut self.samples);
+        }
+    }
+
+    /// Samples at the output sample rate.
+    #[inline]
+    #[must_use]
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn samples_mut(&mut self) -> &mut [f32] {
+        &mut self.samples
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    #[inline]
+    pub fn extend(&mut self, samples: &[f32]) {
+        self.samples.extend_from_slice(samples);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::f32::consts::PI;
+    use super::*;
+
+    const CLOCK_RATE: f32 = 1_789_773.0;
+
+    fn run(output: &mut AudioOutput, seconds: f32, frequency: f32) {
+        let cycles = (CLOCK_RATE * seconds) as usize;
+        for cycle in 0..cycles {
+            let t = cycle as f32 / CLOCK_RATE;
+            output.push(0.5 + 0.25 * (2.0 * PI * frequency * t).sin());
+        }
+    }
+
+    #[test]
+    fn output_rate() {
+        for sample_rate in [44_100.0, 48_000.0] {
+            let mut output = AudioOutput::new(CLOCK_RATE, sample_rate);
+            run(&mut output, 0.5, 1000.0);
+            let expected = sample_rate * 0.5;
+            let len = output.samples().len() as f32;
+            assert!((len - expected).abs() < 64.0, "{len} != {expected}");
+        }
+    }
+
+    #[test]
+    fn filter_chain() {
+        let mut output = AudioOutput::new(CLOCK_RATE, 48_000.0);
+        run(&mut output, 0.5, 1000.0);
+        let samples = &output.samples()[12_000..];
+        // DC offset is removed by the high-pass filters
+        let mean = samples.iter().sum::<f32>() / samples.len() as f32;
+        assert!(mean.abs() < 0.01, "mean: {mean}");
+        // Pass band is preserved
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!((0.2..0.3).contains(&peak), "peak: {peak}");
+
+        // Frequencies above the output Nyquist rate are removed
+        let mut output = AudioOutput::new(CLOCK_RATE, 48_000.0);
+        run(&mut output, 0.5, 40_000.0);
+        let peak = output.samples()[12_000..]
+            .iter()
+            .fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(peak < 0.01, "peak: {peak}");
+    }
+
+    #[test]
+    fn dynamic_rate_control() {
+        let mut starved = AudioOutput::new(CLOCK_RATE, 48_000.0);
+        starved.set_buffer_fill(0.0);
+        run(&mut starved, 0.5, 1000.0);
+        let mut full = AudioOutput::new(CLOCK_RATE, 48_000.0);
+        full.set_buffer_fill(1.0);
+        run(&mut full, 0.5, 1000.0);
+        assert!(starved.samples().len() > full.samples().len() + 200);
+    }
+}
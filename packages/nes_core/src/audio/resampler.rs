@@ -0,0 +1,65 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::audio::window_sinc::WindowSinc;
+
+/// Band-limited resampler using a windowed-sinc kernel evaluated at fractional offsets.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Resampler {
+    sinc: WindowSinc,
+    // Input samples consumed per output sample
+    ratio: f64,
+    buffer: Vec<f32>,
+    // Position of the next output sample within `buffer`
+    pos: f64,
+}
+
+impl Resampler {
+    const ZERO_CROSSINGS: usize = 8;
+    // Fraction of the output Nyquist rate to pass through
+    const PASSBAND: f32 = 0.9;
+
+    pub fn new(input_rate: f32, output_rate: f32) -> Self {
+        let cutoff = (Self::PASSBAND * 0.5 * output_rate.min(input_rate)) / input_rate;
+        let sinc = WindowSinc::new(cutoff, Self::ZERO_CROSSINGS);
+        let half_width = sinc.half_width();
+        Self {
+            sinc,
+            ratio: f64::from(input_rate) / f64::from(output_rate),
+            buffer: vec![0.0; half_width],
+            pos: half_width as f64,
+        }
+    }
+
+    /// Change the resampling ratio without resetting filter state. Used for dynamic rate control.
+    pub fn set_ratio(&mut self, input_rate: f32, output_rate: f32) {
+        self.ratio = f64::from(input_rate) / f64::from(output_rate);
+    }
+
+    /// Add an input sample, pushing any output samples that become available to `out`.
+    pub fn push(&mut self, sample: f32, out: &mut Vec<f32>) {
+        self.buffer.push(sample);
+        let half_width = self.sinc.half_width();
+        while self.pos as usize + half_width < self.buffer.len() {
+            let center = self.pos as usize;
+            let frac = (self.pos - center as f64) as f32;
+            let mut output = 0.0;
+            for (i, sample) in self.buffer[center + 1 - half_width..=center + half_width]
+                .iter()
+                .enumerate()
+            {
+                let t = (i as f32 + 1.0 - half_width as f32) - frac;
+                output += sample * self.sinc.kernel(t);
+            }
+            out.push(output);
+            self.pos += self.ratio;
+        }
+
+        // Discard input samples that are no longer within reach of the kernel
+        let consumed = (self.pos as usize).saturating_sub(half_width);
+        if consumed >= 1024 {
+            self.buffer.drain(..consumed);
+            self.pos -= consumed as f64;
+        }
+    }
+}
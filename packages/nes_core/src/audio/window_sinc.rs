@@ -1,116 +1,79 @@
-use alloc::vec;
 use alloc::vec::Vec;
 use core::f32::consts::PI;
 use num_traits::Float;
 
+/// Blackman windowed-sinc low-pass kernel, precomputed at sub-sample resolution so it can be
+/// evaluated at fractional offsets for band-limited resampling.
+#[derive(Clone)]
 #[must_use]
 pub struct WindowSinc {
-    m: usize,
     fc: f32,
-    bw: f32,
-    taps: Vec<f32>,
-    latency: usize,
+    half_width: usize,
+    table: Vec<f32>,
 }
 
 impl WindowSinc {
-    /// Creates a new [`WindowSinc`] instance.
+    /// Kernel entries per input sample.
+    const RESOLUTION: usize = 64;
+
+    /// Creates a new [`WindowSinc`] instance. `cutoff` is relative to the input sample rate and
+    /// the kernel extends `zero_crossings` sinc lobes to either side.
     ///
     /// # Panics
     ///
-    /// Panics if `cutoff` or `bandwidth` ratio to `sample_rate` is greater than `0.5`.
-    pub fn new(sample_rate: f32, cutoff: f32, bandwidth: f32) -> Self {
-        let fc = cutoff / sample_rate;
-        let bw = bandwidth / sample_rate;
-        assert!(
-            (0.0..=0.5).contains(&fc),
-            "cutoff frequency can not be greater than 1/2 the sampling rate: {cutoff} / {sample_rate}",
-        );
+    /// Panics if `cutoff` is greater than `0.5`.
+    pub fn new(cutoff: f32, zero_crossings: usize) -> Self {
         assert!(
-            (0.0..=0.5).contains(&bw),
-            "transition bandwidth can not be greater than 1/2 the sampling rate: {bandwidth} / {sample_rate}",
+            cutoff > 0.0 && cutoff <= 0.5,
+            "cutoff frequency can not be greater than 1/2 the sampling rate: {cutoff}",
         );
 
-        let m = (4.0 / bw) as usize; // Approximation
-        let latency = m / 2; // Middle sample of FIR
-
-        let mut h = Self::blackman_window(m);
-
-        // Apply window sinc filter
-        let p = 2.0 * PI * fc;
-        for (i, h) in h.iter_mut().enumerate() {
-            let i = i as f32 - latency as f32;
-            *h *= if i == 0.0 { p } else { (p * i).sin() / i };
-        }
-
-        // Normalize
-        let sum_inv = 1.0 / h.iter().sum::<f32>();
-        for h in &mut h {
-            *h *= sum_inv;
+        let half_width = (zero_crossings as f32 / (2.0 * cutoff)).ceil() as usize;
+        let len = half_width * Self::RESOLUTION + 1;
+        let mut table = Vec::with_capacity(len + 1);
+        for i in 0..len {
+            let t = i as f32 / Self::RESOLUTION as f32;
+            let x = 2.0 * PI * cutoff * t;
+            let sinc = if i == 0 { 1.0 } else { x.sin() / x };
+            let w = PI * t / half_width as f32;
+            let window = 0.42 + 0.5 * w.cos() + 0.08 * (2.0 * w).cos();
+            table.push(2.0 * cutoff * sinc * window);
         }
+        // Guard entry for interpolating at the edge
+        table.push(0.0);
 
         Self {
-            m,
-            fc,
-            bw,
-            taps: h,
-            latency,
+            fc: cutoff,
+            half_width,
+            table,
         }
     }
 
-    fn blackman_window(m: usize) -> Vec<f32> {
-        let p1 = 2.0 * PI / m as f32;
-        let p2 = 4.0 * PI / m as f32;
-
-        // Force N to be symmetrical
-        let n = if m % 2 == 0 { m + 1 } else { m };
-        let mut h = vec![0.0; n];
-
-        for (i, h) in h.iter_mut().enumerate() {
-            let i = i as f32;
-            *h = 0.42 - 0.5 * (p1 * i).cos() + 0.8 * (p2 * i).cos();
-        }
-
-        h
-    }
-
+    /// Number of input samples the kernel extends to either side of its center.
     #[must_use]
-    pub const fn taps(&self) -> &Vec<f32> {
-        &self.taps
-    }
-
-    pub fn spectral_invert(&mut self) {
-        let mut i = 1.0;
-        for h in &mut self.taps {
-            i *= -1.0;
-            *h *= i;
-        }
-        self.taps[self.latency] += 1.0;
+    pub const fn half_width(&self) -> usize {
+        self.half_width
     }
 
+    /// Evaluate the kernel `t` input samples away from its center.
     #[must_use]
-    pub fn len(&self) -> usize {
-        self.taps.len()
-    }
-
-    #[must_use]
-    pub fn is_empty(&self) -> bool {
-        self.taps.is_empty()
-    }
-
-    #[must_use]
-    pub const fn latency(&self) -> usize {
-        self.latency
+    pub fn kernel(&self, t: f32) -> f32 {
+        let pos = t.abs() * Self::RESOLUTION as f32;
+        let index = pos as usize;
+        if index + 1 >= self.table.len() {
+            return 0.0;
+        }
+        let frac = pos - index as f32;
+        self.table[index] + (self.table[index + 1] - self.table[index]) * frac
     }
 }
 
 impl core::fmt::Debug for WindowSinc {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("WindowSinc")
-            .field("m", &self.m)
             .field("fc", &self.fc)
-            .field("bw", &self.bw)
-            .field("taps_len", &self.taps.len())
-            .field("latency", &self.latency)
+            .field("half_width", &self.half_width)
+            .field("table_len", &self.table.len())
             .finish()
     }
 }
@@ -4,8 +4,9 @@ use alloc::vec;
 use alloc::vec::Vec;
 use crate::{
     apu::{Apu, ApuRegisters, Channel},
-    audio::Audio,
-    cart::Cart,
+    audio::{Audio, AudioOutput},
+    cart::{Cart, TRAINER_OFFSET},
+    cdl::{self, Cdl, CdlError, PrgFlags},
     common::{Clock, ResetKind, NesRegion, Regional, Reset},
     cpu::{Cpu, Irq},
     genie::GenieCode,
@@ -52,15 +53,26 @@ pub struct Bus {
     region: NesRegion,
     pub ram_state: RamState,
     battery_backed: bool,
+    chr_battery_backed: bool,
     prg_ram: Vec<u8>,
+    // NES 2.0 volatile PRG-RAM before this offset isn't saved
+    prg_nvram_offset: usize,
     prg_ram_protect: bool,
+    #[serde(skip)]
+    sram_dirty: bool, // PRG-RAM written since last cleared
+    #[serde(skip)]
     prg_rom: Vec<u8>,
+    #[serde(skip)]
+    prg_cdl: Option<Vec<u8>>, // Code/data log flags for PRG-ROM
+    #[serde(skip)]
+    trainer: Vec<u8>,
     ppu: Ppu,
     apu: Apu,
     input: Input,
     oam_dma: bool,
     oam_dma_addr: u16,
-    audio_samples: Vec<f32>,
+    #[serde(skip)]
+    audio: AudioOutput,
     genie_codes: BTreeMap<u16, GenieCode>,
     cycle: usize, // Total number of CPU cycles ran
     open_bus: u8,
@@ -83,15 +95,20 @@ impl Bus {
             region: NesRegion::default(),
             ram_state,
             battery_backed: false,
+            chr_battery_backed: false,
             prg_ram: vec![],
+            prg_nvram_offset: 0,
             prg_ram_protect: false,
+            sram_dirty: false,
             prg_rom: vec![],
+            prg_cdl: None,
+            trainer: vec![],
             ppu: Ppu::new(),
             apu: Apu::new(),
             input: Input::new(),
             oam_dma: false,
             oam_dma_addr: 0x0000,
-            audio_samples: vec![],
+            audio: AudioOutput::default(),
             genie_codes: BTreeMap::new(),
             cycle: 0,
             open_bus: 0x00,
@@ -155,17 +172,40 @@ impl Bus {
 
     #[inline]
     pub fn load_cart(&mut self, cart: Cart) {
-        // Start with ~20ms of audio capacity
-        self.audio_samples
-            .resize((Cpu::region_clock_rate(cart.region()) * 0.02) as usize, 0.0);
+        self.audio.clear();
         self.battery_backed = cart.battery_backed();
+        self.chr_battery_backed = cart.chr_battery_backed();
+        self.prg_nvram_offset = cart.prg_nvram_offset();
+        self.input.connect_expansion_device(cart.expansion_device());
         self.set_region(cart.region());
         self.load_prg_rom(cart.prg_rom);
         self.load_prg_ram(cart.prg_ram);
+        self.trainer = cart.trainer;
         self.ppu.load_chr_rom(cart.chr_rom);
         self.ppu.load_chr_ram(cart.chr_ram);
         self.ppu.load_ex_ram(cart.ex_ram);
         self.ppu.load_mapper(cart.mapper);
+        if self.cdl_enabled() {
+            // Start over with a log sized for the new cartridge
+            self.start_cdl();
+        }
+    }
+
+    /// Re-initialize all RAM using `ram_state` as if the console was powered on. Battery-backed
+    /// PRG-RAM is cleared as well.
+    pub fn power_on(&mut self, ram_state: RamState) {
+        self.ram_state = ram_state;
+        RamState::fill(&mut self.wram, ram_state);
+        RamState::fill(&mut self.prg_ram, ram_state);
+        // Copiers load the trainer on every power on
+        if let Some(prg_ram) = self
+            .prg_ram
+            .get_mut(TRAINER_OFFSET..TRAINER_OFFSET + self.trainer.len())
+        {
+            prg_ram.copy_from_slice(&self.trainer);
+        }
+        self.ppu.power_on(ram_state);
+        self.ppu.mapper_mut().load_sram(&self.prg_ram);
     }
 
     #[inline]
@@ -173,9 +213,21 @@ impl Bus {
         self.prg_rom = prg_rom;
     }
 
+    /// Swaps PRG-ROM, CHR-ROM, the trainer, code/data logs, PPU debug views and audio output with
+    /// another `Bus`. Used to restore save states, which don't serialize them.
+    #[inline]
+    pub(crate) fn swap_unserialized(&mut self, other: &mut Self) {
+        core::mem::swap(&mut self.prg_rom, &mut other.prg_rom);
+        core::mem::swap(&mut self.trainer, &mut other.trainer);
+        core::mem::swap(&mut self.prg_cdl, &mut other.prg_cdl);
+        core::mem::swap(&mut self.audio, &mut other.audio);
+        self.ppu.swap_unserialized(&mut other.ppu);
+    }
+
     #[inline]
     pub fn load_prg_ram(&mut self, prg_ram: Vec<u8>) {
         self.prg_ram = prg_ram;
+        self.sram_dirty = false;
     }
 
     #[inline]
@@ -184,19 +236,127 @@ impl Bus {
         self.battery_backed
     }
 
+    /// Battery-backed PRG-RAM, excluding any NES 2.0 volatile PRG-RAM.
     #[inline]
     #[must_use]
     pub fn sram(&self) -> &[u8] {
-        &self.prg_ram
+        self.prg_ram.get(self.prg_nvram_offset..).unwrap_or_default()
     }
 
-    #[inline]
+    /// Load battery-backed PRG-RAM. If `sram` is smaller than the cartridge PRG-NVRAM, the
+    /// remainder is kept as is, and if it's larger the excess is ignored.
     pub fn load_sram(&mut self, sram: Vec<u8>) {
         if self.cart_battery_backed() {
-            self.prg_ram = sram;
+            if let Some(nvram) = self.prg_ram.get_mut(self.prg_nvram_offset..) {
+                let len = sram.len().min(nvram.len());
+                nvram[..len].copy_from_slice(&sram[..len]);
+            }
+            self.sram_dirty = false;
+            self.ppu.mapper_mut().load_sram(&self.prg_ram);
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn cart_chr_battery_backed(&self) -> bool {
+        self.chr_battery_backed
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_sram(&self) -> &[u8] {
+        if self.chr_battery_backed {
+            self.ppu.chr_ram()
+        } else {
+            &[]
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn cdl_enabled(&self) -> bool {
+        self.prg_cdl.is_some()
+    }
+
+    /// Start logging PRG-ROM and CHR-ROM accesses to an empty code/data log, or stop and discard
+    /// the log. Enabling keeps any log already in progress.
+    pub fn enable_cdl(&mut self, enabled: bool) {
+        if !enabled {
+            self.prg_cdl = None;
+            self.ppu.set_chr_cdl(None);
+        } else if !self.cdl_enabled() {
+            self.start_cdl();
+        }
+    }
+
+    /// A copy of the code/data log, if logging is enabled.
+    pub fn cdl(&self) -> Option<Cdl> {
+        let prg = self.prg_cdl.as_ref()?;
+        let chr = self.ppu.chr_cdl().unwrap_or_default();
+        Some(Cdl::from_parts(prg.clone(), chr.to_vec()))
+    }
+
+    /// Continue logging from an FCEUX `.cdl` file.
+    ///
+    /// # Errors
+    ///
+    /// If the log doesn't match the loaded PRG-ROM and CHR-ROM sizes, an error is returned.
+    pub fn load_cdl(&mut self, data: &[u8]) -> Result<(), CdlError> {
+        let cdl = Cdl::from_bytes(data, self.prg_rom.len(), self.ppu.chr_rom().len())?;
+        let (prg, chr) = cdl.into_parts();
+        self.prg_cdl = Some(prg);
+        self.ppu.set_chr_cdl(Some(chr));
+        Ok(())
+    }
+
+    fn start_cdl(&mut self) {
+        self.prg_cdl = Some(vec![0x00; self.prg_rom.len()]);
+        self.ppu.set_chr_cdl(Some(vec![0x00; self.ppu.chr_rom().len()]));
+    }
+
+    /// Add `flags` to the code/data log for the PRG-ROM byte mapped at `addr`, if any, without
+    /// reading it.
+    pub fn log_prg(&mut self, addr: u16, flags: PrgFlags) {
+        if self.cdl_enabled() {
+            if let MappedRead::PrgRom(offset) = self.mapper().map_peek(addr) {
+                if let Some(prg_cdl) = &mut self.prg_cdl {
+                    cdl::log_prg(prg_cdl, offset, addr, flags);
+                }
+            }
+        }
+    }
+
+    /// Read a DMC sample byte for DMA, logging it as PCM data.
+    pub fn read_dmc(&mut self, addr: u16) -> u8 {
+        self.log_prg(addr, PrgFlags::PCM);
+        self.read(addr, Access::Dummy)
+    }
+
+    /// Load battery-backed CHR-RAM. Ignored if the cartridge has no battery-backed CHR-RAM or the
+    /// size doesn't match.
+    pub fn load_chr_sram(&mut self, chr_sram: Vec<u8>) {
+        if self.chr_battery_backed && chr_sram.len() == self.ppu.chr_ram().len() {
+            self.ppu.load_chr_ram(chr_sram);
         }
     }
 
+    /// Whether PRG-RAM has changed since the last call to [`Bus::clear_sram_dirty`].
+    #[inline]
+    #[must_use]
+    pub const fn sram_dirty(&self) -> bool {
+        self.sram_dirty
+    }
+
+    #[inline]
+    pub fn clear_sram_dirty(&mut self) {
+        self.sram_dirty = false;
+    }
+
+    #[inline]
+    pub(crate) fn mark_sram_dirty(&mut self) {
+        self.sram_dirty = true;
+    }
+
     #[inline]
     #[must_use]
     pub fn wram(&self) -> &[u8] {
@@ -229,18 +389,45 @@ impl Bus {
 
     #[inline]
     fn mix_audio(&mut self, sample1: f32, sample2: f32) {
-        self.audio_samples.push(sample1 + sample2);
+        self.audio.push(sample1 + sample2);
     }
 
     #[inline]
     #[must_use]
     pub fn audio_samples(&self) -> &[f32] {
-        &self.audio_samples
+        self.audio.samples()
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) fn audio_samples_mut(&mut self) -> &mut [f32] {
+        self.audio.samples_mut()
     }
 
     #[inline]
     pub fn clear_audio_samples(&mut self) {
-        self.audio_samples.clear();
+        self.audio.clear();
+    }
+
+    #[inline]
+    pub(crate) fn extend_audio_samples(&mut self, samples: &[f32]) {
+        self.audio.extend(samples);
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn sample_rate(&self) -> f32 {
+        self.audio.sample_rate()
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.audio.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    pub fn set_audio_buffer_fill(&mut self, fill: f32) {
+        self.audio.set_buffer_fill(fill);
     }
 
     #[inline]
@@ -347,7 +534,12 @@ impl Clock for Bus {
         let apu_output = self.apu.output();
         let mapper_output = match self.mapper() {
             Mapper::Exrom(ref exrom) => exrom.output(),
+            Mapper::Namco163(ref namco163) => namco163.output(),
+            Mapper::Fds(ref fds) => fds.output(),
             Mapper::Vrc6(ref vrc6) => vrc6.output(),
+            Mapper::Fme7(ref fme7) => fme7.output(),
+            Mapper::Vrc7(ref vrc7) => vrc7.output(),
+            Mapper::NsfPlayer(ref nsf_player) => nsf_player.output(),
             _ => 0.0,
         };
         self.mix_audio(apu_output, mapper_output);
@@ -361,14 +553,24 @@ impl Clock for Bus {
 }
 
 impl Mem for Bus {
-    fn read(&mut self, addr: u16, _access: Access) -> u8 {
+    fn read(&mut self, addr: u16, access: Access) -> u8 {
         let val = match addr {
             0x0000..=0x07FF => self.wram[addr as usize],
             0x4020..=0xFFFF => {
                 let val = match self.mapper_mut().map_read(addr) {
                     MappedRead::Data(val) => val,
                     MappedRead::PrgRam(addr) => self.prg_ram[addr],
-                    MappedRead::PrgRom(addr) => self.prg_rom[addr],
+                    MappedRead::PrgRom(offset) => {
+                        if let Some(prg_cdl) = &mut self.prg_cdl {
+                            let flags = match access {
+                                Access::Execute => PrgFlags::CODE,
+                                Access::Read => PrgFlags::DATA,
+                                Access::Write | Access::Dummy => PrgFlags::empty(),
+                            };
+                            cdl::log_prg(prg_cdl, offset, addr, flags);
+                        }
+                        self.prg_rom[offset]
+                    }
                     _ => self.open_bus,
                 };
                 self.genie_read(addr, val)
@@ -380,8 +582,8 @@ impl Mem for Bus {
             0x4016 => self.input.read(Player::One, &self.ppu),
             0x4017 => self.input.read(Player::Two, &self.ppu),
             0x2000 | 0x2001 | 0x2003 | 0x2005 | 0x2006 => self.ppu.open_bus(),
-            0x0800..=0x1FFF => self.read(addr & 0x07FF, _access), // WRAM Mirrors
-            0x2008..=0x3FFF => self.read(addr & 0x2007, _access), // Ppu Mirrors
+            0x0800..=0x1FFF => self.read(addr & 0x07FF, access), // WRAM Mirrors
+            0x2008..=0x3FFF => self.read(addr & 0x2007, access), // Ppu Mirrors
             _ => self.open_bus,
         };
         self.open_bus = val;
@@ -420,7 +622,12 @@ impl Mem for Bus {
             0x4020..=0xFFFF => {
                 let prg_ram_enabled = !self.prg_ram.is_empty() && !self.prg_ram_protect;
                 match self.mapper_mut().map_write(addr, val) {
-                    MappedWrite::PrgRam(addr, val) if prg_ram_enabled => self.prg_ram[addr] = val,
+                    MappedWrite::PrgRam(addr, val)
+                        if prg_ram_enabled && self.prg_ram[addr] != val =>
+                    {
+                        self.prg_ram[addr] = val;
+                        self.sram_dirty |= addr >= self.prg_nvram_offset;
+                    }
                     MappedWrite::PrgRamProtect(protect) => self.prg_ram_protect = protect,
                     _ => (),
                 }
@@ -479,6 +686,7 @@ impl Regional for Bus {
         self.region = region;
         self.ppu.set_region(region);
         self.apu.set_region(region);
+        self.audio.set_clock_rate(Cpu::region_clock_rate(region));
     }
 }
 
@@ -499,6 +707,7 @@ impl core::fmt::Debug for Bus {
             .field("region", &self.region)
             .field("ram_state", &self.ram_state)
             .field("battery_backed", &self.battery_backed)
+            .field("chr_battery_backed", &self.chr_battery_backed)
             .field("prg_ram_len", &self.prg_ram.len())
             .field("prg_ram_protect", &self.prg_ram_protect)
             .field("prg_rom_len", &self.prg_rom.len())
@@ -507,7 +716,7 @@ impl core::fmt::Debug for Bus {
             .field("input", &self.input)
             .field("oam_dma", &self.oam_dma)
             .field("oam_dma_addr", &self.oam_dma_addr)
-            .field("audio_samples_len", &self.audio_samples.len())
+            .field("audio", &self.audio)
             .field("genie_codes", &self.genie_codes.values())
             .field("cycle", &self.cycle)
             .field("open_bus", &format_args!("${:02X}", &self.open_bus))
@@ -520,7 +729,7 @@ mod test {
     use alloc::string::ToString;
     use alloc::vec;
     use super::*;
-    use crate::cart::Cart;
+    use crate::{cart::Cart, cdl::ChrFlags};
 
     #[test]
     fn load_cart_values() {
@@ -551,6 +760,28 @@ mod test {
         assert_eq!(bus.cart_battery_backed(), expected_battery, "battery");
     }
 
+    #[test]
+    fn load_cart_nes2() {
+        let mut bus = Bus::default();
+        // NES 2.0 NROM, 8K CHR-NVRAM, Four Score
+        let mut rom = vec![
+            0x4E, 0x45, 0x53, 0x1A, 0x01, 0x00, 0x02, 0x08, 0x00, 0x00, 0x00, 0x70, 0x00, 0x00,
+            0x00, 0x02,
+        ];
+        rom.resize(16 + 0x4000, 0x00);
+        let cart = Cart::from_rom("load_cart_nes2".to_string(), rom, RamState::default())
+            .expect("valid cart");
+        bus.load_cart(cart);
+
+        assert_eq!(bus.input.four_player(), FourPlayer::FourScore, "four score");
+        assert!(bus.cart_chr_battery_backed(), "chr battery");
+        assert_eq!(bus.chr_sram().len(), 0x2000);
+        bus.load_chr_sram(vec![0x55; 0x2000]);
+        assert_eq!(bus.chr_sram(), [0x55; 0x2000]);
+        bus.load_chr_sram(vec![0xAA; 0x1000]);
+        assert_eq!(bus.chr_sram(), [0x55; 0x2000], "mismatched size ignored");
+    }
+
     #[test]
     fn load_cart_chr_rom() {
         let mut bus = Bus::default();
@@ -628,6 +859,131 @@ mod test {
         assert_eq!(bus.read(addr, Access::Read), orig_value, "read orig value");
     }
 
+    #[test]
+    fn sram_dirty() {
+        let mut bus = Bus::default();
+        let mut cart = Cart::empty();
+        cart.prg_ram = vec![0x00; 0x2000];
+        bus.load_cart(cart);
+        assert!(!bus.sram_dirty(), "clean after load");
+
+        bus.write(0x6000, 0x00, Access::Write);
+        assert!(!bus.sram_dirty(), "unchanged write");
+        bus.write(0x6000, 0x66, Access::Write);
+        assert!(bus.sram_dirty(), "dirty after write");
+
+        bus.clear_sram_dirty();
+        assert!(!bus.sram_dirty(), "cleared");
+        bus.write(0x0000, 0x66, Access::Write);
+        assert!(!bus.sram_dirty(), "wram write");
+    }
+
+    #[test]
+    fn load_sram() {
+        let mut bus = Bus::default();
+        let mut cart = Cart::empty();
+        cart.prg_ram = vec![0x11; 0x2080];
+        bus.load_cart(cart);
+        bus.battery_backed = true;
+
+        bus.load_sram(vec![0x22; 0x2000]);
+        assert_eq!(bus.sram().len(), 0x2080);
+        assert_eq!(bus.sram()[0x1FFF], 0x22);
+        assert_eq!(bus.sram()[0x2000], 0x11, "remainder kept");
+    }
+
+    #[test]
+    fn load_sram_nes2() {
+        let mut bus = Bus::default();
+        // NES 2.0 MMC1, 8K PRG-RAM and 8K PRG-NVRAM
+        let mut rom = vec![
+            0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01, 0x12, 0x08, 0x00, 0x00, 0x77, 0x00, 0x00, 0x00,
+            0x00, 0x00,
+        ];
+        rom.resize(16 + 0x8000 + 0x2000, 0x00);
+        let cart = Cart::from_rom("load_sram_nes2".to_string(), rom, RamState::AllZeros)
+            .expect("valid cart");
+        bus.load_cart(cart);
+
+        assert_eq!(bus.sram().len(), 0x2000, "nvram only");
+        bus.load_sram(vec![0x22; 0x4000]);
+        assert_eq!(bus.sram(), [0x22; 0x2000]);
+        assert_eq!(bus.prg_ram[..0x2000], [0x00; 0x2000], "volatile ram untouched");
+    }
+
+    #[test]
+    fn expansion_device_reset() {
+        let mut bus = Bus::default();
+        // NES 2.0 NROM, Four Score
+        let mut rom = vec![
+            0x4E, 0x45, 0x53, 0x1A, 0x01, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x02,
+        ];
+        rom.resize(16 + 0x4000, 0x00);
+        let cart = Cart::from_rom("four_score".to_string(), rom, RamState::default())
+            .expect("valid cart");
+        bus.load_cart(cart);
+        assert_eq!(bus.input.four_player(), FourPlayer::FourScore);
+
+        bus.load_cart(Cart::empty());
+        assert_eq!(bus.input.four_player(), FourPlayer::Disabled, "reset for next cart");
+    }
+
+    #[test]
+    fn code_data_log() {
+        let mut bus = Bus::default();
+        bus.load_cart(Cart::empty());
+        assert!(bus.cdl().is_none(), "disabled by default");
+        bus.enable_cdl(true);
+
+        bus.read(0x8000, Access::Execute);
+        bus.read(0xC001, Access::Read);
+        bus.read(0x8002, Access::Dummy);
+        bus.read_dmc(0xC003);
+        bus.read(0x8004, Access::Read);
+        bus.log_prg(0x8004, PrgFlags::INDIRECT_DATA);
+        bus.write(0x2006, 0x00, Access::Write);
+        bus.write(0x2006, 0x10, Access::Write);
+        bus.read(0x2007, Access::Read);
+
+        let cdl = bus.cdl().expect("cdl enabled");
+        assert_eq!(cdl.prg().len(), 0x4000);
+        assert_eq!(cdl.prg_flags(0), Some(PrgFlags::CODE), "code");
+        assert_eq!(
+            cdl.prg_flags(1),
+            Some(PrgFlags::DATA | PrgFlags::from_bits_retain(0x08)),
+            "data in $C000 bank"
+        );
+        assert_eq!(cdl.prg_flags(2), Some(PrgFlags::empty()), "dummy read");
+        assert_eq!(
+            cdl.prg_flags(3),
+            Some(PrgFlags::PCM | PrgFlags::from_bits_retain(0x08)),
+            "dmc sample"
+        );
+        assert_eq!(
+            cdl.prg_flags(4),
+            Some(PrgFlags::DATA | PrgFlags::INDIRECT_DATA),
+            "indirect data"
+        );
+        assert_eq!(cdl.chr_flags(0x0010), Some(ChrFlags::READ), "ppudata read");
+
+        let mut data = cdl.to_bytes();
+        assert_eq!(
+            bus.load_cdl(&data[1..]),
+            Err(CdlError::SizeMismatch {
+                expected: 0x6000,
+                found: 0x5FFF
+            })
+        );
+        data[0x4000] = ChrFlags::DRAWN.bits();
+        bus.load_cdl(&data).expect("valid cdl");
+        let cdl = bus.cdl().expect("cdl enabled");
+        assert_eq!(cdl.chr_flags(0), Some(ChrFlags::DRAWN), "loaded");
+
+        bus.enable_cdl(false);
+        assert!(bus.cdl().is_none(), "disabled");
+    }
+
     #[test]
     fn clock() {
         let mut bus = Bus::default();
@@ -3,19 +3,29 @@ use alloc::string::{String, ToString};
 use alloc::vec::Vec;
 use anyhow::{anyhow, bail, Context};
 use crate::{
+    checksum::{Crc32, Sha1},
     common::{NesRegion, Regional},
+    db::GameInfo,
+    fds::FdsImage,
     mapper::{
-        m024_m026_vrc6::Vrc6Revision, Axrom, Bf909x, Cnrom, Exrom, Gxrom, Mapper, Mmc1Revision,
-        Nrom, Pxrom, Sxrom, Txrom, Uxrom, Vrc6,
+        m024_m026_vrc6::Vrc6Revision, Axrom, Bf909x, Cnrom, Exrom, Fds, Fme7, Fxrom, Gxrom,
+        Mapper, Mmc1Revision, Namco163, Namco163Volume, Nrom, NsfPlayer, Pxrom, Sxrom, Txrom,
+        Uxrom, Vrc4, Vrc4Revision, Vrc6, Vrc7, Vrc7Revision,
     },
     mem::RamState,
+    nsf::Nsf,
     ppu::Mirroring,
+    unif::{self, Unif, UnifError},
 };
 use anyhow::Result;
 use core::convert::TryInto;
 
 const PRG_ROM_BANK_SIZE: usize = 0x4000;
 const CHR_ROM_BANK_SIZE: usize = 0x2000;
+const FDS_BIOS_SIZE: usize = 0x2000;
+const TRAINER_SIZE: usize = 0x0200;
+// Trainers are loaded at $7000 in PRG-RAM mapped at $6000
+pub(crate) const TRAINER_OFFSET: usize = 0x1000;
 
 /// An NES cartridge.
 #[derive(Default, Clone)]
@@ -35,6 +45,10 @@ pub struct Cart {
     pub(crate) prg_rom: Vec<u8>,
     // Program ROM
     pub(crate) prg_ram: Vec<u8>, // Program RAM
+    pub(crate) trainer: Vec<u8>, // 512-byte trainer loaded into PRG-RAM at $7000
+    db_info: Option<&'static GameInfo>,
+    // Single screen mirroring from a UNIF `MIRR` chunk, which the header can't express
+    single_screen: Option<Mirroring>,
 }
 
 impl Cart {
@@ -50,6 +64,9 @@ impl Cart {
             ex_ram: vec![],
             prg_rom: vec![0x00; PRG_ROM_BANK_SIZE],
             prg_ram: vec![],
+            trainer: vec![],
+            db_info: None,
+            single_screen: None,
         };
         empty.mapper = Nrom::load(&mut empty);
         empty
@@ -63,29 +80,54 @@ impl Cart {
     /// returned.
     pub fn from_rom(name: String, rom_data: Vec<u8>, ram_state: RamState) -> Result<Self>
     {
-        let header = NesHeader::load(&rom_data[0..16])?;
-        let prg_rom_len = (header.prg_rom_banks as usize) * PRG_ROM_BANK_SIZE;
-        let mut prg_rom = rom_data[16..16 + prg_rom_len].to_vec();
-        let prg_ram_size = Self::calculate_ram_size(header.prg_ram_shift).context("prg_ram")?;
-        let mut prg_ram = vec![0x00; prg_ram_size];
-        RamState::fill(&mut prg_ram, ram_state);
-        let chr_rom_len = (header.chr_rom_banks as usize) * CHR_ROM_BANK_SIZE;
-        let mut chr_rom = if header.chr_rom_banks > 0 {
-            rom_data[16 + prg_rom_len..16 + prg_rom_len + chr_rom_len].to_vec()
+        if rom_data.starts_with(&unif::MAGIC) {
+            return Self::from_unif(name, &rom_data, ram_state).map_err(|err| anyhow!("{err}"));
+        }
+        let mut header = NesHeader::load(rom_data.get(0..16).context("truncated header")?)?;
+        let trainer_len = if header.has_trainer() { TRAINER_SIZE } else { 0 };
+        let trainer = rom_data.get(16..16 + trainer_len).context("truncated trainer")?.to_vec();
+        let prg_rom_start = 16 + trainer_len;
+        let prg_rom_len = header.prg_rom_size().unwrap_or_default();
+        let prg_rom = rom_data
+            .get(prg_rom_start..prg_rom_start + prg_rom_len)
+            .context("truncated prg-rom")?
+            .to_vec();
+        let chr_rom_len = header.chr_rom_size().unwrap_or_default();
+        let chr_rom = if chr_rom_len > 0 {
+            let chr_rom_start = prg_rom_start + prg_rom_len;
+            rom_data
+                .get(chr_rom_start..chr_rom_start + chr_rom_len)
+                .context("truncated chr-rom")?
+                .to_vec()
         } else {
-            vec![0x00; chr_rom_len]
+            vec![]
         };
+
+        let mut crc = Crc32::new();
+        let mut sha = Sha1::new();
+        for data in [&prg_rom, &chr_rom] {
+            crc.update(data);
+            sha.update(data);
+        }
+        let db_info = GameInfo::lookup(crc.finish(), &sha.finish());
+        if let Some(info) = db_info {
+            log::info!("Found `{}` in game database", info.title);
+            info.correct_header(&mut header);
+        }
+
+        let prg_ram_size = header.prg_ram_size() + header.prg_nvram_size();
+        let mut prg_ram = vec![0x00; prg_ram_size];
+        RamState::fill(&mut prg_ram, ram_state);
         let mut chr_ram = vec![];
         if chr_rom.is_empty() {
-            let chr_ram_size = Self::calculate_ram_size(header.chr_ram_shift).context("chr_ram")?;
-            chr_ram.resize(chr_ram_size, 0x00);
+            chr_ram.resize(header.chr_ram_size() + header.chr_nvram_size(), 0x00);
             RamState::fill(&mut chr_ram, ram_state);
         }
 
         let mut cart = Self {
             name,
             header,
-            region: NesRegion::default(),
+            region: header.region(),
             ram_state,
             mapper: Mapper::none(),
             chr_rom,
@@ -93,29 +135,185 @@ impl Cart {
             ex_ram: vec![],
             prg_rom,
             prg_ram,
+            trainer,
+            db_info,
+            single_screen: None,
         };
-        cart.mapper = match cart.header.mapper_num {
-            0 => Nrom::load(&mut cart),
-            1 => Sxrom::load(&mut cart, Mmc1Revision::BC),
-            2 => Uxrom::load(&mut cart),
-            3 => Cnrom::load(&mut cart),
-            4 => Txrom::load(&mut cart),
-            5 => Exrom::load(&mut cart),
-            7 => Axrom::load(&mut cart),
-            9 => Pxrom::load(&mut cart),
-            24 => Vrc6::load(&mut cart, Vrc6Revision::A),
-            26 => Vrc6::load(&mut cart, Vrc6Revision::B),
-            66 => Gxrom::load(&mut cart),
-            71 => Bf909x::load(&mut cart),
-            155 => Sxrom::load(&mut cart, Mmc1Revision::A),
+        if !cart.trainer.is_empty() {
+            // Copiers provide 8K PRG-RAM for the trainer, even on boards without it
+            cart.add_prg_ram(0x2000);
+        }
+        cart.mapper = Self::load_mapper(&mut cart)?;
+        cart.load_trainer();
+
+        log::info!("Loaded `{}`", cart);
+        log::debug!("{:?}", cart);
+        Ok(cart)
+    }
+
+    /// Load `Cart` from a UNIF ROM, using the mapper implementing its board.
+    ///
+    /// # Errors
+    ///
+    /// If the UNIF data is invalid or the board is unsupported, then an error is returned.
+    pub fn from_unif(name: String, data: &[u8], ram_state: RamState) -> Result<Self, UnifError> {
+        let unif = Unif::load(data)?;
+        let mut flags = 0x00;
+        let mut single_screen = None;
+        match unif.mirroring {
+            Some(Mirroring::Vertical) => flags |= 0x01,
+            Some(Mirroring::FourScreen) => flags |= 0x08,
+            Some(mirroring @ (Mirroring::SingleScreenA | Mirroring::SingleScreenB)) => {
+                single_screen = Some(mirroring);
+            }
+            _ => (),
+        }
+        if unif.battery {
+            flags |= 0x02;
+        }
+        let header = NesHeader {
+            version: 1,
+            mapper_num: unif.mapper_num,
+            submapper_num: unif.submapper_num,
+            flags,
+            prg_rom_banks: (unif.prg_rom.len() / PRG_ROM_BANK_SIZE) as u16,
+            chr_rom_banks: (unif.chr_rom.len() / CHR_ROM_BANK_SIZE) as u16,
+            ..NesHeader::default()
+        };
+        let mut cart = Self {
+            name,
+            header,
+            region: unif.region,
+            ram_state,
+            mapper: Mapper::none(),
+            chr_rom: unif.chr_rom,
+            chr_ram: vec![],
+            ex_ram: vec![],
+            prg_rom: unif.prg_rom,
+            prg_ram: vec![],
+            trainer: vec![],
+            db_info: None,
+            single_screen,
+        };
+        cart.mapper =
+            Self::load_mapper(&mut cart).map_err(|_| UnifError::UnknownBoard(unif.board))?;
+
+        log::info!("Loaded `{}`", cart);
+        log::debug!("{:?}", cart);
+        Ok(cart)
+    }
+
+    fn load_mapper(cart: &mut Self) -> Result<Mapper> {
+        let mapper = match cart.header.mapper_num {
+            0 => Nrom::load(cart),
+            1 => {
+                let revision = cart
+                    .db_info
+                    .and_then(|info| info.mmc1_revision)
+                    .unwrap_or(Mmc1Revision::BC);
+                Sxrom::load(cart, revision)
+            }
+            2 => Uxrom::load(cart),
+            3 => Cnrom::load(cart),
+            4 => Txrom::load(cart),
+            5 => Exrom::load(cart),
+            7 => Axrom::load(cart),
+            9 => Pxrom::load(cart),
+            10 => Fxrom::load(cart),
+            19 => {
+                let volume = Namco163Volume::from_submapper(cart.submapper_num());
+                Namco163::load(cart, volume)
+            }
+            21 | 22 | 23 | 25 => {
+                let revision = Vrc4Revision::from_cart(cart);
+                Vrc4::load(cart, revision)
+            }
+            24 => Vrc6::load(cart, Vrc6Revision::A),
+            26 => Vrc6::load(cart, Vrc6Revision::B),
+            66 => Gxrom::load(cart),
+            69 => Fme7::load(cart),
+            71 => Bf909x::load(cart),
+            85 => {
+                let revision = Vrc7Revision::from_submapper(cart.submapper_num());
+                Vrc7::load(cart, revision)
+            }
+            155 => Sxrom::load(cart, Mmc1Revision::A),
             _ => bail!("unimplemented mapper: {}", cart.header.mapper_num),
         };
+        Ok(mapper)
+    }
+
+    /// Load a Famicom Disk System `Cart` from a disk image and the 8K FDS BIOS.
+    ///
+    /// # Errors
+    ///
+    /// If the BIOS is the wrong size, then an error is returned.
+    pub fn from_fds(
+        name: String,
+        image: &FdsImage,
+        bios: Vec<u8>,
+        ram_state: RamState,
+    ) -> Result<Self> {
+        if bios.len() != FDS_BIOS_SIZE {
+            bail!(
+                "invalid FDS BIOS size: {}, expected {FDS_BIOS_SIZE}",
+                bios.len()
+            );
+        }
+        let mut cart = Self {
+            name,
+            header: NesHeader {
+                version: 1,
+                mapper_num: 20,
+                ..NesHeader::default()
+            },
+            region: NesRegion::default(),
+            ram_state,
+            mapper: Mapper::none(),
+            chr_rom: vec![],
+            chr_ram: vec![],
+            ex_ram: vec![],
+            prg_rom: bios,
+            prg_ram: vec![],
+            trainer: vec![],
+            db_info: None,
+            single_screen: None,
+        };
+        cart.mapper = Fds::load(&mut cart, image.to_raw_sides());
 
         log::info!("Loaded `{}`", cart);
         log::debug!("{:?}", cart);
         Ok(cart)
     }
 
+    /// Load a `Cart` that plays an NSF, with the NSF program data as PRG-ROM.
+    pub fn from_nsf(name: String, nsf: &Nsf, ram_state: RamState) -> Self {
+        let mut cart = Self {
+            name,
+            header: NesHeader {
+                version: 1,
+                mapper_num: NsfPlayer::MAPPER_NUM,
+                ..NesHeader::default()
+            },
+            region: nsf.region,
+            ram_state,
+            mapper: Mapper::none(),
+            chr_rom: vec![],
+            chr_ram: vec![],
+            ex_ram: vec![],
+            prg_rom: vec![],
+            prg_ram: vec![],
+            trainer: vec![],
+            db_info: None,
+            single_screen: None,
+        };
+        cart.mapper = NsfPlayer::load(&mut cart, nsf);
+
+        log::info!("Loaded `{}`", cart);
+        log::debug!("{:?}", cart);
+        cart
+    }
+
     #[inline]
     #[must_use]
     pub fn name(&self) -> &str {
@@ -177,6 +375,35 @@ impl Cart {
         self.header.flags & 0x02 == 0x02
     }
 
+    /// Returns whether this cartridge has battery-backed CHR-RAM.
+    #[inline]
+    #[must_use]
+    pub fn chr_battery_backed(&self) -> bool {
+        self.chr_rom.is_empty() && self.header.chr_nvram_size() > 0
+    }
+
+    /// Offset of battery-backed PRG-NVRAM in PRG-RAM. When a NES 2.0 header specifies both,
+    /// volatile PRG-RAM comes first, as on SOROM boards. Otherwise all PRG-RAM is battery-backed
+    /// if the cartridge has a battery.
+    #[inline]
+    #[must_use]
+    pub const fn prg_nvram_offset(&self) -> usize {
+        if self.header.prg_nvram_size() > 0 {
+            self.header.prg_ram_size()
+        } else {
+            0
+        }
+    }
+
+    /// Returns the `NES 2.0` default expansion device, or `0` if unspecified.
+    ///
+    /// <https://www.nesdev.org/wiki/NES_2.0#Default_Expansion_Device>
+    #[inline]
+    #[must_use]
+    pub const fn expansion_device(&self) -> u8 {
+        self.header.expansion_device
+    }
+
     /// Returns `RamState`.
     #[inline]
     pub const fn ram_state(&self) -> RamState {
@@ -186,7 +413,9 @@ impl Cart {
     /// Returns hardware configured `Mirroring`.
     #[inline]
     pub fn mirroring(&self) -> Mirroring {
-        if self.header.flags & 0x08 == 0x08 {
+        if let Some(mirroring) = self.single_screen {
+            mirroring
+        } else if self.header.flags & 0x08 == 0x08 {
             Mirroring::FourScreen
         } else {
             match self.header.flags & 0x01 {
@@ -218,15 +447,48 @@ impl Cart {
         self.header.mapper_board()
     }
 
-    /// Allows mappers to add PRG-RAM.
+    /// Returns the CRC-32 of the PRG-ROM followed by the CHR-ROM, used to identify the ROM
+    /// independently of its header.
+    #[must_use]
+    pub fn crc32(&self) -> u32 {
+        let mut crc = Crc32::new();
+        crc.update(&self.prg_rom);
+        crc.update(&self.chr_rom);
+        crc.finish()
+    }
+
+    /// The 512-byte trainer, if the ROM has one.
+    #[inline]
+    #[must_use]
+    pub fn trainer(&self) -> &[u8] {
+        &self.trainer
+    }
+
+    /// The game database entry used to correct the header, if the ROM is a known game.
+    #[inline]
+    #[must_use]
+    pub const fn db_info(&self) -> Option<&'static GameInfo> {
+        self.db_info
+    }
+
+    /// Copy the trainer into PRG-RAM at $7000. Mappers may refill PRG-RAM when loading, so this
+    /// runs after the mapper is loaded.
+    fn load_trainer(&mut self) {
+        if self.trainer.is_empty() {
+            return;
+        }
+        self.prg_ram[TRAINER_OFFSET..TRAINER_OFFSET + TRAINER_SIZE].copy_from_slice(&self.trainer);
+    }
+
+    /// Allows mappers to add PRG-RAM. Never shrinks a larger NES 2.0 header size.
     pub(crate) fn add_prg_ram(&mut self, capacity: usize) {
-        self.prg_ram.resize(capacity, 0x00);
+        self.prg_ram.resize(capacity.max(self.prg_ram.len()), 0x00);
         RamState::fill(&mut self.prg_ram, self.ram_state);
     }
 
-    /// Allows mappers to add CHR-RAM.
+    /// Allows mappers to add CHR-RAM. Never shrinks a larger NES 2.0 header size.
     pub(crate) fn add_chr_ram(&mut self, capacity: usize) {
-        self.chr_ram.resize(capacity, 0x00);
+        self.chr_ram.resize(capacity.max(self.chr_ram.len()), 0x00);
         RamState::fill(&mut self.chr_ram, self.ram_state);
     }
 
@@ -236,15 +498,6 @@ impl Cart {
         RamState::fill(&mut self.ex_ram, self.ram_state);
     }
 
-    fn calculate_ram_size(value: u8) -> Result<usize> {
-        if value > 0 {
-            64usize
-                .checked_shl(value.into())
-                .ok_or_else(|| anyhow!("invalid header ram size: ${:02X}", value))
-        } else {
-            Ok(0)
-        }
-    }
 }
 
 impl Regional for Cart {
@@ -291,6 +544,7 @@ impl core::fmt::Debug for Cart {
             .field("ex_ram_len", &self.ex_ram.len())
             .field("prg_rom_len", &self.prg_rom.len())
             .field("prg_ram_len", &self.prg_ram.len())
+            .field("db_info", &self.db_info)
             .finish()
     }
 }
@@ -322,6 +576,8 @@ pub struct NesHeader {
     pub tv_mode: u8,
     // NES 2.0 NTSC/PAL indicator
     pub vs_data: u8,        // NES 2.0 VS System data
+    pub misc_roms: u8,      // NES 2.0 number of miscellaneous ROMs
+    pub expansion_device: u8, // NES 2.0 default expansion device
 }
 
 impl NesHeader {
@@ -355,6 +611,8 @@ impl NesHeader {
         let mut chr_ram_shift = 0;
         let mut tv_mode = 0;
         let mut vs_data = 0;
+        let mut misc_roms = 0;
+        let mut expansion_device = 0;
         // If D2..D3 of flag 7 == 2
         if header[7] & 0x0C == 0x08 {
             version = 2;
@@ -370,15 +628,13 @@ impl NesHeader {
             chr_ram_shift = header[11];
             tv_mode = header[12];
             vs_data = header[13];
+            misc_roms = header[14] & 0x03;
+            expansion_device = header[15] & 0x3F;
 
             if prg_ram_shift & 0x0F == 0x0F || prg_ram_shift & 0xF0 == 0xF0 {
                 bail!("invalid prg-ram size in header");
             } else if chr_ram_shift & 0x0F == 0x0F || chr_ram_shift & 0xF0 == 0xF0 {
                 bail!("invalid chr-ram size in header");
-            } else if chr_ram_shift & 0xF0 == 0xF0 {
-                bail!("battery-backed chr-ram is currently not supported");
-            } else if header[14] > 0 || header[15] > 0 {
-                bail!("unrecognized data found at header offsets 14-15");
             }
         } else {
             for (i, header) in header.iter().enumerate().take(16).skip(8) {
@@ -391,12 +647,7 @@ impl NesHeader {
             }
         }
 
-        // Trainer
-        if flags & 0x04 == 0x04 {
-            bail!("trained roms are currently not supported.");
-        }
-
-        Ok(Self {
+        let header = Self {
             version,
             mapper_num,
             submapper_num,
@@ -407,7 +658,94 @@ impl NesHeader {
             chr_ram_shift,
             tv_mode,
             vs_data,
-        })
+            misc_roms,
+            expansion_device,
+        };
+        // Exponent-multiplier sizes can describe more ROM than is addressable
+        header.prg_rom_size().context("invalid prg-rom size in header")?;
+        header.chr_rom_size().context("invalid chr-rom size in header")?;
+        Ok(header)
+    }
+
+    /// Decode a NES 2.0 ROM size. If the MSB nibble is $F, the LSB is `EEEEEEMM` and the size is
+    /// `2^E * (MM * 2 + 1)` bytes, otherwise the size is in `bank_size` units.
+    const fn rom_size(banks: u16, bank_size: usize) -> Option<usize> {
+        if banks & 0x0F00 == 0x0F00 {
+            let exponent = (banks & 0xFF) >> 2;
+            let multiplier = (banks & 0x03) as usize * 2 + 1;
+            if exponent >= usize::BITS as u16 - 2 {
+                return None;
+            }
+            Some((1 << exponent) * multiplier)
+        } else {
+            Some(banks as usize * bank_size)
+        }
+    }
+
+    /// RAM size encoded as a shift count of `64 << shift` bytes, where `0` means none.
+    const fn ram_size(shift: u8) -> usize {
+        if shift == 0 {
+            0
+        } else {
+            64 << shift
+        }
+    }
+
+    /// PRG-ROM size in bytes, or `None` if it isn't addressable.
+    #[must_use]
+    pub const fn prg_rom_size(&self) -> Option<usize> {
+        Self::rom_size(self.prg_rom_banks, PRG_ROM_BANK_SIZE)
+    }
+
+    /// CHR-ROM size in bytes, or `None` if it isn't addressable.
+    #[must_use]
+    pub const fn chr_rom_size(&self) -> Option<usize> {
+        Self::rom_size(self.chr_rom_banks, CHR_ROM_BANK_SIZE)
+    }
+
+    /// Volatile PRG-RAM size in bytes. Always `0` for `iNES`, where mappers choose the size.
+    #[inline]
+    #[must_use]
+    pub const fn prg_ram_size(&self) -> usize {
+        Self::ram_size(self.prg_ram_shift & 0x0F)
+    }
+
+    /// Battery-backed PRG-NVRAM size in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn prg_nvram_size(&self) -> usize {
+        Self::ram_size(self.prg_ram_shift >> 4)
+    }
+
+    /// Volatile CHR-RAM size in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn chr_ram_size(&self) -> usize {
+        Self::ram_size(self.chr_ram_shift & 0x0F)
+    }
+
+    /// Battery-backed CHR-NVRAM size in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn chr_nvram_size(&self) -> usize {
+        Self::ram_size(self.chr_ram_shift >> 4)
+    }
+
+    /// The CPU/PPU timing the cartridge expects. Multi-region carts run as NTSC.
+    #[inline]
+    pub const fn region(&self) -> NesRegion {
+        match self.tv_mode & 0x03 {
+            1 => NesRegion::Pal,
+            3 => NesRegion::Dendy,
+            _ => NesRegion::Ntsc,
+        }
+    }
+
+    /// Whether a 512-byte trainer precedes PRG-ROM.
+    #[inline]
+    #[must_use]
+    pub const fn has_trainer(&self) -> bool {
+        self.flags & 0x04 == 0x04
     }
 
     #[must_use]
@@ -421,11 +759,21 @@ impl NesHeader {
             5 => "Mapper 005 - ExROM/MMC5",
             7 => "Mapper 007 - AxROM",
             9 => "Mapper 009 - PxROM",
+            10 => "Mapper 010 - FxROM/MMC4",
+            19 => "Mapper 019 - Namco 163",
+            20 => "Mapper 020 - Famicom Disk System",
+            21 => "Mapper 021 - Vrc4a/Vrc4c",
+            22 => "Mapper 022 - Vrc2a",
+            23 => "Mapper 023 - Vrc2b/Vrc4e/Vrc4f",
+            25 => "Mapper 025 - Vrc2c/Vrc4b/Vrc4d",
             24 => "Mapper 024 - Vrc6a",
             26 => "Mapper 026 - Vrc6b",
             66 => "Mapper 066 - GxROM/MxROM",
+            69 => "Mapper 069 - Sunsoft FME-7/5B",
             71 => "Mapper 071 - Camerica/Codemasters/BF909x",
+            85 => "Mapper 085 - Vrc7",
             155 => "Mapper 155 - SxROM/MMC1A",
+            NsfPlayer::MAPPER_NUM => "NSF Player",
             _ => "Unimplemented Mapper",
         }
     }
@@ -444,6 +792,8 @@ impl core::fmt::Debug for NesHeader {
             .field("chr_ram_shift", &self.chr_ram_shift)
             .field("tv_mode", &self.tv_mode)
             .field("vs_data", &self.vs_data)
+            .field("misc_roms", &self.misc_roms)
+            .field("expansion_device", &self.expansion_device)
             .finish()
     }
 }
@@ -451,6 +801,7 @@ impl core::fmt::Debug for NesHeader {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mapper::{Mapped, MappedRead, MemMap};
 
     macro_rules! test_headers {
         ($(($test:ident, $data:expr, $header:expr$(,)?)),*$(,)?) => {$(
@@ -494,5 +845,138 @@ mod tests {
                 ..NesHeader::default()
             },
         ),
+        (
+            mapper004_nes2,
+            [0x4Eu8, 0x45, 0x53, 0x1A,
+             0x3D, 0x00, 0x42, 0x08,
+             0x10, 0x0F, 0x70, 0x07,
+             0x01, 0x00, 0x00, 0x02],
+            NesHeader {
+                version: 2,
+                mapper_num: 4,
+                submapper_num: 1,
+                flags: 0b1000_0010,
+                prg_rom_banks: 0x0F3D,
+                chr_rom_banks: 0,
+                prg_ram_shift: 0x70,
+                chr_ram_shift: 0x07,
+                tv_mode: 1,
+                expansion_device: 2,
+                ..NesHeader::default()
+            },
+        ),
     );
+
+    #[test]
+    fn nes2_sizes() {
+        let header = NesHeader {
+            version: 2,
+            prg_rom_banks: 0x0F3D,
+            chr_rom_banks: 0x0102,
+            prg_ram_shift: 0x70,
+            chr_ram_shift: 0x97,
+            tv_mode: 3,
+            ..NesHeader::default()
+        };
+        // 2^15 * (1 * 2 + 1)
+        assert_eq!(header.prg_rom_size(), Some(0x18000));
+        assert_eq!(header.chr_rom_size(), Some(0x102 * CHR_ROM_BANK_SIZE));
+        assert_eq!(header.prg_ram_size(), 0);
+        assert_eq!(header.prg_nvram_size(), 0x2000);
+        assert_eq!(header.chr_ram_size(), 0x2000);
+        assert_eq!(header.chr_nvram_size(), 0x8000);
+        assert_eq!(header.region(), NesRegion::Dendy);
+
+        let header = NesHeader {
+            prg_rom_banks: 0x0FFF,
+            ..header
+        };
+        assert_eq!(header.prg_rom_size(), None);
+    }
+
+    #[test]
+    fn load_nes2() {
+        // NROM, PAL, 8K CHR-NVRAM, Zapper
+        let mut rom = vec![
+            0x4E, 0x45, 0x53, 0x1A, 0x01, 0x00, 0x02, 0x08, 0x00, 0x00, 0x00, 0x70, 0x01, 0x00,
+            0x00, 0x08,
+        ];
+        rom.extend_from_slice(&[0x01; PRG_ROM_BANK_SIZE]);
+        let cart = Cart::from_rom("test".to_string(), rom, RamState::AllZeros).expect("valid rom");
+        assert_eq!(cart.region(), NesRegion::Pal);
+        assert!(cart.chr_rom().is_empty());
+        assert_eq!(cart.chr_ram().len(), 0x2000);
+        assert!(cart.chr_battery_backed());
+        assert!(cart.battery_backed());
+        assert_eq!(cart.expansion_device(), 0x08);
+        assert_eq!(cart.db_info(), None, "unknown game");
+    }
+
+    #[test]
+    fn load_unif() {
+        let mut rom = unif::MAGIC.to_vec();
+        rom.resize(unif::HEADER_SIZE, 0x00);
+        for (id, data) in [
+            (b"MAPR", b"NES-UOROM\0".to_vec()),
+            (b"PRG0", vec![0x00; 0x20000]),
+            (b"MIRR", vec![0x01]),
+            (b"TVCI", vec![0x01]),
+        ] {
+            rom.extend_from_slice(id);
+            rom.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            rom.extend_from_slice(&data);
+        }
+        let cart = Cart::from_rom("test".to_string(), rom.clone(), RamState::AllZeros)
+            .expect("valid unif");
+        assert_eq!(cart.mapper_num(), 2);
+        assert!(matches!(cart.mapper, Mapper::Uxrom(_)));
+        assert_eq!(cart.mirroring(), Mirroring::Vertical);
+        assert_eq!(cart.region(), NesRegion::Pal);
+        assert_eq!(cart.prg_rom().len(), 0x20000);
+
+        let mirr = rom.len() - 10;
+        rom[mirr] = 0x03;
+        let cart = Cart::from_rom("test".to_string(), rom.clone(), RamState::AllZeros)
+            .expect("valid unif");
+        assert_eq!(cart.mirroring(), Mirroring::SingleScreenB);
+        assert_eq!(cart.mapper.mirroring(), Mirroring::SingleScreenB);
+
+        rom[unif::HEADER_SIZE + 8..unif::HEADER_SIZE + 17].copy_from_slice(b"UNL-XYZ\0\0");
+        assert!(matches!(
+            Cart::from_unif("test".to_string(), &rom, RamState::AllZeros),
+            Err(UnifError::UnknownBoard(board)) if board == "UNL-XYZ"
+        ));
+    }
+
+    #[test]
+    fn load_trainer() {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01, 0x04, 0x00];
+        rom.resize(16, 0x00);
+        rom.extend_from_slice(&[0xAA; TRAINER_SIZE]);
+        rom.extend_from_slice(&[0x01; PRG_ROM_BANK_SIZE]);
+        rom.extend_from_slice(&[0x02; CHR_ROM_BANK_SIZE]);
+        let cart = Cart::from_rom("test".to_string(), rom.clone(), RamState::AllZeros)
+            .expect("valid rom");
+        assert_eq!(cart.trainer(), [0xAA; TRAINER_SIZE]);
+        assert_eq!(cart.prg_rom(), [0x01; PRG_ROM_BANK_SIZE]);
+        assert_eq!(cart.chr_rom(), [0x02; CHR_ROM_BANK_SIZE]);
+        assert_eq!(cart.prg_ram().len(), 0x2000);
+        assert_eq!(
+            cart.prg_ram()[TRAINER_OFFSET..TRAINER_OFFSET + TRAINER_SIZE],
+            [0xAA; TRAINER_SIZE]
+        );
+
+        // UxROM has no PRG-RAM of its own
+        rom[6] = 0x24;
+        let cart = Cart::from_rom("test".to_string(), rom.clone(), RamState::AllZeros)
+            .expect("valid rom");
+        assert_eq!(cart.mapper.map_peek(0x7000), MappedRead::PrgRam(TRAINER_OFFSET));
+        assert_eq!(cart.prg_ram()[TRAINER_OFFSET], 0xAA);
+
+        rom.truncate(16 + TRAINER_SIZE + PRG_ROM_BANK_SIZE);
+        assert!(
+            Cart::from_rom("test".to_string(), rom, RamState::AllZeros).is_err(),
+            "truncated chr-rom"
+        );
+    }
 }
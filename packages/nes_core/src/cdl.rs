@@ -0,0 +1,207 @@
+//! Code/Data Logger (CDL) marking how each PRG-ROM and CHR-ROM byte was used.
+//!
+//! Files use the FCEUX layout: one flag byte per PRG-ROM byte followed by one per CHR-ROM byte.
+//!
+//! <https://fceux.com/web/help/CodeDataLogger.html>
+
+use alloc::vec;
+use alloc::vec::Vec;
+use bitflags::bitflags;
+use core::fmt;
+
+bitflags! {
+    /// How a PRG-ROM byte was accessed.
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    #[must_use]
+    pub struct PrgFlags: u8 {
+        /// Executed as an opcode or operand.
+        const CODE = 0x01;
+        /// Read as data.
+        const DATA = 0x02;
+        /// The 8K CPU bank ($8000, $A000, $C000 or $E000) it was last accessed through.
+        const BANK = 0x0C;
+        /// Executed as the target of an indirect jump.
+        const INDIRECT_CODE = 0x10;
+        /// Read through an indirect pointer.
+        const INDIRECT_DATA = 0x20;
+        /// Played as a DMC sample.
+        const PCM = 0x40;
+    }
+}
+
+bitflags! {
+    /// How a CHR-ROM byte was accessed.
+    #[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+    #[must_use]
+    pub struct ChrFlags: u8 {
+        /// Fetched by the PPU while rendering.
+        const DRAWN = 0x01;
+        /// Read by the CPU through `PPUDATA` ($2007).
+        const READ = 0x02;
+    }
+}
+
+/// Errors returned when loading a code/data log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum CdlError {
+    /// The log doesn't match the size of the loaded PRG-ROM and CHR-ROM.
+    SizeMismatch { expected: usize, found: usize },
+}
+
+impl fmt::Display for CdlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SizeMismatch { expected, found } => write!(
+                f,
+                "code/data log is {found} bytes, expected {expected} for the loaded ROM"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for CdlError {}
+
+/// Access flags for every PRG-ROM and CHR-ROM byte of a cartridge.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Cdl {
+    prg: Vec<u8>,
+    chr: Vec<u8>,
+}
+
+impl Cdl {
+    /// Create an empty log for a cartridge with the given ROM sizes.
+    pub fn new(prg_rom_size: usize, chr_rom_size: usize) -> Self {
+        Self {
+            prg: vec![0x00; prg_rom_size],
+            chr: vec![0x00; chr_rom_size],
+        }
+    }
+
+    pub(crate) fn from_parts(prg: Vec<u8>, chr: Vec<u8>) -> Self {
+        Self { prg, chr }
+    }
+
+    pub(crate) fn into_parts(self) -> (Vec<u8>, Vec<u8>) {
+        (self.prg, self.chr)
+    }
+
+    /// Load a log in the FCEUX `.cdl` format.
+    ///
+    /// # Errors
+    ///
+    /// If `data` doesn't match the ROM sizes, then an error is returned.
+    pub fn from_bytes(
+        data: &[u8],
+        prg_rom_size: usize,
+        chr_rom_size: usize,
+    ) -> Result<Self, CdlError> {
+        let expected = prg_rom_size + chr_rom_size;
+        if data.len() != expected {
+            return Err(CdlError::SizeMismatch {
+                expected,
+                found: data.len(),
+            });
+        }
+        let (prg, chr) = data.split_at(prg_rom_size);
+        Ok(Self::from_parts(prg.to_vec(), chr.to_vec()))
+    }
+
+    /// Serialize to the FCEUX `.cdl` format.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(self.prg.len() + self.chr.len());
+        data.extend_from_slice(&self.prg);
+        data.extend_from_slice(&self.chr);
+        data
+    }
+
+    /// Flags for every PRG-ROM byte, indexed by absolute offset.
+    #[inline]
+    #[must_use]
+    pub fn prg(&self) -> &[u8] {
+        &self.prg
+    }
+
+    /// Flags for every CHR-ROM byte, indexed by absolute offset.
+    #[inline]
+    #[must_use]
+    pub fn chr(&self) -> &[u8] {
+        &self.chr
+    }
+
+    /// Flags for the PRG-ROM byte at `offset`, if it's in range.
+    #[inline]
+    pub fn prg_flags(&self, offset: usize) -> Option<PrgFlags> {
+        self.prg
+            .get(offset)
+            .copied()
+            .map(PrgFlags::from_bits_retain)
+    }
+
+    /// Flags for the CHR-ROM byte at `offset`, if it's in range.
+    #[inline]
+    pub fn chr_flags(&self, offset: usize) -> Option<ChrFlags> {
+        self.chr
+            .get(offset)
+            .copied()
+            .map(ChrFlags::from_bits_retain)
+    }
+}
+
+/// Mark the PRG-ROM byte at `offset`, accessed through CPU address `addr`.
+#[inline]
+pub(crate) fn log_prg(prg: &mut [u8], offset: usize, addr: u16, flags: PrgFlags) {
+    if flags.is_empty() {
+        return;
+    }
+    if let Some(entry) = prg.get_mut(offset) {
+        let bank = ((addr >> 13) & 0x03) as u8;
+        *entry = (*entry & !PrgFlags::BANK.bits()) | (bank << 2) | flags.bits();
+    }
+}
+
+/// Mark the CHR-ROM byte at `offset`.
+#[inline]
+pub(crate) fn log_chr(chr: &mut [u8], offset: usize, flags: ChrFlags) {
+    if let Some(entry) = chr.get_mut(offset) {
+        *entry |= flags.bits();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_flags() {
+        let mut prg = vec![0x00; 4];
+        log_prg(&mut prg, 1, 0xC001, PrgFlags::CODE);
+        log_prg(&mut prg, 1, 0xE001, PrgFlags::DATA);
+        log_prg(&mut prg, 8, 0x8000, PrgFlags::DATA);
+        assert_eq!(prg, [0x00, 0x0F, 0x00, 0x00], "bank is last access");
+
+        let mut chr = vec![0x00; 2];
+        log_chr(&mut chr, 0, ChrFlags::DRAWN);
+        log_chr(&mut chr, 0, ChrFlags::READ);
+        assert_eq!(chr, [0x03, 0x00]);
+    }
+
+    #[test]
+    fn cdl_file() {
+        let data = [0x01, 0x42, 0x00, 0x01];
+        assert_eq!(
+            Cdl::from_bytes(&data, 4, 2),
+            Err(CdlError::SizeMismatch {
+                expected: 6,
+                found: 4
+            })
+        );
+        let cdl = Cdl::from_bytes(&data, 2, 2).expect("valid cdl");
+        assert_eq!(cdl.prg_flags(1), Some(PrgFlags::PCM | PrgFlags::DATA));
+        assert_eq!(cdl.chr_flags(1), Some(ChrFlags::DRAWN));
+        assert_eq!(cdl.chr_flags(2), None);
+        assert_eq!(cdl.to_bytes(), data);
+    }
+}
@@ -6,26 +6,61 @@ use crate::{
     apu::{Apu, Channel},
     bus::Bus,
     cart::Cart,
+    cdl::Cdl,
+    checksum,
     common::{Clock, ResetKind, NesRegion, Regional, Reset},
     cpu::Cpu,
+    debugger::{DebugBreak, Debugger, Step},
+    fds::FdsImage,
     input::{FourPlayer, Joypad, Player},
-    mapper::Mapper,
-    mem::RamState,
+    mapper::{Fds, Mapper, NsfPlayer},
+    mem::{Access, Mem, RamState},
+    nsf::{Nsf, NsfTrack},
+    patch,
     ppu::Ppu,
+    rewind::{Rewind, RewindConfig},
+    state::{self, StateError},
+    trace::TraceLogger,
     video::{Video, VideoFilter},
 };
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Result};
 use crate::genie::GenieCode;
 
 /// Represents an NES Control Deck
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 #[must_use]
 pub struct ControlDeck {
     running: bool,
     video: Video,
     loaded_rom: Option<String>,
+    rom_crc32: Option<u32>,
+    // The disk image as loaded, which disk writes are diffed against
+    disk_image: Option<FdsImage>,
+    nsf: Option<Nsf>,
     cycles_remaining: f32,
     cpu: Cpu,
+    rewind: Option<Rewind>,
+    debugger: Debugger,
+    trace_logger: Option<TraceLogger>,
+}
+
+impl Clone for ControlDeck {
+    // The trace logger isn't cloned, so clones never write to the same sink
+    fn clone(&self) -> Self {
+        Self {
+            running: self.running,
+            video: self.video.clone(),
+            loaded_rom: self.loaded_rom.clone(),
+            rom_crc32: self.rom_crc32,
+            disk_image: self.disk_image.clone(),
+            nsf: self.nsf.clone(),
+            cycles_remaining: self.cycles_remaining,
+            cpu: self.cpu.clone(),
+            rewind: self.rewind.clone(),
+            debugger: self.debugger.clone(),
+            trace_logger: None,
+        }
+    }
 }
 
 impl Default for ControlDeck {
@@ -34,6 +69,14 @@ impl Default for ControlDeck {
     }
 }
 
+/// A frame restored by [`ControlDeck::rewind_step`].
+#[derive(Debug)]
+#[must_use]
+pub struct RewindFrame<'a> {
+    pub frame_buffer: &'a [u8],
+    pub audio_samples: &'a [f32],
+}
+
 pub struct Config {
     pub filter: VideoFilter,
     pub region: NesRegion,
@@ -76,8 +119,14 @@ impl ControlDeck {
             running: false,
             video,
             loaded_rom: None,
+            rom_crc32: None,
+            disk_image: None,
+            nsf: None,
             cycles_remaining: 0.0,
             cpu,
+            rewind: None,
+            debugger: Debugger::new(),
+            trace_logger: None,
         }
     }
 
@@ -89,12 +138,274 @@ impl ControlDeck {
     pub fn load_rom(&mut self, name: String, rom: Vec<u8>) -> Result<()> {
         self.loaded_rom = Some(name.clone());
         let cart = Cart::from_rom(name, rom, self.cpu.ram_state())?;
+        self.rom_crc32 = Some(cart.crc32());
+        self.disk_image = None;
+        self.nsf = None;
+        self.load_cart(cart);
+        Ok(())
+    }
+
+    /// Loads a ROM after applying IPS, UPS or BPS `patches` to it in order.
+    ///
+    /// # Errors
+    ///
+    /// If a patch is invalid or doesn't match the ROM, or the patched ROM is invalid, then an
+    /// error is returned.
+    pub fn load_rom_with_patches(
+        &mut self,
+        name: String,
+        mut rom: Vec<u8>,
+        patches: &[Vec<u8>],
+    ) -> Result<()> {
+        for data in patches {
+            rom = patch::apply(&rom, data).map_err(|err| anyhow!("{err}"))?;
+        }
+        self.load_rom(name, rom)
+    }
+
+    /// Loads a Famicom Disk System disk image, in `.fds` format with or without an fwNES header,
+    /// using the 8K FDS BIOS. The first disk side is inserted.
+    ///
+    /// # Errors
+    ///
+    /// If the disk image or BIOS is invalid, then an error is returned.
+    pub fn load_disk(&mut self, name: String, image: &[u8], bios: Vec<u8>) -> Result<()> {
+        let disk_image = FdsImage::load(image)?;
+        let cart = Cart::from_fds(name.clone(), &disk_image, bios, self.cpu.ram_state())?;
+        self.loaded_rom = Some(name);
+        self.rom_crc32 = Some(checksum::crc32(image));
+        self.disk_image = Some(disk_image);
+        self.nsf = None;
+        self.load_cart(cart);
+        Ok(())
+    }
+
+    /// Loads an NSF, NSF2 or NSFe music rip and starts playing its starting track. PAL-only rips
+    /// switch the region to PAL.
+    ///
+    /// # Errors
+    ///
+    /// If the file is invalid, then an error is returned.
+    pub fn load_nsf(&mut self, name: String, data: &[u8]) -> Result<()> {
+        let nsf = Nsf::load(data)?;
+        let cart = Cart::from_nsf(name.clone(), &nsf, self.cpu.ram_state());
+        self.loaded_rom = Some(name);
+        self.rom_crc32 = Some(checksum::crc32(data));
+        self.disk_image = None;
+        self.nsf = Some(nsf);
+        self.load_cart(cart);
+        Ok(())
+    }
+
+    fn load_cart(&mut self, cart: Cart) {
+        if let Some(rewind) = &mut self.rewind {
+            rewind.clear();
+        }
         self.set_region(cart.region());
         self.cpu.load_cart(cart);
         self.reset(ResetKind::Hard);
+    }
+
+    #[inline]
+    fn fds(&self) -> Option<&Fds> {
+        match self.cpu.mapper() {
+            Mapper::Fds(fds) => Some(fds),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn fds_mut(&mut self) -> Result<&mut Fds> {
+        match self.cpu.mapper_mut() {
+            Mapper::Fds(fds) => Ok(fds),
+            _ => Err(anyhow!("no disk loaded")),
+        }
+    }
+
+    #[inline]
+    fn nsf_player(&self) -> Option<&NsfPlayer> {
+        match self.cpu.mapper() {
+            Mapper::NsfPlayer(nsf_player) => Some(nsf_player),
+            _ => None,
+        }
+    }
+
+    /// Returns the loaded NSF, with its title, artist and copyright, if any.
+    #[inline]
+    #[must_use]
+    pub const fn nsf(&self) -> Option<&Nsf> {
+        self.nsf.as_ref()
+    }
+
+    /// Returns the number of NSF tracks, or `0` if no NSF is loaded.
+    #[inline]
+    #[must_use]
+    pub fn track_count(&self) -> usize {
+        self.nsf.as_ref().map_or(0, |nsf| nsf.total_songs.into())
+    }
+
+    /// Returns the playing NSF track, starting at `0`.
+    #[inline]
+    #[must_use]
+    pub fn track(&self) -> Option<usize> {
+        self.nsf_player()
+            .map(|nsf_player| nsf_player.song().into())
+    }
+
+    /// Returns the label, length and fade of an NSF track, if any.
+    #[inline]
+    #[must_use]
+    pub fn track_info(&self, track: usize) -> Option<&NsfTrack> {
+        self.nsf.as_ref().and_then(|nsf| nsf.tracks.get(track))
+    }
+
+    /// Restart playback at an NSF track, starting at `0`.
+    ///
+    /// # Errors
+    ///
+    /// If no NSF is loaded or `track` doesn't exist, then an error is returned.
+    pub fn select_track(&mut self, track: usize) -> Result<()> {
+        let track_count = self.track_count();
+        if track >= track_count {
+            bail!("invalid track: {track}, NSF has {track_count} tracks");
+        }
+        match self.cpu.mapper_mut() {
+            Mapper::NsfPlayer(nsf_player) => nsf_player.set_song(track as u8),
+            _ => bail!("no NSF loaded"),
+        }
+        self.reset(ResetKind::Hard);
         Ok(())
     }
 
+    /// Play the next NSF track, wrapping around to the first.
+    ///
+    /// # Errors
+    ///
+    /// If no NSF is loaded, then an error is returned.
+    pub fn next_track(&mut self) -> Result<()> {
+        let track = self.track().ok_or_else(|| anyhow!("no NSF loaded"))?;
+        self.select_track((track + 1) % self.track_count())
+    }
+
+    /// Play the previous NSF track, wrapping around to the last.
+    ///
+    /// # Errors
+    ///
+    /// If no NSF is loaded, then an error is returned.
+    pub fn previous_track(&mut self) -> Result<()> {
+        let track = self.track().ok_or_else(|| anyhow!("no NSF loaded"))?;
+        let track_count = self.track_count();
+        self.select_track((track + track_count - 1) % track_count)
+    }
+
+    /// Returns the milliseconds the NSF track has been playing.
+    #[must_use]
+    pub fn track_elapsed(&self) -> Option<u32> {
+        let elapsed = self.nsf_player()?.elapsed() as f64;
+        let clock_rate = f64::from(Cpu::region_clock_rate(self.region()));
+        Some((elapsed * 1000.0 / clock_rate) as u32)
+    }
+
+    /// Returns whether the NSF track has played for its length and fade, as set by NSFe `time`
+    /// and `fade` chunks. Tracks without a length never finish.
+    #[must_use]
+    pub fn track_finished(&self) -> bool {
+        self.track_volume() == Some(0.0)
+    }
+
+    /// Volume of the NSF track while fading out after its length, or `None` if it has no length.
+    fn track_volume(&self) -> Option<f32> {
+        let info = self.track_info(self.track()?)?;
+        let length = info.length?;
+        let elapsed = self.track_elapsed()?;
+        let fade = info.fade.unwrap_or(0);
+        Some(if elapsed <= length {
+            1.0
+        } else if elapsed >= length + fade {
+            0.0
+        } else {
+            1.0 - (elapsed - length) as f32 / fade as f32
+        })
+    }
+
+    /// Returns the number of disk sides, or `0` if no disk is loaded.
+    #[inline]
+    #[must_use]
+    pub fn disk_sides(&self) -> usize {
+        self.fds().map_or(0, Fds::sides)
+    }
+
+    /// Returns the disk side in the drive, if any.
+    #[inline]
+    #[must_use]
+    pub fn disk_side(&self) -> Option<usize> {
+        self.fds().and_then(Fds::side)
+    }
+
+    /// Insert a disk side into the drive, ejecting the current one. The BIOS sees the drive
+    /// empty for about a second before the new side is ready.
+    ///
+    /// # Errors
+    ///
+    /// If no disk is loaded or `side` doesn't exist, then an error is returned.
+    pub fn insert_disk(&mut self, side: usize) -> Result<()> {
+        self.fds_mut()?.insert_disk(side)
+    }
+
+    /// Eject the disk from the drive.
+    #[inline]
+    pub fn eject_disk(&mut self) {
+        if let Ok(fds) = self.fds_mut() {
+            fds.eject_disk();
+        }
+    }
+
+    /// Flip the disk in the drive over, or insert side A of the first disk if the drive is
+    /// empty.
+    ///
+    /// # Errors
+    ///
+    /// If no disk is loaded or the disk in the drive is single-sided, then an error is returned.
+    pub fn flip_disk(&mut self) -> Result<()> {
+        let side = self.disk_side().map_or(0, |side| side ^ 1);
+        self.insert_disk(side)
+    }
+
+    /// Returns an IPS patch of the changes the game has written to disk, relative to the image
+    /// passed to [`ControlDeck::load_disk`], or `None` if nothing has changed. Persist it and pass
+    /// it to [`ControlDeck::apply_disk_patch`] after loading the same image to restore saves.
+    #[must_use]
+    pub fn disk_patch(&self) -> Option<Vec<u8>> {
+        let original = self.disk_image.as_ref()?;
+        let modified = original.with_raw_sides(self.fds()?.disk());
+        (modified != *original).then(|| original.diff(&modified))
+    }
+
+    /// Apply an IPS patch created by [`ControlDeck::disk_patch`] to the loaded disk.
+    ///
+    /// # Errors
+    ///
+    /// If no disk is loaded, or the patch is invalid, then an error is returned.
+    pub fn apply_disk_patch(&mut self, ips: &[u8]) -> Result<()> {
+        let original = self
+            .disk_image
+            .as_ref()
+            .ok_or_else(|| anyhow!("no disk loaded"))?;
+        let modified = original.patch(ips)?;
+        if modified.sides() != original.sides() {
+            return Err(anyhow!("disk patch changes the number of disk sides"));
+        }
+        self.fds_mut()?.set_disk(modified.to_raw_sides());
+        Ok(())
+    }
+
+    /// Power cycle the console, re-initializing all RAM with `ram_state`, including battery-backed
+    /// Save RAM.
+    pub fn power_on(&mut self, ram_state: RamState) {
+        self.cpu.power_on(ram_state);
+        self.reset(ResetKind::Hard);
+    }
+
     #[inline]
     pub fn load_cpu(&mut self, cpu: Cpu) {
         self.cpu = cpu;
@@ -123,12 +434,112 @@ impl ControlDeck {
         self.cpu.load_sram(sram);
     }
 
+    /// Whether the cartridge has battery-backed CHR-RAM (NES 2.0 only) which should be persisted
+    /// alongside SRAM.
+    #[inline]
+    #[must_use]
+    pub const fn cart_chr_battery_backed(&self) -> bool {
+        self.cpu.cart_chr_battery_backed()
+    }
+
+    /// Battery-backed CHR-RAM, or empty if the cartridge has none.
+    #[inline]
+    #[must_use]
+    pub fn chr_sram(&self) -> &[u8] {
+        self.cpu.chr_sram()
+    }
+
+    #[inline]
+    pub fn load_chr_sram(&mut self, chr_sram: Vec<u8>) {
+        self.cpu.load_chr_sram(chr_sram);
+    }
+
+    /// Whether the code/data logger is recording PRG-ROM and CHR-ROM accesses.
+    #[inline]
+    #[must_use]
+    pub const fn cdl_enabled(&self) -> bool {
+        self.cpu.cdl_enabled()
+    }
+
+    /// Start recording a code/data log for the loaded ROM, or stop and discard it. The log is
+    /// reset when a new ROM is loaded.
+    #[inline]
+    pub fn enable_cdl(&mut self, enabled: bool) {
+        self.cpu.enable_cdl(enabled);
+    }
+
+    /// A copy of the code/data log, if enabled. Use [`Cdl::to_bytes`] to save it as an FCEUX
+    /// `.cdl` file.
+    #[inline]
+    pub fn cdl(&self) -> Option<Cdl> {
+        self.cpu.cdl()
+    }
+
+    /// Continue recording from an FCEUX `.cdl` file, enabling the code/data logger.
+    ///
+    /// # Errors
+    ///
+    /// If no ROM is loaded or the file doesn't match its PRG-ROM and CHR-ROM sizes, an error is
+    /// returned.
+    pub fn load_cdl(&mut self, data: &[u8]) -> Result<()> {
+        if self.loaded_rom.is_none() {
+            bail!("no ROM loaded");
+        }
+        self.cpu.load_cdl(data).map_err(|err| anyhow!("{err}"))
+    }
+
+    /// Whether the game has written to battery-backed PRG-RAM since the last call to
+    /// [`ControlDeck::clear_sram_dirty`]. Useful to only persist SRAM when it changed.
+    #[inline]
+    #[must_use]
+    pub const fn sram_dirty(&self) -> bool {
+        self.cart_battery_backed() && self.cpu.sram_dirty()
+    }
+
+    #[inline]
+    pub fn clear_sram_dirty(&mut self) {
+        self.cpu.clear_sram_dirty();
+    }
+
     #[inline]
     #[must_use]
     pub fn wram(&self) -> &[u8] {
         self.cpu.wram()
     }
 
+    /// Returns the CRC-32 of the loaded ROM's PRG-ROM and CHR-ROM.
+    #[inline]
+    #[must_use]
+    pub const fn rom_crc32(&self) -> Option<u32> {
+        self.rom_crc32
+    }
+
+    /// Save a snapshot of the running console as a versioned binary blob. PRG-ROM and CHR-ROM are
+    /// not included.
+    ///
+    /// # Errors
+    ///
+    /// If no ROM is loaded or serialization fails, an error is returned.
+    pub fn save_state(&self) -> Result<Vec<u8>, StateError> {
+        let rom_crc32 = self.rom_crc32.ok_or(StateError::NoRomLoaded)?;
+        state::save(&self.cpu, rom_crc32)
+    }
+
+    /// Restore a snapshot created by [`ControlDeck::save_state`]. The running game is left
+    /// untouched if the state is invalid or was taken from a different ROM.
+    ///
+    /// # Errors
+    ///
+    /// If no ROM is loaded, the header or version is invalid, the state belongs to a different
+    /// ROM, or deserialization fails, an error is returned.
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), StateError> {
+        let rom_crc32 = self.rom_crc32.ok_or(StateError::NoRomLoaded)?;
+        let snapshot = state::load(data, rom_crc32)?;
+        self.cpu.restore(snapshot);
+        self.cycles_remaining = 0.0;
+        Ok(())
+    }
+
     /// Get a frame worth of pixels.
     #[inline]
     #[must_use]
@@ -145,14 +556,27 @@ impl ControlDeck {
         self.cpu.frame_number()
     }
 
-    /// Audio sample rate.
+    /// Audio output sample rate.
     #[inline]
     #[must_use]
     pub const fn sample_rate(&self) -> f32 {
-        self.cpu.clock_rate()
+        self.cpu.sample_rate()
+    }
+
+    /// Set the audio output sample rate, e.g. `44_100.0` or `48_000.0`.
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.cpu.set_sample_rate(sample_rate);
     }
 
-    /// Get audio samples.
+    /// Dynamic rate control. Report how full the audio device buffer is, from `0.0` (empty) to
+    /// `1.0` (full), so the output rate can be adjusted to keep it around half full.
+    #[inline]
+    pub fn set_audio_buffer_fill(&mut self, fill: f32) {
+        self.cpu.set_audio_buffer_fill(fill);
+    }
+
+    /// Get audio samples at the output sample rate.
     #[inline]
     #[must_use]
     pub fn audio_samples(&self) -> &[f32] {
@@ -175,13 +599,38 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If CPU encounteres an invalid opcode, an error is returned.
-    pub fn clock_instr(&mut self) -> Result<ControlFlow<usize, usize>> {
-        let cycles = self.clock();
+    pub fn clock_instr(&mut self) -> Result<ControlFlow<DebugBreak, usize>> {
+        self.clock_instr_inspect(|_| {})
+    }
+
+    /// Steps the control deck one CPU clock, checking the debugger before and after the
+    /// instruction when it's active and tracing the instruction if a trace logger is set.
+    fn clock_instr_inspect<F>(&mut self, inspect: F) -> Result<ControlFlow<DebugBreak, usize>>
+        where
+            F: FnMut(&mut Cpu),
+    {
+        let debugging = self.debugger.is_active();
+        self.cpu.record_accesses(debugging && self.debugger.has_watchpoints());
+        if debugging {
+            if let Some(reason) = self.debugger.check_before(&self.cpu) {
+                return Ok(ControlFlow::Break(DebugBreak { cycles: 0, reason }));
+            }
+        }
+        if let Some(trace_logger) = &mut self.trace_logger {
+            trace_logger
+                .log(&self.cpu)
+                .map_err(|_| anyhow!("failed to write trace log"))?;
+        }
+        let cycles = self.cpu.clock_inspect(inspect);
         if self.cpu_corrupted() {
-            Err(anyhow!("cpu corrupted"))
-        } else {
-            Ok(ControlFlow::Continue(cycles))
+            return Err(anyhow!("cpu corrupted"));
         }
+        if debugging {
+            if let Some(reason) = self.debugger.check_after(&self.cpu) {
+                return Ok(ControlFlow::Break(DebugBreak { cycles, reason }));
+            }
+        }
+        Ok(ControlFlow::Continue(cycles))
     }
 
     /// Steps the control deck the number of seconds.
@@ -189,15 +638,16 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If CPU encounteres an invalid opcode, an error is returned.
-    pub fn clock_seconds(&mut self, seconds: f32) -> Result<ControlFlow<usize, usize>> {
+    pub fn clock_seconds(&mut self, seconds: f32) -> Result<ControlFlow<DebugBreak, usize>> {
         self.cycles_remaining += self.clock_rate() * seconds;
         let mut total_cycles = 0;
         while self.cycles_remaining > 0.0 {
             match self.clock_instr()? {
-                ControlFlow::Break(cycles) => {
-                    total_cycles += cycles;
-                    self.cycles_remaining -= cycles as f32;
-                    return Ok(ControlFlow::Break(total_cycles));
+                ControlFlow::Break(mut debug_break) => {
+                    total_cycles += debug_break.cycles;
+                    self.cycles_remaining -= debug_break.cycles as f32;
+                    debug_break.cycles = total_cycles;
+                    return Ok(ControlFlow::Break(debug_break));
                 }
                 ControlFlow::Continue(cycles) => {
                     total_cycles += cycles;
@@ -213,21 +663,30 @@ impl ControlDeck {
     ///
     /// # Errors
     ///
-    /// This function will return an error if .
+    /// If CPU encounteres an invalid opcode, an error is returned.
     pub fn clock_seconds_inspect<F>(
         &mut self,
         seconds: f32,
         mut inspect: F,
-    ) -> Result<ControlFlow<usize, usize>>
+    ) -> Result<ControlFlow<DebugBreak, usize>>
         where
             F: FnMut(&mut Cpu),
     {
         self.cycles_remaining += self.clock_rate() * seconds;
         let mut total_cycles = 0;
         while self.cycles_remaining > 0.0 {
-            let cycles = self.cpu.clock_inspect(&mut inspect);
-            total_cycles += cycles;
-            self.cycles_remaining -= cycles as f32;
+            match self.clock_instr_inspect(&mut inspect)? {
+                ControlFlow::Break(mut debug_break) => {
+                    total_cycles += debug_break.cycles;
+                    self.cycles_remaining -= debug_break.cycles as f32;
+                    debug_break.cycles = total_cycles;
+                    return Ok(ControlFlow::Break(debug_break));
+                }
+                ControlFlow::Continue(cycles) => {
+                    total_cycles += cycles;
+                    self.cycles_remaining -= cycles as f32;
+                }
+            }
         }
         Ok(ControlFlow::Continue(total_cycles))
     }
@@ -237,36 +696,95 @@ impl ControlDeck {
     /// # Errors
     ///
     /// If CPU encounteres an invalid opcode, an error is returned.
-    pub fn clock_frame(&mut self) -> Result<ControlFlow<usize, usize>> {
+    pub fn clock_frame(&mut self) -> Result<ControlFlow<DebugBreak, usize>> {
         let mut total_cycles = 0;
         let frame = self.frame_number();
+        let audio_start = self.cpu.audio_samples().len();
         while frame == self.frame_number() {
             match self.clock_instr()? {
-                ControlFlow::Break(cycles) => {
-                    total_cycles += cycles;
-                    return Ok(ControlFlow::Break(total_cycles));
+                ControlFlow::Break(mut debug_break) => {
+                    total_cycles += debug_break.cycles;
+                    debug_break.cycles = total_cycles;
+                    return Ok(ControlFlow::Break(debug_break));
                 }
                 ControlFlow::Continue(cycles) => {
                     total_cycles += cycles;
                 }
             }
         }
+        if let Some(volume) = self.track_volume().filter(|&volume| volume < 1.0) {
+            for sample in &mut self.cpu.audio_samples_mut()[audio_start..] {
+                *sample *= volume;
+            }
+        }
+        self.record_rewind(audio_start)?;
         Ok(ControlFlow::Continue(total_cycles))
     }
 
+    /// Enable rewind with the given configuration, or disable it with `None`. Snapshots are
+    /// recorded at the end of each [`ControlDeck::clock_frame`].
+    pub fn set_rewind(&mut self, config: Option<RewindConfig>) {
+        self.rewind = config.map(Rewind::new);
+    }
+
+    #[inline]
+    pub const fn rewind(&self) -> Option<&Rewind> {
+        self.rewind.as_ref()
+    }
+
+    fn record_rewind(&mut self, audio_start: usize) -> Result<()> {
+        let Some(rewind) = &mut self.rewind else {
+            return Ok(());
+        };
+        if self.rom_crc32.is_none() || !rewind.frame_finished() {
+            return Ok(());
+        }
+        let state = self.save_state().map_err(|err| anyhow!("{err}"))?;
+        let audio = self.cpu.audio_samples();
+        let audio = audio.get(audio_start..).unwrap_or_default();
+        if let Some(rewind) = &mut self.rewind {
+            rewind.push(state, audio);
+        }
+        Ok(())
+    }
+
+    /// Step backwards to the most recent rewind snapshot, returning its frame buffer and the audio
+    /// for that frame reversed. The reversed audio also replaces the pending audio samples.
+    /// Returns `None` if rewind is disabled or there are no snapshots left.
+    ///
+    /// # Errors
+    ///
+    /// If the snapshot fails to restore, an error is returned.
+    pub fn rewind_step(&mut self) -> Result<Option<RewindFrame<'_>>> {
+        let Some((state, mut audio)) = self.rewind.as_mut().and_then(Rewind::step_back) else {
+            return Ok(None);
+        };
+        self.load_state(&state).map_err(|err| anyhow!("{err}"))?;
+        audio.reverse();
+        self.cpu.clear_audio_samples();
+        self.cpu.extend_audio_samples(&audio);
+        self.video
+            .apply_filter(self.cpu.frame_buffer(), self.cpu.frame_number());
+        Ok(Some(RewindFrame {
+            frame_buffer: self.video.output(),
+            audio_samples: self.cpu.audio_samples(),
+        }))
+    }
+
     /// Steps the control deck a single scanline.
     ///
     /// # Errors
     ///
     /// If CPU encounteres an invalid opcode, an error is returned.
-    pub fn clock_scanline(&mut self) -> Result<ControlFlow<usize, usize>> {
+    pub fn clock_scanline(&mut self) -> Result<ControlFlow<DebugBreak, usize>> {
         let current_scanline = self.cpu.ppu_scanline();
         let mut total_cycles = 0;
         while current_scanline == self.cpu.ppu_scanline() {
             match self.clock_instr()? {
-                ControlFlow::Break(cycles) => {
-                    total_cycles += cycles;
-                    return Ok(ControlFlow::Break(total_cycles));
+                ControlFlow::Break(mut debug_break) => {
+                    total_cycles += debug_break.cycles;
+                    debug_break.cycles = total_cycles;
+                    return Ok(ControlFlow::Break(debug_break));
                 }
                 ControlFlow::Continue(cycles) => {
                     total_cycles += cycles;
@@ -276,6 +794,69 @@ impl ControlDeck {
         Ok(ControlFlow::Continue(total_cycles))
     }
 
+    #[inline]
+    pub const fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    #[inline]
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn trace_logger(&self) -> Option<&TraceLogger> {
+        self.trace_logger.as_ref()
+    }
+
+    #[inline]
+    pub fn trace_logger_mut(&mut self) -> Option<&mut TraceLogger> {
+        self.trace_logger.as_mut()
+    }
+
+    /// Log every instruction before it executes, replacing any previous logger.
+    #[inline]
+    pub fn set_trace_logger(&mut self, trace_logger: TraceLogger) {
+        self.trace_logger = Some(trace_logger);
+    }
+
+    /// Stop tracing, returning the logger so its sink can be flushed or reused.
+    #[inline]
+    pub fn take_trace_logger(&mut self) -> Option<TraceLogger> {
+        self.trace_logger.take()
+    }
+
+    /// Break after the next instruction.
+    pub fn step_into(&mut self) {
+        self.debugger.set_step(Some(Step::Into));
+    }
+
+    /// Break after the next instruction, running any subroutine it calls to completion.
+    pub fn step_over(&mut self) {
+        const JSR: u8 = 0x20;
+        let pc = self.cpu.pc();
+        let step = if self.cpu.peek(pc, Access::Dummy) == JSR {
+            Step::Over {
+                pc: pc.wrapping_add(3),
+                sp: self.cpu.sp(),
+            }
+        } else {
+            Step::Into
+        };
+        self.debugger.set_step(Some(step));
+    }
+
+    /// Break after returning from the current subroutine or interrupt handler.
+    pub fn step_out(&mut self) {
+        self.debugger.set_step(Some(Step::Out { sp: self.cpu.sp() }));
+    }
+
+    /// Break when the PPU next reaches `scanline`.
+    pub fn run_to_scanline(&mut self, scanline: u32) {
+        self.debugger.set_step(Some(Step::Scanline(scanline)));
+    }
+
     /// Returns whether the CPU is corrupted or not.
     #[inline]
     #[must_use]
@@ -336,6 +917,12 @@ impl ControlDeck {
         self.cpu.set_cycle_accurate(enabled);
     }
 
+    /// Returns a reference to a joypad.
+    #[inline]
+    pub const fn joypad(&self, slot: Player) -> &Joypad {
+        self.cpu.joypad(slot)
+    }
+
     /// Returns a mutable reference to a joypad.
     #[inline]
     pub fn joypad_mut(&mut self, slot: Player) -> &mut Joypad {
@@ -0,0 +1,598 @@
+//! Structured disassembly of CPU memory, with labels from debug symbol files.
+//!
+//! Supported symbol formats:
+//!
+//! - ca65/ld65 debug info (`.dbg`)
+//! - FCEUX name lists (`.nl`)
+//! - Mesen label files (`.mlb`)
+//!
+//! <https://cc65.github.io/doc/ld65.html#option--dbgfile>
+//! <https://fceux.com/web/help/NLFilesFormat.html>
+
+use crate::{
+    cpu::{
+        instr::{
+            AddrMode::{self, ABS, ABX, ABY, ACC, IDX, IDY, IMM, IMP, IND, REL, ZP0, ZPX, ZPY},
+            Instr,
+            Operation::{JMP, JSR},
+        },
+        Cpu,
+    },
+    mapper::{MappedRead, MemMap},
+    mem::{Access, Mem},
+};
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{fmt, ops::RangeInclusive};
+
+// Size of PRG-ROM banks in FCEUX bank-specific `.nl` files
+const NL_BANK_SIZE: usize = 0x4000;
+// ld65 output offsets include the iNES header
+const INES_HEADER_SIZE: usize = 16;
+
+/// A single disassembled instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Disassembly {
+    pub addr: u16,
+    pub instr: Instr,
+    // Opcode followed by the operand, only the first `len` are valid
+    bytes: [u8; 3],
+    len: u8,
+    // Operand as encoded: an immediate, zero page or absolute address, or a branch offset
+    pub operand: u16,
+    // Address read or written after indexing and indirection, or the branch/jump target
+    pub effective_addr: Option<u16>,
+    // Immediate value or the value currently at `effective_addr`
+    pub value: Option<u8>,
+    pub label: Option<String>,
+    // Label for the address in the operand
+    pub operand_label: Option<String>,
+}
+
+impl Disassembly {
+    /// The encoded instruction bytes.
+    #[inline]
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    /// Number of bytes the instruction takes up.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> u16 {
+        self.len as u16
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    #[inline]
+    pub const fn addr_mode(&self) -> AddrMode {
+        self.instr.addr_mode()
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn mnemonic(&self) -> &'static str {
+        self.instr.mnemonic()
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn is_unofficial(&self) -> bool {
+        self.instr.is_unofficial()
+    }
+
+    // Operand address, or its label if one is known
+    fn fmt_addr(&self, f: &mut fmt::Formatter<'_>, addr: u16, zero_page: bool) -> fmt::Result {
+        match &self.operand_label {
+            Some(label) => write!(f, "{label}"),
+            None if zero_page => write!(f, "${addr:02X}"),
+            None => write!(f, "${addr:04X}"),
+        }
+    }
+}
+
+impl fmt::Display for Disassembly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04X} ", self.addr)?;
+        for byte in self.bytes() {
+            write!(f, "{byte:02X} ")?;
+        }
+        for _ in self.bytes().len()..3 {
+            write!(f, "   ")?;
+        }
+        write!(f, "{:?}", self.instr)?;
+
+        let effective_addr = self.effective_addr.unwrap_or_default();
+        let value = self.value.unwrap_or_default();
+        match self.addr_mode() {
+            IMM => write!(f, " #${value:02X}"),
+            ZP0 | ZPX | ZPY => {
+                write!(f, " ")?;
+                self.fmt_addr(f, self.operand, true)?;
+                match self.addr_mode() {
+                    ZPX => write!(f, ",X @ ${effective_addr:02X}")?,
+                    ZPY => write!(f, ",Y @ ${effective_addr:02X}")?,
+                    _ => (),
+                }
+                write!(f, " = #${value:02X}")
+            }
+            ABS | ABX | ABY => {
+                write!(f, " ")?;
+                self.fmt_addr(f, self.operand, false)?;
+                match self.addr_mode() {
+                    ABX => write!(f, ",X @ ${effective_addr:04X}")?,
+                    ABY => write!(f, ",Y @ ${effective_addr:04X}")?,
+                    _ => (),
+                }
+                match self.value {
+                    Some(value) => write!(f, " = #${value:02X}"),
+                    None => Ok(()),
+                }
+            }
+            IND => {
+                write!(f, " (")?;
+                self.fmt_addr(f, self.operand, false)?;
+                write!(f, ") = ${effective_addr:04X}")
+            }
+            IDX => {
+                write!(f, " (")?;
+                self.fmt_addr(f, self.operand, true)?;
+                write!(f, ",X) @ ${effective_addr:04X} = #${value:02X}")
+            }
+            IDY => {
+                write!(f, " (")?;
+                self.fmt_addr(f, self.operand, true)?;
+                write!(f, "),Y @ ${effective_addr:04X} = #${value:02X}")
+            }
+            REL => {
+                write!(f, " ")?;
+                self.fmt_addr(f, effective_addr, false)
+            }
+            ACC | IMP => Ok(()),
+        }
+    }
+}
+
+impl Cpu {
+    /// Disassemble the instruction at `addr` without side effects, resolving effective addresses
+    /// and values from the current CPU state.
+    pub fn disassemble(&self, addr: u16, symbols: Option<&Symbols>) -> Disassembly {
+        let opcode = self.peek(addr, Access::Dummy);
+        let instr = Cpu::INSTRUCTIONS[opcode as usize];
+        let operand_addr = addr.wrapping_add(1);
+        let len = match instr.addr_mode() {
+            ACC | IMP => 1,
+            IMM | ZP0 | ZPX | ZPY | IDX | IDY | REL => 2,
+            ABS | ABX | ABY | IND => 3,
+        };
+        let mut bytes = [opcode, 0x00, 0x00];
+        for (i, byte) in bytes.iter_mut().enumerate().take(len).skip(1) {
+            *byte = self.peek(addr.wrapping_add(i as u16), Access::Dummy);
+        }
+        let operand = match len {
+            2 => u16::from(bytes[1]),
+            3 => u16::from_le_bytes([bytes[1], bytes[2]]),
+            _ => 0x0000,
+        };
+
+        let effective_addr = match instr.addr_mode() {
+            ZP0 => Some(operand),
+            ZPX => Some(u16::from(bytes[1].wrapping_add(self.x))),
+            ZPY => Some(u16::from(bytes[1].wrapping_add(self.y))),
+            ABS => Some(operand),
+            ABX => Some(operand.wrapping_add(self.x.into())),
+            ABY => Some(operand.wrapping_add(self.y.into())),
+            IND => {
+                // JMP ($xxFF) wraps within the page
+                let lo = self.peek(operand, Access::Dummy);
+                let hi = self.peek(
+                    (operand & 0xFF00) | (operand.wrapping_add(1) & 0x00FF),
+                    Access::Dummy,
+                );
+                Some(u16::from_le_bytes([lo, hi]))
+            }
+            IDX => Some(self.peek_zp_u16(bytes[1].wrapping_add(self.x))),
+            IDY => Some(self.peek_zp_u16(bytes[1]).wrapping_add(self.y.into())),
+            // Sign extend the offset
+            REL => Some(
+                operand_addr
+                    .wrapping_add(1)
+                    .wrapping_add(i16::from(bytes[1] as i8) as u16),
+            ),
+            IMM | ACC | IMP => None,
+        };
+        let value = match instr.addr_mode() {
+            IMM => Some(bytes[1]),
+            ABS if matches!(instr.op(), JMP | JSR) => None,
+            ZP0 | ZPX | ZPY | ABS | ABX | ABY | IDX | IDY => {
+                effective_addr.map(|addr| self.peek(addr, Access::Dummy))
+            }
+            IND | REL | ACC | IMP => None,
+        };
+
+        let (label, operand_label) = symbols.map_or((None, None), |symbols| {
+            let label = symbols.label(self, addr).map(ToString::to_string);
+            let operand_label = match instr.addr_mode() {
+                REL => effective_addr.and_then(|addr| symbols.label(self, addr)),
+                IMM | ACC | IMP => None,
+                _ => symbols.label(self, operand),
+            };
+            (label, operand_label.map(ToString::to_string))
+        });
+
+        Disassembly {
+            addr,
+            instr,
+            bytes,
+            len: len as u8,
+            operand,
+            effective_addr,
+            value,
+            label,
+            operand_label,
+        }
+    }
+
+    /// Disassemble every instruction starting in `range`, decoding sequentially from its start.
+    pub fn disassemble_range(
+        &self,
+        range: RangeInclusive<u16>,
+        symbols: Option<&Symbols>,
+    ) -> Vec<Disassembly> {
+        let mut instrs = Vec::new();
+        let mut addr = u32::from(*range.start());
+        while addr <= u32::from(*range.end()) {
+            let disasm = self.disassemble(addr as u16, symbols);
+            addr += u32::from(disasm.len());
+            instrs.push(disasm);
+        }
+        instrs
+    }
+}
+
+/// The memory a label refers to. Labels in banked memory follow the bank currently mapped in.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[must_use]
+pub enum SymbolAddr {
+    /// A fixed CPU address, such as internal RAM or a register.
+    Cpu(u16),
+    /// An absolute offset into PRG-ROM.
+    PrgRom(usize),
+    /// An absolute offset into PRG-RAM.
+    PrgRam(usize),
+}
+
+/// Errors returned when loading a symbol file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum SymbolError {
+    /// A line could not be parsed. Line numbers start at 1.
+    Syntax { line: usize },
+}
+
+impl fmt::Display for SymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax { line } => write!(f, "invalid symbol definition on line {line}"),
+        }
+    }
+}
+
+impl core::error::Error for SymbolError {}
+
+/// Labels for CPU addresses, loaded from debug symbol files.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Symbols {
+    labels: BTreeMap<SymbolAddr, String>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, addr: SymbolAddr, label: impl Into<String>) {
+        self.labels.insert(addr, label.into());
+    }
+
+    #[must_use]
+    pub fn get(&self, addr: SymbolAddr) -> Option<&str> {
+        self.labels.get(&addr).map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.labels.clear();
+    }
+
+    /// The label for a CPU address, mapping it through the cartridge to PRG-ROM or PRG-RAM.
+    #[must_use]
+    pub fn label(&self, cpu: &Cpu, addr: u16) -> Option<&str> {
+        let mapped = match addr {
+            0x4020..=0xFFFF => match cpu.mapper().map_peek(addr) {
+                MappedRead::PrgRom(offset) => self.get(SymbolAddr::PrgRom(offset)),
+                MappedRead::PrgRam(offset) => self.get(SymbolAddr::PrgRam(offset)),
+                _ => None,
+            },
+            _ => None,
+        };
+        // Internal RAM is mirrored up to $1FFF
+        let addr = if addr < 0x2000 { addr & 0x07FF } else { addr };
+        mapped.or_else(|| self.get(SymbolAddr::Cpu(addr)))
+    }
+
+    /// Load labels from ld65 debug info. Labels in read-only segments written to the ROM are
+    /// mapped to PRG-ROM, assuming the output starts with a 16-byte `iNES` header. Returns the
+    /// number of labels loaded.
+    pub fn load_dbg(&mut self, text: &str) -> Result<usize, SymbolError> {
+        // Segment id -> (start address, PRG-ROM offset)
+        let mut segments = BTreeMap::new();
+        let mut labels = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line_num = index + 1;
+            let Some((kind, fields)) = line.trim().split_once(char::is_whitespace) else {
+                continue;
+            };
+            let field = |key: &str| {
+                fields.split(',').find_map(|field| {
+                    let (name, value) = field.split_once('=')?;
+                    (name.trim() == key).then(|| value.trim().trim_matches('"'))
+                })
+            };
+            match kind {
+                "seg" => {
+                    let (Some(id), Some(start)) = (
+                        field("id").and_then(parse_num),
+                        field("start").and_then(parse_num),
+                    ) else {
+                        return Err(SymbolError::Syntax { line: line_num });
+                    };
+                    let offset = field("ooffs")
+                        .and_then(parse_num)
+                        .filter(|&offset| field("type") == Some("ro") && offset >= INES_HEADER_SIZE)
+                        .map(|offset| offset - INES_HEADER_SIZE);
+                    segments.insert(id, (start, offset));
+                }
+                "sym" if field("type") == Some("lab") => {
+                    let (Some(name), Some(val)) = (field("name"), field("val").and_then(parse_num))
+                    else {
+                        return Err(SymbolError::Syntax { line: line_num });
+                    };
+                    let seg = field("seg").and_then(parse_num);
+                    labels.push((name, val, seg, line_num));
+                }
+                _ => (),
+            }
+        }
+
+        let count = labels.len();
+        for (name, val, seg, line_num) in labels {
+            let addr = match seg.and_then(|seg| segments.get(&seg)) {
+                Some(&(start, Some(offset))) if val >= start => {
+                    SymbolAddr::PrgRom(offset + val - start)
+                }
+                _ => SymbolAddr::Cpu(
+                    u16::try_from(val).map_err(|_| SymbolError::Syntax { line: line_num })?,
+                ),
+            };
+            self.insert(addr, name);
+        }
+        Ok(count)
+    }
+
+    /// Load labels from an FCEUX name list, one `$addr#label#comment` per line. Pass the PRG-ROM
+    /// bank for bank files (`game.nes.0.nl`) or `None` for `game.nes.ram.nl`. Returns the number
+    /// of labels loaded.
+    pub fn load_nl(&mut self, text: &str, bank: Option<usize>) -> Result<usize, SymbolError> {
+        let mut count = 0;
+        for (index, line) in text.lines().enumerate() {
+            let Some(line) = line.trim().strip_prefix('$') else {
+                continue;
+            };
+            let mut fields = line.splitn(3, '#');
+            // Arrays are written as `$addr/size`
+            let addr = fields
+                .next()
+                .and_then(|addr| addr.split('/').next())
+                .and_then(|addr| u16::from_str_radix(addr, 16).ok());
+            let (Some(addr), Some(label)) = (addr, fields.next()) else {
+                return Err(SymbolError::Syntax { line: index + 1 });
+            };
+            if label.is_empty() {
+                continue;
+            }
+            let addr = match bank {
+                Some(bank) if addr >= 0x8000 => {
+                    SymbolAddr::PrgRom(bank * NL_BANK_SIZE + (addr as usize % NL_BANK_SIZE))
+                }
+                _ => SymbolAddr::Cpu(addr),
+            };
+            self.insert(addr, label);
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Load labels from a Mesen label file, one `type:addr:label:comment` per line. Work RAM and
+    /// save RAM labels both map to PRG-RAM. Returns the number of labels loaded.
+    pub fn load_mlb(&mut self, text: &str) -> Result<usize, SymbolError> {
+        let mut count = 0;
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.splitn(4, ':');
+            let kind = fields.next().unwrap_or_default();
+            // Multi-byte labels are written as `start-end`
+            let addr = fields
+                .next()
+                .and_then(|addr| addr.split('-').next())
+                .and_then(|addr| usize::from_str_radix(addr, 16).ok());
+            let (Some(addr), Some(label)) = (addr, fields.next()) else {
+                return Err(SymbolError::Syntax { line: index + 1 });
+            };
+            if label.is_empty() {
+                continue;
+            }
+            let cpu_addr = || {
+                u16::try_from(addr)
+                    .map(SymbolAddr::Cpu)
+                    .map_err(|_| SymbolError::Syntax { line: index + 1 })
+            };
+            let addr = match kind {
+                "P" | "NesPrgRom" => SymbolAddr::PrgRom(addr),
+                "S" | "W" | "NesSaveRam" | "NesWorkRam" => SymbolAddr::PrgRam(addr),
+                "R" | "NesInternalRam" | "G" | "NesMemory" => cpu_addr()?,
+                // CHR and other memory types can't be labeled in CPU disassembly
+                _ => continue,
+            };
+            self.insert(addr, label);
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+fn parse_num(s: &str) -> Option<usize> {
+    match s.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_deck::ControlDeck;
+    use alloc::{format, vec};
+
+    #[rustfmt::skip]
+    const PROGRAM: &[u8] = &[
+        0xA2, 0x02,             // $8000 LDX #$02
+        0x8E, 0x00, 0x03,       // $8002 STX $0300
+        0xBD, 0xFE, 0x02,       // $8005 LDA $02FE,X
+        0xD0, 0xF7,             // $8008 BNE $8001
+        0x6C, 0x20, 0x80,       // $800A JMP ($8020)
+        0x07, 0x10,             // $800D SLO $10
+        0x80, 0x01,             // $800F NOP #$01
+        0x02,                   // $8011 STP
+    ];
+
+    fn test_deck() -> ControlDeck {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x02, 0x01];
+        rom.resize(16, 0x00);
+        let mut prg_rom = vec![0xEA; 0x8000];
+        prg_rom[..PROGRAM.len()].copy_from_slice(PROGRAM);
+        prg_rom[0x20..0x22].copy_from_slice(&[0x34, 0x12]);
+        // Reset vector
+        prg_rom[0x7FFC..0x7FFE].copy_from_slice(&[0x00, 0x80]);
+        rom.extend_from_slice(&prg_rom);
+        rom.resize(rom.len() + 0x2000, 0x00);
+        let mut deck = ControlDeck::new();
+        deck.load_rom("disasm".into(), rom).expect("valid rom");
+        deck
+    }
+
+    #[test]
+    fn disassemble() {
+        let mut deck = test_deck();
+        let _ = deck.clock_instr().expect("valid clock");
+        let _ = deck.clock_instr().expect("valid clock");
+        let cpu = deck.cpu();
+
+        let ldx = cpu.disassemble(0x8000, None);
+        assert_eq!(ldx.bytes(), [0xA2, 0x02]);
+        assert_eq!(ldx.mnemonic(), "LDX");
+        assert_eq!(ldx.addr_mode(), IMM);
+        assert_eq!((ldx.effective_addr, ldx.value), (None, Some(0x02)));
+
+        let lda = cpu.disassemble(0x8005, None);
+        assert_eq!(lda.operand, 0x02FE);
+        assert_eq!((lda.effective_addr, lda.value), (Some(0x0300), Some(0x02)));
+        assert_eq!(
+            format!("{lda}"),
+            "8005 BD FE 02  LDA $02FE,X @ $0300 = #$02"
+        );
+
+        let bne = cpu.disassemble(0x8008, None);
+        assert_eq!(bne.effective_addr, Some(0x8001));
+        assert_eq!(format!("{bne}"), "8008 D0 F7     BNE $8001");
+
+        let jmp = cpu.disassemble(0x800A, None);
+        assert_eq!((jmp.effective_addr, jmp.value), (Some(0x1234), None));
+
+        let instrs = cpu.disassemble_range(0x800D..=0x8011, None);
+        let names = instrs
+            .iter()
+            .map(|instr| format!("{:?}", instr.instr))
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["*SLO", "*NOP", "*STP"]);
+        assert!(instrs.iter().all(Disassembly::is_unofficial));
+        assert_eq!(
+            instrs.iter().map(|instr| instr.addr).collect::<Vec<_>>(),
+            [0x800D, 0x800F, 0x8011]
+        );
+    }
+
+    #[test]
+    fn labels() {
+        let deck = test_deck();
+        let cpu = deck.cpu();
+        let mut symbols = Symbols::new();
+
+        let dbg = "version\tmajor=2,minor=0\n\
+            seg\tid=0,name=\"CODE\",start=0x008000,size=0x0012,addrsize=absolute,type=ro,oname=\"game.nes\",ooffs=16\n\
+            seg\tid=1,name=\"BSS\",start=0x000300,size=0x0010,addrsize=absolute,type=rw\n\
+            sym\tid=0,name=\"reset\",addrsize=absolute,scope=0,def=1,val=0x8000,seg=0,type=lab\n\
+            sym\tid=1,name=\"buffer\",addrsize=absolute,scope=0,def=2,val=0x300,seg=1,type=lab\n\
+            sym\tid=2,name=\"COUNT\",addrsize=zeropage,scope=0,def=3,val=0x2,type=equ\n";
+        assert_eq!(symbols.load_dbg(dbg), Ok(2));
+        assert_eq!(symbols.get(SymbolAddr::PrgRom(0x0000)), Some("reset"));
+        assert_eq!(symbols.get(SymbolAddr::Cpu(0x0300)), Some("buffer"));
+
+        let nl = "$8001#loop#Main loop\n$8008##\n$C011#stop#";
+        assert_eq!(symbols.load_nl(nl, Some(1)), Ok(2));
+        assert_eq!(symbols.get(SymbolAddr::PrgRom(0x4001)), Some("loop"));
+        assert_eq!(symbols.get(SymbolAddr::PrgRom(0x4011)), Some("stop"));
+        assert_eq!(
+            symbols.load_nl("$zz#bad#", None),
+            Err(SymbolError::Syntax { line: 1 })
+        );
+
+        let mlb = "P:0001:loop2:comment: with colon\nR:0010-0011:ptr\nW:0000:save\nC:0000:tile\n";
+        assert_eq!(symbols.load_mlb(mlb), Ok(3));
+        assert_eq!(symbols.get(SymbolAddr::Cpu(0x0010)), Some("ptr"));
+        assert_eq!(symbols.get(SymbolAddr::PrgRam(0x0000)), Some("save"));
+
+        // 32K NROM maps $8000 to PRG-ROM offset 0 and mirrors internal RAM
+        assert_eq!(symbols.label(cpu, 0x8000), Some("reset"));
+        assert_eq!(symbols.label(cpu, 0x8001), Some("loop2"));
+        assert_eq!(symbols.label(cpu, 0x0B00), Some("buffer"));
+        assert_eq!(symbols.label(cpu, 0x6000), Some("save"));
+
+        let instrs = cpu.disassemble_range(0x8000..=0x8009, Some(&symbols));
+        assert_eq!(instrs[0].label.as_deref(), Some("reset"));
+        assert_eq!(format!("{}", instrs[1]), "8002 8E 00 03  STX buffer = #$00");
+        assert_eq!(format!("{}", instrs[3]), "8008 D0 F7     BNE loop2");
+    }
+}
@@ -1,5 +1,6 @@
 use bitflags::Flags;
 use crate::{
+    cdl::PrgFlags,
     cpu::{Cpu, Status},
     mem::{Access, Mem},
 };
@@ -66,6 +67,42 @@ impl Instr {
     pub const fn cycles(&self) -> usize {
         self.3
     }
+
+    /// Whether this opcode is not part of the documented 6502 instruction set.
+    #[must_use]
+    pub const fn is_unofficial(&self) -> bool {
+        match self.op() {
+            NOP => self.opcode() != 0xEA, // 0xEA is the only official NOP
+            SBC => self.opcode() == 0xEB,
+            XXX | SKB | IGN | ISB | DCP | AXS | LAS | LAX | AHX | SAX | XAA | SXA | RRA | TAS
+            | SYA | ARR | SRE | ALR | RLA | ANC | SLO => true,
+            _ => false,
+        }
+    }
+
+    /// The common assembler mnemonic, e.g. `NOP` for `SKB`/`IGN` and `STP` for the opcodes that
+    /// halt the CPU.
+    #[must_use]
+    #[rustfmt::skip]
+    pub const fn mnemonic(&self) -> &'static str {
+        match self.op() {
+            ADC => "ADC", AND => "AND", ASL => "ASL", BCC => "BCC", BCS => "BCS", BEQ => "BEQ",
+            BIT => "BIT", BMI => "BMI", BNE => "BNE", BPL => "BPL", BRK => "BRK", BVC => "BVC",
+            BVS => "BVS", CLC => "CLC", CLD => "CLD", CLI => "CLI", CLV => "CLV", CMP => "CMP",
+            CPX => "CPX", CPY => "CPY", DEC => "DEC", DEX => "DEX", DEY => "DEY", EOR => "EOR",
+            INC => "INC", INX => "INX", INY => "INY", JMP => "JMP", JSR => "JSR", LDA => "LDA",
+            LDX => "LDX", LDY => "LDY", LSR => "LSR", ORA => "ORA", PHA => "PHA", PHP => "PHP",
+            PLA => "PLA", PLP => "PLP", ROL => "ROL", ROR => "ROR", RTI => "RTI", RTS => "RTS",
+            SBC => "SBC", SEC => "SEC", SED => "SED", SEI => "SEI", STA => "STA", STX => "STX",
+            STY => "STY", TAX => "TAX", TAY => "TAY", TSX => "TSX", TXA => "TXA", TXS => "TXS",
+            TYA => "TYA",
+            NOP | SKB | IGN => "NOP",
+            ISB => "ISB", DCP => "DCP", AXS => "AXS", LAS => "LAS", LAX => "LAX", AHX => "AHX",
+            SAX => "SAX", XAA => "XAA", SXA => "SHX", SYA => "SHY", RRA => "RRA", TAS => "TAS",
+            ARR => "ARR", SRE => "SRE", ALR => "ALR", RLA => "RLA", ANC => "ANC", SLO => "SLO",
+            XXX => "STP",
+        }
+    }
 }
 
 /// CPU Addressing Modes
@@ -103,7 +140,7 @@ impl Cpu {
     //  2    PC     R  read next instruction byte (and throw it away)
     #[inline]
     pub(super) fn acc(&mut self) {
-        let _ = self.read(self.pc, Access::Read); // Cycle 2, Read and throw away
+        let _ = self.read(self.pc, Access::Dummy); // Cycle 2, Read and throw away
     }
 
     /// Implied
@@ -114,7 +151,7 @@ impl Cpu {
     //    2    PC     R  read next instruction byte (and throw it away)
     #[inline]
     pub(super) fn imp(&mut self) {
-        let _ = self.read(self.pc, Access::Read); // Cycle 2, Read and throw away
+        let _ = self.read(self.pc, Access::Dummy); // Cycle 2, Read and throw away
     }
 
     /// Immediate
@@ -855,7 +892,7 @@ impl Cpu {
             self.run_irq = false;
         }
 
-        self.read(self.pc, Access::Read); // Dummy read
+        self.read(self.pc, Access::Dummy); // Dummy read
 
         self.abs_addr = if self.rel_addr & 0x80 == 0x80 {
             self.pc.wrapping_add(self.rel_addr | 0xFF00)
@@ -863,7 +900,7 @@ impl Cpu {
             self.pc.wrapping_add(self.rel_addr)
         };
         if Self::pages_differ(self.abs_addr, self.pc) {
-            self.read(self.pc, Access::Read); // Dummy read
+            self.read(self.pc, Access::Dummy); // Dummy read
         }
         self.pc = self.abs_addr;
     }
@@ -936,6 +973,9 @@ impl Cpu {
     #[inline]
     pub(super) fn jmp(&mut self) {
         self.pc = self.abs_addr;
+        if self.instr.addr_mode() == IND {
+            self.bus.log_prg(self.pc, PrgFlags::INDIRECT_CODE);
+        }
     }
     /// JSR: Jump to Location Save Return addr
     //  #  address R/W description
@@ -983,7 +1023,7 @@ impl Cpu {
     pub(super) fn rts(&mut self) {
         let _ = self.read(Self::SP_BASE | u16::from(self.sp), Access::Read); // Cycle 3
         self.pc = self.pop_u16().wrapping_add(1); // Cycles 4 & 5
-        let _ = self.read(self.pc, Access::Read); // Cycle 6
+        let _ = self.read(self.pc, Access::Dummy); // Cycle 6
     }
 
     ///  Register opcodes
@@ -1396,18 +1436,7 @@ impl Cpu {
 
 impl core::fmt::Debug for Instr {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::result::Result<(), core::fmt::Error> {
-        let mut op = self.op();
-        let unofficial = match self.op() {
-            XXX | ISB | DCP | AXS | LAS | LAX | AHX | SAX | XAA | SXA | RRA | TAS | SYA | ARR
-            | SRE | ALR | RLA | ANC | SLO => "*",
-            NOP if self.opcode() != 0xEA => "*", // 0xEA is the only official NOP
-            SKB | IGN => {
-                op = NOP;
-                "*"
-            }
-            SBC if self.opcode() == 0xEB => "*",
-            _ => "",
-        };
-        write!(f, "{unofficial:1}{op:?}")
+        let unofficial = if self.is_unofficial() { "*" } else { "" };
+        write!(f, "{unofficial:1}{}", self.mnemonic())
     }
 }
@@ -2,18 +2,20 @@
 //!
 //! <http://wiki.nesdev.com/w/index.php/CPU>
 
-use alloc::format;
-use alloc::string::{String, ToString};
+use alloc::string::String;
 use alloc::vec::Vec;
 use crate::{
     apu::{Apu, Channel},
     bus::Bus,
     cart::Cart,
+    cdl::{Cdl, CdlError, PrgFlags},
     common::{Clock, ResetKind, NesRegion, Regional, Reset},
+    debugger::{AddrSpace, MemAccess},
     input::{FourPlayer, Joypad, Player, Zapper},
     mapper::Mapper,
     mem::{Access, Mem},
     ppu::Ppu,
+    trace::TraceFormat,
 };
 use bitflags::{bitflags, Flags};
 use instr::{
@@ -28,10 +30,11 @@ use instr::{
     },
 };
 use serde::{Deserialize, Serialize};
-use core::fmt::{self, Write};
+use core::fmt;
 use anyhow::Result;
 use crate::mem::RamState;
 
+pub mod disasm;
 pub mod instr;
 
 bitflags! {
@@ -125,7 +128,9 @@ pub struct Cpu {
     halt: bool,
     dummy_read: bool,
     cycle_accurate: bool,
-    disasm: String,
+    // Memory accesses by the current instruction, recorded for debugger watchpoints
+    #[serde(skip)]
+    accesses: Option<Vec<MemAccess>>,
 }
 
 impl Cpu {
@@ -181,7 +186,7 @@ impl Cpu {
             halt: false,
             dummy_read: false,
             cycle_accurate: true,
-            disasm: String::with_capacity(100),
+            accesses: None,
         };
         cpu.set_region(cpu.region);
         cpu
@@ -250,10 +255,39 @@ impl Cpu {
         self.corrupted
     }
 
+    /// Enable or disable recording the memory accesses made by each instruction.
+    pub fn record_accesses(&mut self, enabled: bool) {
+        if enabled != self.accesses.is_some() {
+            self.accesses = enabled.then(Vec::new);
+        }
+    }
+
+    /// Memory accesses made by the last instruction, if recording is enabled. Includes PPU
+    /// memory accessed through `PPUDATA` ($2007).
     #[inline]
-    #[must_use]
-    pub fn disasm(&self) -> &str {
-        &self.disasm
+    pub fn accesses(&self) -> &[MemAccess] {
+        self.accesses.as_deref().unwrap_or_default()
+    }
+
+    /// The PPU address and value accessed if `addr` is `PPUDATA` ($2007) or a mirror, while
+    /// recording accesses.
+    fn ppudata_access(&self, addr: u16) -> Option<(u16, u8)> {
+        if self.accesses.is_none() || addr & 0xE007 != 0x2007 {
+            return None;
+        }
+        let ppu_addr = self.bus.ppu().addr() & 0x3FFF;
+        Some((ppu_addr, self.bus.ppu().peek(ppu_addr, Access::Dummy)))
+    }
+
+    fn record_access(&mut self, space: AddrSpace, addr: u16, val: u8, access: Access) {
+        if let Some(accesses) = &mut self.accesses {
+            accesses.push(MemAccess {
+                space,
+                addr,
+                val,
+                access,
+            });
+        }
     }
 
     #[inline]
@@ -320,6 +354,22 @@ impl Cpu {
         self.bus.load_cart(cart);
     }
 
+    #[inline]
+    pub fn power_on(&mut self, ram_state: RamState) {
+        self.bus.power_on(ram_state);
+    }
+
+    /// Replaces the current state with a deserialized snapshot, keeping the currently loaded
+    /// PRG-ROM, CHR-ROM and audio output since those are not part of serialized state.
+    pub(crate) fn restore(&mut self, mut snapshot: Self) {
+        let sram_dirty = self.bus.sram_dirty() || self.bus.sram() != snapshot.bus.sram();
+        snapshot.bus.swap_unserialized(&mut self.bus);
+        *self = snapshot;
+        if sram_dirty {
+            self.bus.mark_sram_dirty();
+        }
+    }
+
     #[inline]
     #[must_use]
     pub const fn cart_battery_backed(&self) -> bool {
@@ -337,6 +387,55 @@ impl Cpu {
         self.bus.load_sram(sram);
     }
 
+    #[inline]
+    #[must_use]
+    pub const fn cart_chr_battery_backed(&self) -> bool {
+        self.bus.cart_chr_battery_backed()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_sram(&self) -> &[u8] {
+        self.bus.chr_sram()
+    }
+
+    #[inline]
+    pub fn load_chr_sram(&mut self, chr_sram: Vec<u8>) {
+        self.bus.load_chr_sram(chr_sram);
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn cdl_enabled(&self) -> bool {
+        self.bus.cdl_enabled()
+    }
+
+    #[inline]
+    pub fn enable_cdl(&mut self, enabled: bool) {
+        self.bus.enable_cdl(enabled);
+    }
+
+    #[inline]
+    pub fn cdl(&self) -> Option<Cdl> {
+        self.bus.cdl()
+    }
+
+    #[inline]
+    pub fn load_cdl(&mut self, data: &[u8]) -> Result<(), CdlError> {
+        self.bus.load_cdl(data)
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn sram_dirty(&self) -> bool {
+        self.bus.sram_dirty()
+    }
+
+    #[inline]
+    pub fn clear_sram_dirty(&mut self) {
+        self.bus.clear_sram_dirty();
+    }
+
     #[inline]
     #[must_use]
     pub fn wram(&self) -> &[u8] {
@@ -399,11 +498,38 @@ impl Cpu {
         self.bus.audio_samples()
     }
 
+    #[inline]
+    #[must_use]
+    pub(crate) fn audio_samples_mut(&mut self) -> &mut [f32] {
+        self.bus.audio_samples_mut()
+    }
+
     #[inline]
     pub fn clear_audio_samples(&mut self) {
         self.bus.clear_audio_samples();
     }
 
+    #[inline]
+    pub(crate) fn extend_audio_samples(&mut self, samples: &[f32]) {
+        self.bus.extend_audio_samples(samples);
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn sample_rate(&self) -> f32 {
+        self.bus.sample_rate()
+    }
+
+    #[inline]
+    pub fn set_sample_rate(&mut self, sample_rate: f32) {
+        self.bus.set_sample_rate(sample_rate);
+    }
+
+    #[inline]
+    pub fn set_audio_buffer_fill(&mut self, fill: f32) {
+        self.bus.set_audio_buffer_fill(fill);
+    }
+
     #[inline]
     pub const fn four_player(&self) -> FourPlayer {
         self.bus.four_player()
@@ -549,7 +675,7 @@ impl Cpu {
                 if self.dmc_dma && !self.halt && !self.dummy_read {
                     // DMC DMA ready to read a byte (halt and dummy read done before)
                     self.process_dma_cycle();
-                    read_val = self.bus.read(self.bus.dmc_dma_addr(), Access::Dummy);
+                    read_val = self.bus.read_dmc(self.bus.dmc_dma_addr());
                     self.end_cycle(Cycle::Read);
                     self.bus.load_dmc_buffer(read_val);
                     self.dmc_dma = false;
@@ -691,8 +817,13 @@ impl Cpu {
                     _ => self.read(self.abs_addr, Access::Read), // Cycle 2/4/5 read
                 }
             }
+            // Immediate values are part of the instruction
+            IMM => self.read(self.abs_addr, Access::Execute),
             _ => self.read(self.abs_addr, Access::Read), // Cycle 2/4/5 read
         };
+        if matches!(mode, IDX | IDY) {
+            self.bus.log_prg(self.abs_addr, PrgFlags::INDIRECT_DATA);
+        }
     }
 
     // Writes data back to where fetched_data was sourced from. Either accumulator or memory
@@ -710,7 +841,7 @@ impl Cpu {
     #[must_use]
     #[inline]
     fn read_instr(&mut self) -> u8 {
-        let val = self.read(self.pc, Access::Read);
+        let val = self.read(self.pc, Access::Execute);
         self.pc = self.pc.wrapping_add(1);
         val
     }
@@ -760,152 +891,12 @@ impl Cpu {
         u16::from_le_bytes([lo, hi])
     }
 
-    pub fn disassemble(&mut self, pc: &mut u16) {
-        let opcode = self.peek(*pc, Access::Dummy);
-        let instr = Cpu::INSTRUCTIONS[opcode as usize];
-        let mut bytes = Vec::with_capacity(3);
-        self.disasm.clear();
-        let _ = write!(self.disasm, "{pc:04X} ");
-        bytes.push(opcode);
-        let mut addr = pc.wrapping_add(1);
-        let mode = match instr.addr_mode() {
-            IMM => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                addr = addr.wrapping_add(1);
-                format!(" #${:02X}", bytes[1])
-            }
-            ZP0 => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                addr = addr.wrapping_add(1);
-                let val = self.peek(bytes[1].into(), Access::Dummy);
-                format!(" ${:02X} = #${val:02X}", bytes[1])
-            }
-            ZPX => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                addr = addr.wrapping_add(1);
-                let x_offset = bytes[1].wrapping_add(self.x);
-                let val = self.peek(x_offset.into(), Access::Dummy);
-                format!(" ${:02X},X @ ${x_offset:02X} = #${val:02X}", bytes[1])
-            }
-            ZPY => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                addr = addr.wrapping_add(1);
-                let y_offset = bytes[1].wrapping_add(self.y);
-                let val = self.peek(y_offset.into(), Access::Dummy);
-                format!(" ${:02X},Y @ ${y_offset:02X} = #${val:02X}", bytes[1])
-            }
-            ABS => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                bytes.push(self.peek(addr.wrapping_add(1), Access::Dummy));
-                let abs_addr = self.peek_u16(addr);
-                addr = addr.wrapping_add(2);
-                if instr.op() == JMP || instr.op() == JSR {
-                    format!(" ${abs_addr:04X}")
-                } else {
-                    let val = self.peek(abs_addr, Access::Dummy);
-                    format!(" ${abs_addr:04X} = #${val:02X}")
-                }
-            }
-            ABX => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                bytes.push(self.peek(addr.wrapping_add(1), Access::Dummy));
-                let abs_addr = self.peek_u16(addr);
-                addr = addr.wrapping_add(2);
-                let x_offset = abs_addr.wrapping_add(self.x.into());
-                let val = self.peek(x_offset, Access::Dummy);
-                format!(" ${abs_addr:04X},X @ ${x_offset:04X} = #${val:02X}")
-            }
-            ABY => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                bytes.push(self.peek(addr.wrapping_add(1), Access::Dummy));
-                let abs_addr = self.peek_u16(addr);
-                addr = addr.wrapping_add(2);
-                let y_offset = abs_addr.wrapping_add(self.y.into());
-                let val = self.peek(y_offset, Access::Dummy);
-                format!(" ${abs_addr:04X},Y @ ${y_offset:04X} = #${val:02X}")
-            }
-            IND => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                bytes.push(self.peek(addr.wrapping_add(1), Access::Dummy));
-                let abs_addr = self.peek_u16(addr);
-                addr = addr.wrapping_add(2);
-                let lo = self.peek(abs_addr, Access::Dummy);
-                let hi = if abs_addr & 0x00FF == 0x00FF {
-                    self.peek(abs_addr & 0xFF00, Access::Dummy)
-                } else {
-                    self.peek(abs_addr + 1, Access::Dummy)
-                };
-                let val = u16::from_le_bytes([lo, hi]);
-                format!(" (${abs_addr:04X}) = ${val:04X}")
-            }
-            IDX => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                addr = addr.wrapping_add(1);
-                let x_offset = bytes[1].wrapping_add(self.x);
-                let abs_addr = self.peek_zp_u16(x_offset);
-                let val = self.peek(abs_addr, Access::Dummy);
-                format!(" (${:02X},X) @ ${abs_addr:04X} = #${val:02X}", bytes[1])
-            }
-            IDY => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                addr = addr.wrapping_add(1);
-                let abs_addr = self.peek_zp_u16(bytes[1]);
-                let y_offset = abs_addr.wrapping_add(self.y.into());
-                let val = self.peek(y_offset, Access::Dummy);
-                format!(" (${:02X}),Y @ ${y_offset:04X} = #${val:02X}", bytes[1])
-            }
-            REL => {
-                bytes.push(self.peek(addr, Access::Dummy));
-                let mut rel_addr: u16 = self.peek(addr, Access::Dummy).into();
-                addr = addr.wrapping_add(1);
-                if rel_addr & 0x80 == 0x80 {
-                    // If address is negative, extend sign to 16-bits
-                    rel_addr |= 0xFF00;
-                }
-                format!(" ${:04X}", addr.wrapping_add(rel_addr))
-            }
-            ACC | IMP => "".to_string(),
-        };
-        *pc = addr;
-        for byte in &bytes {
-            let _ = write!(self.disasm, "{byte:02X} ");
+    // Print the current instruction and status in the nestest log format
+    pub fn trace_instr(&self) {
+        let mut line = String::with_capacity(128);
+        if TraceFormat::Nintendulator.write_line(self, &mut line).is_ok() {
+            log::trace!("{line}");
         }
-        for _ in 0..(3 - bytes.len()) {
-            self.disasm.push_str("   ");
-        }
-        let _ = write!(self.disasm, "{instr:?}{mode}");
-    }
-
-    // Print the current instruction and status
-    pub fn trace_instr(&mut self) {
-        let mut pc = self.pc;
-        self.disassemble(&mut pc);
-
-        let status_str = |status: Status, set: char, clear: char| {
-            if self.status.contains(status) {
-                set
-            } else {
-                clear
-            }
-        };
-
-        log::trace!(
-            "{:<50} A:{:02X} X:{:02X} Y:{:02X} P:{}{}--{}{}{}{} SP:{:02X} PPU:{:3},{:3} CYC:{}",
-            self.disasm,
-            self.acc,
-            self.x,
-            self.y,
-            status_str(Status::N, 'N', 'n'),
-            status_str(Status::V, 'V', 'v'),
-            status_str(Status::D, 'd', 'd'),
-            status_str(Status::I, 'I', 'i'),
-            status_str(Status::Z, 'Z', 'z'),
-            status_str(Status::C, 'C', 'c'),
-            self.sp,
-            self.bus.ppu_cycle(),
-            self.bus.ppu_scanline(),
-            self.cycle,
-        );
     }
 
     /// Utilities
@@ -928,6 +919,9 @@ impl Cpu {
         }
         inspect(self);
 
+        if let Some(accesses) = &mut self.accesses {
+            accesses.clear();
+        }
         let opcode = self.read_instr(); // Cycle 1 of instruction
         self.instr = Cpu::INSTRUCTIONS[opcode as usize];
 
@@ -1057,9 +1051,16 @@ impl Mem for Cpu {
             self.handle_dma(addr);
         }
 
+        let ppudata = self.ppudata_access(addr);
         self.start_cycle(Cycle::Read);
         let val = self.bus.read(addr, access);
         self.end_cycle(Cycle::Read);
+        if self.accesses.is_some() {
+            self.record_access(AddrSpace::Cpu, addr, val, access);
+            if let Some((ppu_addr, ppu_val)) = ppudata {
+                self.record_access(AddrSpace::Ppu, ppu_addr, ppu_val, access);
+            }
+        }
         val
     }
 
@@ -1068,9 +1069,16 @@ impl Mem for Cpu {
     }
 
     fn write(&mut self, addr: u16, val: u8, access: Access) {
+        let ppudata = self.ppudata_access(addr);
         self.start_cycle(Cycle::Write);
         self.bus.write(addr, val, access);
         self.end_cycle(Cycle::Write);
+        if self.accesses.is_some() {
+            self.record_access(AddrSpace::Cpu, addr, val, access);
+            if let Some((ppu_addr, _)) = ppudata {
+                self.record_access(AddrSpace::Ppu, ppu_addr, val, access);
+            }
+        }
     }
 }
 
@@ -1210,4 +1218,43 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn code_data_log_indirect() {
+        use super::*;
+        let mut cpu = Cpu::new(Bus::default());
+        cpu.load_cart(Cart::empty());
+        cpu.enable_cdl(true);
+
+        // LDA ($20),Y with $20 pointing to $8008
+        cpu.reset(ResetKind::Hard);
+        for (addr, val) in [
+            (0x0000, 0xB1),
+            (0x0001, 0x20),
+            (0x0020, 0x08),
+            (0x0021, 0x80),
+        ] {
+            cpu.bus.write(addr, val, Access::Write);
+        }
+        cpu.clock();
+
+        // JMP ($0010) with $0010 pointing to $8005
+        cpu.reset(ResetKind::Hard);
+        for (addr, val) in [
+            (0x0000, 0x6C),
+            (0x0001, 0x10),
+            (0x0010, 0x05),
+            (0x0011, 0x80),
+        ] {
+            cpu.bus.write(addr, val, Access::Write);
+        }
+        cpu.clock();
+
+        let cdl = cpu.cdl().expect("cdl enabled");
+        assert_eq!(
+            cdl.prg_flags(8),
+            Some(PrgFlags::DATA | PrgFlags::INDIRECT_DATA)
+        );
+        assert_eq!(cdl.prg_flags(5), Some(PrgFlags::INDIRECT_CODE));
+    }
 }
@@ -0,0 +1,259 @@
+//! Embedded game database used to correct bad or incomplete `iNES` headers.
+//!
+//! Entries are keyed by the CRC-32 and SHA-1 of PRG-ROM followed by CHR-ROM, excluding the header
+//! and any trainer, which matches the checksums used by No-Intro and the NES 2.0 header database.
+//!
+//! `GAMES` is regenerated with `tools/gen_db.py` from the NES 2.0 header database (`nes20db.xml`),
+//! with publisher, release year and `MMC1` revisions merged in from Nestopia's `NstDatabase.xml`.
+//! `tools/db_extra.xml` adds entries missing from both, including the revision-sensitive test ROMs
+//! in `test_roms/`.
+
+use crate::{cart::NesHeader, common::NesRegion, mapper::Mmc1Revision, ppu::Mirroring};
+
+/// Hardware details and metadata for a known game.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct GameInfo {
+    pub crc32: u32,
+    pub sha1: [u8; 20],
+    pub title: &'static str,
+    // Empty or 0 if unknown
+    pub publisher: &'static str,
+    pub year: u16,
+    pub mapper_num: u16,
+    // NES 2.0 submapper, also used to select MMC3 revisions
+    pub submapper_num: u8,
+    // `None` if mapper controlled
+    pub mirroring: Option<Mirroring>,
+    pub region: NesRegion,
+    // NES 2.0 shift counts, volatile in the low nibble and battery-backed in the high nibble
+    pub prg_ram_shift: u8,
+    pub chr_ram_shift: u8,
+    pub battery: bool,
+    // NES 2.0 default expansion device
+    pub expansion_device: u8,
+    // `None` if not an `MMC1` board or the revision is unknown
+    pub mmc1_revision: Option<Mmc1Revision>,
+}
+
+impl GameInfo {
+    const fn new(
+        crc32: u32,
+        sha1: &str,
+        title: &'static str,
+        publisher: &'static str,
+        year: u16,
+    ) -> Self {
+        Self {
+            crc32,
+            sha1: parse_sha1(sha1),
+            title,
+            publisher,
+            year,
+            mapper_num: 0,
+            submapper_num: 0,
+            mirroring: None,
+            region: NesRegion::Ntsc,
+            prg_ram_shift: 0,
+            chr_ram_shift: 0,
+            battery: false,
+            expansion_device: 0,
+            mmc1_revision: None,
+        }
+    }
+
+    /// Find the database entry matching the CRC-32 and SHA-1 of PRG-ROM and CHR-ROM.
+    pub fn lookup(crc32: u32, sha1: &[u8; 20]) -> Option<&'static Self> {
+        Self::find(&GAMES, crc32, sha1)
+    }
+
+    fn find<'a>(games: &'a [Self], crc32: u32, sha1: &[u8; 20]) -> Option<&'a Self> {
+        games
+            .binary_search_by_key(&crc32, |game| game.crc32)
+            .ok()
+            .map(|index| &games[index])
+            .filter(|game| game.sha1 == *sha1)
+    }
+
+    /// Overwrite `header` with the hardware details of this entry. ROM sizes are left as is.
+    pub fn correct_header(&self, header: &mut NesHeader) {
+        header.mapper_num = self.mapper_num;
+        header.submapper_num = self.submapper_num;
+        match self.mirroring {
+            Some(Mirroring::Vertical) => header.flags = (header.flags & !0x08) | 0x01,
+            Some(Mirroring::FourScreen) => header.flags |= 0x08,
+            Some(_) => header.flags &= !0x09,
+            None => (),
+        }
+        if self.battery {
+            header.flags |= 0x02;
+        } else {
+            header.flags &= !0x02;
+        }
+        header.prg_ram_shift = self.prg_ram_shift;
+        header.chr_ram_shift = self.chr_ram_shift;
+        header.tv_mode = match self.region {
+            NesRegion::Ntsc => 0,
+            NesRegion::Pal => 1,
+            NesRegion::Dendy => 3,
+        };
+        header.expansion_device = self.expansion_device;
+    }
+}
+
+/// Parse a hex encoded SHA-1 digest at compile time.
+const fn parse_sha1(hex: &str) -> [u8; 20] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("invalid sha1 hex digit"),
+        }
+    }
+
+    let hex = hex.as_bytes();
+    assert!(hex.len() == 40, "sha1 must be 40 hex digits");
+    let mut sha1 = [0x00; 20];
+    let mut i = 0;
+    while i < sha1.len() {
+        sha1[i] = (nibble(hex[2 * i]) << 4) | nibble(hex[2 * i + 1]);
+        i += 1;
+    }
+    sha1
+}
+
+/// Known games sorted by `crc32`.
+// Entries between the markers are replaced by `tools/gen_db.py`.
+// BEGIN GAMES
+static GAMES: [GameInfo; 3] = [
+    GameInfo {
+        mirroring: Some(Mirroring::Vertical),
+        ..GameInfo::new(
+            0x3337_EC46,
+            "EA343F4E445A9050D4B4FBAC2C77D0693B1D0922",
+            "Super Mario Bros.",
+            "Nintendo",
+            1985,
+        )
+    },
+    GameInfo {
+        mapper_num: 4,
+        submapper_num: 4,
+        mirroring: Some(Mirroring::Horizontal),
+        prg_ram_shift: 0x07,
+        chr_ram_shift: 0x07,
+        ..GameInfo::new(
+            0xF312_D1DE,
+            "35C157A921156E47FD3F6573D150F54108D0EDFC",
+            "5.MMC3_rev_A",
+            "Shay Green",
+            0,
+        )
+    },
+    GameInfo {
+        mapper_num: 1,
+        mirroring: Some(Mirroring::Horizontal),
+        prg_ram_shift: 0x07,
+        mmc1_revision: Some(Mmc1Revision::A),
+        ..GameInfo::new(
+            0xF3D8_439E,
+            "C3793B9CEA7EAFAC74ED3EBC4315CF2D8A8B3E8D",
+            "MMC1A PRG-RAM Test",
+            "Homebrew",
+            2026,
+        )
+    },
+];
+// END GAMES
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cart::Cart, mem::RamState};
+    use alloc::{string::ToString, vec};
+
+    const SHA1: &str = "A9993E364706816ABA3E25717850C26C9CD0D89D";
+
+    #[test]
+    fn games_sorted() {
+        assert!(
+            GAMES
+                .windows(2)
+                .all(|games| games[0].crc32 < games[1].crc32),
+            "database must be sorted by crc32 without duplicates"
+        );
+
+        for game in &GAMES {
+            // NES 2.0 header with 32K PRG-ROM and 8K CHR-ROM
+            let mut rom = vec![0x00; 16 + 0x8000 + 0x2000];
+            rom[..4].copy_from_slice(b"NES\x1A");
+            rom[4] = 2;
+            rom[5] = 1;
+            rom[6] = ((game.mapper_num & 0x0F) as u8) << 4;
+            rom[7] = (game.mapper_num & 0xF0) as u8 | 0x08;
+            rom[8] = (game.submapper_num << 4) | (game.mapper_num >> 8) as u8;
+            let cart = Cart::from_rom(game.title.to_string(), rom, RamState::default());
+            assert!(
+                cart.is_ok(),
+                "`{}` mapper {} doesn't load: {:?}",
+                game.title,
+                game.mapper_num,
+                cart.err()
+            );
+        }
+    }
+
+    #[test]
+    fn parse_sha1_digest() {
+        assert_eq!(parse_sha1(SHA1), crate::checksum::sha1(b"abc"));
+    }
+
+    #[test]
+    fn correct_header() {
+        let games = [
+            GameInfo {
+                mapper_num: 4,
+                submapper_num: 4,
+                mirroring: Some(Mirroring::Horizontal),
+                region: NesRegion::Pal,
+                prg_ram_shift: 0x70,
+                battery: true,
+                expansion_device: 0x08,
+                ..GameInfo::new(0x1000, SHA1, "Test", "Homebrew", 2024)
+            },
+            GameInfo::new(0x2000, SHA1, "Other", "Homebrew", 2024),
+        ];
+        let sha1 = parse_sha1(SHA1);
+        assert_eq!(GameInfo::find(&games, 0x1500, &sha1), None);
+        assert_eq!(
+            GameInfo::find(&games, 0x1000, &[0x00; 20]),
+            None,
+            "crc32 collision with a different sha1"
+        );
+        let game = GameInfo::find(&games, 0x1000, &sha1).expect("matching entry");
+
+        let mut header = NesHeader {
+            version: 1,
+            mapper_num: 1,
+            flags: 0x09,
+            prg_rom_banks: 8,
+            ..NesHeader::default()
+        };
+        game.correct_header(&mut header);
+        assert_eq!(
+            header,
+            NesHeader {
+                version: 1,
+                mapper_num: 4,
+                submapper_num: 4,
+                flags: 0x02,
+                prg_rom_banks: 8,
+                prg_ram_shift: 0x70,
+                tv_mode: 1,
+                expansion_device: 0x08,
+                ..NesHeader::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,875 @@
+//! CPU debugger with breakpoints, memory watchpoints, conditions and stepping.
+//!
+//! The [`Debugger`] is owned by the `ControlDeck` and checked around every instruction while it
+//! has anything to do. Hits are returned as `ControlFlow::Break` with a [`DebugBreak`] from the
+//! `ControlDeck::clock_*` methods.
+
+use crate::{
+    cpu::Cpu,
+    mem::{Access, Mem},
+};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::{fmt, ops::RangeInclusive};
+
+/// Address space of a memory access.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum AddrSpace {
+    /// CPU address space, $0000-$FFFF.
+    Cpu,
+    /// PPU address space, $0000-$3FFF, as accessed by the CPU through `PPUDATA` ($2007).
+    /// Rendering fetches made by the PPU itself don't trigger watchpoints.
+    Ppu,
+}
+
+/// A memory access made while executing an instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MemAccess {
+    pub space: AddrSpace,
+    pub addr: u16,
+    pub val: u8,
+    pub access: Access,
+}
+
+/// Why the debugger stopped execution.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum BreakReason {
+    /// A breakpoint was hit before executing the instruction at `pc`.
+    Breakpoint { id: u32, pc: u16 },
+    /// A watched address was accessed by the last instruction.
+    Watchpoint { id: u32, access: MemAccess },
+    /// A step into, over or out finished.
+    Step,
+    /// Execution reached the requested PPU scanline.
+    Scanline(u32),
+}
+
+/// A debugger stop, returned as `ControlFlow::Break` from the `ControlDeck::clock_*` methods.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct DebugBreak {
+    /// CPU cycles run by the call before stopping.
+    pub cycles: usize,
+    pub reason: BreakReason,
+}
+
+/// Breaks before executing the instruction at `addr`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Breakpoint {
+    pub id: u32,
+    pub addr: u16,
+    pub condition: Option<Condition>,
+    pub enabled: bool,
+}
+
+/// Breaks after an instruction accesses an address in `addrs` with the given kind of `access`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Watchpoint {
+    pub id: u32,
+    pub space: AddrSpace,
+    pub addrs: RangeInclusive<u16>,
+    pub access: Access,
+    pub condition: Option<Condition>,
+    pub enabled: bool,
+}
+
+/// A pending step, checked after every instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum Step {
+    /// Break after the next instruction.
+    Into,
+    /// Break once execution returns to `pc` with the stack at `sp`, skipping over a subroutine.
+    Over { pc: u16, sp: u8 },
+    /// Break after an `RTS` or `RTI` pops the stack above `sp`.
+    Out { sp: u8 },
+    /// Break when the PPU reaches `scanline`.
+    Scanline(u32),
+}
+
+/// Breakpoints, watchpoints and stepping state.
+#[derive(Default, Debug, Clone)]
+#[must_use]
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    watchpoints: Vec<Watchpoint>,
+    step: Option<Step>,
+    next_id: u32,
+    // Skips breakpoints at the PC execution stopped at, so resuming doesn't hit them again
+    resume_pc: Option<u16>,
+    opcode: u8,
+    scanline: u32,
+}
+
+impl Debugger {
+    const RTI: u8 = 0x40;
+    const RTS: u8 = 0x60;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a breakpoint at `addr`, returning its id.
+    pub fn add_breakpoint(&mut self, addr: u16, condition: Option<Condition>) -> u32 {
+        let id = self.next_id();
+        self.breakpoints.push(Breakpoint {
+            id,
+            addr,
+            condition,
+            enabled: true,
+        });
+        id
+    }
+
+    /// Add a watchpoint for `access` to `addrs` in `space`, returning its id. PPU watchpoints
+    /// only trigger on CPU reads and writes of `PPUDATA` ($2007), not on rendering fetches.
+    pub fn add_watchpoint(
+        &mut self,
+        space: AddrSpace,
+        addrs: RangeInclusive<u16>,
+        access: Access,
+        condition: Option<Condition>,
+    ) -> u32 {
+        let id = self.next_id();
+        self.watchpoints.push(Watchpoint {
+            id,
+            space,
+            addrs,
+            access,
+            condition,
+            enabled: true,
+        });
+        id
+    }
+
+    /// Remove the breakpoint or watchpoint with `id`. Returns whether it existed.
+    pub fn remove(&mut self, id: u32) -> bool {
+        let len = self.breakpoints.len() + self.watchpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+        self.watchpoints.retain(|watchpoint| watchpoint.id != id);
+        len != self.breakpoints.len() + self.watchpoints.len()
+    }
+
+    /// Enable or disable the breakpoint or watchpoint with `id`. Returns whether it exists.
+    pub fn set_enabled(&mut self, id: u32, enabled: bool) -> bool {
+        if let Some(breakpoint) = self.breakpoints.iter_mut().find(|bp| bp.id == id) {
+            breakpoint.enabled = enabled;
+            true
+        } else if let Some(watchpoint) = self.watchpoints.iter_mut().find(|wp| wp.id == id) {
+            watchpoint.enabled = enabled;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove all breakpoints and watchpoints and cancel any pending step.
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.watchpoints.clear();
+        self.step = None;
+    }
+
+    #[inline]
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    #[inline]
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watchpoints
+    }
+
+    #[inline]
+    pub const fn step(&self) -> Option<Step> {
+        self.step
+    }
+
+    /// Set a step to break on, replacing any pending step. The `ControlDeck::step_*` methods
+    /// compute these from the current CPU state.
+    #[inline]
+    pub fn set_step(&mut self, step: Option<Step>) {
+        self.step = step;
+    }
+
+    /// Whether anything needs to be checked around instructions.
+    #[must_use]
+    pub fn is_active(&self) -> bool {
+        self.step.is_some()
+            || self.breakpoints.iter().any(|bp| bp.enabled)
+            || self.has_watchpoints()
+    }
+
+    /// Whether the CPU needs to record memory accesses for watchpoints.
+    #[must_use]
+    pub fn has_watchpoints(&self) -> bool {
+        self.watchpoints.iter().any(|wp| wp.enabled)
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+        id
+    }
+
+    /// Check breakpoints before executing the next instruction.
+    pub(crate) fn check_before(&mut self, cpu: &Cpu) -> Option<BreakReason> {
+        let pc = cpu.pc();
+        self.opcode = cpu.peek(pc, Access::Dummy);
+        self.scanline = cpu.ppu_scanline();
+        if self.resume_pc.take() == Some(pc) {
+            return None;
+        }
+        let breakpoint = self.breakpoints.iter().find(|bp| {
+            bp.enabled
+                && bp.addr == pc
+                && bp
+                    .condition
+                    .as_ref()
+                    .is_none_or(|condition| condition.eval(cpu, None))
+        })?;
+        let reason = BreakReason::Breakpoint {
+            id: breakpoint.id,
+            pc,
+        };
+        Some(self.stop(cpu, reason))
+    }
+
+    /// Check watchpoints and steps after executing an instruction.
+    pub(crate) fn check_after(&mut self, cpu: &Cpu) -> Option<BreakReason> {
+        let watchpoint = cpu.accesses().iter().find_map(|access| {
+            self.watchpoints
+                .iter()
+                .find(|wp| {
+                    wp.enabled
+                        && wp.space == access.space
+                        && wp.access == access.access
+                        && wp.addrs.contains(&access.addr)
+                        && wp
+                            .condition
+                            .as_ref()
+                            .is_none_or(|condition| condition.eval(cpu, Some(access)))
+                })
+                .map(|wp| BreakReason::Watchpoint {
+                    id: wp.id,
+                    access: *access,
+                })
+        });
+        let reason = watchpoint.or_else(|| {
+            let done = match self.step? {
+                Step::Into => true,
+                Step::Over { pc, sp } => cpu.pc() == pc && cpu.sp() == sp,
+                Step::Out { sp } => matches!(self.opcode, Self::RTS | Self::RTI) && cpu.sp() > sp,
+                Step::Scanline(scanline) => {
+                    let reached = cpu.ppu_scanline() == scanline && self.scanline != scanline;
+                    return reached.then_some(BreakReason::Scanline(scanline));
+                }
+            };
+            done.then_some(BreakReason::Step)
+        })?;
+        Some(self.stop(cpu, reason))
+    }
+
+    fn stop(&mut self, cpu: &Cpu, reason: BreakReason) -> BreakReason {
+        self.step = None;
+        self.resume_pc = Some(cpu.pc());
+        reason
+    }
+}
+
+/// Errors returned when parsing a [`Condition`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum ConditionError {
+    /// An unexpected character or token at the given byte offset.
+    Syntax(usize),
+    /// An unknown register or variable name.
+    UnknownName(String),
+    /// The expression ended early.
+    UnexpectedEnd,
+}
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Syntax(pos) => write!(f, "invalid condition syntax at offset {pos}"),
+            Self::UnknownName(name) => write!(f, "unknown condition variable: {name}"),
+            Self::UnexpectedEnd => write!(f, "unexpected end of condition"),
+        }
+    }
+}
+
+impl core::error::Error for ConditionError {}
+
+/// A conditional expression over CPU registers and memory, such as `A == $10 && [$0300] > 5`.
+///
+/// Values are integers and comparisons evaluate to `1` or `0`. The condition is met when the
+/// expression is non-zero.
+///
+/// - Numbers: `16`, `$10`, `0x10` or `%10000`.
+/// - Variables: `A`, `X`, `Y`, `SP`, `PC`, `P`, `CYCLE`, `SCANLINE`, `DOT` and `FRAME`, plus
+///   `ADDR` and `VALUE` of the access that triggered a watchpoint.
+/// - Memory: `[addr]` reads a CPU byte without side effects.
+/// - Operators: `! - ~ + & ^ | == != < <= > >= && ||` and parentheses, with C precedence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    /// Parse a condition expression.
+    ///
+    /// # Errors
+    ///
+    /// If the expression is invalid, then an error is returned.
+    pub fn parse(source: &str) -> Result<Self, ConditionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+        };
+        let expr = parser.parse_expr(0)?;
+        if let Some(&(pos, _)) = parser.tokens.get(parser.pos) {
+            return Err(ConditionError::Syntax(pos));
+        }
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Evaluate the condition, with the `access` that triggered a watchpoint, if any.
+    #[must_use]
+    pub fn eval(&self, cpu: &Cpu, access: Option<&MemAccess>) -> bool {
+        self.expr.eval(cpu, access) != 0
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Var {
+    A,
+    X,
+    Y,
+    Sp,
+    Pc,
+    P,
+    Cycle,
+    Scanline,
+    Dot,
+    Frame,
+    Addr,
+    Value,
+}
+
+impl Var {
+    fn from_name(name: &str) -> Option<Self> {
+        let var = match name.to_ascii_uppercase().as_str() {
+            "A" => Self::A,
+            "X" => Self::X,
+            "Y" => Self::Y,
+            "SP" => Self::Sp,
+            "PC" => Self::Pc,
+            "P" => Self::P,
+            "CYCLE" => Self::Cycle,
+            "SCANLINE" => Self::Scanline,
+            "DOT" => Self::Dot,
+            "FRAME" => Self::Frame,
+            "ADDR" => Self::Addr,
+            "VALUE" => Self::Value,
+            _ => return None,
+        };
+        Some(var)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum UnaryOp {
+    Not,
+    Neg,
+    BitNot,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    BitOr,
+    BitXor,
+    BitAnd,
+    Add,
+    Sub,
+}
+
+impl BinaryOp {
+    // Binding power, higher binds tighter
+    const fn precedence(self) -> u8 {
+        match self {
+            Self::Or => 1,
+            Self::And => 2,
+            Self::Eq | Self::Ne | Self::Lt | Self::Le | Self::Gt | Self::Ge => 3,
+            Self::BitOr => 4,
+            Self::BitXor => 5,
+            Self::BitAnd => 6,
+            Self::Add | Self::Sub => 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Expr {
+    Num(i64),
+    Var(Var),
+    Mem(Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<(Expr, Expr)>),
+}
+
+impl Expr {
+    fn eval(&self, cpu: &Cpu, access: Option<&MemAccess>) -> i64 {
+        match self {
+            Self::Num(num) => *num,
+            Self::Var(var) => match var {
+                Var::A => cpu.a().into(),
+                Var::X => cpu.x().into(),
+                Var::Y => cpu.y().into(),
+                Var::Sp => cpu.sp().into(),
+                Var::Pc => cpu.pc().into(),
+                Var::P => cpu.status().bits().into(),
+                Var::Cycle => cpu.cycle() as i64,
+                Var::Scanline => cpu.ppu_scanline().into(),
+                Var::Dot => cpu.ppu_cycle().into(),
+                Var::Frame => cpu.frame_number().into(),
+                Var::Addr => access.map_or(0, |access| access.addr.into()),
+                Var::Value => access.map_or(0, |access| access.val.into()),
+            },
+            Self::Mem(addr) => cpu
+                .peek(addr.eval(cpu, access) as u16, Access::Dummy)
+                .into(),
+            Self::Unary(op, expr) => {
+                let val = expr.eval(cpu, access);
+                match op {
+                    UnaryOp::Not => i64::from(val == 0),
+                    UnaryOp::Neg => val.wrapping_neg(),
+                    UnaryOp::BitNot => !val,
+                }
+            }
+            Self::Binary(op, exprs) => {
+                let (lhs, rhs) = &**exprs;
+                let lhs = lhs.eval(cpu, access);
+                // Short-circuit logical operators
+                match op {
+                    BinaryOp::Or if lhs != 0 => return 1,
+                    BinaryOp::And if lhs == 0 => return 0,
+                    _ => (),
+                }
+                let rhs = rhs.eval(cpu, access);
+                match op {
+                    BinaryOp::Or | BinaryOp::And => i64::from(rhs != 0),
+                    BinaryOp::Eq => i64::from(lhs == rhs),
+                    BinaryOp::Ne => i64::from(lhs != rhs),
+                    BinaryOp::Lt => i64::from(lhs < rhs),
+                    BinaryOp::Le => i64::from(lhs <= rhs),
+                    BinaryOp::Gt => i64::from(lhs > rhs),
+                    BinaryOp::Ge => i64::from(lhs >= rhs),
+                    BinaryOp::BitOr => lhs | rhs,
+                    BinaryOp::BitXor => lhs ^ rhs,
+                    BinaryOp::BitAnd => lhs & rhs,
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Sub => lhs.wrapping_sub(rhs),
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Num(i64),
+    Name(String),
+    Op(&'static str),
+}
+
+/// Split a condition into tokens, paired with their byte offsets.
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ConditionError> {
+    const OPS: [&str; 20] = [
+        "==", "!=", "<=", ">=", "&&", "||", "<", ">", "!", "~", "-", "+", "&", "^", "|", "(", ")",
+        "[", "]", "=",
+    ];
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while let Some(c) = source[pos..].chars().next() {
+        let rest = &source[pos..];
+        if c.is_whitespace() {
+            pos += c.len_utf8();
+            continue;
+        }
+        let (token, len) = if c.is_ascii_digit() || c == '$' || c == '%' {
+            let (radix, skip) = if c == '$' {
+                (16, 1)
+            } else if c == '%' {
+                (2, 1)
+            } else if rest.starts_with("0x") || rest.starts_with("0X") {
+                (16, 2)
+            } else {
+                (10, 0)
+            };
+            let digits = rest[skip..]
+                .find(|c: char| !c.is_ascii_alphanumeric())
+                .unwrap_or(rest.len() - skip);
+            let num = i64::from_str_radix(&rest[skip..skip + digits], radix)
+                .map_err(|_| ConditionError::Syntax(pos))?;
+            (Token::Num(num), skip + digits)
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            (Token::Name(rest[..len].to_string()), len)
+        } else {
+            // `=` is accepted as a single character alias for `==`
+            let op = OPS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or(ConditionError::Syntax(pos))?;
+            let token = Token::Op(if *op == "=" { "==" } else { op });
+            (token, op.len())
+        };
+        tokens.push((pos, token));
+        pos += len;
+    }
+    Ok(tokens)
+}
+
+/// Precedence climbing parser over condition tokens.
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn next(&mut self) -> Result<&(usize, Token), ConditionError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .ok_or(ConditionError::UnexpectedEnd)?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn peek_binary_op(&self) -> Option<BinaryOp> {
+        let Some((_, Token::Op(op))) = self.tokens.get(self.pos) else {
+            return None;
+        };
+        let op = match *op {
+            "||" => BinaryOp::Or,
+            "&&" => BinaryOp::And,
+            "==" => BinaryOp::Eq,
+            "!=" => BinaryOp::Ne,
+            "<" => BinaryOp::Lt,
+            "<=" => BinaryOp::Le,
+            ">" => BinaryOp::Gt,
+            ">=" => BinaryOp::Ge,
+            "|" => BinaryOp::BitOr,
+            "^" => BinaryOp::BitXor,
+            "&" => BinaryOp::BitAnd,
+            "+" => BinaryOp::Add,
+            "-" => BinaryOp::Sub,
+            _ => return None,
+        };
+        Some(op)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), ConditionError> {
+        match self.next()? {
+            (_, Token::Op(op)) if *op == expected => Ok(()),
+            &(pos, _) => Err(ConditionError::Syntax(pos)),
+        }
+    }
+
+    fn parse_expr(&mut self, min_precedence: u8) -> Result<Expr, ConditionError> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(op) = self.peek_binary_op() {
+            let precedence = op.precedence();
+            if precedence < min_precedence {
+                break;
+            }
+            self.pos += 1;
+            let rhs = self.parse_expr(precedence + 1)?;
+            lhs = Expr::Binary(op, Box::new((lhs, rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ConditionError> {
+        let (pos, token) = self.next()?;
+        let pos = *pos;
+        let expr = match token {
+            Token::Num(num) => Expr::Num(*num),
+            Token::Name(name) => Expr::Var(
+                Var::from_name(name).ok_or_else(|| ConditionError::UnknownName(name.clone()))?,
+            ),
+            Token::Op(op) => match *op {
+                "(" => {
+                    let expr = self.parse_expr(0)?;
+                    self.expect(")")?;
+                    expr
+                }
+                "[" => {
+                    let expr = self.parse_expr(0)?;
+                    self.expect("]")?;
+                    Expr::Mem(Box::new(expr))
+                }
+                "!" | "-" | "~" => {
+                    let op = match *op {
+                        "!" => UnaryOp::Not,
+                        "-" => UnaryOp::Neg,
+                        _ => UnaryOp::BitNot,
+                    };
+                    Expr::Unary(op, Box::new(self.parse_unary()?))
+                }
+                _ => return Err(ConditionError::Syntax(pos)),
+            },
+        };
+        Ok(expr)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control_deck::ControlDeck;
+    use alloc::vec;
+    use core::ops::ControlFlow;
+
+    #[rustfmt::skip]
+    const PROGRAM: &[u8] = &[
+        0xA9, 0x05,             // $8000 LDA #$05
+        0x20, 0x30, 0x80,       // $8002 JSR $8030
+        0x8D, 0x00, 0x03,       // $8005 STA $0300
+        0x2C, 0x02, 0x20,       // $8008 BIT $2002
+        0x10, 0xFB,             // $800B BPL $8008
+        0x2C, 0x02, 0x20,       // $800D BIT $2002
+        0x10, 0xFB,             // $8010 BPL $800D
+        0xA9, 0x21,             // $8012 LDA #$21
+        0x8D, 0x06, 0x20,       // $8014 STA $2006
+        0xA9, 0x08,             // $8017 LDA #$08
+        0x8D, 0x06, 0x20,       // $8019 STA $2006
+        0x8D, 0x07, 0x20,       // $801C STA $2007
+        0x4C, 0x1F, 0x80,       // $801F JMP $801F
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0xA2, 0x01,             // $8030 LDX #$01
+        0xE8,                   // $8032 INX
+        0x8E, 0x01, 0x03,       // $8033 STX $0301
+        0x60,                   // $8036 RTS
+    ];
+
+    fn test_deck() -> ControlDeck {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, 0x01, 0x01];
+        rom.resize(16, 0x00);
+        let mut prg_rom = vec![0xEA; 0x4000];
+        prg_rom[..PROGRAM.len()].copy_from_slice(PROGRAM);
+        // Reset vector
+        prg_rom[0x3FFC..0x3FFE].copy_from_slice(&[0x00, 0x80]);
+        rom.extend_from_slice(&prg_rom);
+        rom.resize(rom.len() + 0x2000, 0x00);
+        let mut deck = ControlDeck::new();
+        deck.load_rom("debugger".into(), rom).expect("valid rom");
+        deck
+    }
+
+    fn run_until_break(deck: &mut ControlDeck) -> BreakReason {
+        for _ in 0..5 {
+            if let ControlFlow::Break(debug_break) = deck.clock_frame().expect("valid frame") {
+                return debug_break.reason;
+            }
+        }
+        panic!("debugger didn't break");
+    }
+
+    #[test]
+    fn conditions() {
+        let deck = test_deck();
+        let cpu = deck.cpu();
+        let eval = |source: &str| {
+            Condition::parse(source)
+                .expect("valid condition")
+                .eval(cpu, None)
+        };
+        assert_eq!(cpu.pc(), 0x8000);
+        assert!(eval("PC == $8000"));
+        assert!(eval("pc = 0x8000 && !(A != 0)"));
+        assert!(eval("[$FFFD] == $80 || X"));
+        assert!(eval("1 + 2 & 3 == 3"));
+        assert!(eval("-1 < 0 && ~0 == -1 && %101 == 5 && (6 ^ 3) == 5"));
+        assert!(!eval("A >= 1 || SP < 0"));
+
+        let access = MemAccess {
+            space: AddrSpace::Cpu,
+            addr: 0x0300,
+            val: 0x42,
+            access: Access::Write,
+        };
+        let condition = Condition::parse("VALUE == $42 && ADDR == $300").expect("valid condition");
+        assert!(condition.eval(cpu, Some(&access)));
+        assert_eq!(condition.source(), "VALUE == $42 && ADDR == $300");
+
+        assert_eq!(Condition::parse("A =="), Err(ConditionError::UnexpectedEnd));
+        assert_eq!(Condition::parse("A == #1"), Err(ConditionError::Syntax(5)));
+        assert_eq!(Condition::parse("(A"), Err(ConditionError::UnexpectedEnd));
+        assert_eq!(Condition::parse("A B"), Err(ConditionError::Syntax(2)));
+        assert_eq!(
+            Condition::parse("Q > 1"),
+            Err(ConditionError::UnknownName("Q".into()))
+        );
+    }
+
+    #[test]
+    fn breakpoints() {
+        let mut deck = test_deck();
+        let id = deck.debugger_mut().add_breakpoint(0x8030, None);
+        assert_eq!(
+            run_until_break(&mut deck),
+            BreakReason::Breakpoint { id, pc: 0x8030 }
+        );
+        assert_eq!(deck.cpu().pc(), 0x8030);
+        assert_eq!(deck.cpu().x(), 0x00, "breaks before executing");
+
+        // Resuming executes the instruction at the breakpoint
+        assert_eq!(deck.clock_instr().ok(), Some(ControlFlow::Continue(2)));
+        assert_eq!(deck.cpu().x(), 0x01);
+
+        assert!(deck.debugger_mut().set_enabled(id, false));
+        let condition = Condition::parse("X == 2").expect("valid condition");
+        let id = deck.debugger_mut().add_breakpoint(0x8033, Some(condition));
+        assert_eq!(
+            run_until_break(&mut deck),
+            BreakReason::Breakpoint { id, pc: 0x8033 }
+        );
+
+        let condition = Condition::parse("A == 6").expect("valid condition");
+        let mut deck = test_deck();
+        deck.debugger_mut().add_breakpoint(0x8005, Some(condition));
+        assert!(matches!(
+            deck.clock_frame().ok(),
+            Some(ControlFlow::Continue(_))
+        ));
+        assert!(deck.debugger_mut().remove(0));
+        assert!(!deck.debugger_mut().remove(0));
+        assert!(!deck.debugger().is_active());
+    }
+
+    #[test]
+    fn watchpoints() {
+        let mut deck = test_deck();
+        let read =
+            deck.debugger_mut()
+                .add_watchpoint(AddrSpace::Cpu, 0x0300..=0x0301, Access::Read, None);
+        let condition = Condition::parse("VALUE == 5").expect("valid condition");
+        let write = deck.debugger_mut().add_watchpoint(
+            AddrSpace::Cpu,
+            0x0300..=0x0301,
+            Access::Write,
+            Some(condition),
+        );
+        assert_eq!(
+            run_until_break(&mut deck),
+            BreakReason::Watchpoint {
+                id: write,
+                access: MemAccess {
+                    space: AddrSpace::Cpu,
+                    addr: 0x0300,
+                    val: 0x05,
+                    access: Access::Write,
+                },
+            },
+            "STX $0301 doesn't match VALUE == 5"
+        );
+        assert_eq!(deck.cpu().pc(), 0x8008, "breaks after the instruction");
+        assert!(deck.debugger_mut().remove(read));
+        assert!(deck.debugger_mut().remove(write));
+
+        let id = deck.debugger_mut().add_watchpoint(
+            AddrSpace::Ppu,
+            0x2100..=0x21FF,
+            Access::Write,
+            None,
+        );
+        assert_eq!(
+            run_until_break(&mut deck),
+            BreakReason::Watchpoint {
+                id,
+                access: MemAccess {
+                    space: AddrSpace::Ppu,
+                    addr: 0x2108,
+                    val: 0x08,
+                    access: Access::Write,
+                },
+            }
+        );
+
+        let mut deck = test_deck();
+        let id = deck.debugger_mut().add_watchpoint(
+            AddrSpace::Cpu,
+            0x8030..=0x8030,
+            Access::Execute,
+            None,
+        );
+        assert!(matches!(
+            run_until_break(&mut deck),
+            BreakReason::Watchpoint { id: hit, .. } if hit == id
+        ));
+        assert_eq!(deck.cpu().x(), 0x01);
+    }
+
+    #[test]
+    fn stepping() {
+        let mut deck = test_deck();
+        let clock_step = |deck: &mut ControlDeck| match deck.clock_frame().expect("valid frame") {
+            ControlFlow::Break(debug_break) => debug_break.reason,
+            ControlFlow::Continue(_) => panic!("debugger didn't break"),
+        };
+
+        deck.step_into();
+        assert_eq!(clock_step(&mut deck), BreakReason::Step);
+        assert_eq!(deck.cpu().pc(), 0x8002);
+
+        deck.step_over();
+        assert_eq!(clock_step(&mut deck), BreakReason::Step);
+        assert_eq!(deck.cpu().pc(), 0x8005);
+        assert_eq!(deck.cpu().x(), 0x02, "subroutine ran");
+
+        let mut deck = test_deck();
+        deck.step_into();
+        let _ = clock_step(&mut deck);
+        deck.step_into();
+        assert_eq!(clock_step(&mut deck), BreakReason::Step);
+        assert_eq!(deck.cpu().pc(), 0x8030, "stepped into subroutine");
+        deck.step_out();
+        assert_eq!(clock_step(&mut deck), BreakReason::Step);
+        assert_eq!(deck.cpu().pc(), 0x8005);
+        assert_eq!(deck.debugger().step(), None);
+
+        deck.run_to_scanline(100);
+        assert_eq!(clock_step(&mut deck), BreakReason::Scanline(100));
+        assert_eq!(deck.cpu().ppu_scanline(), 100);
+    }
+}
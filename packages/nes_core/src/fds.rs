@@ -0,0 +1,291 @@
+//! Famicom Disk System disk images.
+//!
+//! <https://www.nesdev.org/wiki/FDS_file_format>
+//! <https://www.nesdev.org/wiki/FDS_disk_format>
+
+use alloc::vec;
+use alloc::vec::Vec;
+use crate::patch;
+use anyhow::{anyhow, bail, Context, Result};
+
+const HEADER_MAGIC: [u8; 4] = *b"FDS\x1A";
+const HEADER_SIZE: usize = 16;
+const DISK_VERIFICATION: &[u8] = b"\x01*NINTENDO-HVC*";
+
+/// Bytes per disk side in `.fds` images, which store blocks back to back without gaps, start
+/// marks or CRCs.
+pub const SIDE_SIZE: usize = 65500;
+
+// The drive reads raw disk data: a long gap before the first block, then each block preceded by a
+// start mark and followed by its CRC and a gap.
+const LEAD_IN_GAP: usize = 28300 / 8;
+const BLOCK_GAP: usize = 976 / 8;
+const BLOCK_START: u8 = 0x80;
+const CRC_SIZE: usize = 2;
+// Room for a full side of blocks plus gaps, so games can append files.
+const RAW_SIDE_SIZE: usize = 75_000;
+
+/// A Famicom Disk System disk image made up of one or more disk sides.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct FdsImage {
+    header: bool,
+    sides: Vec<Vec<u8>>,
+}
+
+impl FdsImage {
+    /// Load a `.fds` image, with or without an fwNES header.
+    ///
+    /// # Errors
+    ///
+    /// If the image isn't a whole number of disk sides, or a side is missing the disk info block,
+    /// then an error is returned.
+    pub fn load(data: &[u8]) -> Result<Self> {
+        let header = data.starts_with(&HEADER_MAGIC);
+        let sides_data = if header {
+            data.get(HEADER_SIZE..).context("truncated fwNES header")?
+        } else {
+            data
+        };
+        if sides_data.is_empty() || sides_data.len() % SIDE_SIZE != 0 {
+            bail!(
+                "invalid disk image size: {} is not a multiple of {SIDE_SIZE}",
+                sides_data.len()
+            );
+        }
+        let sides = sides_data
+            .chunks_exact(SIDE_SIZE)
+            .map(<[u8]>::to_vec)
+            .collect::<Vec<_>>();
+        for (i, side) in sides.iter().enumerate() {
+            if !side.starts_with(DISK_VERIFICATION) {
+                bail!("invalid disk side {i}: missing disk info block");
+            }
+        }
+        Ok(Self { header, sides })
+    }
+
+    /// Number of disk sides.
+    #[inline]
+    #[must_use]
+    pub fn sides(&self) -> usize {
+        self.sides.len()
+    }
+
+    /// Disk side data in `.fds` layout.
+    #[inline]
+    #[must_use]
+    pub fn side(&self, side: usize) -> Option<&[u8]> {
+        self.sides.get(side).map(Vec::as_slice)
+    }
+
+    /// Serialize the image in the same layout it was loaded from.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(HEADER_SIZE + self.sides.len() * SIDE_SIZE);
+        if self.header {
+            data.extend_from_slice(&HEADER_MAGIC);
+            data.push(self.sides.len() as u8);
+            data.resize(HEADER_SIZE, 0x00);
+        }
+        for side in &self.sides {
+            data.extend_from_slice(side);
+        }
+        data
+    }
+
+    /// Create an IPS patch that turns this image into `modified`.
+    #[must_use]
+    pub fn diff(&self, modified: &Self) -> Vec<u8> {
+        patch::ips_diff(&self.to_bytes(), &modified.to_bytes())
+    }
+
+    /// Apply an IPS patch created by [`FdsImage::diff`].
+    ///
+    /// # Errors
+    ///
+    /// If the patch is malformed or the patched data isn't a valid disk image, then an error is
+    /// returned.
+    pub fn patch(&self, ips: &[u8]) -> Result<Self> {
+        let data = patch::apply_ips(&self.to_bytes(), ips).map_err(|err| anyhow!("{err}"))?;
+        Self::load(&data)
+    }
+
+    /// Disk sides as the drive sees them, with gaps, start marks and CRCs.
+    pub(crate) fn to_raw_sides(&self) -> Vec<Vec<u8>> {
+        self.sides.iter().map(|side| encode_side(side)).collect()
+    }
+
+    /// Rebuild an image from raw disk sides written by the drive. Data past the last block is
+    /// kept from this image, so unused space in a dump doesn't show up as a change.
+    pub(crate) fn with_raw_sides(&self, raw_sides: &[Vec<u8>]) -> Self {
+        Self {
+            header: self.header,
+            sides: raw_sides
+                .iter()
+                .zip(&self.sides)
+                .map(|(raw, side)| decode_side(raw, side))
+                .collect(),
+        }
+    }
+}
+
+/// Length of the block starting with `block_type`, or `None` if it's not a valid block.
+/// `file_size` is the size from the preceding file header block.
+const fn block_len(block_type: u8, file_size: usize) -> Option<usize> {
+    match block_type {
+        1 => Some(56),
+        2 => Some(2),
+        3 => Some(16),
+        4 => Some(1 + file_size),
+        _ => None,
+    }
+}
+
+/// File size stored in a file header block.
+fn file_size(block: &[u8]) -> usize {
+    usize::from(u16::from_le_bytes([block[13], block[14]]))
+}
+
+fn encode_side(side: &[u8]) -> Vec<u8> {
+    let mut raw = vec![0x00; LEAD_IN_GAP];
+    let mut pos = 0;
+    let mut size = 0;
+    while let Some(len) = side
+        .get(pos)
+        .and_then(|&block_type| block_len(block_type, size))
+    {
+        let Some(block) = side.get(pos..pos + len) else {
+            break;
+        };
+        if block[0] == 3 {
+            size = file_size(block);
+        }
+        raw.push(BLOCK_START);
+        raw.extend_from_slice(block);
+        // The drive doesn't verify CRCs, so they're left blank
+        raw.extend_from_slice(&[0x00; CRC_SIZE]);
+        raw.resize(raw.len() + BLOCK_GAP, 0x00);
+        pos += len;
+    }
+    raw.resize(raw.len().max(RAW_SIDE_SIZE), 0x00);
+    raw
+}
+
+fn decode_side(raw: &[u8], base: &[u8]) -> Vec<u8> {
+    let mut side = Vec::with_capacity(SIDE_SIZE);
+    let mut pos = 0;
+    let mut size = 0;
+    loop {
+        while raw.get(pos) == Some(&0x00) {
+            pos += 1;
+        }
+        if raw.get(pos) != Some(&BLOCK_START) {
+            break;
+        }
+        pos += 1;
+        let Some(len) = raw
+            .get(pos)
+            .and_then(|&block_type| block_len(block_type, size))
+        else {
+            break;
+        };
+        let Some(block) = raw.get(pos..pos + len) else {
+            break;
+        };
+        if block[0] == 3 {
+            size = file_size(block);
+        }
+        side.extend_from_slice(block);
+        pos += len + CRC_SIZE;
+    }
+    side.truncate(SIDE_SIZE);
+    if let Some(rest) = base.get(side.len()..) {
+        side.extend_from_slice(rest);
+    }
+    side.resize(SIDE_SIZE, 0x00);
+    side
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A disk side with one 4 byte file.
+    fn test_side() -> Vec<u8> {
+        let mut side = DISK_VERIFICATION.to_vec();
+        side.resize(56, 0x00);
+        side.extend_from_slice(&[0x02, 0x01]);
+        let mut header = [0x00; 16];
+        header[0] = 0x03;
+        header[13] = 0x04;
+        side.extend_from_slice(&header);
+        side.extend_from_slice(&[0x04, 0xDE, 0xAD, 0xBE, 0xEF]);
+        side.resize(SIDE_SIZE, 0x00);
+        side
+    }
+
+    fn with_header(sides: &[&[u8]]) -> Vec<u8> {
+        let mut data = HEADER_MAGIC.to_vec();
+        data.push(sides.len() as u8);
+        data.resize(HEADER_SIZE, 0x00);
+        for side in sides {
+            data.extend_from_slice(side);
+        }
+        data
+    }
+
+    #[test]
+    fn load_image() {
+        let side = test_side();
+        let image = FdsImage::load(&side).expect("headerless image");
+        assert_eq!(image.sides(), 1);
+        assert_eq!(image.to_bytes(), side);
+
+        let data = with_header(&[&side, &side]);
+        let image = FdsImage::load(&data).expect("fwNES image");
+        assert_eq!(image.sides(), 2);
+        assert_eq!(image.side(1), Some(side.as_slice()));
+        assert_eq!(image.to_bytes(), data);
+
+        assert!(FdsImage::load(&side[..100]).is_err(), "truncated");
+        assert!(
+            FdsImage::load(&[0x00; SIDE_SIZE]).is_err(),
+            "no disk info block"
+        );
+    }
+
+    #[test]
+    fn raw_side_round_trip() {
+        let side = test_side();
+        let raw = encode_side(&side);
+        assert_eq!(raw.len(), RAW_SIDE_SIZE);
+        assert!(raw[..LEAD_IN_GAP].iter().all(|&b| b == 0x00));
+        assert_eq!(raw[LEAD_IN_GAP], BLOCK_START);
+        assert_eq!(
+            &raw[LEAD_IN_GAP + 1..][..DISK_VERIFICATION.len()],
+            DISK_VERIFICATION
+        );
+        assert_eq!(decode_side(&raw, &[]), side);
+    }
+
+    #[test]
+    fn ips_round_trip() {
+        let image = FdsImage::load(&test_side()).expect("valid image");
+        let mut raw_sides = image.to_raw_sides();
+        // Overwrite the file data as the drive would
+        let data = raw_sides[0]
+            .windows(4)
+            .position(|w| w == [0xDE, 0xAD, 0xBE, 0xEF])
+            .expect("file data");
+        raw_sides[0][data..data + 2].copy_from_slice(&[0x12, 0x34]);
+        let modified = image.with_raw_sides(&raw_sides);
+        assert_ne!(modified, image);
+
+        let ips = image.diff(&modified);
+        assert_eq!(ips, b"PATCH\x00\x00\x4B\x00\x02\x12\x34EOF");
+        assert_eq!(image.patch(&ips).expect("valid patch"), modified);
+        assert_eq!(image.diff(&image), b"PATCHEOF");
+        assert!(image.patch(b"PATCH\x00\x00").is_err());
+    }
+}
@@ -28,7 +28,7 @@ pub trait InputRegisters {
     fn write(&mut self, val: u8);
 }
 
-#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[must_use]
 pub enum FourPlayer {
     #[default]
@@ -85,6 +85,8 @@ pub struct Input {
     zapper: Zapper,
     turbo_timer: u32,
     four_player: FourPlayer,
+    // Device connected from the cartridge header, see `connect_expansion_device`
+    expansion_device: u8,
 }
 
 impl Input {
@@ -99,6 +101,7 @@ impl Input {
             zapper: Zapper::new(),
             turbo_timer: 30,
             four_player: FourPlayer::default(),
+            expansion_device: 0x00,
         }
     }
 
@@ -137,6 +140,25 @@ impl Input {
         self.four_player = four_player;
         self.reset(ResetKind::Hard);
     }
+
+    /// Connect the NES 2.0 default expansion device from the cartridge header, if supported.
+    /// The device connected by the previous cartridge is reset to the default first, while other
+    /// devices leave the current configuration as is.
+    pub fn connect_expansion_device(&mut self, device: u8) {
+        match self.expansion_device {
+            0x02 => self.set_four_player(FourPlayer::default()),
+            0x08 | 0x09 => self.connect_zapper(false),
+            _ => (),
+        }
+        self.expansion_device = device;
+        match device {
+            // NES Four Score/Satellite
+            0x02 => self.set_four_player(FourPlayer::FourScore),
+            // Zapper ($4017) or two Zappers
+            0x08 | 0x09 => self.connect_zapper(true),
+            _ => (),
+        }
+    }
 }
 
 impl InputRegisters for Input {
@@ -290,7 +312,7 @@ impl AsRef<str> for JoypadBtn {
 }
 
 bitflags! {
-    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone)]
+    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
     #[must_use]
     pub struct JoypadBtnState: u16 {
         const A = 0x01;
@@ -352,6 +374,16 @@ impl Joypad {
         self.buttons.set(button, pressed);
     }
 
+    #[inline]
+    pub const fn buttons(&self) -> JoypadBtnState {
+        self.buttons
+    }
+
+    #[inline]
+    pub fn set_buttons(&mut self, buttons: JoypadBtnState) {
+        self.buttons = buttons;
+    }
+
     pub const fn signature(val: u16) -> Self {
         Self {
             buttons: JoypadBtnState::from_bits_truncate(val),
@@ -6,13 +6,25 @@ pub mod apu;
 pub mod audio;
 pub mod bus;
 pub mod cart;
+pub mod cdl;
+pub mod checksum;
 #[macro_use]
 pub mod common;
 pub mod control_deck;
 pub mod cpu;
+pub mod debugger;
+pub mod db;
+pub mod fds;
 pub mod genie;
 pub mod input;
 pub mod mapper;
 pub mod mem;
+pub mod movie;
+pub mod nsf;
+pub mod patch;
 pub mod ppu;
+pub mod rewind;
+pub mod state;
+pub mod trace;
+pub mod unif;
 pub mod video;
\ No newline at end of file
@@ -15,6 +15,8 @@ use serde::{Deserialize, Serialize};
 #[must_use]
 pub struct Uxrom {
     mirroring: Mirroring,
+    // Only present for trainers or NES 2.0 headers asking for PRG-RAM
+    has_prg_ram: bool,
     prg_rom_banks: MemBanks,
 }
 
@@ -28,6 +30,7 @@ impl Uxrom {
         };
         let mut uxrom = Self {
             mirroring: cart.mirroring(),
+            has_prg_ram: cart.has_prg_ram(),
             prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW),
         };
         let last_bank = uxrom.prg_rom_banks.last();
@@ -38,12 +41,14 @@ impl Uxrom {
 
 impl MemMap for Uxrom {
     // PPU $0000..=$1FFF 8K Fixed CHR-ROM/CHR-RAM Bank
+    // CPU $6000..=$7FFF 8K PRG-RAM, if present
     // CPU $8000..=$BFFF 16K PRG-ROM Bank Switchable
     // CPU $C000..=$FFFF 16K PRG-ROM Fixed to Last Bank
 
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
             0x0000..=0x1FFF => MappedRead::Chr(addr.into()),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedRead::PrgRam((addr & 0x1FFF).into()),
             0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
             _ => MappedRead::None,
         }
@@ -52,6 +57,7 @@ impl MemMap for Uxrom {
     fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
         match addr {
             0x0000..=0x1FFF => MappedWrite::Chr(addr.into(), val),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
             0x8000..=0xFFFF => {
                 self.prg_rom_banks.set(0, val.into());
                 MappedWrite::None
@@ -16,6 +16,8 @@ use serde::{Deserialize, Serialize};
 #[must_use]
 pub struct Cnrom {
     mirroring: Mirroring,
+    // Only present for trainers or NES 2.0 headers asking for PRG-RAM
+    has_prg_ram: bool,
     chr_banks: MemBanks,
     mirror_prg_rom: bool,
 }
@@ -26,6 +28,7 @@ impl Cnrom {
     pub fn load(cart: &mut Cart) -> Mapper {
         let cnrom = Self {
             mirroring: cart.mirroring(),
+            has_prg_ram: cart.has_prg_ram(),
             chr_banks: MemBanks::new(0x0000, 0x1FFFF, cart.chr_rom.len(), Self::CHR_ROM_WINDOW),
             mirror_prg_rom: cart.prg_rom.len() <= 0x4000,
         };
@@ -35,12 +38,14 @@ impl Cnrom {
 
 impl MemMap for Cnrom {
     // PPU $0000..=$1FFF 8K CHR-ROM Banks Switchable
+    // CPU $6000..=$7FFF 8K PRG-RAM, if present
     // CPU $8000..=$BFFF 16K PRG-ROM Bank Fixed
     // CPU $C000..=$FFFF 16K PRG-ROM Bank Fixed or Bank 1 Mirror if only 16 KB PRG-ROM
 
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
             0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedRead::PrgRam((addr & 0x1FFF).into()),
             0x8000..=0xBFFF => MappedRead::PrgRom((addr & 0x3FFF).into()),
             0xC000..=0xFFFF => {
                 let mirror = if self.mirror_prg_rom { 0x3FFF } else { 0x7FFF };
@@ -51,8 +56,12 @@ impl MemMap for Cnrom {
     }
 
     fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
-        if matches!(addr, 0x8000..=0xFFFF) {
-            self.chr_banks.set(0, val.into());
+        match addr {
+            0x6000..=0x7FFF if self.has_prg_ram => {
+                return MappedWrite::PrgRam((addr & 0x1FFF).into(), val);
+            }
+            0x8000..=0xFFFF => self.chr_banks.set(0, val.into()),
+            _ => (),
         }
         MappedWrite::None
     }
@@ -35,6 +35,19 @@ pub enum Mmc3Revision {
     Acc,
 }
 
+impl Mmc3Revision {
+    /// Select the revision from the NES 2.0 submapper number.
+    ///
+    /// <https://www.nesdev.org/wiki/NES_2.0_submappers#004:_MMC3>
+    pub const fn from_submapper(submapper_num: u8) -> Self {
+        match submapper_num {
+            3 => Self::Acc,
+            4 => Self::A,
+            _ => Self::BC,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[must_use]
 struct TxRegs {
@@ -96,7 +109,7 @@ impl Txrom {
             regs: TxRegs::new(),
             mirroring: cart.mirroring(),
             irq_pending: false,
-            revision: Mmc3Revision::BC, // TODO compare to known games
+            revision: Mmc3Revision::from_submapper(cart.submapper_num()),
             chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_len(), Self::CHR_WINDOW),
             prg_ram_banks: MemBanks::new(0x6000, 0x7FFF, cart.prg_ram.len(), Self::PRG_WINDOW),
             prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
@@ -266,13 +266,7 @@ pub struct Exrom {
     chr_banks: MemBanks,
     tile_cache: usize,
     last_chr_write: ChrBank,
-    region: NesRegion,
-    pulse1: Pulse,
-    pulse2: Pulse,
-    dmc: Dmc,
-    dmc_mode: u8,
-    cpu_cycle: usize,
-    pulse_timer: f32,
+    audio: Mmc5Audio,
 }
 
 impl Exrom {
@@ -344,13 +338,7 @@ impl Exrom {
             chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_WINDOW),
             tile_cache: 0,
             last_chr_write: ChrBank::Spr,
-            region: NesRegion::default(),
-            pulse1: Pulse::new(PulseChannel::One, OutputFreq::Ultrasonic),
-            pulse2: Pulse::new(PulseChannel::Two, OutputFreq::Ultrasonic),
-            dmc: Dmc::new(),
-            dmc_mode: 0x01, // Default to read mode
-            cpu_cycle: 0,
-            pulse_timer: 0.0,
+            audio: Mmc5Audio::new(),
         };
         exrom.regs.prg_banks[4] = exrom.prg_rom_banks.last() | Self::ROM_SELECT_MASK;
         exrom.update_prg_banks();
@@ -540,12 +528,12 @@ impl Mapped for Exrom {
 impl Regional for Exrom {
     #[inline]
     fn region(&self) -> NesRegion {
-        self.dmc.region()
+        self.audio.region()
     }
 
     #[inline]
     fn set_region(&mut self, region: NesRegion) {
-        self.dmc.set_region(region);
+        self.audio.set_region(region);
     }
 }
 
@@ -654,7 +642,7 @@ impl MemMap for Exrom {
         let val = self.map_peek(addr);
         match addr {
             0x5204 => self.irq_pending = false, // Reading from IRQ status clears it
-            0x5010 => self.dmc.acknowledge_irq(),
+            0x5010 => self.audio.acknowledge_irq(),
             _ => (),
         }
         val
@@ -711,30 +699,13 @@ impl MemMap for Exrom {
                     }
                 }
             }
-            0x5010 => {
-                // [I... ...M] DMC
-                // I = IRQ (0 = No IRQ triggered. 1 = IRQ was triggered.) Reading $5010 acknowledges the IRQ and clears this flag.
-                // M = Mode select (0 = write mode. 1 = read mode.)
-                let irq = self.dmc.irq_pending() && self.dmc.irq_enabled();
-                MappedRead::Data(u8::from(irq) << 7 | self.dmc_mode)
-            }
+            0x5010 | 0x5015 => MappedRead::Data(self.audio.peek(addr)),
             0x5100 => MappedRead::Data(self.regs.prg_mode as u8),
             0x5101 => MappedRead::Data(self.regs.chr_mode as u8),
             0x5104 => MappedRead::Data(self.regs.exram_mode.bits),
             0x5105 => MappedRead::Data(self.regs.nametable_mapping.mode),
             0x5106 => MappedRead::Data(self.regs.fill.tile),
             0x5107 => MappedRead::Data(self.regs.fill.attr as u8),
-            0x5015 => {
-                // [.... ..BA]   Length status for Pulse 1 (A), 2 (B)
-                let mut status = 0x00;
-                if self.pulse1.length_counter() > 0 {
-                    status |= 0x01;
-                }
-                if self.pulse2.length_counter() > 0 {
-                    status |= 0x02;
-                }
-                MappedRead::Data(status)
-            }
             0x5113..=0x5117 => {
                 MappedRead::Data(self.regs.prg_banks[(addr - 0x5113) as usize] as u8)
             }
@@ -788,33 +759,7 @@ impl MemMap for Exrom {
                 }
                 _ => (),
             },
-            0x5000 => self.pulse1.write_ctrl(val),
-            // 0x5001 Has no effect since there is no Sweep unit
-            0x5002 => self.pulse1.write_timer_lo(val),
-            0x5003 => self.pulse1.write_timer_hi(val),
-            0x5004 => self.pulse2.write_ctrl(val),
-            // 0x5005 Has no effect since there is no Sweep unit
-            0x5006 => self.pulse2.write_timer_lo(val),
-            0x5007 => self.pulse2.write_timer_hi(val),
-            0x5010 => {
-                // [I... ...M] DMC
-                //   I = PCM IRQ enable (1 = enabled.)
-                //   M = Mode select (0 = write mode. 1 = read mode.)
-                self.dmc_mode = val & 0x01;
-                self.dmc.set_enabled(val & 0x80 == 0x80, self.cpu_cycle);
-            }
-            0x5011 => {
-                // [DDDD DDDD] PCM Data
-                // Write mode - writing $00 has no effect
-                if self.dmc_mode == 0 && val != 0x00 {
-                    self.dmc.write_output(val);
-                }
-            }
-            0x5015 => {
-                //  [.... ..BA]   Enable flags for Pulse 1 (A), 2 (B)  (0=disable, 1=enable)
-                self.pulse1.set_enabled(val & 0x01 == 0x01);
-                self.pulse2.set_enabled(val & 0x02 == 0x02);
-            }
+            0x5000..=0x5015 => self.audio.write(addr, val),
             0x5100 => {
                 // [.... ..PP] PRG Mode
                 self.regs.prg_mode = match val & 0x03 {
@@ -974,12 +919,7 @@ impl MemMap for Exrom {
 impl Audio for Exrom {
     #[must_use]
     fn output(&self) -> f32 {
-        let pulse1 = self.pulse1.output();
-        let pulse2 = self.pulse2.output();
-        let dmc = self.dmc.output();
-        let pulse_scale = PULSE_TABLE[PULSE_TABLE.len() - 1] / 15.0;
-        let out = -(pulse1 + pulse2 + dmc);
-        pulse_scale * out
+        self.audio.output()
     }
 }
 
@@ -997,20 +937,7 @@ impl Clock for Exrom {
             }
         }
         self.ppu_status.reading = false;
-        if self.cpu_cycle & 0x01 == 0x00 {
-            self.pulse1.clock();
-            self.pulse2.clock();
-            self.dmc.clock();
-        }
-        self.pulse_timer -= 1.0;
-        if self.pulse_timer <= 0.0 {
-            self.pulse1.clock_quarter_frame();
-            self.pulse1.clock_half_frame();
-            self.pulse2.clock_quarter_frame();
-            self.pulse2.clock_half_frame();
-            self.pulse_timer = Cpu::region_clock_rate(self.region) / 240.0;
-        }
-        self.cpu_cycle = self.cpu_cycle.wrapping_add(1);
+        self.audio.clock();
         1
     }
 }
@@ -1035,13 +962,154 @@ impl core::fmt::Debug for Exrom {
             .field("chr_banks", &self.chr_banks)
             .field("tile_cache", &self.tile_cache)
             .field("last_chr_write", &self.last_chr_write)
-            .field("region", &self.region)
-            .field("pulse1", &self.pulse1)
-            .field("pulse2", &self.pulse2)
-            .field("dmc", &self.dmc)
-            .field("dmc_mode", &self.dmc_mode)
-            .field("cpu_cycle", &self.cpu_cycle)
-            .field("pulse_timer", &self.pulse_timer)
+            .field("audio", &self.audio)
             .finish()
     }
-}
\ No newline at end of file
+}
+
+/// MMC5 expansion audio: two pulse channels like the APU's, without sweep units, and a raw PCM
+/// channel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Mmc5Audio {
+    region: NesRegion,
+    pulse1: Pulse,
+    pulse2: Pulse,
+    dmc: Dmc,
+    dmc_mode: u8,
+    cpu_cycle: usize,
+    pulse_timer: f32,
+}
+
+impl Default for Mmc5Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Mmc5Audio {
+    pub fn new() -> Self {
+        Self {
+            region: NesRegion::default(),
+            pulse1: Pulse::new(PulseChannel::One, OutputFreq::Ultrasonic),
+            pulse2: Pulse::new(PulseChannel::Two, OutputFreq::Ultrasonic),
+            dmc: Dmc::new(),
+            dmc_mode: 0x01, // Default to read mode
+            cpu_cycle: 0,
+            pulse_timer: 0.0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> f32 {
+        let pulse1 = self.pulse1.output();
+        let pulse2 = self.pulse2.output();
+        let dmc = self.dmc.output();
+        let pulse_scale = PULSE_TABLE[PULSE_TABLE.len() - 1] / 15.0;
+        let out = -(pulse1 + pulse2 + dmc);
+        pulse_scale * out
+    }
+
+    #[must_use]
+    pub fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x5010 => {
+                // [I... ...M] DMC
+                // I = IRQ (0 = No IRQ triggered. 1 = IRQ was triggered.) Reading $5010 acknowledges the IRQ and clears this flag.
+                // M = Mode select (0 = write mode. 1 = read mode.)
+                let irq = self.dmc.irq_pending() && self.dmc.irq_enabled();
+                u8::from(irq) << 7 | self.dmc_mode
+            }
+            0x5015 => {
+                // [.... ..BA]   Length status for Pulse 1 (A), 2 (B)
+                let mut status = 0x00;
+                if self.pulse1.length_counter() > 0 {
+                    status |= 0x01;
+                }
+                if self.pulse2.length_counter() > 0 {
+                    status |= 0x02;
+                }
+                status
+            }
+            _ => 0x00,
+        }
+    }
+
+    #[inline]
+    pub fn acknowledge_irq(&mut self) {
+        self.dmc.acknowledge_irq();
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0x5000 => self.pulse1.write_ctrl(val),
+            // 0x5001 Has no effect since there is no Sweep unit
+            0x5002 => self.pulse1.write_timer_lo(val),
+            0x5003 => self.pulse1.write_timer_hi(val),
+            0x5004 => self.pulse2.write_ctrl(val),
+            // 0x5005 Has no effect since there is no Sweep unit
+            0x5006 => self.pulse2.write_timer_lo(val),
+            0x5007 => self.pulse2.write_timer_hi(val),
+            0x5010 => {
+                // [I... ...M] DMC
+                //   I = PCM IRQ enable (1 = enabled.)
+                //   M = Mode select (0 = write mode. 1 = read mode.)
+                self.dmc_mode = val & 0x01;
+                self.dmc.set_enabled(val & 0x80 == 0x80, self.cpu_cycle);
+            }
+            0x5011 => {
+                // [DDDD DDDD] PCM Data
+                // Write mode - writing $00 has no effect
+                if self.dmc_mode == 0 && val != 0x00 {
+                    self.dmc.write_output(val);
+                }
+            }
+            0x5015 => {
+                //  [.... ..BA]   Enable flags for Pulse 1 (A), 2 (B)  (0=disable, 1=enable)
+                self.pulse1.set_enabled(val & 0x01 == 0x01);
+                self.pulse2.set_enabled(val & 0x02 == 0x02);
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Audio for Mmc5Audio {
+    #[inline]
+    fn output(&self) -> f32 {
+        Self::output(self)
+    }
+}
+
+impl Clock for Mmc5Audio {
+    fn clock(&mut self) -> usize {
+        if self.cpu_cycle & 0x01 == 0x00 {
+            self.pulse1.clock();
+            self.pulse2.clock();
+            self.dmc.clock();
+        }
+        self.pulse_timer -= 1.0;
+        if self.pulse_timer <= 0.0 {
+            self.pulse1.clock_quarter_frame();
+            self.pulse1.clock_half_frame();
+            self.pulse2.clock_quarter_frame();
+            self.pulse2.clock_half_frame();
+            self.pulse_timer = Cpu::region_clock_rate(self.region) / 240.0;
+        }
+        self.cpu_cycle = self.cpu_cycle.wrapping_add(1);
+        1
+    }
+}
+
+impl Regional for Mmc5Audio {
+    #[inline]
+    fn region(&self) -> NesRegion {
+        self.dmc.region()
+    }
+
+    #[inline]
+    fn set_region(&mut self, region: NesRegion) {
+        self.dmc.set_region(region);
+    }
+}
@@ -15,6 +15,8 @@ use serde::{Deserialize, Serialize};
 #[must_use]
 pub struct Axrom {
     mirroring: Mirroring,
+    // Only present for trainers or NES 2.0 headers asking for PRG-RAM
+    has_prg_ram: bool,
     prg_rom_banks: MemBanks,
 }
 
@@ -29,6 +31,7 @@ impl Axrom {
         }
         let axrom = Self {
             mirroring: cart.mirroring(),
+            has_prg_ram: cart.has_prg_ram(),
             prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW),
         };
         axrom.into()
@@ -49,11 +52,13 @@ impl Mapped for Axrom {
 
 impl MemMap for Axrom {
     // PPU $0000..=$1FFF 8K CHR-RAM Bank Fixed
+    // CPU $6000..=$7FFF 8K PRG-RAM, if present
     // CPU $8000..=$FFFF 32K switchable PRG-ROM bank
 
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
             0x0000..=0x1FFF => MappedRead::Chr(addr.into()),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedRead::PrgRam((addr & 0x1FFF).into()),
             0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
             _ => MappedRead::None,
         }
@@ -62,6 +67,7 @@ impl MemMap for Axrom {
     fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
         match addr {
             0x0000..=0x1FFF => MappedWrite::Chr(addr.into(), val),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
             0x8000..=0xFFFF => {
                 self.prg_rom_banks.set(0, (val & 0x0F).into());
                 self.mirroring = if val & Self::SINGLE_SCREEN_B == Self::SINGLE_SCREEN_B {
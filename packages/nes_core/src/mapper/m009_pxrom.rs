@@ -10,10 +10,13 @@ use crate::{
 };
 use serde::{Deserialize, Serialize};
 
+/// Latch-based CHR-ROM switching shared by `MMC2` and `MMC4`.
+///
+/// The PPU reading tile $FD or $FE from either pattern table flips that table's latch, which
+/// selects between two 4K CHR-ROM banks.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[must_use]
-pub struct Pxrom {
-    mirroring: Mirroring,
+pub struct ChrLatch {
     // CHR-ROM $FD/0000 bank select ($B000-$BFFF)
     // CHR-ROM $FE/0000 bank select ($C000-$CFFF)
     // CHR-ROM $FD/1000 bank select ($D000-$DFFF)
@@ -27,12 +30,65 @@ pub struct Pxrom {
     latch: [usize; 2],
     latch_banks: [u8; 4],
     chr_banks: MemBanks,
+}
+
+impl ChrLatch {
+    const CHR_ROM_WINDOW: usize = 4 * 1024;
+
+    pub fn new(cart: &Cart) -> Self {
+        Self {
+            latch: [0x00; 2],
+            latch_banks: [0x00; 4],
+            chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_ROM_WINDOW),
+        }
+    }
+
+    /// Flip the latch for the pattern table of `addr`, which must be within one of the
+    /// $xFD8/$xFE8 tile ranges.
+    pub fn update(&mut self, addr: u16) {
+        let addr = addr as usize;
+        self.latch[addr >> 12] = ((addr >> 4) & 0xFF) - 0xFD;
+        self.update_banks();
+    }
+
+    /// Set the CHR-ROM bank for one of the $B000-$EFFF bank select registers.
+    pub fn set_bank(&mut self, addr: u16, val: u8) {
+        self.latch_banks[((addr - 0xB000) >> 12) as usize] = val & 0x1F;
+        self.update_banks();
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn translate(&self, addr: u16) -> usize {
+        self.chr_banks.translate(addr)
+    }
+
+    fn update_banks(&mut self) {
+        let bank0 = self.latch_banks[self.latch[0]] as usize;
+        let bank1 = self.latch_banks[self.latch[1] + 2] as usize;
+        self.chr_banks.set(0, bank0);
+        self.chr_banks.set(1, bank1);
+    }
+}
+
+impl Reset for ChrLatch {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.latch = [0x00; 2];
+        self.latch_banks = [0x00; 4];
+        self.update_banks();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Pxrom {
+    mirroring: Mirroring,
+    chr: ChrLatch,
     prg_rom_banks: MemBanks,
 }
 
 impl Pxrom {
     const PRG_WINDOW: usize = 8 * 1024;
-    const CHR_ROM_WINDOW: usize = 4 * 1024;
     const PRG_RAM_SIZE: usize = 8 * 1024;
 
     const MIRRORING_MASK: u8 = 0x01;
@@ -41,9 +97,7 @@ impl Pxrom {
         cart.add_prg_ram(Self::PRG_RAM_SIZE);
         let mut pxrom = Self {
             mirroring: cart.mirroring(),
-            latch: [0x00; 2],
-            latch_banks: [0x00; 4],
-            chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_ROM_WINDOW),
+            chr: ChrLatch::new(cart),
             prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
         };
         let last_bank = pxrom.prg_rom_banks.last();
@@ -52,13 +106,6 @@ impl Pxrom {
         pxrom.prg_rom_banks.set(3, last_bank);
         pxrom.into()
     }
-
-    fn update_banks(&mut self) {
-        let bank0 = self.latch_banks[self.latch[0]] as usize;
-        let bank1 = self.latch_banks[self.latch[1] + 2] as usize;
-        self.chr_banks.set(0, bank0);
-        self.chr_banks.set(1, bank1);
-    }
 }
 
 impl Mapped for Pxrom {
@@ -83,20 +130,15 @@ impl MemMap for Pxrom {
     fn map_read(&mut self, addr: u16) -> MappedRead {
         let val = self.map_peek(addr);
         // Update latch after read
-        match addr {
-            0x0FD8 | 0x0FE8 | 0x1FD8..=0x1FDF | 0x1FE8..=0x1FEF => {
-                let addr = addr as usize;
-                self.latch[addr >> 12] = ((addr >> 4) & 0xFF) - 0xFD;
-                self.update_banks();
-            }
-            _ => (),
+        if let 0x0FD8 | 0x0FE8 | 0x1FD8..=0x1FDF | 0x1FE8..=0x1FEF = addr {
+            self.chr.update(addr);
         }
         val
     }
 
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
-            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr.translate(addr)),
             0x6000..=0x7FFF => MappedRead::PrgRam((addr & 0x1FFF).into()),
             0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
             _ => MappedRead::None,
@@ -111,8 +153,7 @@ impl MemMap for Pxrom {
                 MappedWrite::None
             }
             0xB000..=0xEFFF => {
-                self.latch_banks[((addr - 0xB000) >> 12) as usize] = val & 0x1F;
-                self.update_banks();
+                self.chr.set_bank(addr, val);
                 MappedWrite::None
             }
             0xF000..=0xFFFF => {
@@ -129,10 +170,8 @@ impl MemMap for Pxrom {
 }
 
 impl Reset for Pxrom {
-    fn reset(&mut self, _kind: ResetKind) {
-        self.latch = [0x00; 2];
-        self.latch_banks = [0x00; 4];
-        self.update_banks();
+    fn reset(&mut self, kind: ResetKind) {
+        self.chr.reset(kind);
     }
 }
 
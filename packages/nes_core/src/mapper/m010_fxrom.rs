@@ -0,0 +1,108 @@
+//! `FxROM`/`MMC4` (Mapper 010)
+//!
+//! <http://wiki.nesdev.com/w/index.php/MMC4>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, ResetKind, Regional, Reset},
+    mapper::{m009_pxrom::ChrLatch, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mirroring},
+    mem::MemBanks,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Fxrom {
+    mirroring: Mirroring,
+    chr: ChrLatch,
+    prg_rom_banks: MemBanks,
+}
+
+impl Fxrom {
+    const PRG_WINDOW: usize = 16 * 1024;
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+
+    const MIRRORING_MASK: u8 = 0x01;
+
+    pub fn load(cart: &mut Cart) -> Mapper {
+        cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        let mut fxrom = Self {
+            mirroring: cart.mirroring(),
+            chr: ChrLatch::new(cart),
+            prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
+        };
+        let last_bank = fxrom.prg_rom_banks.last();
+        fxrom.prg_rom_banks.set(1, last_bank);
+        fxrom.into()
+    }
+}
+
+impl Mapped for Fxrom {
+    #[inline]
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    #[inline]
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+}
+
+impl MemMap for Fxrom {
+    // PPU $0000..=$0FFF Two 4K switchable CHR-ROM banks
+    // PPU $1000..=$1FFF Two 4K switchable CHR-ROM banks
+    // CPU $6000..=$7FFF 8K PRG-RAM bank, usually battery-backed
+    // CPU $8000..=$BFFF 16K switchable PRG-ROM bank
+    // CPU $C000..=$FFFF 16K PRG-ROM bank, fixed to the last bank
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        let val = self.map_peek(addr);
+        // Update latch after read. Unlike MMC2, both latches trigger on the full tile range.
+        if let 0x0FD8..=0x0FDF | 0x0FE8..=0x0FEF | 0x1FD8..=0x1FDF | 0x1FE8..=0x1FEF = addr {
+            self.chr.update(addr);
+        }
+        val
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr.translate(addr)),
+            0x6000..=0x7FFF => MappedRead::PrgRam((addr & 0x1FFF).into()),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::None,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x6000..=0x7FFF => MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
+            0xA000..=0xAFFF => {
+                self.prg_rom_banks.set(0, (val & 0x0F).into());
+                MappedWrite::None
+            }
+            0xB000..=0xEFFF => {
+                self.chr.set_bank(addr, val);
+                MappedWrite::None
+            }
+            0xF000..=0xFFFF => {
+                self.mirroring = match val & Self::MIRRORING_MASK {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    _ => unreachable!("impossible mirroring mode"),
+                };
+                MappedWrite::None
+            }
+            _ => MappedWrite::None,
+        }
+    }
+}
+
+impl Reset for Fxrom {
+    fn reset(&mut self, kind: ResetKind) {
+        self.chr.reset(kind);
+    }
+}
+
+impl Clock for Fxrom {}
+impl Regional for Fxrom {}
@@ -0,0 +1,597 @@
+//! `Namco 163` (Mapper 019)
+//!
+//! <https://www.nesdev.org/wiki/INES_Mapper_019>
+//! <https://www.nesdev.org/wiki/Namco_163_audio>
+
+use crate::{
+    audio::Audio,
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind},
+    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::MemBanks,
+    ppu::Mirroring,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// Expansion audio level relative to the APU, which varies by board. Selected by the NES 2.0
+/// submapper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum Namco163Volume {
+    /// No expansion audio
+    Silent,
+    /// 11-13 dB louder than the APU
+    Low,
+    /// 16-17 dB louder than the APU
+    Medium,
+    /// 18-19.5 dB louder than the APU
+    High,
+}
+
+impl Namco163Volume {
+    pub const fn from_submapper(submapper_num: u8) -> Self {
+        match submapper_num {
+            2 => Self::Silent,
+            3 => Self::Low,
+            5 => Self::High,
+            _ => Self::Medium,
+        }
+    }
+
+    /// Output gain relative to `High`.
+    const fn gain(self) -> f32 {
+        match self {
+            Self::Silent => 0.0,
+            Self::Low => 0.46,
+            Self::Medium => 0.77,
+            Self::High => 1.0,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Namco163Regs {
+    chr: [u8; 8],
+    nametables: [u8; 4],
+    // [HL.. ....]
+    //  ||
+    //  |+-------- Disable CIRAM for $0000-$0FFF
+    //  +--------- Disable CIRAM for $1000-$1FFF
+    chr_ram_disable: u8,
+    // [KKKK DCBA]
+    //  |||| ||||
+    //  |||| |||+- Write protect $6000-$67FF
+    //  |||| ||+-- Write protect $6800-$6FFF
+    //  |||| |+--- Write protect $7000-$77FF
+    //  |||| +---- Write protect $7800-$7FFF
+    //  ++++------ PRG-RAM writes are enabled when set to $4
+    prg_ram_protect: u8,
+    irq_counter: u16,
+    irq_enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Namco163 {
+    regs: Namco163Regs,
+    mirroring: Mirroring,
+    irq_pending: bool,
+    audio: Namco163Audio,
+    // Sound RAM is mirrored into PRG-RAM starting at this offset to be battery-backed with it
+    sound_ram_offset: usize,
+    chr_banks: MemBanks,
+    nt_banks: MemBanks,
+    prg_rom_banks: MemBanks,
+}
+
+impl Namco163 {
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+    const PRG_WINDOW: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 1024;
+    // Bank numbers $E0-$FF select CIRAM instead of CHR
+    const CIRAM_BANK: u8 = 0xE0;
+
+    pub fn load(cart: &mut Cart, volume: Namco163Volume) -> Mapper {
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        if !cart.has_chr() {
+            cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        }
+        let sound_ram_offset = cart.prg_ram.len();
+        cart.add_prg_ram(sound_ram_offset + Namco163Audio::RAM_SIZE);
+        let mut audio = Namco163Audio::new(volume);
+        audio.ram.copy_from_slice(&cart.prg_ram[sound_ram_offset..]);
+        let mut namco163 = Self {
+            regs: Namco163Regs::default(),
+            mirroring: cart.mirroring(),
+            irq_pending: false,
+            audio,
+            sound_ram_offset,
+            chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_len(), Self::CHR_WINDOW),
+            nt_banks: MemBanks::new(0x2000, 0x2FFF, cart.chr_len(), Self::CHR_WINDOW),
+            prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
+        };
+        let last_bank = namco163.prg_rom_banks.last();
+        namco163.prg_rom_banks.set(3, last_bank);
+        namco163.into()
+    }
+
+    #[inline]
+    #[must_use]
+    const fn prg_ram_writable(&self, addr: u16) -> bool {
+        let protect = self.regs.prg_ram_protect;
+        protect & 0xF0 == 0x40 && protect & (1 << ((addr - 0x6000) >> 11)) == 0
+    }
+
+    /// Pattern table bank `slot` maps to CIRAM.
+    #[inline]
+    #[must_use]
+    const fn chr_ciram(&self, slot: usize) -> bool {
+        let disable = if slot < 4 { 0x40 } else { 0x80 };
+        self.regs.chr[slot] >= Self::CIRAM_BANK && self.regs.chr_ram_disable & disable == 0
+    }
+
+    #[inline]
+    #[must_use]
+    const fn ciram_addr(bank: u8, addr: u16) -> usize {
+        ((bank as usize & 0x01) << 10) | (addr as usize & 0x03FF)
+    }
+
+    const fn read_irq_counter(&self, addr: u16) -> u8 {
+        if addr < 0x5800 {
+            (self.regs.irq_counter & 0xFF) as u8
+        } else {
+            ((self.regs.irq_counter >> 8) as u8) | if self.regs.irq_enabled { 0x80 } else { 0x00 }
+        }
+    }
+}
+
+impl Mapped for Namco163 {
+    #[inline]
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    #[inline]
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    #[inline]
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+
+    fn load_sram(&mut self, sram: &[u8]) {
+        let sound_ram = self.sound_ram_offset..self.sound_ram_offset + Namco163Audio::RAM_SIZE;
+        if let Some(ram) = sram.get(sound_ram) {
+            self.audio.ram.copy_from_slice(ram);
+        }
+    }
+}
+
+impl MemMap for Namco163 {
+    // PPU $0000..=$03FF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $0400..=$07FF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $0800..=$0BFF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $0C00..=$0FFF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $1000..=$13FF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $1400..=$17FF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $1800..=$1BFF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $1C00..=$1FFF 1K switchable CHR-ROM or CIRAM bank
+    // PPU $2000..=$2FFF 4 1K switchable CHR-ROM or CIRAM nametables
+    //
+    // CPU $4800..=$4FFF Sound RAM data port
+    // CPU $5000..=$5FFF IRQ counter
+    // CPU $6000..=$7FFF 8K PRG-RAM bank
+    // CPU $8000..=$9FFF 8K switchable PRG-ROM bank
+    // CPU $A000..=$BFFF 8K switchable PRG-ROM bank
+    // CPU $C000..=$DFFF 8K switchable PRG-ROM bank
+    // CPU $E000..=$FFFF 8K PRG-ROM bank, fixed to the last bank
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        match addr {
+            0x4800..=0x4FFF => MappedRead::Data(self.audio.read_data()),
+            _ => self.map_peek(addr),
+        }
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => {
+                let slot = usize::from(addr >> 10);
+                if self.chr_ciram(slot) {
+                    MappedRead::CIRam(Self::ciram_addr(self.regs.chr[slot], addr))
+                } else {
+                    MappedRead::Chr(self.chr_banks.translate(addr))
+                }
+            }
+            0x2000..=0x3EFF => {
+                let bank = self.regs.nametables[usize::from((addr >> 10) & 0x03)];
+                if bank >= Self::CIRAM_BANK {
+                    MappedRead::CIRam(Self::ciram_addr(bank, addr))
+                } else {
+                    MappedRead::Chr(self.nt_banks.translate(addr))
+                }
+            }
+            0x4800..=0x4FFF => MappedRead::Data(self.audio.peek_data()),
+            0x5000..=0x5FFF => MappedRead::Data(self.read_irq_counter(addr)),
+            0x6000..=0x7FFF => MappedRead::PrgRam((addr & 0x1FFF).into()),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::None,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => {
+                let slot = usize::from(addr >> 10);
+                if self.chr_ciram(slot) {
+                    MappedWrite::CIRam(Self::ciram_addr(self.regs.chr[slot], addr), val)
+                } else {
+                    MappedWrite::Chr(self.chr_banks.translate(addr), val)
+                }
+            }
+            0x2000..=0x3EFF => {
+                let bank = self.regs.nametables[usize::from((addr >> 10) & 0x03)];
+                if bank >= Self::CIRAM_BANK {
+                    MappedWrite::CIRam(Self::ciram_addr(bank, addr), val)
+                } else {
+                    MappedWrite::Chr(self.nt_banks.translate(addr), val)
+                }
+            }
+            0x4800..=0x4FFF => {
+                let addr = self.audio.write_data(val);
+                MappedWrite::PrgRam(self.sound_ram_offset + addr, val)
+            }
+            0x5000..=0x57FF => {
+                self.regs.irq_counter = (self.regs.irq_counter & 0x7F00) | u16::from(val);
+                self.irq_pending = false;
+                MappedWrite::None
+            }
+            0x5800..=0x5FFF => {
+                // [EHHH HHHH]
+                //  |||| ||||
+                //  |+++-++++- High 7 bits of the IRQ counter
+                //  +--------- IRQ enable
+                self.regs.irq_counter =
+                    (self.regs.irq_counter & 0x00FF) | (u16::from(val & 0x7F) << 8);
+                self.regs.irq_enabled = val & 0x80 == 0x80;
+                self.irq_pending = false;
+                MappedWrite::None
+            }
+            0x6000..=0x7FFF if self.prg_ram_writable(addr) => {
+                MappedWrite::PrgRam((addr & 0x1FFF).into(), val)
+            }
+            0x8000..=0xBFFF => {
+                let slot = usize::from((addr - 0x8000) >> 11);
+                self.regs.chr[slot] = val;
+                self.chr_banks.set(slot, val.into());
+                MappedWrite::None
+            }
+            0xC000..=0xDFFF => {
+                let slot = usize::from((addr - 0xC000) >> 11);
+                self.regs.nametables[slot] = val;
+                self.nt_banks.set(slot, val.into());
+                MappedWrite::None
+            }
+            0xE000..=0xE7FF => {
+                // [.SPP PPPP]
+                //   ||| ||||
+                //   |++-++++- PRG-ROM bank at $8000
+                //   +-------- Disable sound
+                self.prg_rom_banks.set(0, (val & 0x3F).into());
+                self.audio.set_disabled(val & 0x40 == 0x40);
+                MappedWrite::None
+            }
+            0xE800..=0xEFFF => {
+                self.prg_rom_banks.set(1, (val & 0x3F).into());
+                self.regs.chr_ram_disable = val & 0xC0;
+                MappedWrite::None
+            }
+            0xF000..=0xF7FF => {
+                self.prg_rom_banks.set(2, (val & 0x3F).into());
+                MappedWrite::None
+            }
+            0xF800..=0xFFFF => {
+                self.regs.prg_ram_protect = val;
+                self.audio.write_addr(val);
+                MappedWrite::None
+            }
+            _ => MappedWrite::None,
+        }
+    }
+}
+
+impl Audio for Namco163 {
+    #[inline]
+    fn output(&self) -> f32 {
+        self.audio.output()
+    }
+}
+
+impl Clock for Namco163 {
+    fn clock(&mut self) -> usize {
+        if self.regs.irq_enabled && self.regs.irq_counter < 0x7FFF {
+            self.regs.irq_counter += 1;
+            if self.regs.irq_counter == 0x7FFF {
+                self.irq_pending = true;
+            }
+        }
+        self.audio.clock();
+        1
+    }
+}
+
+impl Reset for Namco163 {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.irq_pending = false;
+    }
+}
+
+impl Regional for Namco163 {}
+
+/// Namco 163 expansion audio. Up to 8 wavetable channels play 4-bit samples out of 128 bytes
+/// of internal RAM, which also holds the channel registers.
+///
+/// Only one channel is updated and output at a time, cycling through the enabled channels, so
+/// enabling more channels lowers each channel's volume and sample rate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Namco163Audio {
+    ram: Vec<u8>,
+    addr: u8,
+    auto_increment: bool,
+    disabled: bool,
+    volume: Namco163Volume,
+    channel: u8,
+    divider: u8,
+    out: f32,
+}
+
+impl Namco163Audio {
+    const RAM_SIZE: usize = 128;
+    /// CPU cycles per channel update.
+    const CLOCK_DIVIDER: u8 = 15;
+    /// Channel registers are 8 bytes each, starting at $40 for channel 0.
+    const CHANNEL_BASE: usize = 0x40;
+    // A channel at full volume outputs up to 8 * 15, scaled to a full volume APU at `High`
+    const OUTPUT_SCALE: f32 = 0.26 / 120.0;
+
+    pub fn new(volume: Namco163Volume) -> Self {
+        Self {
+            ram: vec![0x00; Self::RAM_SIZE],
+            addr: 0x00,
+            auto_increment: false,
+            disabled: false,
+            volume,
+            channel: 7,
+            divider: 0,
+            out: 0.0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> f32 {
+        Self::OUTPUT_SCALE * self.volume.gain() * self.out
+    }
+
+    /// Select the sound RAM address for the data port.
+    ///
+    /// [IAAA AAAA]
+    ///  |||| ||||
+    ///  |+++-++++- Address
+    ///  +--------- Auto-increment after each access
+    #[inline]
+    pub fn write_addr(&mut self, val: u8) {
+        self.addr = val & 0x7F;
+        self.auto_increment = val & 0x80 == 0x80;
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn peek_data(&self) -> u8 {
+        self.ram[usize::from(self.addr)]
+    }
+
+    pub fn read_data(&mut self) -> u8 {
+        let val = self.peek_data();
+        self.increment_addr();
+        val
+    }
+
+    /// Write to sound RAM, returning the address written.
+    pub fn write_data(&mut self, val: u8) -> usize {
+        let addr = usize::from(self.addr);
+        self.ram[addr] = val;
+        self.increment_addr();
+        addr
+    }
+
+    #[inline]
+    pub fn set_disabled(&mut self, disabled: bool) {
+        self.disabled = disabled;
+        if disabled {
+            self.out = 0.0;
+        }
+    }
+
+    #[inline]
+    fn increment_addr(&mut self) {
+        if self.auto_increment {
+            self.addr = (self.addr + 1) & 0x7F;
+        }
+    }
+
+    /// Lowest enabled channel. Channels are enabled from 7 downwards.
+    #[inline]
+    fn first_channel(&self) -> u8 {
+        7 - ((self.ram[0x7F] >> 4) & 0x07)
+    }
+
+    fn clock_channel(&mut self, channel: u8) {
+        let base = Self::CHANNEL_BASE + 8 * usize::from(channel);
+        let mut regs = [0x00; 8];
+        regs.copy_from_slice(&self.ram[base..base + 8]);
+        let reg = |offset: usize| u32::from(regs[offset]);
+
+        let freq = reg(0) | (reg(2) << 8) | ((reg(4) & 0x03) << 16);
+        let phase = reg(1) | (reg(3) << 8) | (reg(5) << 16);
+        let length = (256 - (reg(4) & 0xFC)) << 16;
+        let phase = (phase + freq) % length;
+        self.ram[base + 1] = phase as u8;
+        self.ram[base + 3] = (phase >> 8) as u8;
+        self.ram[base + 5] = (phase >> 16) as u8;
+
+        let sample_addr = ((phase >> 16) + reg(6)) & 0xFF;
+        let sample = self.ram[(sample_addr >> 1) as usize];
+        let sample = if sample_addr & 0x01 == 0x01 {
+            sample >> 4
+        } else {
+            sample & 0x0F
+        };
+        let volume = reg(7) & 0x0F;
+        self.out = (f32::from(sample) - 8.0) * volume as f32;
+    }
+}
+
+impl Audio for Namco163Audio {
+    #[inline]
+    fn output(&self) -> f32 {
+        Self::output(self)
+    }
+}
+
+impl Clock for Namco163Audio {
+    fn clock(&mut self) -> usize {
+        if self.disabled {
+            return 0;
+        }
+        self.divider += 1;
+        if self.divider < Self::CLOCK_DIVIDER {
+            return 0;
+        }
+        self.divider = 0;
+
+        let channel = self.channel;
+        self.clock_channel(channel);
+        self.channel = if channel <= self.first_channel() {
+            7
+        } else {
+            channel - 1
+        };
+        1
+    }
+}
+
+impl Reset for Namco163Audio {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.channel = 7;
+        self.divider = 0;
+        self.out = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(audio: &mut Namco163Audio, addr: u8, val: u8) {
+        audio.write_addr(addr);
+        audio.write_data(val);
+    }
+
+    /// Square wave of `length` 4-bit samples at sound RAM address 0.
+    fn square_wave(audio: &mut Namco163Audio, length: u8) {
+        audio.write_addr(0x80);
+        for _ in 0..length / 4 {
+            audio.write_data(0x00);
+        }
+        for _ in 0..length / 4 {
+            audio.write_data(0xFF);
+        }
+    }
+
+    /// Count rising edges of channel output over one second, sampling every channel update.
+    fn count_cycles(audio: &mut Namco163Audio) -> usize {
+        let mut cycles = 0;
+        let mut last = 0.0;
+        for _ in 0..1_789_773 {
+            if audio.clock() == 1 {
+                let out = audio.output();
+                if out != 0.0 && last < 0.0 && out > 0.0 {
+                    cycles += 1;
+                }
+                if out != 0.0 {
+                    last = out;
+                }
+            }
+        }
+        cycles
+    }
+
+    #[test]
+    fn sound_ram_port() {
+        let mut audio = Namco163Audio::new(Namco163Volume::Medium);
+        audio.write_addr(0xFE);
+        assert_eq!(audio.write_data(0x12), 0x7E);
+        assert_eq!(audio.write_data(0x34), 0x7F);
+        assert_eq!(audio.write_data(0x56), 0x00);
+        audio.write_addr(0x7E);
+        assert_eq!(audio.read_data(), 0x12);
+        assert_eq!(audio.read_data(), 0x12, "no auto-increment");
+        audio.write_addr(0xFF);
+        assert_eq!(audio.read_data(), 0x34);
+        assert_eq!(audio.read_data(), 0x56);
+    }
+
+    #[test]
+    fn channel_frequency() {
+        // Frequency = CPU * freq / (15 * 65536 * length * channels)
+        for channels in [1u8, 8] {
+            let mut audio = Namco163Audio::new(Namco163Volume::Medium);
+            square_wave(&mut audio, 16);
+            let freq = 440 * 15 * 65536 * 16 * u64::from(channels) / 1_789_773;
+            let base = 0x78;
+            write(&mut audio, base, freq as u8);
+            write(&mut audio, base + 2, (freq >> 8) as u8);
+            write(&mut audio, base + 4, (256 - 16) as u8 | (freq >> 16) as u8);
+            write(&mut audio, base + 6, 0x00);
+            write(&mut audio, base + 7, ((channels - 1) << 4) | 0x0F);
+            let cycles = count_cycles(&mut audio);
+            assert!(
+                (435..=445).contains(&cycles),
+                "channels {channels}: {cycles}"
+            );
+        }
+    }
+
+    #[test]
+    fn volume_levels() {
+        let output = |volume| {
+            let mut audio = Namco163Audio::new(volume);
+            // Single channel with a 4 sample wave of $F at full volume
+            write(&mut audio, 0x00, 0xFF);
+            write(&mut audio, 0x7C, 0xFC);
+            write(&mut audio, 0x7F, 0x0F);
+            for _ in 0..Namco163Audio::CLOCK_DIVIDER {
+                audio.clock();
+            }
+            audio.output()
+        };
+        let low = output(Namco163Volume::Low);
+        let medium = output(Namco163Volume::Medium);
+        let high = output(Namco163Volume::High);
+        assert!(
+            0.0 < low && low < medium && medium < high,
+            "{low} {medium} {high}"
+        );
+        assert_eq!(output(Namco163Volume::from_submapper(2)), 0.0);
+    }
+}
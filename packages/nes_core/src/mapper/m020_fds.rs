@@ -0,0 +1,918 @@
+//! `Famicom Disk System` (Mapper 020)
+//!
+//! <https://www.nesdev.org/wiki/Family_Computer_Disk_System>
+//! <https://www.nesdev.org/wiki/FDS_audio>
+
+use crate::{
+    audio::Audio,
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind},
+    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    ppu::Mirroring,
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct FdsRegs {
+    irq_reload: u16,
+    irq_counter: u16,
+    irq_repeat: bool,
+    irq_enabled: bool,
+    disk_io_enabled: bool,
+    sound_io_enabled: bool,
+    // [IS1B MRTD]
+    //  |||| ||||
+    //  |||| |||+- Drive motor on
+    //  |||| ||+-- Transfer reset
+    //  |||| |+--- 0: Write, 1: Read
+    //  |||| +---- Mirroring, 0: Vertical, 1: Horizontal
+    //  |||+------ CRC control
+    //  ||+------- Unused
+    //  |+-------- Start reading/writing past the gap
+    //  +--------- Disk IRQ enable
+    motor_on: bool,
+    transfer_reset: bool,
+    read_mode: bool,
+    crc_control: bool,
+    disk_ready: bool,
+    disk_irq_enabled: bool,
+    write_data: u8,
+    read_data: u8,
+    ext_output: u8,
+}
+
+/// The disk drive position and the side currently in it.
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct FdsDrive {
+    side: Option<usize>,
+    // Side waiting to be inserted, so the BIOS sees the previous side ejected first
+    next_side: Option<usize>,
+    insert_delay: u32,
+    position: usize,
+    delay: u32,
+    end_of_head: bool,
+    scanning: bool,
+    gap_ended: bool,
+    transfer_complete: bool,
+}
+
+/// The Famicom Disk System RAM adapter, with the BIOS as PRG-ROM and the disk sides in raw drive
+/// layout as produced by [`FdsImage`](crate::fds::FdsImage).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Fds {
+    regs: FdsRegs,
+    mirroring: Mirroring,
+    timer_irq: bool,
+    disk_irq: bool,
+    drive: FdsDrive,
+    disk: Vec<Vec<u8>>,
+    audio: FdsAudio,
+}
+
+impl Fds {
+    const PRG_RAM_SIZE: usize = 32 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+    /// CPU cycles to transfer a byte at the drive's ~96.4 kHz bit rate.
+    const BYTE_CYCLES: u32 = 150;
+    /// CPU cycles for the head to return to the start of the disk.
+    const REWIND_CYCLES: u32 = 50_000;
+    /// CPU cycles a newly inserted disk is reported as missing, about a second.
+    const INSERT_CYCLES: u32 = 1_789_773;
+
+    pub fn load(cart: &mut Cart, disk: Vec<Vec<u8>>) -> Mapper {
+        cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        let fds = Self {
+            regs: FdsRegs::default(),
+            mirroring: Mirroring::Horizontal,
+            timer_irq: false,
+            disk_irq: false,
+            drive: FdsDrive {
+                side: (!disk.is_empty()).then_some(0),
+                end_of_head: true,
+                ..FdsDrive::default()
+            },
+            disk,
+            audio: FdsAudio::new(),
+        };
+        fds.into()
+    }
+
+    /// Number of disk sides.
+    #[inline]
+    #[must_use]
+    pub fn sides(&self) -> usize {
+        self.disk.len()
+    }
+
+    /// The disk side in the drive, if any.
+    #[inline]
+    #[must_use]
+    pub const fn side(&self) -> Option<usize> {
+        match self.drive.side {
+            Some(side) => Some(side),
+            None => self.drive.next_side,
+        }
+    }
+
+    /// Insert a disk side, ejecting the current one.
+    ///
+    /// # Errors
+    ///
+    /// If `side` doesn't exist, an error is returned.
+    pub fn insert_disk(&mut self, side: usize) -> Result<()> {
+        if side >= self.sides() {
+            bail!("invalid disk side: {side}, disk has {} sides", self.sides());
+        }
+        self.drive.side = None;
+        self.drive.next_side = Some(side);
+        self.drive.insert_delay = Self::INSERT_CYCLES;
+        Ok(())
+    }
+
+    /// Eject the disk from the drive.
+    pub fn eject_disk(&mut self) {
+        self.drive.side = None;
+        self.drive.next_side = None;
+        self.drive.insert_delay = 0;
+    }
+
+    /// Raw disk sides, including anything written by the game.
+    #[inline]
+    #[must_use]
+    pub(crate) fn disk(&self) -> &[Vec<u8>] {
+        &self.disk
+    }
+
+    /// Replace the raw disk sides, keeping the drive state.
+    pub(crate) fn set_disk(&mut self, disk: Vec<Vec<u8>>) {
+        self.disk = disk;
+    }
+
+    fn write_ctrl(&mut self, val: u8) {
+        self.regs.motor_on = val & 0x01 == 0x01;
+        self.regs.transfer_reset = val & 0x02 == 0x02;
+        self.regs.read_mode = val & 0x04 == 0x04;
+        self.mirroring = if val & 0x08 == 0x08 {
+            Mirroring::Horizontal
+        } else {
+            Mirroring::Vertical
+        };
+        self.regs.crc_control = val & 0x10 == 0x10;
+        self.regs.disk_ready = val & 0x40 == 0x40;
+        self.regs.disk_irq_enabled = val & 0x80 == 0x80;
+        self.disk_irq = false;
+    }
+
+    const fn peek_status(&self) -> u8 {
+        // [.E.C ..DT]
+        //   | |   ||
+        //   | |   |+- Timer IRQ
+        //   | |   +-- Byte transferred
+        //   | +------ CRC error
+        //   +-------- End of disk
+        let mut status = 0x00;
+        if self.timer_irq {
+            status |= 0x01;
+        }
+        if self.drive.transfer_complete {
+            status |= 0x02;
+        }
+        if self.drive.end_of_head {
+            status |= 0x40;
+        }
+        status
+    }
+
+    const fn peek_drive_status(&self) -> u8 {
+        // [.... .PRS]
+        //        |||
+        //        ||+- Disk missing
+        //        |+-- Disk not ready
+        //        +--- Disk write protected or missing
+        let mut status = 0x40;
+        if self.drive.side.is_none() {
+            status |= 0x07;
+        } else if !self.drive.scanning {
+            status |= 0x02;
+        }
+        status
+    }
+
+    fn clock_timer(&mut self) {
+        if !self.regs.irq_enabled {
+            return;
+        }
+        if self.regs.irq_counter == 0 {
+            self.timer_irq = true;
+            self.regs.irq_counter = self.regs.irq_reload;
+            self.regs.irq_enabled = self.regs.irq_repeat;
+        } else {
+            self.regs.irq_counter -= 1;
+        }
+    }
+
+    fn clock_drive(&mut self) {
+        if self.drive.insert_delay > 0 {
+            self.drive.insert_delay -= 1;
+            if self.drive.insert_delay == 0 {
+                self.drive.side = self.drive.next_side.take();
+            }
+        }
+        let Some(side) = self.drive.side else {
+            self.drive.end_of_head = true;
+            self.drive.scanning = false;
+            return;
+        };
+        if !self.regs.motor_on {
+            self.drive.end_of_head = true;
+            self.drive.scanning = false;
+            return;
+        }
+        if self.regs.transfer_reset && !self.drive.scanning {
+            return;
+        }
+        if self.drive.end_of_head {
+            self.drive.end_of_head = false;
+            self.drive.delay = Self::REWIND_CYCLES;
+            self.drive.position = 0;
+            self.drive.gap_ended = false;
+            return;
+        }
+        if self.drive.delay > 0 {
+            self.drive.delay -= 1;
+            return;
+        }
+
+        self.drive.scanning = true;
+        let mut irq = self.regs.disk_irq_enabled;
+        let data = &mut self.disk[side];
+        if self.regs.read_mode {
+            let val = data[self.drive.position];
+            if !self.regs.disk_ready {
+                self.drive.gap_ended = false;
+            } else if val != 0x00 && !self.drive.gap_ended {
+                // The block start mark ends the gap, but isn't transferred
+                self.drive.gap_ended = true;
+                irq = false;
+            }
+            if self.drive.gap_ended {
+                self.drive.transfer_complete = true;
+                self.regs.read_data = val;
+                self.disk_irq |= irq;
+            }
+        } else {
+            let mut val = 0x00;
+            if !self.regs.crc_control {
+                self.drive.transfer_complete = true;
+                val = self.regs.write_data;
+                self.disk_irq |= irq;
+            }
+            // CRCs aren't verified, so they're written blank
+            if !self.regs.disk_ready || self.regs.crc_control {
+                val = 0x00;
+            }
+            data[self.drive.position] = val;
+            self.drive.gap_ended = false;
+        }
+
+        self.drive.position += 1;
+        if self.drive.position >= data.len() {
+            self.regs.motor_on = false;
+            self.drive.end_of_head = true;
+        } else {
+            self.drive.delay = Self::BYTE_CYCLES;
+        }
+    }
+}
+
+impl Mapped for Fds {
+    #[inline]
+    fn irq_pending(&self) -> bool {
+        self.timer_irq || self.disk_irq
+    }
+
+    #[inline]
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    #[inline]
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+}
+
+impl MemMap for Fds {
+    // PPU $0000..=$1FFF 8K CHR-RAM
+    //
+    // CPU $4020..=$4026 Timer IRQ, disk and external connector control
+    // CPU $4030..=$4033 Disk status and data
+    // CPU $4040..=$4092 Audio
+    // CPU $6000..=$DFFF 32K PRG-RAM
+    // CPU $E000..=$FFFF 8K BIOS PRG-ROM
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        match addr {
+            0x4030 if self.regs.disk_io_enabled => {
+                let status = self.peek_status();
+                self.drive.transfer_complete = false;
+                self.timer_irq = false;
+                self.disk_irq = false;
+                MappedRead::Data(status)
+            }
+            0x4031 if self.regs.disk_io_enabled => {
+                self.drive.transfer_complete = false;
+                self.disk_irq = false;
+                MappedRead::Data(self.regs.read_data)
+            }
+            _ => self.map_peek(addr),
+        }
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(addr.into()),
+            0x4030..=0x4033 if self.regs.disk_io_enabled => MappedRead::Data(match addr {
+                0x4030 => self.peek_status(),
+                0x4031 => self.regs.read_data,
+                0x4032 => self.peek_drive_status(),
+                // Battery good
+                _ => self.regs.ext_output & 0x80,
+            }),
+            0x4040..=0x407F | 0x4090 | 0x4092 => MappedRead::Data(self.audio.peek(addr)),
+            0x6000..=0xDFFF => MappedRead::PrgRam((addr - 0x6000).into()),
+            0xE000..=0xFFFF => MappedRead::PrgRom((addr & 0x1FFF).into()),
+            _ => MappedRead::None,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => return MappedWrite::Chr(addr.into(), val),
+            0x6000..=0xDFFF => return MappedWrite::PrgRam((addr - 0x6000).into(), val),
+            0x4023 => {
+                // [.... ..SD]
+                //         |+- Enable disk I/O registers
+                //         +-- Enable sound I/O registers
+                self.regs.disk_io_enabled = val & 0x01 == 0x01;
+                self.regs.sound_io_enabled = val & 0x02 == 0x02;
+                if !self.regs.disk_io_enabled {
+                    self.regs.irq_enabled = false;
+                    self.timer_irq = false;
+                    self.disk_irq = false;
+                }
+            }
+            0x4020..=0x4026 if self.regs.disk_io_enabled => match addr {
+                0x4020 => self.regs.irq_reload = (self.regs.irq_reload & 0xFF00) | u16::from(val),
+                0x4021 => {
+                    self.regs.irq_reload = (self.regs.irq_reload & 0x00FF) | (u16::from(val) << 8);
+                }
+                0x4022 => {
+                    // [.... ..ER]
+                    //         |+- Repeat
+                    //         +-- Enable, reloading the counter
+                    self.regs.irq_repeat = val & 0x01 == 0x01;
+                    self.regs.irq_enabled = val & 0x02 == 0x02;
+                    if self.regs.irq_enabled {
+                        self.regs.irq_counter = self.regs.irq_reload;
+                    } else {
+                        self.timer_irq = false;
+                    }
+                }
+                0x4024 => {
+                    self.regs.write_data = val;
+                    self.drive.transfer_complete = false;
+                    self.disk_irq = false;
+                }
+                0x4025 => self.write_ctrl(val),
+                0x4026 => self.regs.ext_output = val,
+                _ => (),
+            },
+            0x4040..=0x408A if self.regs.sound_io_enabled => self.audio.write(addr, val),
+            _ => (),
+        }
+        MappedWrite::None
+    }
+}
+
+impl Audio for Fds {
+    #[inline]
+    fn output(&self) -> f32 {
+        self.audio.output()
+    }
+}
+
+impl Clock for Fds {
+    fn clock(&mut self) -> usize {
+        self.clock_timer();
+        self.clock_drive();
+        self.audio.clock();
+        1
+    }
+}
+
+impl Reset for Fds {
+    fn reset(&mut self, kind: ResetKind) {
+        self.timer_irq = false;
+        self.disk_irq = false;
+        if kind == ResetKind::Hard {
+            self.regs = FdsRegs::default();
+            self.drive.end_of_head = true;
+            self.drive.scanning = false;
+            self.drive.transfer_complete = false;
+            self.audio.reset(kind);
+        }
+    }
+}
+
+impl Regional for Fds {}
+
+/// A volume or modulation envelope. Both tick at a rate scaled by the shared master envelope
+/// speed.
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct FdsEnvelope {
+    speed: u8,
+    gain: u8,
+    increase: bool,
+    disabled: bool,
+    timer: u32,
+}
+
+impl FdsEnvelope {
+    const MAX_GAIN: u8 = 32;
+
+    /// [MDSS SSSS]
+    ///  |||| ||||
+    ///  ||++-++++- Speed, or gain when the envelope is disabled
+    ///  |+-------- 0: Decrease, 1: Increase
+    ///  +--------- Disable envelope
+    fn write(&mut self, val: u8, master_speed: u8) {
+        self.speed = val & 0x3F;
+        self.increase = val & 0x40 == 0x40;
+        self.disabled = val & 0x80 == 0x80;
+        if self.disabled {
+            self.gain = self.speed;
+        }
+        self.reset_timer(master_speed);
+    }
+
+    fn reset_timer(&mut self, master_speed: u8) {
+        self.timer = 8 * (u32::from(self.speed) + 1) * u32::from(master_speed);
+    }
+
+    /// Returns whether the gain was updated.
+    fn tick(&mut self, master_speed: u8) -> bool {
+        if self.disabled || master_speed == 0 {
+            return false;
+        }
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer > 0 {
+            return false;
+        }
+        self.reset_timer(master_speed);
+        if self.increase && self.gain < Self::MAX_GAIN {
+            self.gain += 1;
+        } else if !self.increase && self.gain > 0 {
+            self.gain -= 1;
+        }
+        true
+    }
+}
+
+/// FDS expansion audio. A single channel plays a 64 step, 6-bit wavetable, with its pitch bent
+/// by a second wavetable of modulation steps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct FdsAudio {
+    wave_table: Vec<u8>,
+    wave_write: bool,
+    wave_pos: u8,
+    wave_acc: u16,
+    halt_wave: bool,
+    halt_envelopes: bool,
+    freq: u16,
+    master_volume: u8,
+    master_speed: u8,
+    volume: FdsEnvelope,
+    mod_env: FdsEnvelope,
+    mod_table: Vec<u8>,
+    mod_pos: u8,
+    mod_acc: u16,
+    mod_freq: u16,
+    mod_halted: bool,
+    mod_counter: i8,
+    mod_pitch: i32,
+    out: u8,
+}
+
+impl Default for FdsAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FdsAudio {
+    const MASTER_SPEED: u8 = 0xE8;
+    // Master volume levels 1, 2/3, 1/2, 2/5, in 36ths
+    const MASTER_VOLUME: [u32; 4] = [36, 24, 17, 14];
+    // Counter adjustment for each modulation step, where `None` resets it
+    const MOD_STEPS: [Option<i8>; 8] = [
+        Some(0),
+        Some(1),
+        Some(2),
+        Some(4),
+        None,
+        Some(-4),
+        Some(-2),
+        Some(-1),
+    ];
+    // Full volume is about 2.4 times a full volume APU pulse channel
+    const OUTPUT_SCALE: f32 = 0.36 / 63.0;
+
+    pub fn new() -> Self {
+        Self {
+            wave_table: vec![0x00; 64],
+            wave_write: false,
+            wave_pos: 0,
+            wave_acc: 0,
+            halt_wave: false,
+            halt_envelopes: false,
+            freq: 0,
+            master_volume: 0,
+            master_speed: Self::MASTER_SPEED,
+            volume: FdsEnvelope {
+                speed: 0,
+                gain: 0,
+                increase: false,
+                disabled: false,
+                timer: 0,
+            },
+            mod_env: FdsEnvelope {
+                speed: 0,
+                gain: 0,
+                increase: false,
+                disabled: false,
+                timer: 0,
+            },
+            mod_table: vec![0x00; 64],
+            mod_pos: 0,
+            mod_acc: 0,
+            mod_freq: 0,
+            mod_halted: true,
+            mod_counter: 0,
+            mod_pitch: 0,
+            out: 0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> f32 {
+        Self::OUTPUT_SCALE * f32::from(self.out)
+    }
+
+    #[must_use]
+    pub fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            0x4040..=0x407F => self.wave_table[usize::from(addr & 0x3F)] | 0x40,
+            0x4090 => self.volume.gain | 0x40,
+            0x4092 => self.mod_env.gain | 0x40,
+            _ => 0x00,
+        }
+    }
+
+    pub fn write(&mut self, addr: u16, val: u8) {
+        match addr {
+            0x4040..=0x407F if self.wave_write => {
+                self.wave_table[usize::from(addr & 0x3F)] = val & 0x3F;
+            }
+            0x4080 => self.volume.write(val, self.master_speed),
+            0x4082 => {
+                self.freq = (self.freq & 0x0F00) | u16::from(val);
+                self.update_mod_pitch();
+            }
+            0x4083 => {
+                // [HE.. FFFF]
+                //  ||   ||||
+                //  ||   ++++- High 4 bits of frequency
+                //  |+-------- Halt envelopes
+                //  +--------- Halt and reset the wave
+                self.freq = (self.freq & 0x00FF) | (u16::from(val & 0x0F) << 8);
+                self.halt_wave = val & 0x80 == 0x80;
+                self.halt_envelopes = val & 0x40 == 0x40;
+                if self.halt_wave {
+                    self.wave_pos = 0;
+                    self.wave_acc = 0;
+                }
+                if self.halt_envelopes {
+                    self.volume.reset_timer(self.master_speed);
+                    self.mod_env.reset_timer(self.master_speed);
+                }
+                self.update_mod_pitch();
+            }
+            0x4084 => {
+                self.mod_env.write(val, self.master_speed);
+                self.update_mod_pitch();
+            }
+            0x4085 => {
+                self.set_mod_counter(val & 0x7F);
+                self.update_mod_pitch();
+            }
+            0x4086 => self.mod_freq = (self.mod_freq & 0x0F00) | u16::from(val),
+            0x4087 => {
+                self.mod_freq = (self.mod_freq & 0x00FF) | (u16::from(val & 0x0F) << 8);
+                self.mod_halted = val & 0x80 == 0x80;
+                if self.mod_halted {
+                    self.mod_acc = 0;
+                }
+            }
+            0x4088 if self.mod_halted => {
+                // Each write fills two steps
+                for _ in 0..2 {
+                    self.mod_table[usize::from(self.mod_pos)] = val & 0x07;
+                    self.mod_pos = (self.mod_pos + 1) & 0x3F;
+                }
+            }
+            0x4089 => {
+                // [W... ..VV]
+                //  |      ||
+                //  |      ++- Master volume
+                //  +--------- Wavetable write enable, holding the output
+                self.wave_write = val & 0x80 == 0x80;
+                self.master_volume = val & 0x03;
+            }
+            0x408A => self.master_speed = val,
+            _ => (),
+        }
+    }
+
+    /// Set the 7-bit signed modulation counter.
+    fn set_mod_counter(&mut self, val: u8) {
+        self.mod_counter = ((val << 1) as i8) >> 1;
+    }
+
+    /// Pitch adjustment from the modulation counter and gain.
+    ///
+    /// <https://www.nesdev.org/wiki/FDS_audio#Frequency_calculation>
+    fn update_mod_pitch(&mut self) {
+        let counter = i32::from(self.mod_counter);
+        let mut temp = counter * i32::from(self.mod_env.gain);
+        let remainder = temp & 0x0F;
+        temp >>= 4;
+        if remainder > 0 && temp & 0x80 == 0 {
+            temp += if counter < 0 { -1 } else { 2 };
+        }
+        if temp >= 192 {
+            temp -= 256;
+        } else if temp < -64 {
+            temp += 256;
+        }
+        temp *= i32::from(self.freq);
+        let remainder = temp & 0x3F;
+        temp >>= 6;
+        if remainder >= 32 {
+            temp += 1;
+        }
+        self.mod_pitch = temp;
+    }
+
+    fn clock_modulator(&mut self) {
+        if self.mod_halted || self.mod_freq == 0 {
+            return;
+        }
+        let (acc, overflow) = self.mod_acc.overflowing_add(self.mod_freq);
+        self.mod_acc = acc;
+        if overflow {
+            let step = self.mod_table[usize::from(self.mod_pos)];
+            let counter = Self::MOD_STEPS[usize::from(step)]
+                .map_or(0, |offset| self.mod_counter.wrapping_add(offset));
+            self.set_mod_counter(counter as u8);
+            self.mod_pos = (self.mod_pos + 1) & 0x3F;
+            self.update_mod_pitch();
+        }
+    }
+
+    fn update_output(&mut self) {
+        if self.wave_write {
+            return;
+        }
+        let gain = u32::from(self.volume.gain.min(FdsEnvelope::MAX_GAIN));
+        let level = gain * Self::MASTER_VOLUME[usize::from(self.master_volume)];
+        let sample = u32::from(self.wave_table[usize::from(self.wave_pos)]);
+        self.out = (sample * level / 1152) as u8;
+    }
+}
+
+impl Audio for FdsAudio {
+    #[inline]
+    fn output(&self) -> f32 {
+        Self::output(self)
+    }
+}
+
+impl Clock for FdsAudio {
+    fn clock(&mut self) -> usize {
+        if !self.halt_wave && !self.halt_envelopes {
+            self.volume.tick(self.master_speed);
+            if self.mod_env.tick(self.master_speed) {
+                self.update_mod_pitch();
+            }
+        }
+        self.clock_modulator();
+        self.update_output();
+        if self.halt_wave || self.wave_write {
+            return 0;
+        }
+        let pitch = i32::from(self.freq) + self.mod_pitch;
+        if pitch > 0 {
+            let (acc, overflow) = self.wave_acc.overflowing_add(pitch as u16);
+            self.wave_acc = acc;
+            if overflow {
+                self.wave_pos = (self.wave_pos + 1) & 0x3F;
+            }
+        }
+        1
+    }
+}
+
+impl Reset for FdsAudio {
+    fn reset(&mut self, _kind: ResetKind) {
+        *self = Self::new();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fds::FdsImage, mem::RamState};
+    use alloc::string::ToString;
+
+    fn disk_side() -> Vec<u8> {
+        let mut side = b"\x01*NINTENDO-HVC*".to_vec();
+        side.resize(56, 0x00);
+        side.extend_from_slice(&[0x02, 0x00]);
+        side.resize(crate::fds::SIDE_SIZE, 0x00);
+        side
+    }
+
+    fn load_fds() -> Fds {
+        let image = FdsImage::load(&disk_side()).expect("valid image");
+        let mut cart = Cart::from_fds(
+            "test".to_string(),
+            &image,
+            vec![0x00; 0x2000],
+            RamState::AllZeros,
+        )
+        .expect("valid cart");
+        match core::mem::take(&mut cart.mapper) {
+            Mapper::Fds(fds) => fds,
+            _ => panic!("expected FDS mapper"),
+        }
+    }
+
+    fn write(fds: &mut Fds, addr: u16, val: u8) {
+        let _ = fds.map_write(addr, val);
+    }
+
+    fn clock_until(fds: &mut Fds, mut done: impl FnMut(&mut Fds) -> bool) -> bool {
+        for _ in 0..2_000_000 {
+            fds.clock();
+            if done(fds) {
+                return true;
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn timer_irq() {
+        let mut fds = load_fds();
+        write(&mut fds, 0x4023, 0x01);
+        write(&mut fds, 0x4020, 0x10);
+        write(&mut fds, 0x4021, 0x00);
+        write(&mut fds, 0x4022, 0x03);
+        for _ in 0..0x10 {
+            fds.clock();
+        }
+        assert!(!fds.irq_pending());
+        fds.clock();
+        assert!(fds.irq_pending());
+        assert_eq!(
+            fds.map_read(0x4030),
+            MappedRead::Data(0x41),
+            "timer IRQ, motor off"
+        );
+        assert!(!fds.irq_pending(), "acknowledged by reading $4030");
+        for _ in 0..0x11 {
+            fds.clock();
+        }
+        assert!(fds.irq_pending(), "repeats");
+        write(&mut fds, 0x4022, 0x00);
+        assert!(!fds.irq_pending(), "acknowledged by disabling");
+    }
+
+    #[test]
+    fn disk_read() {
+        let mut fds = load_fds();
+        write(&mut fds, 0x4023, 0x01);
+        assert_eq!(
+            fds.map_peek(0x4032),
+            MappedRead::Data(0x42),
+            "inserted, not scanning"
+        );
+
+        // Motor on, read mode, start past the gap with disk IRQs
+        write(&mut fds, 0x4025, 0xC5);
+        let mut bytes = Vec::new();
+        while bytes.len() < 16 {
+            assert!(
+                clock_until(&mut fds, |fds| fds.irq_pending()),
+                "byte transferred"
+            );
+            assert_eq!(fds.map_read(0x4030), MappedRead::Data(0x02));
+            if let MappedRead::Data(val) = fds.map_read(0x4031) {
+                bytes.push(val);
+            }
+        }
+        assert_eq!(bytes, disk_side()[..16]);
+        assert_eq!(fds.map_peek(0x4032), MappedRead::Data(0x40), "scanning");
+
+        fds.eject_disk();
+        fds.clock();
+        assert_eq!(fds.map_peek(0x4032), MappedRead::Data(0x47), "ejected");
+        fds.insert_disk(0).expect("valid side");
+        assert!(fds.insert_disk(1).is_err(), "invalid side");
+        assert_eq!(fds.side(), Some(0));
+        assert!(clock_until(&mut fds, |fds| fds.map_peek(0x4032)
+            != MappedRead::Data(0x47)));
+    }
+
+    #[test]
+    fn disk_write() {
+        let mut fds = load_fds();
+        write(&mut fds, 0x4023, 0x01);
+        // Motor on, write mode, writing data past the gap
+        write(&mut fds, 0x4024, 0x00);
+        write(&mut fds, 0x4025, 0xC1);
+        let mut written = 0;
+        let done = clock_until(&mut fds, |fds| {
+            if fds.irq_pending() {
+                write(fds, 0x4024, [0x80, 0x03][written.min(1)]);
+                written += 1;
+            }
+            written == 4
+        });
+        assert!(done, "bytes written");
+        let disk = &fds.disk()[0];
+        let pos = disk.iter().position(|&b| b == 0x80).expect("start mark");
+        assert_eq!(&disk[pos..pos + 3], [0x80, 0x03, 0x03]);
+    }
+
+    #[test]
+    fn wave_frequency() {
+        let mut audio = FdsAudio::new();
+        audio.write(0x4089, 0x80);
+        for i in 0..64 {
+            audio.write(0x4040 + i, if i < 32 { 0x00 } else { 0x3F });
+        }
+        audio.write(0x4089, 0x00);
+        audio.write(0x4080, 0x80 | 0x20);
+        // Frequency = CPU * freq / (64 * 65536)
+        let freq = 440 * 64 * 65536 / 1_789_773;
+        audio.write(0x4082, freq as u8);
+        audio.write(0x4083, (freq >> 8) as u8);
+        let mut cycles = 0;
+        let mut last = 0.0;
+        for _ in 0..1_789_773 {
+            audio.clock();
+            let out = audio.output();
+            if last == 0.0 && out > 0.0 {
+                cycles += 1;
+            }
+            last = out;
+        }
+        assert!((435..=445).contains(&cycles), "{cycles}");
+    }
+
+    #[test]
+    fn mod_pitch() {
+        let mut audio = FdsAudio::new();
+        audio.write(0x4082, 0x00);
+        audio.write(0x4083, 0x01);
+        audio.write(0x4084, 0x80 | 0x10);
+        audio.write(0x4085, 0x10);
+        // Counter 16 * gain 16 / 16 = 16, scaled by frequency 256 / 64
+        assert_eq!(audio.mod_pitch, 64);
+        audio.write(0x4085, 0x70);
+        assert_eq!(audio.mod_counter, -16);
+        assert_eq!(audio.mod_pitch, -64);
+    }
+}
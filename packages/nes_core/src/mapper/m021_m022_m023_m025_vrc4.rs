@@ -0,0 +1,340 @@
+//! `VRC2`/`VRC4` (Mappers 021, 022, 023 and 025)
+//!
+//! <https://www.nesdev.org/wiki/VRC2_and_VRC4>
+
+use crate::{
+    cart::Cart,
+    common::{Clock, ResetKind, Regional, Reset},
+    mapper::{vrc_irq::VrcIrq, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::MemBanks,
+    ppu::Mirroring,
+};
+use serde::{Deserialize, Serialize};
+
+/// VRC2/VRC4 board, which determines the CPU address lines wired to the chip's register select
+/// pins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum Vrc4Revision {
+    /// VRC2a (Mapper 022), A1/A0, CHR A10 ignored
+    Vrc2a,
+    /// VRC2b (Mapper 023, Submapper 3), A0/A1
+    Vrc2b,
+    /// VRC2c (Mapper 025, Submapper 3), A1/A0
+    Vrc2c,
+    /// VRC4a (Mapper 021, Submapper 1), A1/A2
+    Vrc4a,
+    /// VRC4b (Mapper 025, Submapper 1), A1/A0
+    Vrc4b,
+    /// VRC4c (Mapper 021, Submapper 2), A6/A7
+    Vrc4c,
+    /// VRC4d (Mapper 025, Submapper 2), A3/A2
+    Vrc4d,
+    /// VRC4e (Mapper 023, Submapper 2), A2/A3
+    Vrc4e,
+    /// VRC4f (Mapper 023, Submapper 1), A0/A1
+    Vrc4f,
+    /// iNES 1.0 Mapper 021, decodes both VRC4a and VRC4c lines
+    Vrc4ac,
+    /// iNES 1.0 Mapper 023, decodes both VRC4e and VRC4f lines
+    Vrc4ef,
+    /// iNES 1.0 Mapper 025, decodes both VRC4b and VRC4d lines
+    Vrc4bd,
+}
+
+impl Vrc4Revision {
+    /// Select the board from the NES 2.0 submapper, falling back to decoding the address lines
+    /// of every VRC4 board sharing the mapper number when the submapper isn't specified.
+    ///
+    /// VRC2b and VRC2c share mappers 023 and 025 with VRC4 and can't be told apart by the header
+    /// alone. Without a submapper, boards with at most 128K PRG-ROM and no PRG-RAM or battery
+    /// are assumed to be VRC2, like every licensed VRC2 game.
+    pub fn from_cart(cart: &Cart) -> Self {
+        const VRC2_MAX_PRG_ROM: usize = 0x20000;
+
+        let vrc2 = cart.submapper_num() == 0
+            && cart.prg_rom().len() <= VRC2_MAX_PRG_ROM
+            && !cart.has_prg_ram()
+            && !cart.battery_backed();
+        match (cart.mapper_num(), cart.submapper_num()) {
+            (21, 1) => Self::Vrc4a,
+            (21, 2) => Self::Vrc4c,
+            (21, _) => Self::Vrc4ac,
+            (22, _) => Self::Vrc2a,
+            (23, 1) => Self::Vrc4f,
+            (23, 2) => Self::Vrc4e,
+            (23, 3) => Self::Vrc2b,
+            (23, _) if vrc2 => Self::Vrc2b,
+            (25, 1) => Self::Vrc4b,
+            (25, 2) => Self::Vrc4d,
+            (25, 3) => Self::Vrc2c,
+            (25, _) if vrc2 => Self::Vrc2c,
+            (25, _) => Self::Vrc4bd,
+            // Mapper 023
+            _ => Self::Vrc4ef,
+        }
+    }
+
+    #[must_use]
+    pub const fn is_vrc2(&self) -> bool {
+        matches!(self, Self::Vrc2a | Self::Vrc2b | Self::Vrc2c)
+    }
+
+    /// CPU address lines connected to register select pins A0 and A1.
+    const fn pins(&self) -> (u16, u16) {
+        const A0: u16 = 0x01;
+        const A1: u16 = 0x02;
+        const A2: u16 = 0x04;
+        const A3: u16 = 0x08;
+        const A6: u16 = 0x40;
+        const A7: u16 = 0x80;
+        match self {
+            Self::Vrc2a | Self::Vrc2c | Self::Vrc4b => (A1, A0),
+            Self::Vrc2b | Self::Vrc4f => (A0, A1),
+            Self::Vrc4a => (A1, A2),
+            Self::Vrc4c => (A6, A7),
+            Self::Vrc4d => (A3, A2),
+            Self::Vrc4e => (A2, A3),
+            Self::Vrc4ac => (A1 | A6, A2 | A7),
+            Self::Vrc4ef => (A0 | A2, A1 | A3),
+            Self::Vrc4bd => (A1 | A3, A0 | A2),
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Vrc4Regs {
+    prg: [usize; 2],
+    chr: [usize; 8],
+    swap_mode: bool,
+    prg_ram_enabled: bool,
+    // VRC2 1-bit latch at $6000-$6FFF, used by some games for EEPROM-style microwire access
+    microwire_latch: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Vrc4 {
+    regs: Vrc4Regs,
+    revision: Vrc4Revision,
+    mirroring: Mirroring,
+    irq: VrcIrq,
+    has_prg_ram: bool,
+    chr_banks: MemBanks,
+    prg_rom_banks: MemBanks,
+}
+
+impl Vrc4 {
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const PRG_WINDOW: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 1024;
+
+    pub fn load(cart: &mut Cart, revision: Vrc4Revision) -> Mapper {
+        // VRC2 boards usually have no PRG-RAM and expose the microwire latch instead
+        if !revision.is_vrc2() && !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        let mut vrc4 = Self {
+            regs: Vrc4Regs::default(),
+            revision,
+            mirroring: cart.mirroring(),
+            irq: VrcIrq::default(),
+            has_prg_ram: cart.has_prg_ram(),
+            chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_WINDOW),
+            prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
+        };
+        vrc4.update_prg_banks();
+        vrc4.into()
+    }
+
+    #[inline]
+    pub const fn revision(&self) -> Vrc4Revision {
+        self.revision
+    }
+
+    #[inline]
+    #[must_use]
+    const fn prg_ram_enabled(&self) -> bool {
+        self.has_prg_ram && (self.revision.is_vrc2() || self.regs.prg_ram_enabled)
+    }
+
+    /// Translate a CPU address to one of the 4 registers in its $1000 range.
+    fn register(&self, addr: u16) -> u16 {
+        let (a0, a1) = self.revision.pins();
+        let mut reg = addr & 0xF000;
+        if addr & a0 != 0 {
+            reg |= 0x01;
+        }
+        if addr & a1 != 0 {
+            reg |= 0x02;
+        }
+        reg
+    }
+
+    fn update_prg_banks(&mut self) {
+        let second_last = self.prg_rom_banks.last().saturating_sub(1);
+        let (bank0, bank2) = if self.regs.swap_mode {
+            (second_last, self.regs.prg[0])
+        } else {
+            (self.regs.prg[0], second_last)
+        };
+        self.prg_rom_banks.set(0, bank0);
+        self.prg_rom_banks.set(1, self.regs.prg[1]);
+        self.prg_rom_banks.set(2, bank2);
+        self.prg_rom_banks.set(3, self.prg_rom_banks.last());
+    }
+
+    fn update_chr_banks(&mut self) {
+        // VRC2a doesn't connect the lowest CHR bank bit
+        let shift = usize::from(self.revision == Vrc4Revision::Vrc2a);
+        for (slot, bank) in self.regs.chr.iter().enumerate() {
+            self.chr_banks.set(slot, bank >> shift);
+        }
+    }
+
+    fn write_chr(&mut self, reg: u16, val: u8) {
+        let slot = (((reg - 0xB000) >> 11) | ((reg >> 1) & 0x01)) as usize;
+        let bank = &mut self.regs.chr[slot];
+        if reg & 0x01 == 0x00 {
+            *bank = (*bank & 0x1F0) | usize::from(val & 0x0F);
+        } else {
+            let mask = if self.revision.is_vrc2() { 0x0F } else { 0x1F };
+            *bank = (*bank & 0x0F) | (usize::from(val & mask) << 4);
+        }
+        self.update_chr_banks();
+    }
+}
+
+impl Mapped for Vrc4 {
+    #[inline]
+    fn irq_pending(&self) -> bool {
+        self.irq.pending()
+    }
+
+    #[inline]
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    #[inline]
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+}
+
+impl MemMap for Vrc4 {
+    // PPU $0000..=$03FF 1K switchable CHR-ROM bank
+    // PPU $0400..=$07FF 1K switchable CHR-ROM bank
+    // PPU $0800..=$0BFF 1K switchable CHR-ROM bank
+    // PPU $0C00..=$0FFF 1K switchable CHR-ROM bank
+    // PPU $1000..=$13FF 1K switchable CHR-ROM bank
+    // PPU $1400..=$17FF 1K switchable CHR-ROM bank
+    // PPU $1800..=$1BFF 1K switchable CHR-ROM bank
+    // PPU $1C00..=$1FFF 1K switchable CHR-ROM bank
+    //
+    // CPU $6000..=$7FFF 8K PRG-RAM bank, or the VRC2 microwire latch at $6000..=$6FFF
+    // CPU $8000..=$9FFF 8K switchable PRG-ROM bank or fixed to the second-last bank
+    // CPU $A000..=$BFFF 8K switchable PRG-ROM bank
+    // CPU $C000..=$DFFF 8K PRG-ROM bank fixed to the second-last bank or switchable
+    // CPU $E000..=$FFFF 8K PRG-ROM bank, fixed to the last bank
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                MappedRead::PrgRam(usize::from(addr & 0x1FFF))
+            }
+            0x6000..=0x6FFF if self.revision.is_vrc2() => {
+                // Upper bits are open bus, which is the high byte of the address for absolute
+                // addressing
+                MappedRead::Data(((addr >> 8) as u8 & 0xFE) | self.regs.microwire_latch)
+            }
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::None,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                return MappedWrite::PrgRam(usize::from(addr & 0x1FFF), val);
+            }
+            0x6000..=0x6FFF if self.revision.is_vrc2() => {
+                self.regs.microwire_latch = val & 0x01;
+                return MappedWrite::None;
+            }
+            0x8000..=0xFFFF => (),
+            _ => return MappedWrite::None,
+        }
+
+        let vrc2 = self.revision.is_vrc2();
+        match self.register(addr) {
+            0x8000..=0x8003 => {
+                // [...P PPPP]
+                //     | ||||
+                //     +-++++- Select 8 KB PRG-ROM bank at $8000-$9FFF or $C000-$DFFF
+                self.regs.prg[0] = usize::from(val & 0x1F);
+                self.update_prg_banks();
+            }
+            0x9000..=0x9003 if vrc2 => {
+                self.mirroring = if val & 0x01 == 0x01 {
+                    Mirroring::Horizontal
+                } else {
+                    Mirroring::Vertical
+                };
+            }
+            0x9000 | 0x9001 => {
+                self.mirroring = match val & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenA,
+                    3 => Mirroring::SingleScreenB,
+                    _ => unreachable!("impossible mirroring mode"),
+                };
+            }
+            0x9002 | 0x9003 => {
+                // [.... ..MR]
+                //         ||
+                //         |+- PRG-RAM enable
+                //         +-- PRG-ROM swap mode
+                self.regs.prg_ram_enabled = val & 0x01 == 0x01;
+                self.regs.swap_mode = val & 0x02 == 0x02;
+                self.update_prg_banks();
+            }
+            0xA000..=0xA003 => {
+                // [...P PPPP]
+                //     | ||||
+                //     +-++++- Select 8 KB PRG-ROM bank at $A000-$BFFF
+                self.regs.prg[1] = usize::from(val & 0x1F);
+                self.update_prg_banks();
+            }
+            reg @ 0xB000..=0xEFFF => self.write_chr(reg, val),
+            _ if vrc2 => (),
+            0xF000 => self.irq.write_reload_low(val),
+            0xF001 => self.irq.write_reload_high(val),
+            0xF002 => self.irq.write_control(val),
+            0xF003 => self.irq.acknowledge(),
+            _ => (),
+        }
+        MappedWrite::None
+    }
+}
+
+impl Clock for Vrc4 {
+    fn clock(&mut self) -> usize {
+        if self.revision.is_vrc2() {
+            0
+        } else {
+            self.irq.clock()
+        }
+    }
+}
+
+impl Reset for Vrc4 {
+    fn reset(&mut self, kind: ResetKind) {
+        self.irq.reset(kind);
+    }
+}
+
+impl Regional for Vrc4 {}
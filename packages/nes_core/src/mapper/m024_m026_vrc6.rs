@@ -377,7 +377,7 @@ impl Default for Vrc6Audio {
 }
 
 impl Vrc6Audio {
-    const fn new() -> Self {
+    pub const fn new() -> Self {
         Self {
             pulse1: Vrc6Pulse::new(),
             pulse2: Vrc6Pulse::new(),
@@ -390,12 +390,12 @@ impl Vrc6Audio {
 
     #[inline]
     #[must_use]
-    fn output(&self) -> f32 {
+    pub fn output(&self) -> f32 {
         let pulse_scale = PULSE_TABLE[PULSE_TABLE.len() - 1] / 15.0;
         pulse_scale * self.out
     }
 
-    fn write_register(&mut self, addr: u16, val: u8) {
+    pub fn write_register(&mut self, addr: u16, val: u8) {
         // Only A0, A1 and A12-15 are used for registers, remaining addresses are mirrored.
         match addr & 0xF003 {
             0x9000..=0x9002 => self.pulse1.write_register(addr, val),
@@ -15,6 +15,8 @@ use serde::{Deserialize, Serialize};
 #[must_use]
 pub struct Gxrom {
     mirroring: Mirroring,
+    // Only present for trainers or NES 2.0 headers asking for PRG-RAM
+    has_prg_ram: bool,
     chr_banks: MemBanks,
     prg_rom_banks: MemBanks,
 }
@@ -29,6 +31,7 @@ impl Gxrom {
     pub fn load(cart: &mut Cart) -> Mapper {
         let gxrom = Self {
             mirroring: cart.mirroring(),
+            has_prg_ram: cart.has_prg_ram(),
             chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_rom.len(), Self::CHR_WINDOW),
             prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW),
         };
@@ -38,21 +41,29 @@ impl Gxrom {
 
 impl MemMap for Gxrom {
     // PPU $0000..=$1FFF 8K CHR-ROM Bank Switchable
+    // CPU $6000..=$7FFF 8K PRG-RAM, if present
     // CPU $8000..=$FFFF 32K PRG-ROM Bank Switchable
 
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
             0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedRead::PrgRam((addr & 0x1FFF).into()),
             0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
             _ => MappedRead::None,
         }
     }
 
     fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
-        if matches!(addr, 0x8000..=0xFFFF) {
-            self.chr_banks.set(0, (val & Self::CHR_BANK_MASK).into());
-            self.prg_rom_banks
-                .set(0, ((val & Self::PRG_BANK_MASK) >> 4).into());
+        match addr {
+            0x6000..=0x7FFF if self.has_prg_ram => {
+                return MappedWrite::PrgRam((addr & 0x1FFF).into(), val);
+            }
+            0x8000..=0xFFFF => {
+                self.chr_banks.set(0, (val & Self::CHR_BANK_MASK).into());
+                self.prg_rom_banks
+                    .set(0, ((val & Self::PRG_BANK_MASK) >> 4).into());
+            }
+            _ => (),
         }
         MappedWrite::None
     }
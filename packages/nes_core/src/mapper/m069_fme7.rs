@@ -0,0 +1,615 @@
+//! `Sunsoft FME-7` and `Sunsoft 5B` (Mapper 069)
+//!
+//! <https://www.nesdev.org/wiki/Sunsoft_FME-7>
+//! <https://www.nesdev.org/wiki/Sunsoft_5B_audio>
+
+use crate::{
+    audio::Audio,
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind},
+    mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::MemBanks,
+    ppu::Mirroring,
+};
+use lazy_static::lazy_static;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Fme7Regs {
+    command: u8,
+    // [ERbB BBBB]
+    //  ||++-++++- PRG bank at $6000
+    //  |+-------- 0: PRG-ROM, 1: PRG-RAM
+    //  +--------- PRG-RAM enable
+    prg_6000: u8,
+    irq_enabled: bool,
+    irq_counter_enabled: bool,
+    irq_counter: u16,
+}
+
+/// Sunsoft FME-7 and its 5B variant, which adds expansion audio. The audio registers are only
+/// written by 5B games, so both boards share the same mapper.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Fme7 {
+    regs: Fme7Regs,
+    mirroring: Mirroring,
+    irq_pending: bool,
+    audio: Sunsoft5bAudio,
+    chr_banks: MemBanks,
+    prg_ram_banks: MemBanks,
+    prg_rom_6000_banks: MemBanks,
+    prg_rom_banks: MemBanks,
+}
+
+impl Fme7 {
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+    const PRG_WINDOW: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 1024;
+
+    pub fn load(cart: &mut Cart) -> Mapper {
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        if !cart.has_chr() {
+            cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        }
+        let mut fme7 = Self {
+            regs: Fme7Regs::default(),
+            mirroring: cart.mirroring(),
+            irq_pending: false,
+            audio: Sunsoft5bAudio::new(),
+            chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_len(), Self::CHR_WINDOW),
+            prg_ram_banks: MemBanks::new(0x6000, 0x7FFF, cart.prg_ram.len(), Self::PRG_WINDOW),
+            prg_rom_6000_banks: MemBanks::new(0x6000, 0x7FFF, cart.prg_rom.len(), Self::PRG_WINDOW),
+            prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
+        };
+        let last_bank = fme7.prg_rom_banks.last();
+        fme7.prg_rom_banks.set(3, last_bank);
+        fme7.into()
+    }
+
+    #[inline]
+    #[must_use]
+    const fn prg_ram_selected(&self) -> bool {
+        self.regs.prg_6000 & 0x40 == 0x40
+    }
+
+    #[inline]
+    #[must_use]
+    const fn prg_ram_enabled(&self) -> bool {
+        self.regs.prg_6000 & 0xC0 == 0xC0
+    }
+
+    fn write_parameter(&mut self, val: u8) {
+        match self.regs.command {
+            0x00..=0x07 => self
+                .chr_banks
+                .set(usize::from(self.regs.command), usize::from(val)),
+            0x08 => {
+                self.regs.prg_6000 = val;
+                let bank = usize::from(val & 0x3F);
+                self.prg_ram_banks.set(0, bank);
+                self.prg_rom_6000_banks.set(0, bank);
+            }
+            0x09..=0x0B => self.prg_rom_banks.set(
+                usize::from(self.regs.command - 0x09),
+                usize::from(val & 0x3F),
+            ),
+            0x0C => {
+                self.mirroring = match val & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenA,
+                    3 => Mirroring::SingleScreenB,
+                    _ => unreachable!("impossible mirroring mode"),
+                };
+            }
+            0x0D => {
+                // [C... ...T]
+                //  |       |
+                //  |       +- IRQ enable
+                //  +--------- IRQ counter enable
+                self.regs.irq_enabled = val & 0x01 == 0x01;
+                self.regs.irq_counter_enabled = val & 0x80 == 0x80;
+                self.irq_pending = false;
+            }
+            0x0E => self.regs.irq_counter = (self.regs.irq_counter & 0xFF00) | u16::from(val),
+            0x0F => {
+                self.regs.irq_counter = (self.regs.irq_counter & 0x00FF) | (u16::from(val) << 8);
+            }
+            _ => unreachable!("impossible Fme7 command: {}", self.regs.command),
+        }
+    }
+}
+
+impl Mapped for Fme7 {
+    #[inline]
+    fn irq_pending(&self) -> bool {
+        self.irq_pending
+    }
+
+    #[inline]
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    #[inline]
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+}
+
+impl MemMap for Fme7 {
+    // PPU $0000..=$03FF 1K switchable CHR bank
+    // PPU $0400..=$07FF 1K switchable CHR bank
+    // PPU $0800..=$0BFF 1K switchable CHR bank
+    // PPU $0C00..=$0FFF 1K switchable CHR bank
+    // PPU $1000..=$13FF 1K switchable CHR bank
+    // PPU $1400..=$17FF 1K switchable CHR bank
+    // PPU $1800..=$1BFF 1K switchable CHR bank
+    // PPU $1C00..=$1FFF 1K switchable CHR bank
+    //
+    // CPU $6000..=$7FFF 8K switchable PRG-ROM or PRG-RAM bank
+    // CPU $8000..=$9FFF 8K switchable PRG-ROM bank
+    // CPU $A000..=$BFFF 8K switchable PRG-ROM bank
+    // CPU $C000..=$DFFF 8K switchable PRG-ROM bank
+    // CPU $E000..=$FFFF 8K PRG-ROM bank, fixed to the last bank
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                MappedRead::PrgRam(self.prg_ram_banks.translate(addr))
+            }
+            0x6000..=0x7FFF if !self.prg_ram_selected() => {
+                MappedRead::PrgRom(self.prg_rom_6000_banks.translate(addr))
+            }
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::None,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => return MappedWrite::Chr(self.chr_banks.translate(addr), val),
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                return MappedWrite::PrgRam(self.prg_ram_banks.translate(addr), val);
+            }
+            0x8000..=0x9FFF => self.regs.command = val & 0x0F,
+            0xA000..=0xBFFF => self.write_parameter(val),
+            0xC000..=0xDFFF => self.audio.write_addr(val),
+            0xE000..=0xFFFF => self.audio.write_data(val),
+            _ => (),
+        }
+        MappedWrite::None
+    }
+}
+
+impl Audio for Fme7 {
+    #[inline]
+    fn output(&self) -> f32 {
+        self.audio.output()
+    }
+}
+
+impl Clock for Fme7 {
+    fn clock(&mut self) -> usize {
+        if self.regs.irq_counter_enabled {
+            self.regs.irq_counter = self.regs.irq_counter.wrapping_sub(1);
+            if self.regs.irq_counter == 0xFFFF && self.regs.irq_enabled {
+                self.irq_pending = true;
+            }
+        }
+        self.audio.clock();
+        1
+    }
+}
+
+impl Reset for Fme7 {
+    fn reset(&mut self, kind: ResetKind) {
+        self.irq_pending = false;
+        self.audio.reset(kind);
+    }
+}
+
+impl Regional for Fme7 {}
+
+lazy_static! {
+    // Channel amplitude for each of the 32 levels, in 1.5 dB steps
+    static ref VOLUME_TABLE: [f32; 32] = {
+        let mut volume_table = [0.0; 32];
+        for (i, val) in volume_table.iter_mut().enumerate().skip(1) {
+            *val = 10.0.powf((i as f32 - 31.0) * 1.5 / 20.0);
+        }
+        volume_table
+    };
+}
+
+/// Sunsoft 5B expansion audio, a YM2149F (AY-3-8910 variant) with 3 square wave channels,
+/// a shared noise generator and a shared envelope.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Sunsoft5bAudio {
+    addr: u8,
+    write_enabled: bool,
+    divider: u8,
+    tones: [Sunsoft5bTone; 3],
+    noise: Sunsoft5bNoise,
+    envelope: Sunsoft5bEnvelope,
+    out: f32,
+}
+
+impl Default for Sunsoft5bAudio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Sunsoft5bAudio {
+    /// CPU cycles per tick of the tone, noise and envelope counters.
+    const CLOCK_DIVIDER: u8 = 16;
+    // Output level of a single channel at full volume, relative to the APU
+    const OUTPUT_SCALE: f32 = 0.15;
+
+    pub const fn new() -> Self {
+        Self {
+            addr: 0x00,
+            write_enabled: true,
+            divider: 0,
+            tones: [Sunsoft5bTone::new(); 3],
+            noise: Sunsoft5bNoise::new(),
+            envelope: Sunsoft5bEnvelope::new(),
+            out: 0.0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> f32 {
+        Self::OUTPUT_SCALE * self.out
+    }
+
+    /// Select the register written by `write_data`. Writes with any of the upper 4 bits set
+    /// disable register writes until the next select.
+    #[inline]
+    pub fn write_addr(&mut self, val: u8) {
+        self.addr = val & 0x0F;
+        self.write_enabled = val & 0xF0 == 0x00;
+    }
+
+    pub fn write_data(&mut self, val: u8) {
+        if !self.write_enabled {
+            return;
+        }
+        match self.addr {
+            0x00..=0x05 => {
+                let tone = &mut self.tones[usize::from(self.addr >> 1)];
+                if self.addr & 0x01 == 0x00 {
+                    tone.period = (tone.period & 0x0F00) | u16::from(val);
+                } else {
+                    tone.period = (tone.period & 0x00FF) | (u16::from(val & 0x0F) << 8);
+                }
+            }
+            0x06 => self.noise.period = val & 0x1F,
+            0x07 => {
+                // [..NN NTTT]
+                //    || ||||
+                //    || |+++- Tone disable for channels A, B and C
+                //    ++-+---- Noise disable for channels A, B and C
+                for (i, tone) in self.tones.iter_mut().enumerate() {
+                    tone.tone_disabled = val & (0x01 << i) != 0;
+                    tone.noise_disabled = val & (0x08 << i) != 0;
+                }
+            }
+            0x08..=0x0A => {
+                // [...E VVVV]
+                //     | ||||
+                //     | ++++- Volume, 3 dB steps
+                //     +------ Use the envelope instead of volume
+                let tone = &mut self.tones[usize::from(self.addr - 0x08)];
+                tone.volume = val & 0x0F;
+                tone.use_envelope = val & 0x10 == 0x10;
+            }
+            0x0B => self.envelope.period = (self.envelope.period & 0xFF00) | u16::from(val),
+            0x0C => {
+                self.envelope.period = (self.envelope.period & 0x00FF) | (u16::from(val) << 8);
+            }
+            0x0D => self.envelope.write_shape(val),
+            _ => (),
+        }
+    }
+}
+
+impl Audio for Sunsoft5bAudio {
+    #[inline]
+    fn output(&self) -> f32 {
+        Self::output(self)
+    }
+}
+
+impl Clock for Sunsoft5bAudio {
+    fn clock(&mut self) -> usize {
+        self.divider += 1;
+        if self.divider < Self::CLOCK_DIVIDER {
+            return 0;
+        }
+        self.divider = 0;
+
+        for tone in &mut self.tones {
+            tone.clock();
+        }
+        self.noise.clock();
+        self.envelope.clock();
+
+        let noise = self.noise.output();
+        let envelope = self.envelope.level();
+        self.out = self
+            .tones
+            .iter()
+            .map(|tone| tone.output(noise, envelope))
+            .sum();
+        1
+    }
+}
+
+impl Reset for Sunsoft5bAudio {
+    fn reset(&mut self, _kind: ResetKind) {
+        *self = Self::new();
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Sunsoft5bTone {
+    period: u16,
+    counter: u16,
+    step: bool,
+    tone_disabled: bool,
+    noise_disabled: bool,
+    volume: u8,
+    use_envelope: bool,
+}
+
+impl Sunsoft5bTone {
+    const fn new() -> Self {
+        Self {
+            period: 0,
+            counter: 0,
+            step: false,
+            tone_disabled: false,
+            noise_disabled: false,
+            volume: 0,
+            use_envelope: false,
+        }
+    }
+
+    fn output(&self, noise: bool, envelope: u8) -> f32 {
+        if (self.step || self.tone_disabled) && (noise || self.noise_disabled) {
+            let level = if self.use_envelope {
+                envelope
+            } else if self.volume == 0 {
+                0
+            } else {
+                // Volume has half the resolution of the envelope
+                (self.volume << 1) | 0x01
+            };
+            VOLUME_TABLE[usize::from(level)]
+        } else {
+            0.0
+        }
+    }
+}
+
+impl Clock for Sunsoft5bTone {
+    fn clock(&mut self) -> usize {
+        self.counter += 1;
+        if self.counter >= self.period.max(1) {
+            self.counter = 0;
+            self.step = !self.step;
+            return 1;
+        }
+        0
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Sunsoft5bNoise {
+    period: u8,
+    counter: u8,
+    shift: u32,
+}
+
+impl Sunsoft5bNoise {
+    const fn new() -> Self {
+        Self {
+            period: 0,
+            counter: 0,
+            shift: 0x01,
+        }
+    }
+
+    #[inline]
+    const fn output(&self) -> bool {
+        self.shift & 0x01 == 0x01
+    }
+}
+
+impl Clock for Sunsoft5bNoise {
+    fn clock(&mut self) -> usize {
+        // Noise is clocked at half the rate of the tone counters
+        self.counter += 1;
+        if self.counter >= self.period.max(1) << 1 {
+            self.counter = 0;
+            // 17-bit LFSR with taps at bits 0 and 3
+            let feedback = (self.shift ^ (self.shift >> 3)) & 0x01;
+            self.shift = (self.shift >> 1) | (feedback << 16);
+            return 1;
+        }
+        0
+    }
+}
+
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Sunsoft5bEnvelope {
+    period: u16,
+    counter: u16,
+    step: u8,
+    attack: bool,
+    holding: bool,
+    shape: u8,
+}
+
+impl Sunsoft5bEnvelope {
+    const STEPS: u8 = 32;
+
+    const fn new() -> Self {
+        Self {
+            period: 0,
+            counter: 0,
+            step: 0,
+            attack: false,
+            holding: false,
+            shape: 0,
+        }
+    }
+
+    fn write_shape(&mut self, val: u8) {
+        // [.... CAaH]
+        //       ||||
+        //       |||+- Hold at the end of the first cycle
+        //       ||+-- Alternate direction each cycle
+        //       |+--- Attack, start ramping up
+        //       +---- Continue after the first cycle
+        self.shape = val & 0x0F;
+        self.attack = val & 0x04 == 0x04;
+        self.holding = false;
+        self.step = 0;
+        self.counter = 0;
+    }
+
+    #[inline]
+    const fn level(&self) -> u8 {
+        if self.attack {
+            self.step
+        } else {
+            Self::STEPS - 1 - self.step
+        }
+    }
+}
+
+impl Clock for Sunsoft5bEnvelope {
+    fn clock(&mut self) -> usize {
+        if self.holding {
+            return 0;
+        }
+        self.counter += 1;
+        if self.counter < self.period.max(1) {
+            return 0;
+        }
+        self.counter = 0;
+
+        self.step += 1;
+        if self.step == Self::STEPS {
+            let cont = self.shape & 0x08 == 0x08;
+            let alternate = self.shape & 0x02 == 0x02;
+            let hold = self.shape & 0x01 == 0x01;
+            if !cont {
+                // Ends silent regardless of direction
+                self.attack = false;
+                self.holding = true;
+                self.step = Self::STEPS - 1;
+            } else {
+                if alternate {
+                    self.attack = !self.attack;
+                }
+                if hold {
+                    self.holding = true;
+                    self.step = Self::STEPS - 1;
+                } else {
+                    self.step = 0;
+                }
+            }
+        }
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(audio: &mut Sunsoft5bAudio, addr: u8, val: u8) {
+        audio.write_addr(addr);
+        audio.write_data(val);
+    }
+
+    #[test]
+    fn tone_frequency() {
+        let mut audio = Sunsoft5bAudio::new();
+        // Channel A tone only at full volume, 440Hz: period = 1789773 / (32 * 440)
+        write(&mut audio, 0x00, 127);
+        write(&mut audio, 0x07, 0x3E);
+        write(&mut audio, 0x08, 0x0F);
+        let mut rising_edges = 0;
+        let mut last = 0.0;
+        let mut peak = 0.0f32;
+        for _ in 0..1_789_773 {
+            audio.clock();
+            let out = audio.output();
+            if last == 0.0 && out > 0.0 {
+                rising_edges += 1;
+            }
+            last = out;
+            peak = peak.max(out);
+        }
+        assert!((438..=442).contains(&rising_edges), "edges: {rising_edges}");
+        assert!(
+            (peak - Sunsoft5bAudio::OUTPUT_SCALE).abs() < 0.001,
+            "peak: {peak}"
+        );
+    }
+
+    #[test]
+    fn register_write_disable() {
+        let mut audio = Sunsoft5bAudio::new();
+        write(&mut audio, 0x08, 0x0A);
+        write(&mut audio, 0x18, 0x0F);
+        assert_eq!(audio.tones[0].volume, 0x0A);
+    }
+
+    #[test]
+    fn envelope_shapes() {
+        // (shape, level after the first cycle)
+        for (shape, level) in [
+            (0x00, 0),
+            (0x04, 0),
+            (0x09, 0),
+            (0x0B, 31),
+            (0x0D, 31),
+            (0x0F, 0),
+        ] {
+            let mut envelope = Sunsoft5bEnvelope::new();
+            envelope.period = 1;
+            envelope.write_shape(shape);
+            for _ in 0..64 {
+                envelope.clock();
+            }
+            assert_eq!(envelope.level(), level, "shape: {shape:#04X}");
+        }
+
+        // Sawtooth repeats
+        let mut envelope = Sunsoft5bEnvelope::new();
+        envelope.period = 1;
+        envelope.write_shape(0x0C);
+        for _ in 0..40 {
+            envelope.clock();
+        }
+        assert_eq!(envelope.level(), 8);
+    }
+}
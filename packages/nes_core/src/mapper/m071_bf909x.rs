@@ -23,6 +23,8 @@ pub enum Bf909Revision {
 pub struct Bf909x {
     variant: Bf909Revision,
     mirroring: Mirroring,
+    // Only present for trainers or NES 2.0 headers asking for PRG-RAM
+    has_prg_ram: bool,
     prg_rom_banks: MemBanks,
 }
 
@@ -43,6 +45,7 @@ impl Bf909x {
                 Bf909Revision::Bf909x
             },
             mirroring: cart.mirroring(),
+            has_prg_ram: cart.has_prg_ram(),
             prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_ROM_WINDOW),
         };
         bf909x.prg_rom_banks.set(1, bf909x.prg_rom_banks.last());
@@ -64,12 +67,14 @@ impl Mapped for Bf909x {
 
 impl MemMap for Bf909x {
     // PPU $0000..=$1FFF 8K Fixed CHR-ROM Banks
+    // CPU $6000..=$7FFF 8K PRG-RAM, if present
     // CPU $8000..=$BFFF 16K PRG-ROM Bank Switchable
     // CPU $C000..=$FFFF 16K PRG-ROM Fixed to Last Bank
 
     fn map_peek(&self, addr: u16) -> MappedRead {
         match addr {
             0x0000..=0x1FFF => MappedRead::Chr(addr.into()),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedRead::PrgRam((addr & 0x1FFF).into()),
             0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
             _ => MappedRead::None,
         }
@@ -82,6 +87,7 @@ impl MemMap for Bf909x {
         }
         match addr {
             0x0000..=0x1FFF => MappedWrite::Chr(addr.into(), val),
+            0x6000..=0x7FFF if self.has_prg_ram => MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
             0x8000..=0xFFFF => {
                 if addr >= 0xC000 || self.variant != Bf909Revision::Bf9097 {
                     self.prg_rom_banks.set(0, val.into());
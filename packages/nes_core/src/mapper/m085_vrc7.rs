@@ -0,0 +1,827 @@
+//! `VRC7` (Mapper 085)
+//!
+//! <https://www.nesdev.org/wiki/VRC7>
+//! <https://www.nesdev.org/wiki/VRC7_audio>
+
+use crate::{
+    audio::Audio,
+    cart::Cart,
+    common::{Clock, Regional, Reset, ResetKind},
+    mapper::{vrc_irq::VrcIrq, Mapped, MappedRead, MappedWrite, Mapper, MemMap},
+    mem::MemBanks,
+    ppu::Mirroring,
+};
+use core::f32::consts::PI;
+use num_traits::Float;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[must_use]
+pub enum Vrc7Revision {
+    /// VRC7a (Lagrange Point), registers selected by A4
+    A,
+    /// VRC7b (Tiny Toon Adventures 2), registers selected by A3
+    B,
+    /// iNES 1.0 header, decodes both A3 and A4
+    Unknown,
+}
+
+impl Vrc7Revision {
+    pub const fn from_submapper(submapper_num: u8) -> Self {
+        match submapper_num {
+            1 => Self::B,
+            2 => Self::A,
+            _ => Self::Unknown,
+        }
+    }
+
+    const fn select_mask(self) -> u16 {
+        match self {
+            Self::A => 0x10,
+            Self::B => 0x08,
+            Self::Unknown => 0x18,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Vrc7Regs {
+    control: u8,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Vrc7 {
+    regs: Vrc7Regs,
+    revision: Vrc7Revision,
+    mirroring: Mirroring,
+    irq: VrcIrq,
+    audio: Vrc7Audio,
+    chr_banks: MemBanks,
+    prg_rom_banks: MemBanks,
+}
+
+impl Vrc7 {
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+    const PRG_WINDOW: usize = 8 * 1024;
+    const CHR_WINDOW: usize = 1024;
+
+    pub fn load(cart: &mut Cart, revision: Vrc7Revision) -> Mapper {
+        if !cart.has_prg_ram() {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        if !cart.has_chr() {
+            cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        }
+        let mut vrc7 = Self {
+            regs: Vrc7Regs::default(),
+            revision,
+            mirroring: cart.mirroring(),
+            irq: VrcIrq::default(),
+            audio: Vrc7Audio::new(),
+            chr_banks: MemBanks::new(0x0000, 0x1FFF, cart.chr_len(), Self::CHR_WINDOW),
+            prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, cart.prg_rom.len(), Self::PRG_WINDOW),
+        };
+        let last_bank = vrc7.prg_rom_banks.last();
+        vrc7.prg_rom_banks.set(3, last_bank);
+        vrc7.into()
+    }
+
+    #[inline]
+    #[must_use]
+    const fn prg_ram_enabled(&self) -> bool {
+        self.regs.control & 0x80 == 0x80
+    }
+
+    /// Translate a CPU address to one of the 2 registers in its $1000 range. The audio registers
+    /// at $9010 and $9030 decode A4 and A5 on every revision, so they're matched first.
+    const fn register(&self, addr: u16) -> u16 {
+        let audio = addr & 0xF030;
+        if audio == 0x9010 || audio == 0x9030 {
+            audio
+        } else if addr & self.revision.select_mask() != 0 {
+            (addr & 0xF000) | 0x10
+        } else {
+            addr & 0xF000
+        }
+    }
+}
+
+impl Mapped for Vrc7 {
+    #[inline]
+    fn irq_pending(&self) -> bool {
+        self.irq.pending()
+    }
+
+    #[inline]
+    fn mirroring(&self) -> Mirroring {
+        self.mirroring
+    }
+
+    #[inline]
+    fn set_mirroring(&mut self, mirroring: Mirroring) {
+        self.mirroring = mirroring;
+    }
+}
+
+impl MemMap for Vrc7 {
+    // PPU $0000..=$03FF 1K switchable CHR bank
+    // PPU $0400..=$07FF 1K switchable CHR bank
+    // PPU $0800..=$0BFF 1K switchable CHR bank
+    // PPU $0C00..=$0FFF 1K switchable CHR bank
+    // PPU $1000..=$13FF 1K switchable CHR bank
+    // PPU $1400..=$17FF 1K switchable CHR bank
+    // PPU $1800..=$1BFF 1K switchable CHR bank
+    // PPU $1C00..=$1FFF 1K switchable CHR bank
+    //
+    // CPU $6000..=$7FFF 8K PRG-RAM bank
+    // CPU $8000..=$9FFF 8K switchable PRG-ROM bank
+    // CPU $A000..=$BFFF 8K switchable PRG-ROM bank
+    // CPU $C000..=$DFFF 8K switchable PRG-ROM bank
+    // CPU $E000..=$FFFF 8K PRG-ROM bank, fixed to the last bank
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(self.chr_banks.translate(addr)),
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                MappedRead::PrgRam(usize::from(addr & 0x1FFF))
+            }
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => MappedRead::None,
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => return MappedWrite::Chr(self.chr_banks.translate(addr), val),
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                return MappedWrite::PrgRam(usize::from(addr & 0x1FFF), val);
+            }
+            0x8000..=0xFFFF => (),
+            _ => return MappedWrite::None,
+        }
+
+        match self.register(addr) {
+            0x8000 => self.prg_rom_banks.set(0, usize::from(val & 0x3F)),
+            0x8010 => self.prg_rom_banks.set(1, usize::from(val & 0x3F)),
+            0x9000 => self.prg_rom_banks.set(2, usize::from(val & 0x3F)),
+            0x9010 => self.audio.write_addr(val),
+            0x9030 => self.audio.write_data(val),
+            reg @ 0xA000..=0xD010 => {
+                let slot = usize::from(((reg - 0xA000) >> 11) | ((reg >> 4) & 0x01));
+                self.chr_banks.set(slot, usize::from(val));
+            }
+            0xE000 => {
+                // [RS.. ..MM]
+                //  ||     ||
+                //  ||     ++- Mirroring
+                //  |+-------- Silence expansion sound
+                //  +--------- PRG-RAM enable
+                self.regs.control = val;
+                self.mirroring = match val & 0x03 {
+                    0 => Mirroring::Vertical,
+                    1 => Mirroring::Horizontal,
+                    2 => Mirroring::SingleScreenA,
+                    3 => Mirroring::SingleScreenB,
+                    _ => unreachable!("impossible mirroring mode"),
+                };
+                self.audio.set_silenced(val & 0x40 == 0x40);
+            }
+            0xE010 => self.irq.write_reload(val),
+            0xF000 => self.irq.write_control(val),
+            0xF010 => self.irq.acknowledge(),
+            _ => (),
+        }
+        MappedWrite::None
+    }
+}
+
+impl Audio for Vrc7 {
+    #[inline]
+    fn output(&self) -> f32 {
+        self.audio.output()
+    }
+}
+
+impl Clock for Vrc7 {
+    fn clock(&mut self) -> usize {
+        self.irq.clock();
+        self.audio.clock();
+        1
+    }
+}
+
+impl Reset for Vrc7 {
+    fn reset(&mut self, kind: ResetKind) {
+        self.irq.reset(kind);
+        self.audio.reset(kind);
+    }
+}
+
+impl Regional for Vrc7 {}
+
+/// Instrument patch for one operator.
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+struct OperatorPatch {
+    tremolo: bool,
+    vibrato: bool,
+    // Sustained envelope, holds at the sustain level while keyed on
+    sustained: bool,
+    key_scale_rate: bool,
+    multiplier: u8,
+    key_scale_level: u8,
+    // Half-wave rectified sine
+    rectify: bool,
+    attack: u8,
+    decay: u8,
+    sustain_level: u8,
+    release: u8,
+}
+
+/// Instrument patch, decoded from the 8 byte YM2413 patch format.
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+struct Patch {
+    modulator: OperatorPatch,
+    carrier: OperatorPatch,
+    // Modulator total level
+    total_level: u8,
+    feedback: u8,
+}
+
+impl Patch {
+    fn new(data: &[u8; 8]) -> Self {
+        let operator = |flags: u8, ksl: u8, rectify: bool, rates: u8, levels: u8| OperatorPatch {
+            tremolo: flags & 0x80 == 0x80,
+            vibrato: flags & 0x40 == 0x40,
+            sustained: flags & 0x20 == 0x20,
+            key_scale_rate: flags & 0x10 == 0x10,
+            multiplier: flags & 0x0F,
+            key_scale_level: ksl >> 6,
+            rectify,
+            attack: rates >> 4,
+            decay: rates & 0x0F,
+            sustain_level: levels >> 4,
+            release: levels & 0x0F,
+        };
+        Self {
+            modulator: operator(data[0], data[2], data[3] & 0x08 == 0x08, data[4], data[6]),
+            carrier: operator(data[1], data[3], data[3] & 0x10 == 0x10, data[5], data[7]),
+            total_level: data[2] & 0x3F,
+            feedback: data[3] & 0x07,
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[must_use]
+enum EnvelopeState {
+    Attack,
+    Decay,
+    SustainHold,
+    Sustain,
+    Release,
+    #[default]
+    Off,
+}
+
+/// Per-channel values operators depend on.
+#[derive(Debug, Copy, Clone)]
+struct ChannelParams {
+    fnum: u16,
+    block: u8,
+    sustain: bool,
+    key_scale_rate: u8,
+    // Attenuation added by the LFO, in envelope units
+    tremolo: f32,
+    // Phase increment multiplier added by the LFO
+    vibrato: f32,
+}
+
+/// One of the two operators of a channel: a sine oscillator shaped by an envelope.
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+struct Operator {
+    phase: u32,
+    envelope: EnvelopeState,
+    envelope_phase: u32,
+    // Envelope attenuation in 0.375 dB units
+    envelope_out: u32,
+}
+
+impl Operator {
+    const PHASE_BITS: u32 = 19;
+    const ENVELOPE_BITS: u32 = 7;
+    const ENVELOPE_PHASE_BITS: u32 = 22;
+    const ENVELOPE_SHIFT: u32 = Self::ENVELOPE_PHASE_BITS - Self::ENVELOPE_BITS;
+    const ENVELOPE_MAX: u32 = (1 << Self::ENVELOPE_BITS) - 1;
+    // Frequency multipliers, doubled
+    const MULTIPLIERS: [u32; 16] = [1, 2, 4, 6, 8, 10, 12, 14, 16, 18, 20, 20, 24, 24, 30, 30];
+    // Key scale level attenuation of the top 4 bits of fnum at block 7, in dB at 6 dB/octave
+    const KEY_SCALE_LEVELS: [f32; 16] = [
+        0.0, 18.0, 24.0, 27.75, 30.0, 32.25, 33.75, 35.25, 36.0, 37.5, 38.25, 39.0, 39.75, 40.5,
+        41.25, 42.0,
+    ];
+    const DB_PER_UNIT: f32 = 0.375;
+
+    fn key_on(&mut self) {
+        self.phase = 0;
+        self.envelope = EnvelopeState::Attack;
+        self.envelope_phase = 0;
+    }
+
+    fn key_off(&mut self) {
+        if self.envelope == EnvelopeState::Attack {
+            self.envelope_phase = Self::attack_curve(self.envelope_phase >> Self::ENVELOPE_SHIFT)
+                << Self::ENVELOPE_SHIFT;
+        }
+        if self.envelope != EnvelopeState::Off {
+            self.envelope = EnvelopeState::Release;
+        }
+    }
+
+    /// Attenuation during the attack phase, which follows an exponential curve.
+    fn attack_curve(step: u32) -> u32 {
+        if step == 0 {
+            Self::ENVELOPE_MAX
+        } else {
+            let max = Self::ENVELOPE_MAX as f32;
+            (max - max * (step as f32).ln() / ((1 << Self::ENVELOPE_BITS) as f32).ln()) as u32
+        }
+    }
+
+    const fn attack_rate(rate: u8, key_scale_rate: u8) -> u32 {
+        if rate == 0 {
+            return 0;
+        }
+        let rh = rate + (key_scale_rate >> 2);
+        let rh = if rh > 15 { 15 } else { rh };
+        let rl = (key_scale_rate & 0x03) as u32;
+        (3 * (rl + 4)) << (rh + 1)
+    }
+
+    const fn decay_rate(rate: u8, key_scale_rate: u8) -> u32 {
+        if rate == 0 {
+            return 0;
+        }
+        let rh = rate + (key_scale_rate >> 2);
+        let rh = if rh > 15 { 15 } else { rh };
+        let rl = (key_scale_rate & 0x03) as u32;
+        (rl + 4) << (rh - 1)
+    }
+
+    fn clock_envelope(&mut self, patch: &OperatorPatch, params: &ChannelParams) {
+        let ksr = if patch.key_scale_rate {
+            params.key_scale_rate
+        } else {
+            params.key_scale_rate >> 2
+        };
+        let sustain_level = if patch.sustain_level == 15 {
+            1 << Self::ENVELOPE_PHASE_BITS
+        } else {
+            u32::from(patch.sustain_level) << (3 + Self::ENVELOPE_SHIFT)
+        };
+        match self.envelope {
+            EnvelopeState::Attack => {
+                self.envelope_out = Self::attack_curve(self.envelope_phase >> Self::ENVELOPE_SHIFT);
+                self.envelope_phase += Self::attack_rate(patch.attack, ksr);
+                if self.envelope_phase >= 1 << Self::ENVELOPE_PHASE_BITS || patch.attack == 15 {
+                    self.envelope_out = 0;
+                    self.envelope_phase = 0;
+                    self.envelope = EnvelopeState::Decay;
+                }
+            }
+            EnvelopeState::Decay => {
+                self.envelope_out = self.envelope_phase >> Self::ENVELOPE_SHIFT;
+                self.envelope_phase += Self::decay_rate(patch.decay, ksr);
+                if self.envelope_phase >= sustain_level {
+                    self.envelope_phase = sustain_level;
+                    self.envelope = if patch.sustained {
+                        EnvelopeState::SustainHold
+                    } else {
+                        EnvelopeState::Sustain
+                    };
+                }
+            }
+            EnvelopeState::SustainHold => {
+                self.envelope_out = self.envelope_phase >> Self::ENVELOPE_SHIFT;
+                if !patch.sustained {
+                    self.envelope = EnvelopeState::Sustain;
+                }
+            }
+            EnvelopeState::Sustain | EnvelopeState::Release => {
+                let rate = if self.envelope == EnvelopeState::Sustain {
+                    patch.release
+                } else if params.sustain {
+                    5
+                } else if patch.sustained {
+                    patch.release
+                } else {
+                    7
+                };
+                self.envelope_out = self.envelope_phase >> Self::ENVELOPE_SHIFT;
+                self.envelope_phase += Self::decay_rate(rate, ksr);
+                if self.envelope_out > Self::ENVELOPE_MAX {
+                    self.envelope_out = Self::ENVELOPE_MAX;
+                    self.envelope = EnvelopeState::Off;
+                }
+            }
+            EnvelopeState::Off => self.envelope_out = Self::ENVELOPE_MAX,
+        }
+    }
+
+    fn clock_phase(&mut self, patch: &OperatorPatch, params: &ChannelParams) {
+        let multiplier = Self::MULTIPLIERS[usize::from(patch.multiplier)];
+        let mut increment = ((u32::from(params.fnum) * multiplier) << params.block) >> 2;
+        if patch.vibrato {
+            increment = (increment as f32 * (1.0 + params.vibrato)) as u32;
+        }
+        self.phase = (self.phase + increment) & ((1 << Self::PHASE_BITS) - 1);
+    }
+
+    /// Output in `-1.0..=1.0`, with the phase offset by `modulation` cycles and attenuated by
+    /// `level` in envelope units.
+    fn output(
+        &self,
+        patch: &OperatorPatch,
+        params: &ChannelParams,
+        level: f32,
+        modulation: f32,
+    ) -> f32 {
+        let ksl = match patch.key_scale_level {
+            0 => 0.0,
+            ksl => {
+                let db = Self::KEY_SCALE_LEVELS[usize::from(params.fnum >> 5)]
+                    - 6.0 * f32::from(7 - params.block);
+                db.max(0.0) / f32::from(1u8 << (3 - ksl)) / Self::DB_PER_UNIT
+            }
+        };
+        let tremolo = if patch.tremolo { params.tremolo } else { 0.0 };
+        let attenuation = self.envelope_out as f32 + level + ksl + tremolo;
+        if self.envelope == EnvelopeState::Off || attenuation >= Self::ENVELOPE_MAX as f32 {
+            return 0.0;
+        }
+
+        let t = self.phase as f32 / (1 << Self::PHASE_BITS) as f32 + modulation;
+        let sample = (2.0 * PI * t).sin();
+        if patch.rectify && sample < 0.0 {
+            0.0
+        } else {
+            sample * 10.0.powf(-attenuation * Self::DB_PER_UNIT / 20.0)
+        }
+    }
+}
+
+#[derive(Default, Debug, Copy, Clone, Serialize, Deserialize)]
+#[must_use]
+struct FmChannel {
+    fnum: u16,
+    block: u8,
+    sustain: bool,
+    key_on: bool,
+    instrument: u8,
+    volume: u8,
+    modulator: Operator,
+    carrier: Operator,
+    // Last two modulator outputs, averaged for self-feedback
+    feedback: [f32; 2],
+    out: f32,
+}
+
+impl FmChannel {
+    fn set_key_on(&mut self, key_on: bool) {
+        if key_on && !self.key_on {
+            self.modulator.key_on();
+            self.carrier.key_on();
+        } else if !key_on && self.key_on {
+            self.modulator.key_off();
+            self.carrier.key_off();
+        }
+        self.key_on = key_on;
+    }
+
+    fn clock(&mut self, patch: &Patch, tremolo: f32, vibrato: f32) {
+        let params = ChannelParams {
+            fnum: self.fnum,
+            block: self.block,
+            sustain: self.sustain,
+            key_scale_rate: (self.block << 1) | (self.fnum >> 8) as u8,
+            tremolo,
+            vibrato,
+        };
+
+        self.modulator.clock_envelope(&patch.modulator, &params);
+        self.carrier.clock_envelope(&patch.carrier, &params);
+        self.modulator.clock_phase(&patch.modulator, &params);
+        self.carrier.clock_phase(&patch.carrier, &params);
+
+        let feedback = if patch.feedback == 0 {
+            0.0
+        } else {
+            (self.feedback[0] + self.feedback[1]) / f32::from(1u8 << (8 - patch.feedback))
+        };
+        // Total level is in 0.75 dB steps
+        let modulator_level = f32::from(patch.total_level) * 2.0;
+        let modulator = self
+            .modulator
+            .output(&patch.modulator, &params, modulator_level, feedback);
+        self.feedback = [self.feedback[1], modulator];
+
+        // Volume is in 3 dB steps
+        let carrier_level = f32::from(self.volume) * 8.0;
+        self.out = self
+            .carrier
+            .output(&patch.carrier, &params, carrier_level, 4.0 * modulator);
+    }
+}
+
+/// VRC7 expansion audio, a cut-down YM2413 (OPLL) FM synthesizer with 6 channels of two
+/// operators each and its own set of built-in instruments.
+///
+/// <https://www.nesdev.org/wiki/VRC7_audio>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct Vrc7Audio {
+    addr: u8,
+    custom_patch: [u8; 8],
+    channels: [FmChannel; 6],
+    silenced: bool,
+    divider: u8,
+    tremolo_phase: f32,
+    vibrato_phase: f32,
+    out: f32,
+}
+
+impl Default for Vrc7Audio {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Vrc7Audio {
+    /// CPU cycles per FM sample, the OPLL runs at 3.58 MHz / 72.
+    const CLOCK_DIVIDER: u8 = 36;
+    const SAMPLE_RATE: f32 = 1_789_773.0 / Self::CLOCK_DIVIDER as f32;
+    const TREMOLO_RATE: f32 = 3.7;
+    // Tremolo depth of 4.8 dB in envelope units
+    const TREMOLO_DEPTH: f32 = 4.8 / 0.375;
+    const VIBRATO_RATE: f32 = 6.4;
+    // Vibrato depth of about 14 cents
+    const VIBRATO_DEPTH: f32 = 0.008;
+    // Output level of a single channel at full volume, relative to the APU
+    const OUTPUT_SCALE: f32 = 0.15;
+
+    /// Built-in instrument patches 1-15. Patch 0 is user defined through registers $00-$07.
+    ///
+    /// <https://www.nesdev.org/wiki/VRC7_audio#Internal_patch_set>
+    const PATCHES: [[u8; 8]; 15] = [
+        [0x03, 0x21, 0x05, 0x06, 0xE8, 0x81, 0x42, 0x27], // Buzzy Bell
+        [0x13, 0x41, 0x14, 0x0D, 0xD8, 0xF6, 0x23, 0x12], // Guitar
+        [0x11, 0x11, 0x08, 0x08, 0xFA, 0xB2, 0x20, 0x12], // Wurly
+        [0x31, 0x61, 0x0C, 0x07, 0xA8, 0x64, 0x61, 0x27], // Flute
+        [0x32, 0x21, 0x1E, 0x06, 0xE1, 0x76, 0x01, 0x28], // Clarinet
+        [0x02, 0x01, 0x06, 0x00, 0xA3, 0xE2, 0xF4, 0xF4], // Synth
+        [0x21, 0x61, 0x1D, 0x07, 0x82, 0x81, 0x11, 0x07], // Trumpet
+        [0x23, 0x21, 0x22, 0x17, 0xA2, 0x72, 0x01, 0x17], // Organ
+        [0x35, 0x11, 0x25, 0x00, 0x40, 0x73, 0x72, 0x01], // Bells
+        [0xB5, 0x01, 0x0F, 0x0F, 0xA8, 0xA5, 0x51, 0x02], // Vibes
+        [0x17, 0xC1, 0x24, 0x07, 0xF8, 0xF8, 0x22, 0x12], // Vibraphone
+        [0x71, 0x23, 0x11, 0x06, 0x65, 0x74, 0x18, 0x16], // Tutti
+        [0x01, 0x02, 0xD3, 0x05, 0xC9, 0x95, 0x03, 0x02], // Fretless
+        [0x61, 0x63, 0x0C, 0x00, 0x94, 0xC0, 0x33, 0xF6], // Synth Bass
+        [0x21, 0x72, 0x0D, 0x00, 0xC1, 0xD5, 0x56, 0x06], // Sweep
+    ];
+
+    pub fn new() -> Self {
+        Self {
+            addr: 0x00,
+            custom_patch: [0x00; 8],
+            channels: [FmChannel::default(); 6],
+            silenced: false,
+            divider: 0,
+            tremolo_phase: 0.0,
+            vibrato_phase: 0.0,
+            out: 0.0,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn output(&self) -> f32 {
+        if self.silenced {
+            0.0
+        } else {
+            Self::OUTPUT_SCALE * self.out
+        }
+    }
+
+    #[inline]
+    pub fn write_addr(&mut self, val: u8) {
+        self.addr = val;
+    }
+
+    pub fn write_data(&mut self, val: u8) {
+        match self.addr {
+            0x00..=0x07 => self.custom_patch[usize::from(self.addr)] = val,
+            0x10..=0x15 => {
+                let channel = &mut self.channels[usize::from(self.addr & 0x0F)];
+                channel.fnum = (channel.fnum & 0x100) | u16::from(val);
+            }
+            0x20..=0x25 => {
+                // [..SK BBBF]
+                //    || ||||
+                //    || |||+- Bit 8 of fnum
+                //    || +++-- Block/octave
+                //    |+------ Key on
+                //    +------- Sustain
+                let channel = &mut self.channels[usize::from(self.addr & 0x0F)];
+                channel.fnum = (channel.fnum & 0xFF) | (u16::from(val & 0x01) << 8);
+                channel.block = (val >> 1) & 0x07;
+                channel.sustain = val & 0x20 == 0x20;
+                channel.set_key_on(val & 0x10 == 0x10);
+            }
+            0x30..=0x35 => {
+                // [IIII VVVV]
+                //  |||| ||||
+                //  |||| ++++- Volume, 3 dB steps of attenuation
+                //  ++++------ Instrument
+                let channel = &mut self.channels[usize::from(self.addr & 0x0F)];
+                channel.instrument = val >> 4;
+                channel.volume = val & 0x0F;
+            }
+            _ => (),
+        }
+    }
+
+    pub fn set_silenced(&mut self, silenced: bool) {
+        if silenced && !self.silenced {
+            // Silencing also resets the synthesizer
+            self.channels = [FmChannel::default(); 6];
+            self.out = 0.0;
+        }
+        self.silenced = silenced;
+    }
+
+    fn patch(&self, instrument: u8) -> Patch {
+        match instrument {
+            0 => Patch::new(&self.custom_patch),
+            instrument => Patch::new(&Self::PATCHES[usize::from(instrument - 1)]),
+        }
+    }
+
+    fn clock_sample(&mut self) {
+        self.tremolo_phase = (self.tremolo_phase + Self::TREMOLO_RATE / Self::SAMPLE_RATE).fract();
+        self.vibrato_phase = (self.vibrato_phase + Self::VIBRATO_RATE / Self::SAMPLE_RATE).fract();
+        let tremolo = Self::TREMOLO_DEPTH * (1.0 - (2.0 * PI * self.tremolo_phase).cos()) / 2.0;
+        let vibrato = Self::VIBRATO_DEPTH * (2.0 * PI * self.vibrato_phase).sin();
+
+        let mut out = 0.0;
+        for i in 0..self.channels.len() {
+            let patch = self.patch(self.channels[i].instrument);
+            let channel = &mut self.channels[i];
+            channel.clock(&patch, tremolo, vibrato);
+            out += channel.out;
+        }
+        self.out = out;
+    }
+}
+
+impl Audio for Vrc7Audio {
+    #[inline]
+    fn output(&self) -> f32 {
+        Self::output(self)
+    }
+}
+
+impl Clock for Vrc7Audio {
+    fn clock(&mut self) -> usize {
+        if self.silenced {
+            return 0;
+        }
+        self.divider += 1;
+        if self.divider == Self::CLOCK_DIVIDER {
+            self.divider = 0;
+            self.clock_sample();
+            1
+        } else {
+            0
+        }
+    }
+}
+
+impl Reset for Vrc7Audio {
+    fn reset(&mut self, _kind: ResetKind) {
+        self.channels = [FmChannel::default(); 6];
+        self.silenced = false;
+        self.out = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn write(audio: &mut Vrc7Audio, addr: u8, val: u8) {
+        audio.write_addr(addr);
+        audio.write_data(val);
+    }
+
+    /// Run for `seconds` and return the output samples at the FM sample rate.
+    fn run(audio: &mut Vrc7Audio, seconds: f32) -> alloc::vec::Vec<f32> {
+        let cycles = (1_789_773.0 * seconds) as usize;
+        let mut samples = alloc::vec::Vec::new();
+        for _ in 0..cycles {
+            if audio.clock() == 1 {
+                samples.push(audio.output());
+            }
+        }
+        samples
+    }
+
+    #[test]
+    fn silent_until_key_on() {
+        let mut audio = Vrc7Audio::new();
+        write(&mut audio, 0x30, 0x30);
+        write(&mut audio, 0x10, 0x22);
+        assert!(run(&mut audio, 0.05).iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn custom_patch_frequency() {
+        let mut audio = Vrc7Audio::new();
+        // Pure sine: carrier only, instant attack, sustained, modulator silenced
+        for (addr, val) in [0x00, 0x21, 0x3F, 0x00, 0xF0, 0xF0, 0x0F, 0x0F]
+            .into_iter()
+            .enumerate()
+        {
+            write(&mut audio, addr as u8, val);
+        }
+        // 440Hz: fnum = 440 * 2^19 / (49716 * 2^(block - 1))
+        write(&mut audio, 0x30, 0x00);
+        write(&mut audio, 0x10, 0x22);
+        write(&mut audio, 0x20, 0x10 | (5 << 1) | 0x01);
+        let samples = run(&mut audio, 1.0);
+        let crossings = samples
+            .windows(2)
+            .filter(|w| w[0] < 0.0 && w[1] >= 0.0)
+            .count();
+        assert!((435..=445).contains(&crossings), "crossings: {crossings}");
+        let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+        assert!(
+            (0.1..=Vrc7Audio::OUTPUT_SCALE).contains(&peak),
+            "peak: {peak}"
+        );
+
+        // Key off releases the note
+        write(&mut audio, 0x20, (5 << 1) | 0x01);
+        let samples = run(&mut audio, 1.0);
+        assert!(samples[samples.len() - 100..].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn built_in_patches() {
+        for instrument in 1..=15 {
+            let mut audio = Vrc7Audio::new();
+            write(&mut audio, 0x30, instrument << 4);
+            write(&mut audio, 0x10, 0x22);
+            write(&mut audio, 0x20, 0x10 | (4 << 1) | 0x01);
+            let samples = run(&mut audio, 0.1);
+            let peak = samples.iter().fold(0.0f32, |peak, s| peak.max(s.abs()));
+            assert!(peak > 0.01, "instrument {instrument} peak: {peak}");
+        }
+    }
+
+    #[test]
+    fn silence() {
+        let mut audio = Vrc7Audio::new();
+        write(&mut audio, 0x30, 0x30);
+        write(&mut audio, 0x10, 0x22);
+        write(&mut audio, 0x20, 0x10 | (4 << 1) | 0x01);
+        run(&mut audio, 0.05);
+        audio.set_silenced(true);
+        assert_eq!(audio.output(), 0.0);
+        audio.set_silenced(false);
+        assert!(run(&mut audio, 0.05).iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn audio_registers_keep_prg_banks() {
+        for revision in [Vrc7Revision::A, Vrc7Revision::B, Vrc7Revision::Unknown] {
+            let mut cart = Cart::empty();
+            cart.prg_rom = vec![0x00; 8 * Vrc7::PRG_WINDOW];
+            let mut vrc7 = Vrc7::load(&mut cart, revision);
+            let _ = vrc7.map_write(0x9000, 0x02);
+            let _ = vrc7.map_write(0x9010, 0x05);
+            let _ = vrc7.map_write(0x9030, 0x07);
+            assert_eq!(
+                vrc7.map_peek(0xC000),
+                MappedRead::PrgRom(2 * Vrc7::PRG_WINDOW),
+                "{revision:?}"
+            );
+        }
+    }
+}
@@ -14,12 +14,19 @@ pub use m001_sxrom::{Mmc1Revision, Sxrom};
 pub use m002_uxrom::Uxrom;
 pub use m003_cnrom::Cnrom;
 pub use m004_txrom::{Mmc3Revision, Txrom};
-pub use m005_exrom::Exrom;
+pub use m005_exrom::{Exrom, Mmc5Audio};
 pub use m007_axrom::Axrom;
 pub use m009_pxrom::Pxrom;
-pub use m024_m026_vrc6::Vrc6;
+pub use m010_fxrom::Fxrom;
+pub use m019_namco163::{Namco163, Namco163Audio, Namco163Volume};
+pub use m020_fds::{Fds, FdsAudio};
+pub use m021_m022_m023_m025_vrc4::{Vrc4, Vrc4Revision};
+pub use m024_m026_vrc6::{Vrc6, Vrc6Audio};
 pub use m066_gxrom::Gxrom;
+pub use m069_fme7::{Fme7, Sunsoft5bAudio};
 pub use m071_bf909x::{Bf909Revision, Bf909x};
+pub use m085_vrc7::{Vrc7, Vrc7Audio, Vrc7Revision};
+pub use nsf_player::{NsfAudio, NsfPlayer};
 
 pub mod m000_nrom;
 pub mod m001_sxrom;
@@ -29,9 +36,16 @@ pub mod m004_txrom;
 pub mod m005_exrom;
 pub mod m007_axrom;
 pub mod m009_pxrom;
+pub mod m010_fxrom;
+pub mod m019_namco163;
+pub mod m020_fds;
+pub mod m021_m022_m023_m025_vrc4;
 pub mod m024_m026_vrc6;
 pub mod m066_gxrom;
+pub mod m069_fme7;
 pub mod m071_bf909x;
+pub mod m085_vrc7;
+pub mod nsf_player;
 pub mod vrc_irq;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -56,9 +70,16 @@ pub enum Mapper {
     Exrom,
     Axrom,
     Pxrom,
+    Fxrom,
+    Namco163,
+    Fds,
+    Vrc4,
     Vrc6,
     Gxrom,
+    Fme7,
     Bf909x,
+    Vrc7,
+    NsfPlayer,
 }
 
 impl Mapper {
@@ -125,6 +146,9 @@ pub trait Mapped {
     fn ppu_bus_write(&mut self, _addr: u16, _val: u8) {}
     fn cpu_bus_read(&mut self, _addr: u16) {}
     fn cpu_bus_write(&mut self, _addr: u16, _val: u8) {}
+    /// Called when PRG-RAM is replaced, for mappers that mirror internal RAM into battery-backed
+    /// PRG-RAM so it's saved with it.
+    fn load_sram(&mut self, _sram: &[u8]) {}
 }
 
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
@@ -0,0 +1,673 @@
+//! `NSF Player` pseudo-mapper for playing NES Sound Format music rips.
+//!
+//! <https://www.nesdev.org/wiki/NSF>
+
+use crate::{
+    audio::Audio,
+    cart::Cart,
+    common::{Clock, NesRegion, Regional, Reset, ResetKind},
+    cpu::Cpu,
+    mapper::{
+        FdsAudio, Mapped, MappedRead, MappedWrite, Mapper, MemMap, Mmc5Audio, Namco163Audio,
+        Namco163Volume, Sunsoft5bAudio, Vrc6Audio, Vrc7Audio,
+    },
+    mem::MemBanks,
+    nsf::{Nsf, NsfChips},
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::{Deserialize, Serialize};
+
+/// The expansion audio chips an NSF enables.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct NsfAudio {
+    vrc6: Option<Vrc6Audio>,
+    vrc7: Option<Vrc7Audio>,
+    fds: Option<FdsAudio>,
+    mmc5: Option<Mmc5Audio>,
+    namco163: Option<Namco163Audio>,
+    sunsoft5b: Option<Sunsoft5bAudio>,
+}
+
+impl NsfAudio {
+    pub fn new(chips: NsfChips, region: NesRegion) -> Self {
+        let mut mmc5 = chips.contains(NsfChips::MMC5).then(Mmc5Audio::new);
+        if let Some(mmc5) = &mut mmc5 {
+            mmc5.set_region(region);
+        }
+        Self {
+            vrc6: chips.contains(NsfChips::VRC6).then(Vrc6Audio::new),
+            vrc7: chips.contains(NsfChips::VRC7).then(Vrc7Audio::new),
+            fds: chips.contains(NsfChips::FDS).then(FdsAudio::new),
+            mmc5,
+            namco163: chips
+                .contains(NsfChips::NAMCO163)
+                .then(|| Namco163Audio::new(Namco163Volume::Medium)),
+            sunsoft5b: chips
+                .contains(NsfChips::SUNSOFT5B)
+                .then(Sunsoft5bAudio::new),
+        }
+    }
+}
+
+impl Audio for NsfAudio {
+    fn output(&self) -> f32 {
+        self.vrc6.as_ref().map_or(0.0, Vrc6Audio::output)
+            + self.vrc7.as_ref().map_or(0.0, Vrc7Audio::output)
+            + self.fds.as_ref().map_or(0.0, FdsAudio::output)
+            + self.mmc5.as_ref().map_or(0.0, Mmc5Audio::output)
+            + self.namco163.as_ref().map_or(0.0, Namco163Audio::output)
+            + self.sunsoft5b.as_ref().map_or(0.0, Sunsoft5bAudio::output)
+    }
+}
+
+impl Clock for NsfAudio {
+    fn clock(&mut self) -> usize {
+        if let Some(vrc6) = &mut self.vrc6 {
+            vrc6.clock();
+        }
+        if let Some(vrc7) = &mut self.vrc7 {
+            vrc7.clock();
+        }
+        if let Some(fds) = &mut self.fds {
+            fds.clock();
+        }
+        if let Some(mmc5) = &mut self.mmc5 {
+            mmc5.clock();
+        }
+        if let Some(namco163) = &mut self.namco163 {
+            namco163.clock();
+        }
+        if let Some(sunsoft5b) = &mut self.sunsoft5b {
+            sunsoft5b.clock();
+        }
+        1
+    }
+}
+
+/// Plays an NSF by running a small driver program at `$4100` which calls the INIT routine for the
+/// selected song and then the PLAY routine at the rate the NSF header requests.
+///
+/// Without FDS audio, program data is bankswitched into `$8000..=$FFFF` from PRG-ROM with 8K of
+/// PRG-RAM at `$6000..=$7FFF`. FDS rips run from 40K of RAM at `$6000..=$FFFF` instead, with banks
+/// copied into it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[must_use]
+pub struct NsfPlayer {
+    region: NesRegion,
+    chips: NsfChips,
+    song: u8,
+    total_songs: u8,
+    ntsc_speed: u16,
+    pal_speed: u16,
+    initial_banks: [u8; 10],
+    prg_rom_banks: MemBanks,
+    // FDS rips only, since they can write to their program area
+    fds_rom: Vec<u8>,
+    fds_ram: Vec<u8>,
+    mmc5_exram: Vec<u8>,
+    multiplicand: u8,
+    multiplier: u8,
+    driver: Vec<u8>,
+    play_period: f32,
+    play_timer: f32,
+    play_pending: bool,
+    // CPU cycles since the song was started
+    elapsed: u64,
+    audio: NsfAudio,
+}
+
+impl NsfPlayer {
+    /// Pseudo mapper number, outside of the `NES 2.0` range.
+    pub const MAPPER_NUM: u16 = 0xFFFF;
+
+    const PRG_RAM_SIZE: usize = 8 * 1024;
+    const CHR_RAM_SIZE: usize = 8 * 1024;
+    const FDS_RAM_SIZE: usize = 40 * 1024;
+    const EXRAM_SIZE: usize = 1024;
+    const BANK_SIZE: usize = 0x1000;
+    const DRIVER_ADDR: u16 = 0x4100;
+
+    pub fn load(cart: &mut Cart, nsf: &Nsf) -> Mapper {
+        let (prg_rom, initial_banks) = nsf.prg_rom();
+        let fds = nsf.chips.contains(NsfChips::FDS);
+        cart.add_chr_ram(Self::CHR_RAM_SIZE);
+        if !fds {
+            cart.add_prg_ram(Self::PRG_RAM_SIZE);
+        }
+        let mut nsf_player = Self {
+            region: cart.region(),
+            chips: nsf.chips,
+            song: nsf.starting_song,
+            total_songs: nsf.total_songs,
+            ntsc_speed: nsf.play_speed(NesRegion::Ntsc),
+            pal_speed: nsf.play_speed(NesRegion::Pal),
+            initial_banks,
+            prg_rom_banks: MemBanks::new(0x8000, 0xFFFF, prg_rom.len(), Self::BANK_SIZE),
+            fds_rom: if fds { prg_rom.clone() } else { vec![] },
+            fds_ram: if fds {
+                vec![0x00; Self::FDS_RAM_SIZE]
+            } else {
+                vec![]
+            },
+            mmc5_exram: if nsf.chips.contains(NsfChips::MMC5) {
+                vec![0x00; Self::EXRAM_SIZE]
+            } else {
+                vec![]
+            },
+            multiplicand: 0xFF,
+            multiplier: 0xFF,
+            driver: Self::driver(nsf.init_addr, nsf.play_addr, fds),
+            play_period: 0.0,
+            play_timer: 0.0,
+            play_pending: false,
+            elapsed: 0,
+            audio: NsfAudio::new(nsf.chips, cart.region()),
+        };
+        cart.prg_rom = prg_rom;
+        nsf_player.set_region(nsf_player.region);
+        nsf_player.reset(ResetKind::Hard);
+        nsf_player.into()
+    }
+
+    /// The selected song, starting at `0`.
+    #[inline]
+    #[must_use]
+    pub const fn song(&self) -> u8 {
+        self.song
+    }
+
+    /// Select the song INIT is called with on the next reset.
+    #[inline]
+    pub fn set_song(&mut self, song: u8) {
+        self.song = song.min(self.total_songs.saturating_sub(1));
+    }
+
+    /// CPU cycles since the song was started.
+    #[inline]
+    #[must_use]
+    pub const fn elapsed(&self) -> u64 {
+        self.elapsed
+    }
+
+    /// Assemble the driver, which initializes the console the way the NSF spec requires, calls
+    /// INIT with the song in A and the region in X, then calls PLAY whenever $40F0 reports the
+    /// play timer has elapsed. The last byte is an `RTI` for the NMI and IRQ vectors.
+    fn driver(init_addr: u16, play_addr: u16, fds: bool) -> Vec<u8> {
+        let [init_lo, init_hi] = init_addr.to_le_bytes();
+        let [play_lo, play_hi] = play_addr.to_le_bytes();
+        let mut driver = vec![
+            0x78, // SEI
+            0xD8, // CLD
+            0xA2, 0xFF, // LDX #$FF
+            0x9A, // TXS
+            0xA9, 0x00, // LDA #$00
+            0x8D, 0x00, 0x20, // STA $2000
+            0x8D, 0x01, 0x20, // STA $2001
+        ];
+        if !fds {
+            // Clear PRG-RAM through a pointer at $00
+            driver.extend_from_slice(&[
+                0x85, 0x00, // STA $00
+                0xA8, // TAY
+                0xA2, 0x60, // LDX #$60
+                0x86, 0x01, // STX $01
+                0x91, 0x00, // STA ($00),Y
+                0xC8, // INY
+                0xD0, 0xFB, // BNE -5
+                0xE8, // INX
+                0xE0, 0x80, // CPX #$80
+                0xD0, 0xF4, // BNE -12
+            ]);
+        }
+        driver.extend_from_slice(&[
+            0xA2, 0x00, // LDX #$00
+            0x9D, 0x00, 0x00, // STA $0000,X
+            0x9D, 0x00, 0x01, // STA $0100,X
+            0x9D, 0x00, 0x02, // STA $0200,X
+            0x9D, 0x00, 0x03, // STA $0300,X
+            0x9D, 0x00, 0x04, // STA $0400,X
+            0x9D, 0x00, 0x05, // STA $0500,X
+            0x9D, 0x00, 0x06, // STA $0600,X
+            0x9D, 0x00, 0x07, // STA $0700,X
+            0xE8, // INX
+            0xD0, 0xE5, // BNE -27
+            0xA2, 0x13, // LDX #$13
+            0x9D, 0x00, 0x40, // STA $4000,X
+            0xCA, // DEX
+            0x10, 0xFA, // BPL -6
+            0x8D, 0x15, 0x40, // STA $4015
+            0xA9, 0x0F, // LDA #$0F
+            0x8D, 0x15, 0x40, // STA $4015
+            0xA9, 0x40, // LDA #$40
+            0x8D, 0x17, 0x40, // STA $4017
+        ]);
+        if fds {
+            driver.extend_from_slice(&[
+                0xA9, 0x80, // LDA #$80
+                0x8D, 0x89, 0x40, // STA $4089
+                0xA9, 0xE8, // LDA #$E8
+                0x8D, 0x8A, 0x40, // STA $408A
+            ]);
+        }
+        driver.extend_from_slice(&[
+            0xAD, 0xF1, 0x40, // LDA $40F1
+            0xAE, 0xF2, 0x40, // LDX $40F2
+            0x20, init_lo, init_hi, // JSR INIT
+            0x8D, 0xF0, 0x40, // STA $40F0
+        ]);
+        let [loop_lo, loop_hi] = (Self::DRIVER_ADDR + driver.len() as u16).to_le_bytes();
+        driver.extend_from_slice(&[
+            0xAD, 0xF0, 0x40, // LDA $40F0
+            0xF0, 0xFB, // BEQ -5
+            0x20, play_lo, play_hi, // JSR PLAY
+            0x4C, loop_lo, loop_hi, // JMP loop
+            0x40,    // RTI
+        ]);
+        driver
+    }
+
+    fn set_bank(&mut self, slot: usize, bank: u8) {
+        if self.fds_ram.is_empty() {
+            // $5FF6 and $5FF7 are only used by FDS rips
+            if slot >= 2 {
+                self.prg_rom_banks.set(slot - 2, bank.into());
+            }
+        } else {
+            let pages = self.fds_rom.len() / Self::BANK_SIZE;
+            let src = (usize::from(bank) % pages) * Self::BANK_SIZE;
+            let dst = slot * Self::BANK_SIZE;
+            self.fds_ram[dst..dst + Self::BANK_SIZE]
+                .copy_from_slice(&self.fds_rom[src..src + Self::BANK_SIZE]);
+        }
+    }
+
+    #[inline]
+    fn update_play_period(&mut self) {
+        let speed = match self.region {
+            NesRegion::Ntsc => self.ntsc_speed,
+            NesRegion::Pal | NesRegion::Dendy => self.pal_speed,
+        };
+        self.play_period = f32::from(speed) * Cpu::region_clock_rate(self.region) / 1_000_000.0;
+    }
+}
+
+impl Mapped for NsfPlayer {}
+
+impl MemMap for NsfPlayer {
+    // PPU $0000..=$1FFF 8K CHR-RAM
+    //
+    // CPU $40F0         Play timer: read to poll and acknowledge, write to start
+    // CPU $40F1..=$40F2 Song and region for INIT
+    // CPU $4100..=$41FF Driver
+    // CPU $5FF6..=$5FFF 4K bank select for $6000..=$FFFF
+    // CPU $6000..=$7FFF 8K PRG-RAM, or 40K RAM to $FFFF for FDS rips
+    // CPU $8000..=$FFFF 32K switchable PRG-ROM
+    // CPU $FFFA..=$FFFF Vectors into the driver
+
+    fn map_read(&mut self, addr: u16) -> MappedRead {
+        match addr {
+            0x40F0 => {
+                let pending = self.play_pending;
+                self.play_pending = false;
+                MappedRead::Data(pending.into())
+            }
+            0x4800 => match &mut self.audio.namco163 {
+                Some(namco163) => MappedRead::Data(namco163.read_data()),
+                None => MappedRead::None,
+            },
+            0x5010 => match &mut self.audio.mmc5 {
+                Some(mmc5) => {
+                    let val = mmc5.peek(addr);
+                    mmc5.acknowledge_irq();
+                    MappedRead::Data(val)
+                }
+                None => MappedRead::None,
+            },
+            _ => self.map_peek(addr),
+        }
+    }
+
+    fn map_peek(&self, addr: u16) -> MappedRead {
+        match addr {
+            0x0000..=0x1FFF => MappedRead::Chr(addr.into()),
+            0x40F0 => MappedRead::Data(self.play_pending.into()),
+            0x40F1 => MappedRead::Data(self.song),
+            0x40F2 => MappedRead::Data(u8::from(self.region != NesRegion::Ntsc)),
+            0x4100..=0x41FF => {
+                let offset = usize::from(addr - Self::DRIVER_ADDR);
+                MappedRead::Data(self.driver.get(offset).copied().unwrap_or(0x00))
+            }
+            0xFFFA..=0xFFFF => {
+                let rti = Self::DRIVER_ADDR + self.driver.len() as u16 - 1;
+                let vector = if addr & 0xFFFE == 0xFFFC {
+                    Self::DRIVER_ADDR
+                } else {
+                    rti
+                };
+                MappedRead::Data(vector.to_le_bytes()[usize::from(addr & 0x01)])
+            }
+            0x6000..=0xFFFF if !self.fds_ram.is_empty() => {
+                MappedRead::Data(self.fds_ram[usize::from(addr - 0x6000)])
+            }
+            0x6000..=0x7FFF => MappedRead::PrgRam((addr & 0x1FFF).into()),
+            0x8000..=0xFFFF => MappedRead::PrgRom(self.prg_rom_banks.translate(addr)),
+            _ => match (&self.audio, addr) {
+                (NsfAudio { fds: Some(fds), .. }, 0x4040..=0x407F | 0x4090 | 0x4092) => {
+                    MappedRead::Data(fds.peek(addr))
+                }
+                (
+                    NsfAudio {
+                        namco163: Some(namco163),
+                        ..
+                    },
+                    0x4800,
+                ) => MappedRead::Data(namco163.peek_data()),
+                (
+                    NsfAudio {
+                        mmc5: Some(mmc5), ..
+                    },
+                    0x5010 | 0x5015,
+                ) => MappedRead::Data(mmc5.peek(addr)),
+                (NsfAudio { mmc5: Some(_), .. }, 0x5205) => {
+                    let product = u16::from(self.multiplicand) * u16::from(self.multiplier);
+                    MappedRead::Data(product.to_le_bytes()[0])
+                }
+                (NsfAudio { mmc5: Some(_), .. }, 0x5206) => {
+                    let product = u16::from(self.multiplicand) * u16::from(self.multiplier);
+                    MappedRead::Data(product.to_le_bytes()[1])
+                }
+                (NsfAudio { mmc5: Some(_), .. }, 0x5C00..=0x5FF5) => {
+                    MappedRead::Data(self.mmc5_exram[usize::from(addr - 0x5C00)])
+                }
+                _ => MappedRead::None,
+            },
+        }
+    }
+
+    fn map_write(&mut self, addr: u16, val: u8) -> MappedWrite {
+        match addr {
+            0x0000..=0x1FFF => return MappedWrite::Chr(addr.into(), val),
+            0x40F0 => {
+                self.play_timer = self.play_period;
+                self.play_pending = false;
+            }
+            0x5FF6..=0x5FFF => self.set_bank(usize::from(addr - 0x5FF6), val),
+            0x6000..=0xFFFF if !self.fds_ram.is_empty() => {
+                self.fds_ram[usize::from(addr - 0x6000)] = val;
+            }
+            0x6000..=0x7FFF => return MappedWrite::PrgRam((addr & 0x1FFF).into(), val),
+            _ => (),
+        }
+
+        let audio = &mut self.audio;
+        match addr {
+            0x4040..=0x408A => {
+                if let Some(fds) = &mut audio.fds {
+                    fds.write(addr, val);
+                }
+            }
+            0x4800 => {
+                if let Some(namco163) = &mut audio.namco163 {
+                    namco163.write_data(val);
+                }
+            }
+            0xF800..=0xFFFF => {
+                if let Some(namco163) = &mut audio.namco163 {
+                    namco163.write_addr(val);
+                }
+            }
+            0x5000..=0x5015 => {
+                if let Some(mmc5) = &mut audio.mmc5 {
+                    mmc5.write(addr, val);
+                }
+            }
+            0x5205 if audio.mmc5.is_some() => self.multiplicand = val,
+            0x5206 if audio.mmc5.is_some() => self.multiplier = val,
+            0x5C00..=0x5FF5 if audio.mmc5.is_some() => {
+                self.mmc5_exram[usize::from(addr - 0x5C00)] = val;
+            }
+            0x9000..=0x9003 | 0xA000..=0xA002 | 0xB000..=0xB002 => {
+                if let Some(vrc6) = &mut audio.vrc6 {
+                    vrc6.write_register(addr, val);
+                }
+            }
+            _ => (),
+        }
+        match addr {
+            0x9010 => {
+                if let Some(vrc7) = &mut self.audio.vrc7 {
+                    vrc7.write_addr(val);
+                }
+            }
+            0x9030 => {
+                if let Some(vrc7) = &mut self.audio.vrc7 {
+                    vrc7.write_data(val);
+                }
+            }
+            0xC000..=0xDFFF => {
+                if let Some(sunsoft5b) = &mut self.audio.sunsoft5b {
+                    sunsoft5b.write_addr(val);
+                }
+            }
+            0xE000..=0xFFFF => {
+                if let Some(sunsoft5b) = &mut self.audio.sunsoft5b {
+                    sunsoft5b.write_data(val);
+                }
+            }
+            _ => (),
+        }
+        MappedWrite::None
+    }
+}
+
+impl Audio for NsfPlayer {
+    #[inline]
+    fn output(&self) -> f32 {
+        self.audio.output()
+    }
+}
+
+impl Clock for NsfPlayer {
+    fn clock(&mut self) -> usize {
+        self.elapsed += 1;
+        if self.play_timer > 0.0 {
+            self.play_timer -= 1.0;
+            if self.play_timer <= 0.0 {
+                self.play_pending = true;
+                self.play_timer += self.play_period;
+            }
+        }
+        self.audio.clock();
+        1
+    }
+}
+
+impl Reset for NsfPlayer {
+    /// Any reset restarts the selected song.
+    fn reset(&mut self, _kind: ResetKind) {
+        for (slot, bank) in self.initial_banks.into_iter().enumerate() {
+            self.set_bank(slot, bank);
+        }
+        self.mmc5_exram.fill(0x00);
+        self.multiplicand = 0xFF;
+        self.multiplier = 0xFF;
+        self.play_timer = 0.0;
+        self.play_pending = false;
+        self.elapsed = 0;
+        self.audio = NsfAudio::new(self.chips, self.region);
+    }
+}
+
+impl Regional for NsfPlayer {
+    #[inline]
+    fn region(&self) -> NesRegion {
+        self.region
+    }
+
+    fn set_region(&mut self, region: NesRegion) {
+        self.region = region;
+        self.update_play_period();
+        if let Some(mmc5) = &mut self.audio.mmc5 {
+            mmc5.set_region(region);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{control_deck::ControlDeck, mem::RamState};
+    use alloc::string::ToString;
+
+    fn load_player(chips: NsfChips, banks: [u8; 8]) -> NsfPlayer {
+        let nsf = Nsf {
+            total_songs: 4,
+            starting_song: 2,
+            load_addr: 0x8000,
+            init_addr: 0x8000,
+            play_addr: 0x8003,
+            ntsc_speed: 16639,
+            pal_speed: 19997,
+            chips,
+            banks,
+            data: (0..0x6000).map(|i| (i / 0x1000) as u8).collect(),
+            ..Nsf::default()
+        };
+        let mut cart = Cart::from_nsf("test".to_string(), &nsf, RamState::AllZeros);
+        match core::mem::take(&mut cart.mapper) {
+            Mapper::NsfPlayer(nsf_player) => nsf_player,
+            _ => panic!("expected NSF player"),
+        }
+    }
+
+    fn write(nsf_player: &mut NsfPlayer, addr: u16, val: u8) {
+        let _ = nsf_player.map_write(addr, val);
+    }
+
+    #[test]
+    fn vectors_and_registers() {
+        let mut nsf_player = load_player(NsfChips::empty(), [0; 8]);
+        assert_eq!(nsf_player.map_peek(0xFFFC), MappedRead::Data(0x00));
+        assert_eq!(nsf_player.map_peek(0xFFFD), MappedRead::Data(0x41));
+        assert_eq!(nsf_player.map_peek(0x4100), MappedRead::Data(0x78));
+        assert_eq!(nsf_player.map_peek(0x40F1), MappedRead::Data(2));
+
+        nsf_player.set_song(9);
+        assert_eq!(nsf_player.song(), 3, "clamped to last song");
+        nsf_player.set_region(NesRegion::Pal);
+        assert_eq!(nsf_player.map_peek(0x40F2), MappedRead::Data(1));
+    }
+
+    #[test]
+    fn play_timer() {
+        let mut nsf_player = load_player(NsfChips::empty(), [0; 8]);
+        let period = (16639.0 * Cpu::region_clock_rate(NesRegion::Ntsc) / 1_000_000.0) as u32;
+        for _ in 0..period * 2 {
+            nsf_player.clock();
+        }
+        assert_eq!(
+            nsf_player.map_read(0x40F0),
+            MappedRead::Data(0),
+            "timer not started"
+        );
+
+        write(&mut nsf_player, 0x40F0, 0x00);
+        for _ in 0..=period {
+            nsf_player.clock();
+        }
+        assert_eq!(nsf_player.map_read(0x40F0), MappedRead::Data(1));
+        assert_eq!(
+            nsf_player.map_read(0x40F0),
+            MappedRead::Data(0),
+            "acknowledged"
+        );
+        for _ in 0..period + 1 {
+            nsf_player.clock();
+        }
+        assert_eq!(nsf_player.map_read(0x40F0), MappedRead::Data(1), "repeats");
+    }
+
+    #[test]
+    fn bankswitching() {
+        let mut nsf_player = load_player(NsfChips::empty(), [5, 4, 3, 2, 1, 0, 0, 0]);
+        assert_eq!(nsf_player.map_peek(0x8000), MappedRead::PrgRom(0x5000));
+        assert_eq!(nsf_player.map_peek(0x9000), MappedRead::PrgRom(0x4000));
+        write(&mut nsf_player, 0x5FF8, 0x02);
+        assert_eq!(nsf_player.map_peek(0x8000), MappedRead::PrgRom(0x2000));
+        nsf_player.reset(ResetKind::Soft);
+        assert_eq!(nsf_player.map_peek(0x8000), MappedRead::PrgRom(0x5000));
+    }
+
+    #[test]
+    fn fds_ram() {
+        let mut nsf_player = load_player(NsfChips::FDS, [5, 4, 3, 2, 1, 0, 0, 0]);
+        // $6000 starts with the bank in the last slot
+        assert_eq!(nsf_player.map_peek(0x6000), MappedRead::Data(0));
+        assert_eq!(nsf_player.map_peek(0x8000), MappedRead::Data(5));
+        write(&mut nsf_player, 0x8000, 0xAA);
+        assert_eq!(nsf_player.map_peek(0x8000), MappedRead::Data(0xAA));
+        write(&mut nsf_player, 0x5FF8, 0x03);
+        assert_eq!(nsf_player.map_peek(0x8000), MappedRead::Data(3));
+        assert_eq!(nsf_player.map_peek(0x4090), MappedRead::Data(0x40));
+    }
+
+    fn clock_frames(deck: &mut ControlDeck, frames: usize) {
+        for _ in 0..frames {
+            let _ = deck.clock_frame().expect("valid frame");
+        }
+    }
+
+    #[test]
+    fn play_tracks() {
+        let chunk = |id: &[u8], data: &[u8]| {
+            let mut chunk = (data.len() as u32).to_le_bytes().to_vec();
+            chunk.extend_from_slice(id);
+            chunk.extend_from_slice(data);
+            chunk
+        };
+        let mut nsfe = b"NSFE".to_vec();
+        nsfe.extend(chunk(
+            b"INFO",
+            &[0x00, 0x80, 0x00, 0x80, 0x03, 0x80, 0x00, 0x00, 3, 1],
+        ));
+        // INIT: STA $00, RTS; PLAY: INC $01, RTS
+        nsfe.extend(chunk(b"DATA", &[0x85, 0x00, 0x60, 0xE6, 0x01, 0x60]));
+        nsfe.extend(chunk(b"auth", b"Title\0Artist\0Copyright\0"));
+        let mut time = 500i32.to_le_bytes().to_vec();
+        time.extend_from_slice(&(-1i32).to_le_bytes());
+        nsfe.extend(chunk(b"time", &time));
+        nsfe.extend(chunk(b"fade", &500i32.to_le_bytes()));
+        nsfe.extend(chunk(b"NEND", &[]));
+
+        let mut deck = ControlDeck::new();
+        deck.load_nsf("test".to_string(), &nsfe)
+            .expect("valid nsfe");
+        assert_eq!(deck.nsf().map(|nsf| nsf.artist.as_str()), Some("Artist"));
+        assert_eq!(deck.track_count(), 3);
+        assert_eq!(deck.track(), Some(1));
+        clock_frames(&mut deck, 60);
+        assert_eq!(deck.wram()[0x00], 1, "INIT called with starting track");
+        assert!(
+            (55..=60).contains(&deck.wram()[0x01]),
+            "PLAY called each frame"
+        );
+        assert!(!deck.track_finished(), "track without length");
+
+        deck.previous_track().expect("previous track");
+        assert_eq!(deck.track(), Some(0));
+        clock_frames(&mut deck, 30);
+        assert_eq!(deck.wram()[0x00], 0);
+        assert!((25..=30).contains(&deck.wram()[0x01]), "restarted");
+        assert!(!deck.track_finished());
+        clock_frames(&mut deck, 40);
+        assert!(deck.track_finished(), "finished after length and fade");
+
+        deck.previous_track().expect("previous track");
+        assert_eq!(deck.track(), Some(2), "wraps to last track");
+        deck.next_track().expect("next track");
+        assert_eq!(deck.track(), Some(0), "wraps to first track");
+        assert!(deck.select_track(3).is_err());
+    }
+}
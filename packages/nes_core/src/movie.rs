@@ -0,0 +1,870 @@
+//! Input movie recording and playback.
+//!
+//! A `Movie` is a starting point (a save state, or power-on with a given `RamState`) followed by
+//! the input for every frame. Since emulation is deterministic, playing back the same input from
+//! the same starting point reproduces the recorded session exactly. Frames can optionally carry a
+//! hash of the frame buffer which is checked during playback to detect desyncs.
+//!
+//! Movies can be imported from and exported to the FCEUX text movie format.
+//!
+//! <https://fceux.com/web/FM2.html>
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::mem;
+use core::ops::ControlFlow;
+use anyhow::{anyhow, bail, Context, Result};
+use crate::{
+    checksum::Crc32,
+    common::{NesRegion, Regional, Reset, ResetKind},
+    control_deck::ControlDeck,
+    debugger::DebugBreak,
+    input::{FourPlayer, JoypadBtnState, Player},
+    mem::RamState,
+};
+
+const PLAYERS: [Player; 4] = [Player::One, Player::Two, Player::Three, Player::Four];
+/// FM2 gamepad button order, left to right.
+const FM2_BUTTONS: [(char, JoypadBtnState); 8] = [
+    ('R', JoypadBtnState::RIGHT),
+    ('L', JoypadBtnState::LEFT),
+    ('D', JoypadBtnState::DOWN),
+    ('U', JoypadBtnState::UP),
+    ('T', JoypadBtnState::START),
+    ('S', JoypadBtnState::SELECT),
+    ('B', JoypadBtnState::B),
+    ('A', JoypadBtnState::A),
+];
+// FM2 input port device types
+const FM2_PORT_NONE: u8 = 0;
+const FM2_PORT_GAMEPAD: u8 = 1;
+const FM2_PORT_ZAPPER: u8 = 2;
+// FM2 frame commands
+const FM2_SOFT_RESET: u8 = 0x01;
+const FM2_HARD_RESET: u8 = 0x02;
+
+/// Where movie playback starts from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum MovieStart {
+    /// Power on the console, initializing RAM with the given `RamState`.
+    PowerOn(RamState),
+    /// Restore a save state created by `ControlDeck::save_state`.
+    State(Vec<u8>),
+}
+
+/// Zapper input for a single frame.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct ZapperInput {
+    pub x: i32,
+    pub y: i32,
+    pub trigger: bool,
+}
+
+/// Input for a single frame.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct MovieFrame {
+    pub joypads: [JoypadBtnState; 4],
+    /// Reset applied at the start of the frame.
+    pub reset: Option<ResetKind>,
+    /// Zapper input, if a zapper is connected.
+    pub zapper: Option<ZapperInput>,
+    /// Frame buffer hash at the end of the frame, used to detect desyncs.
+    pub hash: Option<u32>,
+}
+
+impl MovieFrame {
+    fn apply(&self, deck: &mut ControlDeck) {
+        if let Some(kind) = self.reset {
+            deck.reset(kind);
+        }
+        for (buttons, player) in self.joypads.iter().zip(PLAYERS) {
+            deck.joypad_mut(player).set_buttons(*buttons);
+        }
+        if let Some(zapper) = self.zapper {
+            deck.aim_zapper(zapper.x, zapper.y);
+            if zapper.trigger {
+                deck.trigger_zapper();
+            }
+        }
+    }
+}
+
+/// A recorded input movie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Movie {
+    pub rom_name: String,
+    /// CRC-32 of the ROM this movie was recorded with. See `Cart::crc32`.
+    pub rom_crc32: Option<u32>,
+    /// FM2 `romChecksum`, preserved when importing and exporting.
+    pub rom_checksum: Option<String>,
+    /// FM2 `guid`, preserved when importing and exporting.
+    pub guid: Option<String>,
+    pub start: MovieStart,
+    pub region: NesRegion,
+    pub four_player: FourPlayer,
+    pub zapper: bool,
+    pub rerecord_count: u32,
+    pub comments: Vec<String>,
+    pub frames: Vec<MovieFrame>,
+}
+
+impl Movie {
+    /// Calculate the hash of the current frame buffer, as stored in `MovieFrame::hash`.
+    #[must_use]
+    pub fn frame_hash(deck: &ControlDeck) -> u32 {
+        let mut crc = Crc32::new();
+        for pixel in deck.cpu().frame_buffer() {
+            crc.update(&pixel.to_le_bytes());
+        }
+        crc.finish()
+    }
+
+    /// Prepare `deck` to record or play back this movie.
+    fn apply_start(&self, deck: &mut ControlDeck) -> Result<()> {
+        if let (Some(expected), Some(found)) = (self.rom_crc32, deck.rom_crc32()) {
+            if expected != found {
+                bail!("movie rom mismatch: {found:#010X} (expected {expected:#010X})");
+            }
+        }
+        deck.set_region(self.region);
+        deck.set_four_player(self.four_player);
+        deck.connect_zapper(self.zapper);
+        match &self.start {
+            MovieStart::PowerOn(ram_state) => deck.power_on(*ram_state),
+            MovieStart::State(state) => deck
+                .load_state(state)
+                .map_err(|err| anyhow!("{err}"))?,
+        }
+        for player in PLAYERS {
+            deck.joypad_mut(player).set_buttons(JoypadBtnState::empty());
+        }
+        Ok(())
+    }
+
+    /// Parse a movie in FCEUX text movie format (`.fm2`).
+    ///
+    /// Movies starting from an FCEUX savestate and binary movies are not supported. `ControlDeck`
+    /// save states and the power-on `RamState` are read from `nesCoreState` and
+    /// `nesCoreRamState` keys, which FCEUX ignores.
+    ///
+    /// # Errors
+    ///
+    /// If the movie is invalid or unsupported, an error is returned.
+    pub fn from_fm2(fm2: &str) -> Result<Self> {
+        let mut movie = Self {
+            rom_name: String::new(),
+            rom_crc32: None,
+            rom_checksum: None,
+            guid: None,
+            start: MovieStart::PowerOn(RamState::default()),
+            region: NesRegion::Ntsc,
+            four_player: FourPlayer::Disabled,
+            zapper: false,
+            rerecord_count: 0,
+            comments: Vec::new(),
+            frames: Vec::new(),
+        };
+        let mut ports = [FM2_PORT_GAMEPAD, FM2_PORT_GAMEPAD];
+        for (number, line) in fm2.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.starts_with('|') {
+                let frame = Self::parse_fm2_frame(line, movie.four_player, ports)
+                    .with_context(|| format!("invalid frame on line {}", number + 1))?;
+                movie.frames.push(frame);
+                continue;
+            }
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "version" if value != "3" => bail!("unsupported fm2 version: {value}"),
+                "binary" if value != "0" => bail!("binary fm2 movies are not supported"),
+                "savestate" => bail!("fm2 movies starting from a savestate are not supported"),
+                "palFlag" if value == "1" => movie.region = NesRegion::Pal,
+                "romFilename" => movie.rom_name = value.to_string(),
+                "romChecksum" => movie.rom_checksum = Some(value.to_string()),
+                "guid" => movie.guid = Some(value.to_string()),
+                "rerecordCount" => movie.rerecord_count = value.parse().unwrap_or_default(),
+                "fourscore" if value == "1" => movie.four_player = FourPlayer::FourScore,
+                "port0" | "port1" => {
+                    let port = usize::from(key == "port1");
+                    ports[port] = value
+                        .parse()
+                        .map_err(|err| anyhow!("invalid port: {err}"))?;
+                    if ports[port] == FM2_PORT_ZAPPER {
+                        // The zapper is always connected to the second controller port
+                        if port == 0 {
+                            bail!("zapper in port0 is not supported");
+                        }
+                        movie.zapper = true;
+                    }
+                }
+                "comment" => movie.comments.push(value.to_string()),
+                "nesCoreRomCrc32" => {
+                    movie.rom_crc32 =
+                        Some(u32::from_str_radix(value, 16).map_err(|err| {
+                        anyhow!("invalid rom crc32: {err}")
+                    })?);
+                }
+                "nesCoreRamState" => {
+                    let ram_state = value.parse().map_err(|err: &str| anyhow!(err))?;
+                    movie.start = MovieStart::PowerOn(ram_state);
+                }
+                "nesCoreState" => {
+                    let state = value
+                        .strip_prefix("base64:")
+                        .and_then(base64_decode)
+                        .ok_or_else(|| anyhow!("invalid nesCoreState"))?;
+                    movie.start = MovieStart::State(state);
+                }
+                _ => (),
+            }
+        }
+        Ok(movie)
+    }
+
+    fn parse_fm2_frame(line: &str, four_player: FourPlayer, ports: [u8; 2]) -> Result<MovieFrame> {
+        let mut fields = line.split('|').skip(1);
+        let mut frame = MovieFrame::default();
+        let commands: u8 = fields
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .parse()
+            .map_err(|err| anyhow!("invalid commands: {err}"))?;
+        if commands & FM2_HARD_RESET == FM2_HARD_RESET {
+            frame.reset = Some(ResetKind::Hard);
+        } else if commands & FM2_SOFT_RESET == FM2_SOFT_RESET {
+            frame.reset = Some(ResetKind::Soft);
+        }
+        if four_player == FourPlayer::Disabled {
+            for (port, joypad) in ports.into_iter().zip(frame.joypads.iter_mut()) {
+                let field = fields.next().unwrap_or_default();
+                match port {
+                    FM2_PORT_GAMEPAD => *joypad = Self::parse_fm2_gamepad(field)?,
+                    FM2_PORT_ZAPPER => frame.zapper = Some(Self::parse_fm2_zapper(field)?),
+                    FM2_PORT_NONE => (),
+                    _ => bail!("unsupported input device: {port}"),
+                }
+            }
+        } else {
+            for joypad in &mut frame.joypads {
+                *joypad = Self::parse_fm2_gamepad(fields.next().unwrap_or_default())?;
+            }
+        }
+        Ok(frame)
+    }
+
+    fn parse_fm2_gamepad(field: &str) -> Result<JoypadBtnState> {
+        if field.len() != FM2_BUTTONS.len() {
+            bail!("invalid gamepad input: {field:?}");
+        }
+        let mut buttons = JoypadBtnState::empty();
+        for (c, (_, button)) in field.chars().zip(FM2_BUTTONS) {
+            buttons.set(button, c != '.' && c != ' ');
+        }
+        Ok(buttons)
+    }
+
+    fn parse_fm2_zapper(field: &str) -> Result<ZapperInput> {
+        let mut values = field.split_whitespace().map(str::parse::<i32>);
+        let mut next = || {
+            values
+                .next()
+                .unwrap_or(Ok(0))
+                .map_err(|_| anyhow!("invalid zapper input: {field:?}"))
+        };
+        Ok(ZapperInput {
+            x: next()?,
+            y: next()?,
+            trigger: next()? & 0x01 == 0x01,
+        })
+    }
+
+    /// Export the movie in FCEUX text movie format (`.fm2`).
+    ///
+    /// Frame hashes and turbo buttons are not representable and are omitted. Dendy movies are
+    /// exported as NTSC.
+    #[must_use]
+    pub fn to_fm2(&self) -> String {
+        let mut fm2 = String::new();
+        let ports = self.fm2_ports();
+        let _ = writeln!(fm2, "version 3");
+        let _ = writeln!(fm2, "emuVersion 22020");
+        let _ = writeln!(fm2, "rerecordCount {}", self.rerecord_count);
+        let _ = writeln!(fm2, "palFlag {}", u8::from(self.region == NesRegion::Pal));
+        let _ = writeln!(fm2, "romFilename {}", self.rom_name);
+        if let Some(checksum) = &self.rom_checksum {
+            let _ = writeln!(fm2, "romChecksum {checksum}");
+        }
+        let guid = self
+            .guid
+            .as_deref()
+            .unwrap_or("00000000-0000-0000-0000-000000000000");
+        let _ = writeln!(fm2, "guid {guid}");
+        let _ = writeln!(
+            fm2,
+            "fourscore {}",
+            u8::from(self.four_player != FourPlayer::Disabled)
+        );
+        let _ = writeln!(fm2, "microphone 0");
+        let _ = writeln!(fm2, "port0 {}", ports[0]);
+        let _ = writeln!(fm2, "port1 {}", ports[1]);
+        let _ = writeln!(fm2, "port2 {FM2_PORT_NONE}");
+        for comment in &self.comments {
+            let _ = writeln!(fm2, "comment {comment}");
+        }
+        if let Some(crc32) = self.rom_crc32 {
+            let _ = writeln!(fm2, "nesCoreRomCrc32 {crc32:08X}");
+        }
+        match &self.start {
+            MovieStart::PowerOn(ram_state) => {
+                let ram_state = match ram_state {
+                    RamState::AllZeros => "all_zeros",
+                    RamState::AllOnes => "all_ones",
+                    RamState::Random => "random",
+                };
+                let _ = writeln!(fm2, "nesCoreRamState {ram_state}");
+            }
+            MovieStart::State(state) => {
+                let _ = writeln!(fm2, "nesCoreState base64:{}", base64_encode(state));
+            }
+        }
+        for frame in &self.frames {
+            let commands = match frame.reset {
+                Some(ResetKind::Soft) => FM2_SOFT_RESET,
+                Some(ResetKind::Hard) => FM2_HARD_RESET,
+                None => 0,
+            };
+            let _ = write!(fm2, "|{commands}|");
+            if self.four_player == FourPlayer::Disabled {
+                for (port, buttons) in ports.into_iter().zip(frame.joypads) {
+                    if port == FM2_PORT_ZAPPER {
+                        let zapper = frame.zapper.unwrap_or_default();
+                        let _ = write!(
+                            fm2,
+                            "{:3} {:3} {} 0 0|",
+                            zapper.x,
+                            zapper.y,
+                            u8::from(zapper.trigger)
+                        );
+                    } else {
+                        Self::write_fm2_gamepad(&mut fm2, buttons);
+                    }
+                }
+            } else {
+                for buttons in frame.joypads {
+                    Self::write_fm2_gamepad(&mut fm2, buttons);
+                }
+            }
+            let _ = writeln!(fm2, "|");
+        }
+        fm2
+    }
+
+    /// FM2 device types connected to the two controller ports. A connected zapper replaces the
+    /// second gamepad, matching where `ControlDeck` reads it from.
+    fn fm2_ports(&self) -> [u8; 2] {
+        if self.four_player == FourPlayer::Disabled && self.zapper {
+            [FM2_PORT_GAMEPAD, FM2_PORT_ZAPPER]
+        } else {
+            [FM2_PORT_GAMEPAD, FM2_PORT_GAMEPAD]
+        }
+    }
+
+    fn write_fm2_gamepad(fm2: &mut String, buttons: JoypadBtnState) {
+        for (c, button) in FM2_BUTTONS {
+            fm2.push(if buttons.contains(button) { c } else { '.' });
+        }
+        fm2.push('|');
+    }
+}
+
+/// Records input from a `ControlDeck` into a `Movie`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct MovieRecorder {
+    movie: Movie,
+    pending: MovieFrame,
+    hash_frames: bool,
+    // Set when a debugger break stopped the current frame, whose input was already captured
+    resuming: bool,
+}
+
+impl MovieRecorder {
+    /// Start recording from `start`. If `hash_frames` is set, a hash of the frame buffer is stored
+    /// for every frame to detect desyncs during playback.
+    ///
+    /// # Errors
+    ///
+    /// If no ROM is loaded or the start state fails to load, an error is returned.
+    pub fn start(deck: &mut ControlDeck, start: MovieStart, hash_frames: bool) -> Result<Self> {
+        let rom_name = deck
+            .loaded_rom()
+            .clone()
+            .ok_or_else(|| anyhow!("no rom loaded"))?;
+        let movie = Movie {
+            rom_name,
+            rom_crc32: deck.rom_crc32(),
+            rom_checksum: None,
+            guid: None,
+            start,
+            region: deck.region(),
+            four_player: deck.four_player(),
+            zapper: deck.cpu().zapper().connected,
+            rerecord_count: 0,
+            comments: Vec::new(),
+            frames: Vec::new(),
+        };
+        movie.apply_start(deck)?;
+        Ok(Self {
+            movie,
+            pending: MovieFrame::default(),
+            hash_frames,
+            resuming: false,
+        })
+    }
+
+    /// Reset the console and record the reset for the next frame.
+    pub fn reset(&mut self, deck: &mut ControlDeck, kind: ResetKind) {
+        deck.reset(kind);
+        if self.pending.reset != Some(ResetKind::Hard) {
+            self.pending.reset = Some(kind);
+        }
+    }
+
+    /// Trigger the zapper and record it for the next frame.
+    pub fn trigger_zapper(&mut self, deck: &mut ControlDeck) {
+        deck.trigger_zapper();
+        self.pending.zapper = Some(ZapperInput {
+            trigger: true,
+            ..ZapperInput::default()
+        });
+    }
+
+    /// Steps the control deck an entire frame, recording the current joypad and zapper state.
+    /// The frame is only recorded once it finishes, so after a debugger break the next call
+    /// resumes it with the input captured when it started.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounteres an invalid opcode, an error is returned.
+    pub fn clock_frame(
+        &mut self,
+        deck: &mut ControlDeck,
+    ) -> Result<ControlFlow<DebugBreak, usize>> {
+        let mut frame = mem::take(&mut self.pending);
+        if self.resuming {
+            // Finish the frame with the input it started with so playback matches
+            for (buttons, player) in frame.joypads.iter().zip(PLAYERS) {
+                deck.joypad_mut(player).set_buttons(*buttons);
+            }
+            if let Some(zapper) = frame.zapper {
+                deck.aim_zapper(zapper.x, zapper.y);
+            }
+        } else {
+            for (buttons, player) in frame.joypads.iter_mut().zip(PLAYERS) {
+                *buttons = deck.joypad(player).buttons();
+            }
+            if self.movie.zapper {
+                let (x, y) = deck.zapper_pos();
+                let trigger = frame.zapper.is_some_and(|zapper| zapper.trigger);
+                frame.zapper = Some(ZapperInput { x, y, trigger });
+            }
+        }
+        match deck.clock_frame()? {
+            ControlFlow::Break(debug_break) => {
+                self.pending = frame;
+                self.resuming = true;
+                Ok(ControlFlow::Break(debug_break))
+            }
+            ControlFlow::Continue(cycles) => {
+                if self.hash_frames {
+                    frame.hash = Some(Movie::frame_hash(deck));
+                }
+                self.movie.frames.push(frame);
+                self.resuming = false;
+                Ok(ControlFlow::Continue(cycles))
+            }
+        }
+    }
+
+    #[inline]
+    pub const fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    /// Stop recording and return the recorded `Movie`.
+    #[inline]
+    pub fn finish(self) -> Movie {
+        self.movie
+    }
+}
+
+/// Result of playing back a single movie frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum PlaybackEvent {
+    /// The frame was played back, running the given number of CPU cycles.
+    Frame(usize),
+    /// The debugger stopped in the middle of the frame. The next call resumes it.
+    Break(DebugBreak),
+    /// The frame buffer hash didn't match the recorded hash.
+    Desync { frame: usize, expected: u32, found: u32 },
+    /// There are no frames left to play back.
+    Finished,
+}
+
+/// Plays back a `Movie` on a `ControlDeck`.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct MoviePlayer {
+    movie: Movie,
+    frame: usize,
+    first_desync: Option<usize>,
+    // Set when a debugger break stopped the current frame, whose input was already applied
+    resuming: bool,
+}
+
+impl MoviePlayer {
+    /// Start playing back `movie`.
+    ///
+    /// # Errors
+    ///
+    /// If the movie was recorded with a different ROM or the start state fails to load, an error
+    /// is returned.
+    pub fn start(deck: &mut ControlDeck, movie: Movie) -> Result<Self> {
+        movie.apply_start(deck)?;
+        Ok(Self {
+            movie,
+            frame: 0,
+            first_desync: None,
+            resuming: false,
+        })
+    }
+
+    /// Steps the control deck an entire frame using the recorded input. After a debugger break
+    /// the next call resumes the same frame without applying its input again.
+    ///
+    /// # Errors
+    ///
+    /// If CPU encounteres an invalid opcode, an error is returned.
+    pub fn clock_frame(&mut self, deck: &mut ControlDeck) -> Result<PlaybackEvent> {
+        let Some(frame) = self.movie.frames.get(self.frame) else {
+            return Ok(PlaybackEvent::Finished);
+        };
+        if !self.resuming {
+            frame.apply(deck);
+        }
+        let cycles = match deck.clock_frame()? {
+            ControlFlow::Break(debug_break) => {
+                self.resuming = true;
+                return Ok(PlaybackEvent::Break(debug_break));
+            }
+            ControlFlow::Continue(cycles) => cycles,
+        };
+        self.resuming = false;
+        let index = self.frame;
+        self.frame += 1;
+        if let Some(expected) = frame.hash {
+            let found = Movie::frame_hash(deck);
+            if found != expected {
+                self.first_desync.get_or_insert(index);
+                return Ok(PlaybackEvent::Desync {
+                    frame: index,
+                    expected,
+                    found,
+                });
+            }
+        }
+        Ok(PlaybackEvent::Frame(cycles))
+    }
+
+    #[inline]
+    pub const fn movie(&self) -> &Movie {
+        &self.movie
+    }
+
+    /// Index of the next frame to be played back.
+    #[inline]
+    #[must_use]
+    pub const fn frame(&self) -> usize {
+        self.frame
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.movie.frames.len()
+    }
+
+    /// Index of the first frame that desynced, if any.
+    #[inline]
+    #[must_use]
+    pub const fn first_desync(&self) -> Option<usize> {
+        self.first_desync
+    }
+}
+
+const BASE64_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_CHARS[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+fn base64_decode(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() / 4 * 3);
+    let mut n = 0u32;
+    let mut bits = 0;
+    for c in data.bytes().filter(|&c| c != b'=') {
+        let value = BASE64_CHARS.iter().position(|&b| b == c)?;
+        n = (n << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((n >> bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{debugger::BreakReason, state::tests::load_deck};
+
+    fn record(deck: &mut ControlDeck, start: MovieStart) -> Movie {
+        let mut recorder = MovieRecorder::start(deck, start, true).expect("valid start");
+        for i in 0..40u16 {
+            let buttons = JoypadBtnState::from_bits_truncate(i.wrapping_mul(37) & 0xFF);
+            deck.joypad_mut(Player::One).set_buttons(buttons);
+            let inverted = JoypadBtnState::from_bits_truncate(!buttons.bits() & 0xFF);
+            deck.joypad_mut(Player::Two).set_buttons(inverted);
+            if i == 20 {
+                recorder.reset(deck, ResetKind::Soft);
+            }
+            let _ = recorder.clock_frame(deck).expect("valid frame");
+        }
+        recorder.finish()
+    }
+
+    fn play(deck: &mut ControlDeck, movie: Movie) -> MoviePlayer {
+        let mut player = MoviePlayer::start(deck, movie).expect("valid start");
+        while !player.is_finished() {
+            let event = player.clock_frame(deck).expect("valid frame");
+            assert!(matches!(event, PlaybackEvent::Frame(_)), "{event:?}");
+        }
+        assert_eq!(player.clock_frame(deck).ok(), Some(PlaybackEvent::Finished));
+        player
+    }
+
+    #[test]
+    fn playback_is_deterministic() {
+        let mut deck = load_deck(0xEA, 1);
+        let movie = record(&mut deck, MovieStart::PowerOn(RamState::AllOnes));
+        let wram = deck.wram().to_vec();
+        assert_eq!(movie.frames.len(), 40);
+        assert_eq!(movie.frames[20].reset, Some(ResetKind::Soft));
+
+        let mut deck = load_deck(0xEA, 1);
+        let _ = deck.clock_frame().expect("valid frame");
+        let player = play(&mut deck, movie);
+        assert_eq!(player.first_desync(), None);
+        assert_eq!(deck.wram(), wram);
+    }
+
+    #[test]
+    fn playback_from_state() {
+        let mut deck = load_deck(0xEA, 1);
+        for _ in 0..5 {
+            let _ = deck.clock_frame().expect("valid frame");
+        }
+        let state = deck.save_state().expect("saved state");
+        let movie = record(&mut deck, MovieStart::State(state));
+        let wram = deck.wram().to_vec();
+
+        let mut deck = load_deck(0xEA, 1);
+        let _ = play(&mut deck, movie);
+        assert_eq!(deck.wram(), wram);
+    }
+
+    #[test]
+    fn playback_desync() {
+        let mut deck = load_deck(0xEA, 1);
+        let mut movie = record(&mut deck, MovieStart::PowerOn(RamState::AllZeros));
+        movie.frames[10].hash = movie.frames[10].hash.map(|hash| !hash);
+
+        let mut player = MoviePlayer::start(&mut deck, movie).expect("valid start");
+        for _ in 0..10 {
+            let _ = player.clock_frame(&mut deck).expect("valid frame");
+        }
+        assert!(matches!(
+            player.clock_frame(&mut deck),
+            Ok(PlaybackEvent::Desync { frame: 10, .. })
+        ));
+        assert_eq!(player.first_desync(), Some(10));
+    }
+
+    #[test]
+    fn debugger_break_mid_frame() {
+        let mut deck = load_deck(0xEA, 1);
+        let start = MovieStart::PowerOn(RamState::AllZeros);
+        let mut recorder = MovieRecorder::start(&mut deck, start, true).expect("valid start");
+        let mut breaks = 0;
+        for i in 0..10 {
+            deck.joypad_mut(Player::One)
+                .set_buttons(JoypadBtnState::from_bits_truncate(i));
+            if i == 5 {
+                recorder.reset(&mut deck, ResetKind::Soft);
+                deck.debugger_mut().add_breakpoint(0x800C, None);
+            }
+            while let ControlFlow::Break(_) = recorder.clock_frame(&mut deck).expect("valid frame")
+            {
+                // Input changed while stopped doesn't apply to the rest of the frame
+                deck.joypad_mut(Player::One).set_buttons(JoypadBtnState::A);
+                deck.debugger_mut().clear();
+                breaks += 1;
+            }
+        }
+        assert_eq!(breaks, 1);
+        let movie = recorder.finish();
+        let wram = deck.wram().to_vec();
+        // Each soft reset moves the stack pointer, so a reset applied twice shows up here
+        let sp = deck.cpu().sp();
+        assert_eq!(movie.frames.len(), 10);
+        assert_eq!(movie.frames[5].reset, Some(ResetKind::Soft));
+        assert_eq!(
+            movie.frames[5].joypads[0],
+            JoypadBtnState::from_bits_truncate(5)
+        );
+
+        let mut deck = load_deck(0xEA, 1);
+        let mut player = MoviePlayer::start(&mut deck, movie).expect("valid start");
+        let mut breaks = 0;
+        while !player.is_finished() {
+            if player.frame() == 5 && breaks == 0 {
+                deck.debugger_mut().add_breakpoint(0x800C, None);
+            }
+            match player.clock_frame(&mut deck).expect("valid frame") {
+                PlaybackEvent::Break(debug_break) => {
+                    assert!(matches!(
+                        debug_break.reason,
+                        BreakReason::Breakpoint { pc: 0x800C, .. }
+                    ));
+                    assert_eq!(player.frame(), 5);
+                    deck.debugger_mut().clear();
+                    breaks += 1;
+                }
+                event => assert!(matches!(event, PlaybackEvent::Frame(_)), "{event:?}"),
+            }
+        }
+        assert_eq!(breaks, 1);
+        assert_eq!(player.first_desync(), None);
+        assert_eq!(deck.wram(), wram);
+        assert_eq!(deck.cpu().sp(), sp);
+    }
+
+    #[test]
+    fn playback_rom_mismatch() {
+        let mut deck = load_deck(0xEA, 1);
+        let movie = record(&mut deck, MovieStart::PowerOn(RamState::AllZeros));
+        let mut other = load_deck(0xFF, 1);
+        assert!(MoviePlayer::start(&mut other, movie).is_err());
+    }
+
+    #[test]
+    fn fm2_round_trip() {
+        let mut deck = load_deck(0xEA, 1);
+        for _ in 0..3 {
+            let _ = deck.clock_frame().expect("valid frame");
+        }
+        let state = deck.save_state().expect("saved state");
+        for start in [MovieStart::PowerOn(RamState::Random), MovieStart::State(state)] {
+            let mut movie = record(&mut deck, start);
+            movie.comments.push("author nes_core".to_string());
+            let fm2 = movie.to_fm2();
+            for frame in &mut movie.frames {
+                frame.hash = None;
+            }
+            let mut imported = Movie::from_fm2(&fm2).expect("valid fm2");
+            // FM2 movies always have a guid
+            imported.guid = None;
+            assert_eq!(imported, movie);
+        }
+    }
+
+    #[test]
+    fn fm2_import() {
+        let fm2 = "version 3\n\
+            emuVersion 22020\n\
+            rerecordCount 7\n\
+            palFlag 1\n\
+            romFilename Zapper Test\n\
+            guid 452DE2C3-EF43-2FA9-77AC-0677FC51543B\n\
+            fourscore 0\n\
+            port0 1\n\
+            port1 2\n\
+            port2 0\n\
+            |0|R......A| 12  34 1 0 0||\n\
+            |2|.L....B.|  0   0 0 0 0||\n";
+        let movie = Movie::from_fm2(fm2).expect("valid fm2");
+        assert_eq!(movie.rom_name, "Zapper Test");
+        assert_eq!(movie.rerecord_count, 7);
+        assert_eq!(movie.region, NesRegion::Pal);
+        assert!(movie.zapper);
+        assert_eq!(movie.frames.len(), 2);
+        assert_eq!(
+            movie.frames[0].joypads[0],
+            JoypadBtnState::RIGHT | JoypadBtnState::A
+        );
+        assert_eq!(
+            movie.frames[0].zapper,
+            Some(ZapperInput {
+                x: 12,
+                y: 34,
+                trigger: true
+            })
+        );
+        assert_eq!(movie.frames[1].reset, Some(ResetKind::Hard));
+        assert_eq!(
+            movie.frames[1].joypads[0],
+            JoypadBtnState::LEFT | JoypadBtnState::B
+        );
+
+        let exported = movie.to_fm2();
+        assert!(exported.contains("port0 1\nport1 2\n"));
+        assert!(exported.contains("|0|R......A| 12  34 1 0 0||\n"));
+
+        assert!(Movie::from_fm2("version 3\nsavestate base64:AAAA\n").is_err());
+        assert!(Movie::from_fm2("version 3\nport0 2\n").is_err());
+        assert!(Movie::from_fm2("version 3\nport0 x\n").is_err());
+        assert!(Movie::from_fm2("version 3\n|0|RLDUTSB|........||\n").is_err());
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        for data in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            let encoded = base64_encode(data);
+            assert_eq!(base64_decode(&encoded).as_deref(), Some(data));
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+    }
+}
@@ -0,0 +1,446 @@
+//! NES Sound Format music rips.
+//!
+//! <https://www.nesdev.org/wiki/NSF>
+//! <https://www.nesdev.org/wiki/NSFe>
+//! <https://www.nesdev.org/wiki/NSF2>
+
+use crate::common::NesRegion;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use anyhow::{bail, Context, Result};
+use bitflags::bitflags;
+use serde::{Deserialize, Serialize};
+
+const NSF_MAGIC: &[u8] = b"NESM\x1A";
+const NSFE_MAGIC: &[u8] = b"NSFE";
+const NSF_HEADER_SIZE: usize = 0x80;
+const BANK_SIZE: usize = 0x1000;
+// Default play rates in microseconds
+const NTSC_PLAY_SPEED: u16 = 16639;
+const PAL_PLAY_SPEED: u16 = 19997;
+
+bitflags! {
+    /// Expansion audio chips used by an NSF.
+    #[derive(Default, Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+    #[must_use]
+    pub struct NsfChips: u8 {
+        const VRC6 = 0x01;
+        const VRC7 = 0x02;
+        const FDS = 0x04;
+        const MMC5 = 0x08;
+        const NAMCO163 = 0x10;
+        const SUNSOFT5B = 0x20;
+    }
+}
+
+/// Per-track metadata from NSFe `time`, `fade` and `tlbl` chunks.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct NsfTrack {
+    pub label: Option<String>,
+    // Milliseconds
+    pub length: Option<u32>,
+    // Milliseconds
+    pub fade: Option<u32>,
+}
+
+/// A parsed NSF, NSF2 or NSFe file.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Nsf {
+    pub title: String,
+    pub artist: String,
+    pub copyright: String,
+    pub ripper: String,
+    pub total_songs: u8,
+    // 0-based
+    pub starting_song: u8,
+    pub load_addr: u16,
+    pub init_addr: u16,
+    pub play_addr: u16,
+    // Play rates in microseconds
+    pub ntsc_speed: u16,
+    pub pal_speed: u16,
+    pub region: NesRegion,
+    pub chips: NsfChips,
+    pub banks: [u8; 8],
+    pub tracks: Vec<NsfTrack>,
+    pub data: Vec<u8>,
+}
+
+impl Nsf {
+    /// Load an NSF, NSF2 or NSFe file.
+    ///
+    /// # Errors
+    ///
+    /// If the file is truncated, has an invalid header, or uses a required NSFe chunk that isn't
+    /// supported, then an error is returned.
+    pub fn load(data: &[u8]) -> Result<Self> {
+        let mut nsf = if data.starts_with(NSF_MAGIC) {
+            Self::load_nsf(data)?
+        } else if let Some(chunks) = data.strip_prefix(NSFE_MAGIC) {
+            let mut nsf = Self {
+                total_songs: 1,
+                ntsc_speed: NTSC_PLAY_SPEED,
+                pal_speed: PAL_PLAY_SPEED,
+                ..Self::default()
+            };
+            nsf.load_chunks(chunks, true)?;
+            nsf
+        } else {
+            bail!("invalid NSF header");
+        };
+        if nsf.total_songs == 0 {
+            bail!("invalid NSF: no songs");
+        }
+        if nsf.data.is_empty() {
+            bail!("invalid NSF: no program data");
+        }
+        if nsf.load_addr < 0x6000 || (nsf.load_addr < 0x8000 && !nsf.chips.contains(NsfChips::FDS))
+        {
+            bail!("invalid NSF load address: ${:04X}", nsf.load_addr);
+        }
+        nsf.starting_song = nsf.starting_song.min(nsf.total_songs - 1);
+        nsf.tracks
+            .resize(nsf.total_songs.into(), NsfTrack::default());
+        Ok(nsf)
+    }
+
+    fn load_nsf(data: &[u8]) -> Result<Self> {
+        let header = data
+            .get(..NSF_HEADER_SIZE)
+            .context("invalid NSF: truncated header")?;
+        let word = |offset: usize| u16::from_le_bytes([header[offset], header[offset + 1]]);
+        let text = |offset: usize| {
+            let field = &header[offset..offset + 32];
+            let len = field.iter().position(|&b| b == 0x00).unwrap_or(field.len());
+            String::from_utf8_lossy(&field[..len]).into_owned()
+        };
+        let version = header[0x05];
+        let mut banks = [0x00; 8];
+        banks.copy_from_slice(&header[0x70..0x78]);
+        // NSF2 stores the program length so NSFe metadata chunks can follow it
+        let data_len = usize::from(header[0x7D])
+            | usize::from(header[0x7E]) << 8
+            | usize::from(header[0x7F]) << 16;
+        let (program, metadata) = if version >= 2 && data_len > 0 {
+            let program = data
+                .get(NSF_HEADER_SIZE..NSF_HEADER_SIZE + data_len)
+                .context("invalid NSF2: truncated program data")?;
+            (program, &data[NSF_HEADER_SIZE + data_len..])
+        } else {
+            (&data[NSF_HEADER_SIZE..], &[][..])
+        };
+        let mut nsf = Self {
+            title: text(0x0E),
+            artist: text(0x2E),
+            copyright: text(0x4E),
+            ripper: String::new(),
+            total_songs: header[0x06],
+            starting_song: header[0x07].saturating_sub(1),
+            load_addr: word(0x08),
+            init_addr: word(0x0A),
+            play_addr: word(0x0C),
+            ntsc_speed: word(0x6E),
+            pal_speed: word(0x78),
+            region: Self::region_from_flags(header[0x7A]),
+            chips: NsfChips::from_bits_truncate(header[0x7B]),
+            banks,
+            tracks: vec![],
+            data: program.to_vec(),
+        };
+        if !metadata.is_empty() {
+            nsf.load_chunks(metadata, false)?;
+        }
+        Ok(nsf)
+    }
+
+    /// Parse NSFe chunks. `header` is false for NSF2 metadata, where the header fields come from
+    /// the NSF header instead.
+    fn load_chunks(&mut self, mut chunks: &[u8], header: bool) -> Result<()> {
+        while chunks.len() >= 8 {
+            let len = u32::from_le_bytes([chunks[0], chunks[1], chunks[2], chunks[3]]) as usize;
+            let id = &chunks[4..8];
+            let chunk = chunks
+                .get(8..8 + len)
+                .context("invalid NSFe: truncated chunk")?;
+            chunks = &chunks[8 + len..];
+            match id {
+                b"INFO" if header => self.load_info(chunk)?,
+                b"DATA" if header => self.data = chunk.to_vec(),
+                b"BANK" if header => {
+                    let len = chunk.len().min(self.banks.len());
+                    self.banks[..len].copy_from_slice(&chunk[..len]);
+                }
+                b"RATE" if header => {
+                    let word = |offset: usize| {
+                        chunk
+                            .get(offset..offset + 2)
+                            .map(|w| u16::from_le_bytes([w[0], w[1]]))
+                    };
+                    self.ntsc_speed = word(0).context("invalid NSFe: truncated RATE")?;
+                    self.pal_speed = word(2).unwrap_or(self.pal_speed);
+                }
+                b"auth" => {
+                    let mut strings = Self::strings(chunk);
+                    let mut next = || strings.next().unwrap_or_default();
+                    self.title = next();
+                    self.artist = next();
+                    self.copyright = next();
+                    self.ripper = next();
+                }
+                b"time" | b"fade" => {
+                    for (i, ms) in chunk.chunks_exact(4).enumerate() {
+                        let ms = i32::from_le_bytes([ms[0], ms[1], ms[2], ms[3]]);
+                        let ms = u32::try_from(ms).ok();
+                        let track = self.track_mut(i);
+                        if id == b"time" {
+                            track.length = ms;
+                        } else {
+                            track.fade = ms;
+                        }
+                    }
+                }
+                b"tlbl" => {
+                    for (i, label) in Self::strings(chunk).enumerate() {
+                        self.track_mut(i).label = Some(label);
+                    }
+                }
+                b"NEND" => break,
+                // Chunks starting with an uppercase letter are required to play correctly
+                [b'A'..=b'Z', ..] if header || !matches!(id, b"DATA" | b"INFO" | b"BANK") => {
+                    bail!("unsupported NSFe chunk: {}", String::from_utf8_lossy(id));
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn load_info(&mut self, chunk: &[u8]) -> Result<()> {
+        if chunk.len() < 8 {
+            bail!("invalid NSFe: truncated INFO");
+        }
+        let word = |offset: usize| u16::from_le_bytes([chunk[offset], chunk[offset + 1]]);
+        self.load_addr = word(0);
+        self.init_addr = word(2);
+        self.play_addr = word(4);
+        self.region = Self::region_from_flags(chunk[6]);
+        self.chips = NsfChips::from_bits_truncate(chunk[7]);
+        self.total_songs = chunk.get(8).copied().unwrap_or(1);
+        self.starting_song = chunk.get(9).copied().unwrap_or(0);
+        Ok(())
+    }
+
+    /// Dual-region rips play as NTSC.
+    const fn region_from_flags(flags: u8) -> NesRegion {
+        if flags & 0x03 == 0x01 {
+            NesRegion::Pal
+        } else {
+            NesRegion::Ntsc
+        }
+    }
+
+    fn strings(chunk: &[u8]) -> impl Iterator<Item = String> + '_ {
+        chunk
+            .strip_suffix(&[0x00])
+            .unwrap_or(chunk)
+            .split(|&b| b == 0x00)
+            .map(|s| String::from_utf8_lossy(s).into_owned())
+    }
+
+    fn track_mut(&mut self, track: usize) -> &mut NsfTrack {
+        if self.tracks.len() <= track {
+            self.tracks.resize(track + 1, NsfTrack::default());
+        }
+        &mut self.tracks[track]
+    }
+
+    /// Returns whether the NSF switches 4K banks into $8000-$FFFF.
+    #[inline]
+    #[must_use]
+    pub fn bankswitched(&self) -> bool {
+        self.banks.iter().any(|&bank| bank != 0x00)
+    }
+
+    /// Play rate in microseconds for `region`.
+    #[inline]
+    #[must_use]
+    pub const fn play_speed(&self, region: NesRegion) -> u16 {
+        let speed = match region {
+            NesRegion::Ntsc => self.ntsc_speed,
+            _ => self.pal_speed,
+        };
+        if speed > 0 {
+            speed
+        } else if matches!(region, NesRegion::Ntsc) {
+            NTSC_PLAY_SPEED
+        } else {
+            PAL_PLAY_SPEED
+        }
+    }
+
+    /// Program data laid out in 4K banks, with the initial bank for each 4K slot from $6000 to
+    /// $FFFF. Without bankswitching, the data is placed at its load address and the slots map
+    /// banks in order, starting at $8000, or at $6000 for FDS rips which can load there.
+    #[must_use]
+    pub fn prg_rom(&self) -> (Vec<u8>, [u8; 10]) {
+        let mut banks = [0x00; 10];
+        let padding = if self.bankswitched() {
+            banks[2..].copy_from_slice(&self.banks);
+            // FDS rips also switch banks into $6000-$7FFF, starting with the last two
+            banks[..2].copy_from_slice(&self.banks[6..]);
+            usize::from(self.load_addr) & (BANK_SIZE - 1)
+        } else if self.chips.contains(NsfChips::FDS) {
+            for (i, bank) in banks.iter_mut().enumerate() {
+                *bank = i as u8;
+            }
+            usize::from(self.load_addr) - 0x6000
+        } else {
+            for (i, bank) in banks.iter_mut().enumerate().skip(2) {
+                *bank = i as u8 - 2;
+            }
+            usize::from(self.load_addr) - 0x8000
+        };
+        let mut prg_rom = vec![0x00; padding];
+        prg_rom.extend_from_slice(&self.data);
+        // Bank numbers wrap, so round up to a power of two covering every initial bank
+        let last_bank = banks.iter().copied().max().unwrap_or_default();
+        let bank_count = prg_rom
+            .len()
+            .div_ceil(BANK_SIZE)
+            .max(usize::from(last_bank) + 1)
+            .next_power_of_two();
+        prg_rom.resize(bank_count * BANK_SIZE, 0x00);
+        (prg_rom, banks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nsf_header(chips: u8, banks: [u8; 8]) -> Vec<u8> {
+        let mut header = vec![0x00; NSF_HEADER_SIZE];
+        header[..5].copy_from_slice(NSF_MAGIC);
+        header[0x05] = 0x01;
+        header[0x06] = 3;
+        header[0x07] = 2;
+        header[0x08..0x0E].copy_from_slice(&[0x00, 0x80, 0x00, 0x80, 0x03, 0x80]);
+        header[0x0E..0x13].copy_from_slice(b"Title");
+        header[0x2E..0x34].copy_from_slice(b"Artist");
+        header[0x4E..0x52].copy_from_slice(b"2024");
+        header[0x6E..0x70].copy_from_slice(&16639u16.to_le_bytes());
+        header[0x70..0x78].copy_from_slice(&banks);
+        header[0x78..0x7A].copy_from_slice(&19997u16.to_le_bytes());
+        header[0x7B] = chips;
+        header
+    }
+
+    fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_le_bytes().to_vec();
+        chunk.extend_from_slice(id);
+        chunk.extend_from_slice(data);
+        chunk
+    }
+
+    #[test]
+    fn load_nsf() {
+        let mut data = nsf_header(0x21, [0; 8]);
+        data.extend_from_slice(&[0x60; 4]);
+        let nsf = Nsf::load(&data).expect("valid nsf");
+        assert_eq!(nsf.title, "Title");
+        assert_eq!(nsf.artist, "Artist");
+        assert_eq!(nsf.copyright, "2024");
+        assert_eq!(nsf.total_songs, 3);
+        assert_eq!(nsf.starting_song, 1);
+        assert_eq!(nsf.tracks.len(), 3);
+        assert_eq!(nsf.chips, NsfChips::VRC6 | NsfChips::SUNSOFT5B);
+        assert_eq!(nsf.play_speed(NesRegion::Pal), 19997);
+        assert!(!nsf.bankswitched());
+
+        let (prg_rom, banks) = nsf.prg_rom();
+        assert_eq!(prg_rom.len(), 0x8000);
+        assert_eq!(&prg_rom[..4], [0x60; 4]);
+        assert_eq!(banks, [0, 0, 0, 1, 2, 3, 4, 5, 6, 7]);
+
+        assert!(Nsf::load(&data[..0x40]).is_err(), "truncated");
+        data[0x09] = 0x40;
+        assert!(Nsf::load(&data).is_err(), "load address");
+    }
+
+    #[test]
+    fn bankswitched_layout() {
+        let mut data = nsf_header(0x00, [0, 1, 2, 3, 4, 5, 6, 9]);
+        data[0x08..0x0A].copy_from_slice(&0x8123u16.to_le_bytes());
+        data.extend_from_slice(&[0xEA; 0x9000]);
+        let nsf = Nsf::load(&data).expect("valid nsf");
+        assert!(nsf.bankswitched());
+        let (prg_rom, banks) = nsf.prg_rom();
+        assert_eq!(prg_rom.len(), 16 * BANK_SIZE);
+        assert_eq!(prg_rom[0x122], 0x00);
+        assert_eq!(prg_rom[0x123], 0xEA);
+        assert_eq!(banks, [6, 9, 0, 1, 2, 3, 4, 5, 6, 9]);
+    }
+
+    #[test]
+    fn load_nsfe() {
+        let mut data = NSFE_MAGIC.to_vec();
+        let mut info = vec![0x00, 0x80, 0x00, 0x80, 0x03, 0x80, 0x01, 0x04, 2, 1];
+        data.extend(chunk(b"INFO", &info));
+        data.extend(chunk(b"DATA", &[0x60; 4]));
+        data.extend(chunk(b"auth", b"Title\0Artist\0Copyright\0Ripper\0"));
+        let mut time = 90_000i32.to_le_bytes().to_vec();
+        time.extend_from_slice(&(-1i32).to_le_bytes());
+        data.extend(chunk(b"time", &time));
+        data.extend(chunk(b"fade", &5_000i32.to_le_bytes()));
+        data.extend(chunk(b"tlbl", b"Intro\0Theme\0"));
+        data.extend(chunk(b"xtra", &[0xFF; 3]));
+        data.extend(chunk(b"NEND", &[]));
+
+        let nsf = Nsf::load(&data).expect("valid nsfe");
+        assert_eq!(nsf.title, "Title");
+        assert_eq!(nsf.ripper, "Ripper");
+        assert_eq!(nsf.region, NesRegion::Pal);
+        assert_eq!(nsf.chips, NsfChips::FDS);
+        assert_eq!(nsf.total_songs, 2);
+        assert_eq!(nsf.starting_song, 1);
+        assert_eq!(nsf.ntsc_speed, NTSC_PLAY_SPEED);
+        assert_eq!(
+            nsf.tracks,
+            [
+                NsfTrack {
+                    label: Some("Intro".into()),
+                    length: Some(90_000),
+                    fade: Some(5_000),
+                },
+                NsfTrack {
+                    label: Some("Theme".into()),
+                    length: None,
+                    fade: None,
+                },
+            ]
+        );
+
+        info.truncate(4);
+        let mut data = NSFE_MAGIC.to_vec();
+        data.extend(chunk(b"INFO", &info));
+        assert!(Nsf::load(&data).is_err(), "truncated INFO");
+        let mut data = NSFE_MAGIC.to_vec();
+        data.extend(chunk(b"XTRA", &[]));
+        assert!(Nsf::load(&data).is_err(), "unsupported required chunk");
+    }
+
+    #[test]
+    fn load_nsf2_metadata() {
+        let mut data = nsf_header(0x00, [0; 8]);
+        data[0x05] = 0x02;
+        data[0x7D] = 0x04;
+        data.extend_from_slice(&[0x60; 4]);
+        data.extend(chunk(b"time", &1_000i32.to_le_bytes()));
+        let nsf = Nsf::load(&data).expect("valid nsf2");
+        assert_eq!(nsf.data, [0x60; 4]);
+        assert_eq!(nsf.tracks[0].length, Some(1_000));
+    }
+}
@@ -0,0 +1,541 @@
+//! Soft-patching ROM images with IPS, UPS and BPS patches.
+//!
+//! Patches apply to the raw file bytes, including any header, before they're loaded.
+//!
+//! <https://zerosoft.zophar.net/ips.php>
+//! <https://www.romhacking.net/documents/392/> (UPS)
+//! <https://www.romhacking.net/documents/746/> (BPS)
+
+use crate::checksum::crc32;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const IPS_MAX_OFFSET: usize = 0xFF_FFFF;
+const IPS_MAX_RECORD: usize = 0xFFFF;
+const UPS_MAGIC: &[u8] = b"UPS1";
+const BPS_MAGIC: &[u8] = b"BPS1";
+// Source, target and patch CRC-32s
+const FOOTER_SIZE: usize = 12;
+// Guards against allocating absurd target sizes from corrupted patches
+const MAX_TARGET_SIZE: usize = 0x0400_0000;
+
+/// Supported patch formats.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum PatchFormat {
+    Ips,
+    Ups,
+    Bps,
+}
+
+impl PatchFormat {
+    /// Detect the format of a patch from its magic bytes.
+    pub fn detect(patch: &[u8]) -> Option<Self> {
+        if patch.starts_with(IPS_MAGIC) {
+            Some(Self::Ips)
+        } else if patch.starts_with(UPS_MAGIC) {
+            Some(Self::Ups)
+        } else if patch.starts_with(BPS_MAGIC) {
+            Some(Self::Bps)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for PatchFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Self::Ips => "IPS",
+            Self::Ups => "UPS",
+            Self::Bps => "BPS",
+        };
+        write!(f, "{s}")
+    }
+}
+
+/// Errors returned when applying a patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum PatchError {
+    /// The patch is not a recognized format.
+    UnknownFormat,
+    /// The patch ends in the middle of a record.
+    Truncated(PatchFormat),
+    /// A record reads or writes outside of the source or target data.
+    OutOfBounds(PatchFormat),
+    /// The patch checksum doesn't match, so the patch file is corrupted.
+    PatchChecksum(PatchFormat),
+    /// The data being patched isn't the ROM the patch was made for.
+    SourceMismatch { expected: u32, found: u32 },
+    /// The patched data doesn't match the checksum stored in the patch.
+    TargetMismatch { expected: u32, found: u32 },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "unrecognized patch format"),
+            Self::Truncated(format) => write!(f, "invalid {format} patch: truncated record"),
+            Self::OutOfBounds(format) => write!(f, "invalid {format} patch: record out of bounds"),
+            Self::PatchChecksum(format) => write!(f, "corrupted {format} patch: checksum mismatch"),
+            Self::SourceMismatch { expected, found } => write!(
+                f,
+                "patch is for a different rom: {found:#010X} (expected {expected:#010X})"
+            ),
+            Self::TargetMismatch { expected, found } => write!(
+                f,
+                "patched rom checksum mismatch: {found:#010X} (expected {expected:#010X})"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for PatchError {}
+
+/// Apply an IPS, UPS or BPS patch to `source`, detecting the format from the patch header.
+///
+/// # Errors
+///
+/// If the patch format is unrecognized, the patch is malformed, or a UPS/BPS checksum doesn't
+/// match, then an error is returned.
+pub fn apply(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    match PatchFormat::detect(patch) {
+        Some(PatchFormat::Ips) => apply_ips(source, patch),
+        Some(PatchFormat::Ups) => apply_ups(source, patch),
+        Some(PatchFormat::Bps) => apply_bps(source, patch),
+        None => Err(PatchError::UnknownFormat),
+    }
+}
+
+/// Create an IPS patch of the bytes that differ between `original` and `modified`, which must be
+/// the same size.
+#[must_use]
+pub fn ips_diff(original: &[u8], modified: &[u8]) -> Vec<u8> {
+    let mut ips = IPS_MAGIC.to_vec();
+    let mut pos = 0;
+    while pos < modified.len().min(IPS_MAX_OFFSET) {
+        if original.get(pos) == Some(&modified[pos]) {
+            pos += 1;
+            continue;
+        }
+        // An offset spelling "EOF" would end the patch early
+        let start = if pos == 0x45_4F46 { pos - 1 } else { pos };
+        let mut end = pos;
+        while end < modified.len()
+            && end - start < IPS_MAX_RECORD
+            && original.get(end) != Some(&modified[end])
+        {
+            end += 1;
+        }
+        ips.extend_from_slice(&(start as u32).to_be_bytes()[1..]);
+        ips.extend_from_slice(&((end - start) as u16).to_be_bytes());
+        ips.extend_from_slice(&modified[start..end]);
+        pos = end;
+    }
+    ips.extend_from_slice(IPS_EOF);
+    ips
+}
+
+/// Apply an IPS patch. Records past the end of `source` grow the data, and an optional 24-bit
+/// length after `EOF` truncates it.
+///
+/// # Errors
+///
+/// If the patch is malformed, then an error is returned.
+pub fn apply_ips(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let truncated = PatchError::Truncated(PatchFormat::Ips);
+    let Some(mut records) = patch.strip_prefix(IPS_MAGIC) else {
+        return Err(PatchError::UnknownFormat);
+    };
+    let mut data = source.to_vec();
+    loop {
+        if let Some(rest) = records.strip_prefix(IPS_EOF) {
+            if let [l1, l2, l3, ..] = *rest {
+                let len = usize::from(l1) << 16 | usize::from(l2) << 8 | usize::from(l3);
+                data.truncate(len);
+            }
+            return Ok(data);
+        }
+        let (offset, len, rest) = match *records {
+            [o1, o2, o3, l1, l2, ref rest @ ..] => (
+                usize::from(o1) << 16 | usize::from(o2) << 8 | usize::from(o3),
+                usize::from(u16::from_be_bytes([l1, l2])),
+                rest,
+            ),
+            _ => return Err(truncated),
+        };
+        let (bytes, rest) = if len == 0 {
+            // RLE record
+            match *rest {
+                [r1, r2, val, ref rest @ ..] => {
+                    (vec![val; usize::from(u16::from_be_bytes([r1, r2]))], rest)
+                }
+                _ => return Err(truncated),
+            }
+        } else {
+            let bytes = rest.get(..len).ok_or(truncated.clone())?;
+            (bytes.to_vec(), &rest[len..])
+        };
+        if data.len() < offset + bytes.len() {
+            data.resize(offset + bytes.len(), 0x00);
+        }
+        data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        records = rest;
+    }
+}
+
+/// Apply a UPS patch. UPS patches are reversible, so applying one to its target data restores
+/// the source.
+///
+/// # Errors
+///
+/// If the patch is malformed or a checksum doesn't match, then an error is returned.
+pub fn apply_ups(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let format = PatchFormat::Ups;
+    let mut reader = PatchReader::new(patch, UPS_MAGIC, format)?;
+    let source_size = reader.read_size()?;
+    let target_size = reader.read_size()?;
+    let [source_crc, target_crc] = reader.footer;
+
+    let found = crc32(source);
+    let (target_size, target_crc) = if source.len() == source_size && found == source_crc {
+        (target_size, target_crc)
+    } else if source.len() == target_size && found == target_crc {
+        (source_size, source_crc)
+    } else {
+        return Err(PatchError::SourceMismatch {
+            expected: source_crc,
+            found,
+        });
+    };
+
+    let mut target = source.to_vec();
+    target.resize(target_size, 0x00);
+    let mut pos = 0;
+    while !reader.is_empty() {
+        pos = reader.read_size()?.saturating_add(pos);
+        loop {
+            let xor = reader.read_u8()?;
+            if let Some(byte) = target.get_mut(pos) {
+                *byte ^= xor;
+            }
+            pos = pos.saturating_add(1);
+            if xor == 0x00 {
+                break;
+            }
+        }
+    }
+    verify_target(&target, target_crc)?;
+    Ok(target)
+}
+
+/// Apply a BPS patch.
+///
+/// # Errors
+///
+/// If the patch is malformed or a checksum doesn't match, then an error is returned.
+pub fn apply_bps(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    const SOURCE_READ: usize = 0;
+    const TARGET_READ: usize = 1;
+    const SOURCE_COPY: usize = 2;
+    const TARGET_COPY: usize = 3;
+
+    let format = PatchFormat::Bps;
+    let out_of_bounds = PatchError::OutOfBounds(format);
+    let mut reader = PatchReader::new(patch, BPS_MAGIC, format)?;
+    let source_size = reader.read_size()?;
+    let target_size = reader.read_size()?;
+    let metadata_size = reader.read_size()?;
+    reader.read_bytes(metadata_size)?;
+    let [source_crc, target_crc] = reader.footer;
+
+    let found = crc32(source);
+    if source.len() != source_size || found != source_crc {
+        return Err(PatchError::SourceMismatch {
+            expected: source_crc,
+            found,
+        });
+    }
+
+    let mut target = Vec::with_capacity(target_size);
+    let mut source_pos = 0usize;
+    let mut target_pos = 0usize;
+    while !reader.is_empty() {
+        let action = reader.read_number()?;
+        let len = usize::try_from(action >> 2)
+            .ok()
+            .and_then(|len| len.checked_add(1))
+            .filter(|len| target.len() + len <= target_size)
+            .ok_or(out_of_bounds.clone())?;
+        match action as usize & 0x03 {
+            SOURCE_READ => {
+                let start = target.len();
+                let bytes = source
+                    .get(start..start + len)
+                    .ok_or(out_of_bounds.clone())?;
+                target.extend_from_slice(bytes);
+            }
+            TARGET_READ => target.extend_from_slice(reader.read_bytes(len)?),
+            SOURCE_COPY => {
+                source_pos = reader.read_offset(source_pos)?;
+                let bytes = source
+                    .get(source_pos..source_pos + len)
+                    .ok_or(out_of_bounds.clone())?;
+                target.extend_from_slice(bytes);
+                source_pos += len;
+            }
+            TARGET_COPY => {
+                target_pos = reader.read_offset(target_pos)?;
+                if target_pos >= target.len() {
+                    return Err(out_of_bounds);
+                }
+                // Copies can overlap the bytes being written to repeat patterns
+                for _ in 0..len {
+                    target.push(target[target_pos]);
+                    target_pos += 1;
+                }
+            }
+            _ => unreachable!("invalid bps action"),
+        }
+    }
+    if target.len() != target_size {
+        return Err(out_of_bounds);
+    }
+    verify_target(&target, target_crc)?;
+    Ok(target)
+}
+
+fn verify_target(target: &[u8], expected: u32) -> Result<(), PatchError> {
+    let found = crc32(target);
+    if found == expected {
+        Ok(())
+    } else {
+        Err(PatchError::TargetMismatch { expected, found })
+    }
+}
+
+/// Reads the variable-length encoded records shared by UPS and BPS patches.
+struct PatchReader<'a> {
+    format: PatchFormat,
+    data: &'a [u8],
+    // Source and target CRC-32s
+    footer: [u32; 2],
+}
+
+impl<'a> PatchReader<'a> {
+    /// Validate the magic bytes and patch checksum, returning a reader over the records.
+    fn new(patch: &'a [u8], magic: &[u8], format: PatchFormat) -> Result<Self, PatchError> {
+        if !patch.starts_with(magic) {
+            return Err(PatchError::UnknownFormat);
+        }
+        let footer_start = patch
+            .len()
+            .checked_sub(FOOTER_SIZE)
+            .filter(|&start| start >= magic.len())
+            .ok_or(PatchError::Truncated(format))?;
+        let crc = |offset: usize| {
+            let bytes = &patch[footer_start + offset..footer_start + offset + 4];
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+        };
+        if crc32(&patch[..patch.len() - 4]) != crc(8) {
+            return Err(PatchError::PatchChecksum(format));
+        }
+        Ok(Self {
+            format,
+            data: &patch[magic.len()..footer_start],
+            footer: [crc(0), crc(4)],
+        })
+    }
+
+    const fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn read_u8(&mut self) -> Result<u8, PatchError> {
+        let (&byte, rest) = self
+            .data
+            .split_first()
+            .ok_or(PatchError::Truncated(self.format))?;
+        self.data = rest;
+        Ok(byte)
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        if len > self.data.len() {
+            return Err(PatchError::Truncated(self.format));
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    /// Read a variable-length number, where each byte holds 7 bits and the high bit marks the
+    /// last byte.
+    fn read_number(&mut self) -> Result<u64, PatchError> {
+        let mut number = 0u64;
+        let mut shift = 1u64;
+        loop {
+            let byte = self.read_u8()?;
+            number = u64::from(byte & 0x7F)
+                .checked_mul(shift)
+                .and_then(|val| number.checked_add(val))
+                .ok_or(PatchError::OutOfBounds(self.format))?;
+            if byte & 0x80 == 0x80 {
+                return Ok(number);
+            }
+            shift = shift
+                .checked_mul(0x80)
+                .ok_or(PatchError::OutOfBounds(self.format))?;
+            number = number
+                .checked_add(shift)
+                .ok_or(PatchError::OutOfBounds(self.format))?;
+        }
+    }
+
+    fn read_size(&mut self) -> Result<usize, PatchError> {
+        usize::try_from(self.read_number()?)
+            .ok()
+            .filter(|&size| size <= MAX_TARGET_SIZE)
+            .ok_or(PatchError::OutOfBounds(self.format))
+    }
+
+    /// Read a signed relative offset, where the low bit is the sign, and apply it to `pos`.
+    fn read_offset(&mut self, pos: usize) -> Result<usize, PatchError> {
+        let number = self.read_number()?;
+        let offset = usize::try_from(number >> 1).ok();
+        let pos = if number & 0x01 == 0x01 {
+            offset.and_then(|offset| pos.checked_sub(offset))
+        } else {
+            offset.and_then(|offset| pos.checked_add(offset))
+        };
+        pos.ok_or(PatchError::OutOfBounds(self.format))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_number(data: &mut Vec<u8>, mut number: usize) {
+        loop {
+            let byte = (number & 0x7F) as u8;
+            number >>= 7;
+            if number == 0 {
+                data.push(byte | 0x80);
+                break;
+            }
+            data.push(byte);
+            number -= 1;
+        }
+    }
+
+    fn with_footer(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(target).to_le_bytes());
+        patch.extend_from_slice(&crc32(&patch).to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn ips_round_trip() {
+        let original = [0x00, 0x01, 0x02, 0x03];
+        let modified = [0x00, 0xAA, 0xBB, 0x03];
+        let ips = ips_diff(&original, &modified);
+        assert_eq!(ips, b"PATCH\x00\x00\x01\x00\x02\xAA\xBBEOF");
+        assert_eq!(apply(&original, &ips), Ok(modified.to_vec()));
+        assert_eq!(
+            apply(&original, b"PATCH\x00\x00"),
+            Err(PatchError::Truncated(PatchFormat::Ips))
+        );
+        assert_eq!(apply(&original, b"NOPE"), Err(PatchError::UnknownFormat));
+    }
+
+    #[test]
+    fn ips_rle() {
+        let data = apply_ips(&[0x00; 4], b"PATCH\x00\x00\x01\x00\x00\x00\x05\xFFEOF")
+            .expect("valid patch");
+        assert_eq!(data, [0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF]);
+    }
+
+    #[test]
+    fn ips_truncation() {
+        let data = apply_ips(&[0x00; 8], b"PATCH\x00\x00\x00\x00\x01\xAAEOF\x00\x00\x03")
+            .expect("valid patch");
+        assert_eq!(data, [0xAA, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn ups() {
+        let source = [0x10, 0x20, 0x30, 0x40];
+        let target = [0x10, 0x21, 0x30, 0x40, 0x50];
+        let mut patch = UPS_MAGIC.to_vec();
+        write_number(&mut patch, source.len());
+        write_number(&mut patch, target.len());
+        // Skip 1, XOR 1 byte, terminator
+        write_number(&mut patch, 1);
+        patch.extend_from_slice(&[0x01, 0x00]);
+        // Skip 1 past the terminated byte, XOR the appended byte
+        write_number(&mut patch, 1);
+        patch.extend_from_slice(&[0x50, 0x00]);
+        let patch = with_footer(patch, &source, &target);
+
+        assert_eq!(apply(&source, &patch), Ok(target.to_vec()));
+        assert_eq!(apply(&target, &patch), Ok(source.to_vec()), "reversed");
+        assert!(matches!(
+            apply(&[0x00; 4], &patch),
+            Err(PatchError::SourceMismatch { .. })
+        ));
+
+        let mut corrupted = patch.clone();
+        corrupted[6] ^= 0xFF;
+        assert_eq!(
+            apply(&source, &corrupted),
+            Err(PatchError::PatchChecksum(PatchFormat::Ups))
+        );
+    }
+
+    #[test]
+    fn bps() {
+        let source = b"ABCDEFGH";
+        let target = b"ABCDxyxyxyGHAB";
+        let mut patch = BPS_MAGIC.to_vec();
+        write_number(&mut patch, source.len());
+        write_number(&mut patch, target.len());
+        write_number(&mut patch, 4);
+        patch.extend_from_slice(b"meta");
+        // SourceRead 4: "ABCD"
+        write_number(&mut patch, 3 << 2);
+        // TargetRead 2: "xy"
+        write_number(&mut patch, (1 << 2) | 1);
+        patch.extend_from_slice(b"xy");
+        // TargetCopy 4 from offset 4: "xyxy"
+        write_number(&mut patch, (3 << 2) | 3);
+        write_number(&mut patch, 4 << 1);
+        // SourceCopy 2 from offset 6: "GH"
+        write_number(&mut patch, (1 << 2) | 2);
+        write_number(&mut patch, 6 << 1);
+        // SourceCopy 2 from offset 0: "AB"
+        write_number(&mut patch, (1 << 2) | 2);
+        write_number(&mut patch, (8 << 1) | 1);
+        let patch = with_footer(patch, source, target);
+
+        assert_eq!(apply(source, &patch), Ok(target.to_vec()));
+        assert!(matches!(
+            apply(b"ABCDEFGX", &patch),
+            Err(PatchError::SourceMismatch { .. })
+        ));
+
+        let mut bad_target = patch[..patch.len() - 12].to_vec();
+        bad_target.extend_from_slice(&crc32(source).to_le_bytes());
+        bad_target.extend_from_slice(&0u32.to_le_bytes());
+        bad_target.extend_from_slice(&crc32(&bad_target).to_le_bytes());
+        assert!(matches!(
+            apply(source, &bad_target),
+            Err(PatchError::TargetMismatch { .. })
+        ));
+    }
+}
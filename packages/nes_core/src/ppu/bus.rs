@@ -2,9 +2,10 @@ use alloc::vec;
 use alloc::vec::Vec;
 use super::Ppu;
 use crate::{
+    cdl::{self, ChrFlags},
     common::{ResetKind, NesRegion, Regional, Reset},
     mapper::{Mapped, MappedRead, MappedWrite, Mapper, MemMap},
-    mem::{Access, Mem},
+    mem::{Access, Mem, RamState},
     ppu::Mirroring,
 };
 use serde::{Deserialize, Serialize};
@@ -33,7 +34,10 @@ pub struct PpuBus {
     mirror_shift: usize,
     ciram: Vec<u8>, // $2007 PPUDATA
     palette: [u8; Self::PALETTE_SIZE],
+    #[serde(skip)]
     chr_rom: Vec<u8>,
+    #[serde(skip)]
+    chr_cdl: Option<Vec<u8>>, // Code/data log flags for CHR-ROM
     chr_ram: Vec<u8>,
     exram: Vec<u8>,
     open_bus: u8,
@@ -56,6 +60,7 @@ impl PpuBus {
             ciram: vec![0x00; Self::VRAM_SIZE],
             palette: [0x00; Self::PALETTE_SIZE],
             chr_rom: vec![],
+            chr_cdl: None,
             chr_ram: vec![],
             exram: vec![],
             open_bus: 0x00,
@@ -77,6 +82,53 @@ impl PpuBus {
         self.chr_rom = chr_rom;
     }
 
+    /// Swaps CHR-ROM and its code/data log, neither of which are serialized.
+    #[inline]
+    pub(crate) fn swap_chr_rom(&mut self, other: &mut Self) {
+        core::mem::swap(&mut self.chr_rom, &mut other.chr_rom);
+        core::mem::swap(&mut self.chr_cdl, &mut other.chr_cdl);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_rom(&self) -> &[u8] {
+        &self.chr_rom
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_cdl(&self) -> Option<&[u8]> {
+        self.chr_cdl.as_deref()
+    }
+
+    #[inline]
+    pub fn set_chr_cdl(&mut self, chr_cdl: Option<Vec<u8>>) {
+        self.chr_cdl = chr_cdl;
+    }
+
+    /// Log an access to the CHR-ROM byte mapped at `addr`. Must be called before the read, as
+    /// some mappers switch banks after reading.
+    #[inline]
+    pub fn log_chr(&mut self, addr: u16, flags: ChrFlags) {
+        let Some(chr_cdl) = &mut self.chr_cdl else {
+            return;
+        };
+        if addr < 0x2000 && self.chr_ram.is_empty() {
+            let offset = match self.mapper.map_peek(addr) {
+                MappedRead::Chr(offset) => offset,
+                MappedRead::CIRam(_) => return,
+                _ => addr.into(),
+            };
+            cdl::log_chr(chr_cdl, offset, flags);
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_ram(&self) -> &[u8] {
+        &self.chr_ram
+    }
+
     #[inline]
     pub fn load_chr_ram(&mut self, chr_ram: Vec<u8>) {
         self.chr_ram = chr_ram;
@@ -87,6 +139,13 @@ impl PpuBus {
         self.exram = ex_ram;
     }
 
+    /// Re-initialize cartridge RAM and nametables as if the console was powered on.
+    pub fn power_on(&mut self, ram_state: RamState) {
+        RamState::fill(&mut self.chr_ram, ram_state);
+        RamState::fill(&mut self.exram, ram_state);
+        self.ciram.fill(0x00);
+    }
+
     #[inline]
     pub fn load_mapper(&mut self, mapper: Mapper) {
         self.mapper = mapper;
@@ -125,6 +184,23 @@ impl PpuBus {
         (nametable) | (!nametable & addr & 0x03FF)
     }
 
+    #[inline]
+    fn read_chr(&self, addr: usize) -> u8 {
+        if self.chr_rom.is_empty() {
+            self.chr_ram[addr]
+        } else {
+            self.chr_rom[addr]
+        }
+    }
+
+    /// CHR-ROM is read-only, so writes only land when the cartridge has CHR-RAM.
+    #[inline]
+    fn write_chr(&mut self, addr: usize, val: u8) {
+        if !self.chr_ram.is_empty() {
+            self.chr_ram[addr] = val;
+        }
+    }
+
     #[inline]
     const fn palette_mirror(&self, addr: usize) -> usize {
         let addr = addr & 0x001F;
@@ -139,19 +215,13 @@ impl PpuBus {
 impl Mem for PpuBus {
     fn read(&mut self, addr: u16, _access: Access) -> u8 {
         let val = match addr {
-            0x0000..=0x1FFF => {
-                let addr = if let MappedRead::Chr(addr) = self.mapper.map_read(addr) {
-                    addr
-                } else {
-                    addr.into()
-                };
-                if self.chr_rom.is_empty() {
-                    self.chr_ram[addr]
-                } else {
-                    self.chr_rom[addr]
-                }
-            }
+            0x0000..=0x1FFF => match self.mapper.map_read(addr) {
+                MappedRead::Chr(addr) => self.read_chr(addr),
+                MappedRead::CIRam(addr) => self.ciram[addr & 0x07FF],
+                _ => self.read_chr(addr.into()),
+            },
             0x2000..=0x3EFF => match self.mapper.map_read(addr) {
+                MappedRead::Chr(addr) => self.read_chr(addr),
                 MappedRead::CIRam(addr) => self.ciram[addr & 0x07FF],
                 MappedRead::ExRam(addr) => self.exram[addr & 0x03FF],
                 MappedRead::Data(data) => data,
@@ -176,6 +246,7 @@ impl Mem for PpuBus {
     fn peek(&self, addr: u16, _access: Access) -> u8 {
         match addr {
             0x2000..=0x3EFF => match self.mapper.map_peek(addr) {
+                MappedRead::Chr(addr) => self.read_chr(addr),
                 MappedRead::CIRam(addr) => self.ciram[addr & 0x07FF],
                 MappedRead::ExRam(addr) => self.exram[addr & 0x03FF],
                 MappedRead::Data(data) => data,
@@ -187,18 +258,11 @@ impl Mem for PpuBus {
                     }
                 }
             },
-            0x0000..=0x1FFF => {
-                let addr = if let MappedRead::Chr(addr) = self.mapper.map_peek(addr) {
-                    addr
-                } else {
-                    addr.into()
-                };
-                if !self.chr_ram.is_empty() {
-                    self.chr_ram[addr]
-                } else {
-                    self.chr_rom[addr]
-                }
-            }
+            0x0000..=0x1FFF => match self.mapper.map_peek(addr) {
+                MappedRead::Chr(addr) => self.read_chr(addr),
+                MappedRead::CIRam(addr) => self.ciram[addr & 0x07FF],
+                _ => self.read_chr(addr.into()),
+            },
             0x3F00..=0x3FFF => self.palette[self.palette_mirror(addr as usize)],
             _ => {
                 log::error!("unexpected PPU memory access at ${:04X}", addr);
@@ -210,6 +274,7 @@ impl Mem for PpuBus {
     fn write(&mut self, addr: u16, val: u8, _access: Access) {
         match addr {
             0x2000..=0x3EFF => match self.mapper.map_write(addr, val) {
+                MappedWrite::Chr(addr, val) => self.write_chr(addr, val),
                 MappedWrite::CIRam(addr, val) => self.ciram[addr] = val,
                 MappedWrite::ExRam(addr, val) => self.exram[addr] = val,
                 _ => {
@@ -219,13 +284,11 @@ impl Mem for PpuBus {
                     }
                 }
             },
-            0x0000..=0x1FFF => {
-                if !self.chr_ram.is_empty() {
-                    if let MappedWrite::Chr(addr, val) = self.mapper.map_write(addr, val) {
-                        self.chr_ram[addr] = val;
-                    }
-                }
-            }
+            0x0000..=0x1FFF => match self.mapper.map_write(addr, val) {
+                MappedWrite::Chr(addr, val) => self.write_chr(addr, val),
+                MappedWrite::CIRam(addr, val) => self.ciram[addr & 0x07FF] = val,
+                _ => (),
+            },
             0x3F00..=0x3FFF => {
                 self.palette[self.palette_mirror(addr as usize)] = val;
             }
@@ -1,9 +1,10 @@
 use alloc::vec;
 use alloc::vec::Vec;
 use crate::{
+    cdl::ChrFlags,
     common::{Clock, ResetKind, NesRegion, Regional, Reset},
     mapper::{Mapped, Mapper},
-    mem::{Access, Mem},
+    mem::{Access, Mem, RamState},
     ppu::{bus::PpuBus, frame::Frame},
 };
 use ctrl::PpuCtrl;
@@ -12,6 +13,7 @@ use scroll::PpuScroll;
 use serde::{Deserialize, Serialize};
 use sprite::Sprite;
 use status::PpuStatus;
+use viewer::PpuView;
 use core::cmp::Ordering;
 
 pub mod bus;
@@ -21,6 +23,7 @@ pub mod mask;
 pub mod scroll;
 pub mod sprite;
 pub mod status;
+pub mod viewer;
 
 /// Nametable Mirroring Mode
 ///
@@ -106,6 +109,11 @@ pub struct Ppu {
     spr_present: Vec<bool>,
 
     open_bus: u8,
+
+    #[serde(skip)]
+    view_scanline: Option<u32>, // Scanline to capture `view` at each frame
+    #[serde(skip)]
+    view: Option<PpuView>,
 }
 
 impl Default for Ppu {
@@ -124,7 +132,7 @@ impl Ppu {
     pub const PALETTE_START: u16 = 0x3F00;
     pub const PALETTE_END: u16 = 0x3F20;
 
-    const OAM_SIZE: usize = 256; // 64 4-byte sprites per frame
+    pub const OAM_SIZE: usize = 256; // 64 4-byte sprites per frame
     const SECONDARY_OAM_SIZE: usize = 32; // 8 4-byte sprites per scanline
 
     // Cycles
@@ -227,6 +235,9 @@ impl Ppu {
             spr_present: vec![false; Self::VISIBLE_END as usize],
 
             open_bus: 0x00,
+
+            view_scanline: None,
+            view: None,
         };
         ppu.set_region(ppu.region);
         ppu
@@ -287,6 +298,43 @@ impl Ppu {
         self.bus.load_chr_rom(chr_rom);
     }
 
+    #[inline]
+    pub fn power_on(&mut self, ram_state: RamState) {
+        self.bus.power_on(ram_state);
+    }
+
+    /// Swaps CHR-ROM, its code/data log and debug views with another `Ppu`, none of which are
+    /// serialized.
+    #[inline]
+    pub(crate) fn swap_unserialized(&mut self, other: &mut Self) {
+        self.bus.swap_chr_rom(&mut other.bus);
+        core::mem::swap(&mut self.view_scanline, &mut other.view_scanline);
+        core::mem::swap(&mut self.view, &mut other.view);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_rom(&self) -> &[u8] {
+        self.bus.chr_rom()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_cdl(&self) -> Option<&[u8]> {
+        self.bus.chr_cdl()
+    }
+
+    #[inline]
+    pub fn set_chr_cdl(&mut self, chr_cdl: Option<Vec<u8>>) {
+        self.bus.set_chr_cdl(chr_cdl);
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn chr_ram(&self) -> &[u8] {
+        self.bus.chr_ram()
+    }
+
     #[inline]
     pub fn load_chr_ram(&mut self, chr_ram: Vec<u8>) {
         self.bus.load_chr_ram(chr_ram);
@@ -341,6 +389,34 @@ impl Ppu {
     pub fn set_open_bus(&mut self, val: u8) {
         self.open_bus = val;
     }
+
+    /// Capture nametables, pattern tables, OAM and palette RAM as they are now.
+    pub fn view(&self) -> PpuView {
+        let mut view = PpuView::new();
+        view.capture(self);
+        view
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn view_scanline(&self) -> Option<u32> {
+        self.view_scanline
+    }
+
+    /// Capture a [`PpuView`] at the start of `scanline` every frame, so games that switch banks
+    /// mid-frame can be inspected, or stop capturing if `None`.
+    pub fn set_view_scanline(&mut self, scanline: Option<u32>) {
+        self.view_scanline = scanline;
+        if scanline.is_none() {
+            self.view = None;
+        }
+    }
+
+    /// The view last captured at [`Ppu::view_scanline`], if any.
+    #[inline]
+    pub const fn captured_view(&self) -> Option<&PpuView> {
+        self.view.as_ref()
+    }
 }
 
 impl Ppu {
@@ -426,8 +502,14 @@ impl Ppu {
         match self.cycle & 0x07 {
             1 => self.fetch_bg_nt_byte(),
             3 => self.fetch_bg_attr_byte(),
-            5 => self.tile_lo = self.bus.read(self.tile_addr, Access::Read),
-            7 => self.tile_hi = self.bus.read(self.tile_addr + 8, Access::Read),
+            5 => {
+                self.bus.log_chr(self.tile_addr, ChrFlags::DRAWN);
+                self.tile_lo = self.bus.read(self.tile_addr, Access::Read);
+            }
+            7 => {
+                self.bus.log_chr(self.tile_addr + 8, ChrFlags::DRAWN);
+                self.tile_hi = self.bus.read(self.tile_addr + 8, Access::Read);
+            }
             _ => (),
         }
     }
@@ -582,6 +664,8 @@ impl Ppu {
             };
 
             if idx < self.spr_count {
+                self.bus.log_chr(tile_addr, ChrFlags::DRAWN);
+                self.bus.log_chr(tile_addr + 8, ChrFlags::DRAWN);
                 let sprite = &mut self.sprites[idx];
                 sprite.x = x;
                 sprite.y = y;
@@ -1031,6 +1115,7 @@ impl PpuRegisters for Ppu {
 
         // Buffering quirk resulting in a dummy read for the CPU
         // for reading pre-palette data in $0000 - $3EFF
+        self.bus.log_chr(addr, ChrFlags::READ);
         let val = self.bus.read(addr, Access::Read);
         let val = if addr < Self::PALETTE_START {
             let buffer = self.vram_buffer;
@@ -1108,6 +1193,11 @@ impl Clock for Ppu {
             } else if self.scanline > self.prerender_scanline {
                 self.scanline = 0;
             }
+            if self.view_scanline == Some(self.scanline) {
+                let mut view = self.view.take().unwrap_or_default();
+                view.capture(self);
+                self.view = Some(view);
+            }
         } else {
             // cycle > 0
             self.cycle += 1;
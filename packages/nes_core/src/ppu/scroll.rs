@@ -68,6 +68,13 @@ impl PpuScroll {
         self.v
     }
 
+    // Returns temporary PPUADDR t, which holds the scroll position for the next frame
+    #[inline]
+    #[must_use]
+    pub const fn temp_addr(&self) -> u16 {
+        self.t
+    }
+
     // Writes to PPUSCROLL affect v and t
     // 1st write writes X
     // 2nd write writes Y
@@ -0,0 +1,468 @@
+//! Debug views of PPU memory: nametables, pattern tables, OAM sprites and palette RAM.
+//!
+//! A [`PpuView`] copies everything through the mapper at a single point in time, so games that
+//! switch CHR banks, mirroring or scroll mid-frame can be inspected as they were at a chosen
+//! scanline. See [`Ppu::set_view_scanline`].
+
+use crate::{
+    mem::{Access, Mem},
+    ppu::{ctrl::PpuCtrl, Ppu},
+};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A sprite decoded from OAM.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct OamSprite {
+    /// Position in OAM, from 0 to 63.
+    pub index: u8,
+    pub x: u8,
+    /// Top of the sprite minus one, as sprites are drawn starting on the next scanline.
+    pub y: u8,
+    pub tile: u8,
+    /// Pattern table address of the (top) tile.
+    pub tile_addr: u16,
+    /// Palette from 4 to 7, as used by [`PpuView::pattern_table`].
+    pub palette: u8,
+    /// Drawn behind the background.
+    pub bg_priority: bool,
+    pub flip_horizontal: bool,
+    pub flip_vertical: bool,
+    /// 8 or 16 pixels tall.
+    pub height: u8,
+}
+
+impl OamSprite {
+    fn new(index: usize, oam: &[u8], ctrl: PpuCtrl) -> Self {
+        let [y, tile, attr, x] = [oam[0], oam[1], oam[2], oam[3]];
+        let (tile_addr, height) = if ctrl.spr_height() == 16 {
+            // Bit 0 of the tile index selects the pattern table
+            let table = if tile & 0x01 == 0x01 { 0x1000 } else { 0x0000 };
+            (table | (u16::from(tile & 0xFE) << 4), 16)
+        } else {
+            (ctrl.spr_select() | (u16::from(tile) << 4), 8)
+        };
+        Self {
+            index: index as u8,
+            x,
+            y,
+            tile,
+            tile_addr,
+            palette: 4 + (attr & 0x03),
+            bg_priority: attr & 0x20 == 0x20,
+            flip_horizontal: attr & 0x40 == 0x40,
+            flip_vertical: attr & 0x80 == 0x80,
+            height,
+        }
+    }
+}
+
+/// PPU memory captured for debug viewers.
+///
+/// Images are RGBA with 4 bytes per pixel, using the colors of [`Ppu::system_palette`] and
+/// ignoring grayscale and color emphasis.
+#[derive(Clone)]
+#[must_use]
+pub struct PpuView {
+    scanline: u32,
+    ctrl: PpuCtrl,
+    scroll_x: u32, // Top-left of the visible screen within the nametables image
+    scroll_y: u32,
+    chr: Vec<u8>,        // $0000-$1FFF pattern tables
+    nametables: Vec<u8>, // $2000-$2FFF
+    palette: [u8; Self::PALETTE_SIZE],
+    oam: Vec<u8>,
+}
+
+impl Default for PpuView {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PpuView {
+    /// Width of the image of all four nametables.
+    pub const NAMETABLES_WIDTH: u32 = 2 * Ppu::WIDTH;
+    /// Height of the image of all four nametables.
+    pub const NAMETABLES_HEIGHT: u32 = 2 * Ppu::HEIGHT;
+    /// Width and height of a pattern table image, 16x16 tiles of 8x8 pixels.
+    pub const PATTERN_TABLE_SIZE: u32 = 128;
+    pub const PALETTE_SIZE: usize = 32;
+
+    const CHR_SIZE: usize = 0x2000;
+    const NT_COUNT: usize = 4;
+    const ATTR_OFFSET: usize = 0x03C0;
+    // Background tiles for the start of a scanline are fetched at the end of the previous one
+    const PREFETCH_WIDTH: u32 = 16;
+
+    pub fn new() -> Self {
+        Self {
+            scanline: 0,
+            ctrl: PpuCtrl::new(),
+            scroll_x: 0,
+            scroll_y: 0,
+            chr: vec![0x00; Self::CHR_SIZE],
+            nametables: vec![0x00; Self::NT_COUNT * Ppu::NT_SIZE as usize],
+            palette: [0x00; Self::PALETTE_SIZE],
+            oam: vec![0x00; Ppu::OAM_SIZE],
+        }
+    }
+
+    /// Copy the current state of `ppu`, reusing allocated buffers.
+    pub(super) fn capture(&mut self, ppu: &Ppu) {
+        self.scanline = ppu.scanline;
+        self.ctrl = ppu.ctrl;
+
+        // Without CHR there's nothing mapped to read
+        let has_chr = !ppu.bus.chr_rom().is_empty() || !ppu.bus.chr_ram().is_empty();
+        for (addr, val) in (0x0000..).zip(self.chr.iter_mut()) {
+            *val = if has_chr {
+                ppu.bus.peek(addr, Access::Dummy)
+            } else {
+                0x00
+            };
+        }
+        for (addr, val) in (Ppu::NT_START..).zip(self.nametables.iter_mut()) {
+            *val = ppu.bus.peek(addr, Access::Dummy);
+        }
+        for (addr, val) in (Ppu::PALETTE_START..).zip(self.palette.iter_mut()) {
+            *val = ppu.bus.peek(addr, Access::Dummy);
+        }
+        self.oam.copy_from_slice(&ppu.oamdata);
+
+        // While rendering, v has already been scrolled to the current scanline and the first two
+        // tiles. Otherwise, t holds the scroll for the top of the next frame.
+        let rendering = ppu.rendering_enabled() && ppu.scanline <= Ppu::VISIBLE_SCANLINE_END;
+        let (addr, x_offset, y_offset) = if rendering {
+            (ppu.scroll.read_addr(), Self::PREFETCH_WIDTH, ppu.scanline)
+        } else {
+            (ppu.scroll.temp_addr(), 0, 0)
+        };
+        let addr = u32::from(addr);
+        let coarse_x = (addr & 0x1F) | ((addr >> 5) & 0x20); // Includes nametable X
+        let coarse_y = (addr >> 5) & 0x1F;
+        let nametable_y = (addr >> 11) & 0x01;
+        let fine_y = (addr >> 12) & 0x07;
+        let (width, height) = (Self::NAMETABLES_WIDTH, Self::NAMETABLES_HEIGHT);
+        self.scroll_x = (8 * coarse_x + u32::from(ppu.scroll.fine_x()) + width - x_offset) % width;
+        self.scroll_y =
+            (nametable_y * Ppu::HEIGHT + 8 * coarse_y + fine_y + 2 * height - y_offset) % height;
+    }
+
+    /// The scanline this view was captured at.
+    #[inline]
+    #[must_use]
+    pub const fn scanline(&self) -> u32 {
+        self.scanline
+    }
+
+    #[inline]
+    pub const fn ctrl(&self) -> PpuCtrl {
+        self.ctrl
+    }
+
+    /// Top-left `(x, y)` of the visible screen within [`PpuView::nametables`].
+    #[inline]
+    #[must_use]
+    pub const fn scroll(&self) -> (u32, u32) {
+        (self.scroll_x, self.scroll_y)
+    }
+
+    /// Palette RAM from $3F00 to $3F1F as system palette indexes, with mirrors resolved.
+    #[inline]
+    #[must_use]
+    pub const fn palette(&self) -> &[u8; Self::PALETTE_SIZE] {
+        &self.palette
+    }
+
+    /// Raw OAM, 4 bytes per sprite.
+    #[inline]
+    #[must_use]
+    pub fn oam(&self) -> &[u8] {
+        &self.oam
+    }
+
+    /// All 64 sprites in OAM order.
+    #[must_use]
+    pub fn sprites(&self) -> Vec<OamSprite> {
+        self.oam
+            .chunks_exact(4)
+            .enumerate()
+            .map(|(index, oam)| OamSprite::new(index, oam, self.ctrl))
+            .collect()
+    }
+
+    /// A `NAMETABLES_WIDTH` x `NAMETABLES_HEIGHT` image of the four nametables using the
+    /// background pattern table, optionally outlining the visible screen.
+    #[must_use]
+    pub fn nametables(&self, show_scroll: bool) -> Vec<u8> {
+        let width = Self::NAMETABLES_WIDTH;
+        let mut image = vec![0x00; (4 * width * Self::NAMETABLES_HEIGHT) as usize];
+        let bg_select = self.ctrl.bg_select();
+        for (nametable, data) in (0..).zip(self.nametables.chunks_exact(Ppu::NT_SIZE as usize)) {
+            let x = (nametable & 0x01) * Ppu::WIDTH;
+            let y = (nametable >> 1) * Ppu::HEIGHT;
+            for tile_y in 0..30 {
+                for tile_x in 0..32 {
+                    let tile = data[tile_y * 32 + tile_x];
+                    let attr = data[Self::ATTR_OFFSET + (tile_y >> 2) * 8 + (tile_x >> 2)];
+                    let shift = ((tile_y & 0x02) << 1) | (tile_x & 0x02);
+                    self.draw_tile(
+                        &mut image,
+                        width,
+                        bg_select | (u16::from(tile) << 4),
+                        (attr >> shift) & 0x03,
+                        x + 8 * tile_x as u32,
+                        y + 8 * tile_y as u32,
+                    );
+                }
+            }
+        }
+        if show_scroll {
+            self.draw_scroll(&mut image);
+        }
+        image
+    }
+
+    /// A `PATTERN_TABLE_SIZE` square image of pattern table 0 ($0000) or 1 ($1000), colored
+    /// with one of the 4 background (0-3) or sprite (4-7) palettes.
+    #[must_use]
+    pub fn pattern_table(&self, table: u16, palette: u8) -> Vec<u8> {
+        let size = Self::PATTERN_TABLE_SIZE;
+        let mut image = vec![0x00; (4 * size * size) as usize];
+        let table = (table & 0x01) << 12;
+        for tile in 0..256 {
+            self.draw_tile(
+                &mut image,
+                size,
+                table | (tile << 4),
+                palette & 0x07,
+                8 * u32::from(tile & 0x0F),
+                8 * u32::from(tile >> 4),
+            );
+        }
+        image
+    }
+
+    // RGBA color of a palette RAM entry
+    fn color(&self, entry: u8) -> [u8; 4] {
+        let (red, green, blue) =
+            Ppu::system_palette(self.palette[usize::from(entry) & 0x1F].into());
+        [red, green, blue, 0xFF]
+    }
+
+    fn draw_tile(&self, image: &mut [u8], width: u32, tile_addr: u16, palette: u8, x: u32, y: u32) {
+        for row in 0..8 {
+            let addr = usize::from(tile_addr) + row;
+            let lo = self.chr[addr & (Self::CHR_SIZE - 1)];
+            let hi = self.chr[(addr + 8) & (Self::CHR_SIZE - 1)];
+            for col in 0..8 {
+                let color = (((hi << col) & 0x80) >> 6) | (((lo << col) & 0x80) >> 7);
+                // Color 0 of every palette is the shared backdrop color
+                let entry = if color == 0 {
+                    0
+                } else {
+                    (palette << 2) | color
+                };
+                let idx = 4 * ((y + row as u32) * width + x + col) as usize;
+                image[idx..idx + 4].copy_from_slice(&self.color(entry));
+            }
+        }
+    }
+
+    // Invert the colors along the edge of the visible screen, wrapping around the nametables
+    fn draw_scroll(&self, image: &mut [u8]) {
+        let (width, height) = (Self::NAMETABLES_WIDTH, Self::NAMETABLES_HEIGHT);
+        let mut invert = |x: u32, y: u32| {
+            let idx = 4 * (((self.scroll_y + y) % height) * width + (self.scroll_x + x) % width);
+            for color in &mut image[idx as usize..idx as usize + 3] {
+                *color = !*color;
+            }
+        };
+        for x in 0..Ppu::WIDTH {
+            invert(x, 0);
+            invert(x, Ppu::HEIGHT - 1);
+        }
+        for y in 1..Ppu::HEIGHT - 1 {
+            invert(0, y);
+            invert(Ppu::WIDTH - 1, y);
+        }
+    }
+}
+
+impl fmt::Debug for PpuView {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PpuView")
+            .field("scanline", &self.scanline)
+            .field("ctrl", &self.ctrl)
+            .field("scroll_x", &self.scroll_x)
+            .field("scroll_y", &self.scroll_y)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cart::Cart, common::Clock, ppu::PpuRegisters};
+
+    fn ppu() -> Ppu {
+        let mut ppu = Ppu::default();
+        ppu.load_mapper(Cart::empty().mapper);
+        ppu.load_chr_ram(vec![0x00; 0x2000]);
+        ppu
+    }
+
+    fn write(ppu: &mut Ppu, addr: u16, data: &[u8]) {
+        ppu.write_addr((addr >> 8) as u8);
+        ppu.write_addr(addr as u8);
+        for val in data {
+            ppu.write_data(*val);
+        }
+    }
+
+    fn pixel(image: &[u8], width: u32, x: u32, y: u32) -> &[u8] {
+        let idx = 4 * (y * width + x) as usize;
+        &image[idx..idx + 4]
+    }
+
+    #[test]
+    fn pattern_tables() {
+        let mut ppu = ppu();
+        // Tile $11 in table 1: row 0 is colors 0, 1, 2, 3, 0...
+        write(&mut ppu, 0x1110, &[0b0101_0000]);
+        write(&mut ppu, 0x1118, &[0b0011_0000]);
+        write(&mut ppu, 0x3F00, &[0x0F, 0x01, 0x02, 0x03]);
+        write(&mut ppu, 0x3F14, &[0x20, 0x11, 0x12, 0x13]);
+
+        let view = ppu.view();
+        assert_eq!(view.palette()[0x10], 0x0F, "$3F10 mirrors $3F00");
+        assert_eq!(view.palette()[0x04], 0x20, "$3F04 mirrors $3F14");
+        let color = |entry: u16| {
+            let (red, green, blue) = Ppu::system_palette(entry);
+            [red, green, blue, 0xFF]
+        };
+        let size = PpuView::PATTERN_TABLE_SIZE;
+        let image = view.pattern_table(1, 0);
+        assert_eq!(image.len(), (4 * size * size) as usize);
+        for (x, entry) in [0x0F, 0x01, 0x02, 0x03, 0x0F].into_iter().enumerate() {
+            assert_eq!(
+                pixel(&image, size, 8 + x as u32, 8),
+                color(entry),
+                "palette 0"
+            );
+        }
+        let image = view.pattern_table(1, 5);
+        assert_eq!(pixel(&image, size, 9, 8), color(0x11), "palette 5");
+        let image = view.pattern_table(0, 0);
+        assert_eq!(pixel(&image, size, 9, 8), color(0x0F), "table 0");
+    }
+
+    #[test]
+    fn nametables() {
+        let mut ppu = ppu();
+        // Tile 1 is solid color 3
+        write(&mut ppu, 0x0010, &[0xFF; 16]);
+        write(&mut ppu, 0x3F00, &[0x0F, 0x00, 0x00, 0x01]);
+        write(&mut ppu, 0x3F0C, &[0x0F, 0x00, 0x00, 0x02]);
+        // Horizontal mirroring: $2800 is the second nametable
+        write(&mut ppu, 0x2801, &[0x01]);
+        write(&mut ppu, 0x2BC0, &[0x03]);
+        ppu.write_ctrl(0x00);
+        ppu.write_scroll(12);
+        ppu.write_scroll(4);
+
+        let view = ppu.view();
+        assert_eq!(view.scroll(), (12, 4));
+        let width = PpuView::NAMETABLES_WIDTH;
+        let image = view.nametables(false);
+        let (red, green, blue) = Ppu::system_palette(0x02);
+        assert_eq!(pixel(&image, width, 8, 240), [red, green, blue, 0xFF]);
+        assert_eq!(
+            pixel(&image, width, 8, 0),
+            pixel(&image, width, 8, 240 + 8),
+            "backdrop"
+        );
+        assert_eq!(
+            pixel(&image, width, 264, 240),
+            pixel(&image, width, 8, 240),
+            "mirrored"
+        );
+
+        let overlay = view.nametables(true);
+        assert_eq!(pixel(&overlay, width, 12, 4)[..3], [!0x00, !0x00, !0x00]);
+        assert_eq!(pixel(&overlay, width, 13, 5), pixel(&image, width, 13, 5));
+        assert_ne!(
+            pixel(&overlay, width, 12, 243),
+            pixel(&image, width, 12, 243),
+            "left edge"
+        );
+        assert_ne!(
+            pixel(&overlay, width, 12 + 255, 4 + 239),
+            pixel(&image, width, 12 + 255, 4 + 239),
+            "bottom right"
+        );
+    }
+
+    #[test]
+    fn sprites() {
+        let mut ppu = ppu();
+        ppu.write_oamaddr(0x04);
+        for val in [0x20, 0x03, 0xE1, 0x40] {
+            ppu.write_oamdata(val);
+        }
+        let sprite = ppu.view().sprites()[1];
+        assert_eq!(
+            sprite,
+            OamSprite {
+                index: 1,
+                x: 0x40,
+                y: 0x20,
+                tile: 0x03,
+                tile_addr: 0x0030,
+                palette: 5,
+                bg_priority: true,
+                flip_horizontal: true,
+                flip_vertical: true,
+                height: 8,
+            }
+        );
+
+        ppu.write_ctrl(0x20);
+        let sprite = ppu.view().sprites()[1];
+        assert_eq!((sprite.tile_addr, sprite.height), (0x1020, 16), "8x16");
+    }
+
+    #[test]
+    fn view_scanline() {
+        let mut ppu = ppu();
+        write(&mut ppu, 0x0000, &[0xAA]);
+        ppu.set_view_scanline(Some(100));
+        while ppu.scanline() != 100 {
+            ppu.clock();
+        }
+        write(&mut ppu, 0x0000, &[0x55]);
+        let view = ppu.captured_view().expect("captured view");
+        assert_eq!(view.scanline(), 100);
+        assert_eq!(view.chr[0], 0xAA, "captured at start of scanline");
+
+        // Scroll is reloaded on the pre-render scanline and tracked while rendering
+        ppu.write_ctrl(0x01);
+        ppu.write_scroll(12);
+        ppu.write_scroll(4);
+        ppu.write_mask(0x18);
+        while ppu.scanline() != 261 {
+            ppu.clock();
+        }
+        while ppu.scanline() != 100 {
+            ppu.clock();
+        }
+        let view = ppu.captured_view().expect("captured view");
+        assert_eq!(view.scroll(), (256 + 12, 4), "rendering scroll");
+
+        ppu.set_view_scanline(None);
+        assert!(ppu.captured_view().is_none());
+    }
+}
@@ -0,0 +1,368 @@
+//! Rewind buffer built on save states.
+//!
+//! Snapshots are taken every `interval` frames and kept in a ring buffer. Every
+//! `keyframe_interval` snapshots a full save state is stored as a keyframe; the snapshots in
+//! between are stored as the XOR against that keyframe, run-length encoded. Since most of the
+//! console state doesn't change from frame to frame, the XOR is mostly zeros and compresses well.
+//!
+//! The audio generated while running each snapshotted frame is kept alongside it so that it can
+//! be played back in reverse while rewinding.
+
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem;
+
+/// Rewind configuration.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct RewindConfig {
+    /// Maximum number of bytes used by stored snapshots. The oldest snapshots are dropped once
+    /// the budget is exceeded.
+    pub budget: usize,
+    /// Number of frames between snapshots.
+    pub interval: u32,
+    /// Number of snapshots between full keyframes.
+    pub keyframe_interval: u32,
+}
+
+impl Default for RewindConfig {
+    fn default() -> Self {
+        Self {
+            budget: 32 * 1024 * 1024,
+            interval: 2,
+            keyframe_interval: 30,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[must_use]
+struct Snapshot {
+    keyframe: Arc<Vec<u8>>,
+    // RLE encoded XOR against `keyframe`, or `None` if this snapshot is the keyframe
+    delta: Option<Vec<u8>>,
+    len: usize,
+    audio: Vec<f32>,
+}
+
+impl Snapshot {
+    fn size(&self) -> usize {
+        self.delta.as_ref().map_or(0, Vec::len) + self.audio.len() * mem::size_of::<f32>()
+    }
+
+    fn decode(&self) -> Vec<u8> {
+        let Some(delta) = &self.delta else {
+            return self.keyframe.to_vec();
+        };
+        let mut state = rle_decode(delta, self.len);
+        for (byte, key) in state.iter_mut().zip(self.keyframe.iter()) {
+            *byte ^= key;
+        }
+        state
+    }
+}
+
+/// Ring buffer of delta-compressed save states.
+#[derive(Debug, Clone)]
+#[must_use]
+pub struct Rewind {
+    config: RewindConfig,
+    snapshots: VecDeque<Snapshot>,
+    frame_counter: u32,
+    snapshot_counter: u32,
+    size: usize,
+    // Whether the most recent snapshot was taken of the current console state
+    at_snapshot: bool,
+}
+
+impl Rewind {
+    pub fn new(config: RewindConfig) -> Self {
+        Self {
+            config,
+            snapshots: VecDeque::new(),
+            frame_counter: 0,
+            snapshot_counter: 0,
+            size: 0,
+            at_snapshot: false,
+        }
+    }
+
+    #[inline]
+    pub const fn config(&self) -> RewindConfig {
+        self.config
+    }
+
+    /// Number of stored snapshots.
+    #[inline]
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    #[inline]
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Number of bytes used by stored snapshots.
+    #[inline]
+    #[must_use]
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Drop all stored snapshots.
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.frame_counter = 0;
+        self.snapshot_counter = 0;
+        self.size = 0;
+        self.at_snapshot = false;
+    }
+
+    /// Returns whether a snapshot should be taken for the frame that just finished.
+    pub(crate) fn frame_finished(&mut self) -> bool {
+        self.at_snapshot = false;
+        self.frame_counter += 1;
+        if self.frame_counter >= self.config.interval.max(1) {
+            self.frame_counter = 0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Store a save state along with the audio generated for its frame.
+    pub fn push(&mut self, state: Vec<u8>, audio: &[f32]) {
+        let keyframe = match self.snapshots.back() {
+            Some(snapshot) if self.snapshot_counter < self.config.keyframe_interval.max(1) => {
+                Some(Arc::clone(&snapshot.keyframe))
+            }
+            _ => None,
+        };
+        let snapshot = match keyframe {
+            Some(keyframe) => {
+                self.snapshot_counter += 1;
+                let len = state.len();
+                let mut delta = state;
+                for (byte, key) in delta.iter_mut().zip(keyframe.iter()) {
+                    *byte ^= key;
+                }
+                Snapshot {
+                    keyframe,
+                    delta: Some(rle_encode(&delta)),
+                    len,
+                    audio: audio.to_vec(),
+                }
+            }
+            None => {
+                self.snapshot_counter = 1;
+                self.size += state.len();
+                Snapshot {
+                    len: state.len(),
+                    keyframe: Arc::new(state),
+                    delta: None,
+                    audio: audio.to_vec(),
+                }
+            }
+        };
+        self.size += snapshot.size();
+        self.snapshots.push_back(snapshot);
+        self.at_snapshot = true;
+        while self.size > self.config.budget && self.snapshots.len() > 1 {
+            self.pop_front();
+        }
+    }
+
+    /// Remove the most recent snapshot, returning the decoded save state and its audio.
+    pub fn pop(&mut self) -> Option<(Vec<u8>, Vec<f32>)> {
+        let snapshot = self.snapshots.pop_back()?;
+        self.release(&snapshot);
+        // Continue delta encoding against the remaining keyframe, if any
+        self.snapshot_counter = self.snapshot_counter.saturating_sub(1);
+        if !self
+            .snapshots
+            .back()
+            .is_some_and(|back| Arc::ptr_eq(&back.keyframe, &snapshot.keyframe))
+        {
+            self.snapshot_counter = 0;
+        }
+        self.frame_counter = 0;
+        self.at_snapshot = false;
+        let state = snapshot.decode();
+        Some((state, snapshot.audio))
+    }
+
+    /// Remove the most recent snapshot that precedes the current console state, returning the
+    /// decoded save state and its audio. A snapshot taken of the current frame is discarded
+    /// first, since restoring it would leave the console where it is.
+    pub fn step_back(&mut self) -> Option<(Vec<u8>, Vec<f32>)> {
+        if self.at_snapshot {
+            self.pop();
+        }
+        self.pop()
+    }
+
+    fn pop_front(&mut self) {
+        if let Some(snapshot) = self.snapshots.pop_front() {
+            self.release(&snapshot);
+        }
+    }
+
+    fn release(&mut self, snapshot: &Snapshot) {
+        self.size -= snapshot.size();
+        // The ring buffer holds the only other reference to a keyframe
+        if Arc::strong_count(&snapshot.keyframe) == 1 {
+            self.size -= snapshot.keyframe.len();
+        }
+    }
+}
+
+/// Run-length encode XOR data as alternating zero-run and literal-run lengths (LEB128),
+/// each literal run followed by its bytes.
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < data.len() {
+        let zeros = data[i..].iter().take_while(|&&b| b == 0).count();
+        i += zeros;
+        let literals = data[i..].iter().take_while(|&&b| b != 0).count();
+        write_varint(&mut out, zeros);
+        write_varint(&mut out, literals);
+        out.extend_from_slice(&data[i..i + literals]);
+        i += literals;
+    }
+    out
+}
+
+fn rle_decode(data: &[u8], len: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(len);
+    let mut i = 0;
+    while i < data.len() {
+        let zeros = read_varint(data, &mut i);
+        let literals = read_varint(data, &mut i);
+        out.resize(out.len() + zeros, 0x00);
+        out.extend_from_slice(&data[i..i + literals]);
+        i += literals;
+    }
+    out.resize(len, 0x00);
+    out
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: usize) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(data: &[u8], i: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some(&byte) = data.get(*i) {
+        *i += 1;
+        value |= usize::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use super::*;
+    use crate::state::tests::load_deck;
+
+    #[test]
+    fn rle_round_trip() {
+        let data = [0, 0, 0, 1, 2, 0, 3, 0, 0, 0, 0, 0];
+        assert_eq!(rle_decode(&rle_encode(&data), data.len()), data);
+        let zeros = vec![0x00; 300];
+        let encoded = rle_encode(&zeros);
+        assert_eq!(encoded.len(), 3);
+        assert_eq!(rle_decode(&encoded, zeros.len()), zeros);
+    }
+
+    #[test]
+    fn push_pop_order() {
+        let mut rewind = Rewind::new(RewindConfig {
+            keyframe_interval: 3,
+            ..RewindConfig::default()
+        });
+        for i in 0..8u8 {
+            let mut state = vec![0x00; 64];
+            state[usize::from(i)] = i + 1;
+            // State size varies between snapshots
+            state.resize(64 + usize::from(i % 2), 0xAA);
+            rewind.push(state, &[f32::from(i)]);
+        }
+        assert_eq!(rewind.len(), 8);
+        for i in (0..8u8).rev() {
+            let (state, audio) = rewind.pop().expect("snapshot");
+            assert_eq!(state.len(), 64 + usize::from(i % 2));
+            assert_eq!(state[usize::from(i)], i + 1);
+            assert_eq!(audio, [f32::from(i)]);
+        }
+        assert!(rewind.pop().is_none());
+        assert_eq!(rewind.size(), 0);
+    }
+
+    #[test]
+    fn memory_budget() {
+        let mut rewind = Rewind::new(RewindConfig {
+            budget: 1024,
+            interval: 1,
+            keyframe_interval: 4,
+        });
+        for i in 0..64 {
+            rewind.push(vec![i; 256], &[]);
+            assert!(rewind.size() <= 1024, "size: {}", rewind.size());
+        }
+        let (state, _) = rewind.pop().expect("snapshot");
+        assert_eq!(state, vec![63; 256]);
+    }
+
+    #[test]
+    fn control_deck_rewind() {
+        let mut deck = load_deck(0xEA, 1);
+        assert!(deck.rewind_step().expect("rewind disabled").is_none());
+
+        deck.set_rewind(Some(RewindConfig {
+            interval: 1,
+            ..RewindConfig::default()
+        }));
+        let mut wram = Vec::new();
+        for _ in 0..10 {
+            let _ = deck.clock_frame().expect("valid frame");
+            wram.push(deck.wram().to_vec());
+        }
+        assert_eq!(deck.rewind().map(Rewind::len), Some(10));
+
+        // The snapshot of the current frame is skipped
+        for expected in wram.iter().rev().skip(1) {
+            let frame = deck.rewind_step().expect("valid snapshot").expect("snapshot");
+            assert!(!frame.frame_buffer.is_empty());
+            assert!(!frame.audio_samples.is_empty());
+            assert_eq!(deck.wram(), expected.as_slice());
+        }
+        assert!(deck.rewind_step().expect("valid snapshot").is_none());
+
+        // Running past a snapshot restores it on the next step
+        deck.set_rewind(Some(RewindConfig {
+            interval: 2,
+            ..RewindConfig::default()
+        }));
+        let _ = deck.clock_frame().expect("valid frame");
+        let _ = deck.clock_frame().expect("valid frame");
+        let expected = deck.wram().to_vec();
+        let _ = deck.clock_frame().expect("valid frame");
+        let _ = deck.rewind_step().expect("valid snapshot").expect("snapshot");
+        assert_eq!(deck.wram(), expected.as_slice());
+    }
+}
@@ -0,0 +1,225 @@
+//! Versioned binary save states.
+//!
+//! A save state is a small header followed by the serialized `Cpu`, which owns the rest of the
+//! emulated hardware. PRG-ROM and CHR-ROM are never serialized, so a state can only be restored
+//! onto the same cartridge it was taken from.
+//!
+//! | Offset | Size | Contents                               |
+//! |--------|------|----------------------------------------|
+//! | 0      | 4    | Magic `NESS`                           |
+//! | 4      | 2    | Format version, little-endian          |
+//! | 6      | 4    | CRC-32 of PRG-ROM + CHR-ROM, LE        |
+//! | 10     | ..   | `postcard` encoded `Cpu`               |
+
+use alloc::vec::Vec;
+use core::fmt;
+use crate::cpu::Cpu;
+
+/// Magic bytes at the start of every save state.
+pub const MAGIC: [u8; 4] = *b"NESS";
+/// Current save state format version. Bump whenever serialized state changes shape.
+pub const VERSION: u16 = 4;
+/// Size of the save state header in bytes.
+pub const HEADER_SIZE: usize = 10;
+
+/// Errors returned when saving or restoring state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum StateError {
+    /// No ROM is loaded, so there is nothing to save or restore.
+    NoRomLoaded,
+    /// The data does not start with a valid save state header.
+    InvalidHeader,
+    /// The save state was written by an incompatible format version.
+    UnsupportedVersion(u16),
+    /// The save state was taken from a different cartridge.
+    RomMismatch { expected: u32, found: u32 },
+    /// The state payload could not be encoded or decoded.
+    Corrupted,
+}
+
+impl fmt::Display for StateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoRomLoaded => write!(f, "no rom loaded"),
+            Self::InvalidHeader => write!(f, "invalid save state header"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "unsupported save state version: {version} (expected {VERSION})"
+            ),
+            Self::RomMismatch { expected, found } => write!(
+                f,
+                "save state is for a different rom: {found:#010X} (expected {expected:#010X})"
+            ),
+            Self::Corrupted => write!(f, "corrupted save state"),
+        }
+    }
+}
+
+impl core::error::Error for StateError {}
+
+/// Save state header.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct StateHeader {
+    pub version: u16,
+    pub rom_crc32: u32,
+}
+
+impl StateHeader {
+    pub const fn new(rom_crc32: u32) -> Self {
+        Self {
+            version: VERSION,
+            rom_crc32,
+        }
+    }
+
+    /// Parse a header from the start of `data`.
+    ///
+    /// # Errors
+    ///
+    /// If the magic bytes are missing or the data is too short, an error is returned.
+    pub fn load(data: &[u8]) -> Result<Self, StateError> {
+        if data.len() < HEADER_SIZE || data[0..4] != MAGIC {
+            return Err(StateError::InvalidHeader);
+        }
+        Ok(Self {
+            version: u16::from_le_bytes([data[4], data[5]]),
+            rom_crc32: u32::from_le_bytes([data[6], data[7], data[8], data[9]]),
+        })
+    }
+
+    fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&self.version.to_le_bytes());
+        out.extend_from_slice(&self.rom_crc32.to_le_bytes());
+    }
+}
+
+/// Serialize `cpu` into a save state for the ROM identified by `rom_crc32`.
+///
+/// # Errors
+///
+/// If the state fails to serialize, an error is returned.
+pub fn save(cpu: &Cpu, rom_crc32: u32) -> Result<Vec<u8>, StateError> {
+    let mut data = Vec::with_capacity(HEADER_SIZE);
+    StateHeader::new(rom_crc32).write(&mut data);
+    postcard::to_extend(cpu, data).map_err(|_| StateError::Corrupted)
+}
+
+/// Deserialize a save state, verifying it was taken from the ROM identified by `rom_crc32`.
+///
+/// The returned `Cpu` has no PRG-ROM or CHR-ROM loaded. See `Cpu::restore`.
+///
+/// # Errors
+///
+/// If the header is invalid, the version is unsupported, the ROM doesn't match or the payload
+/// fails to deserialize, an error is returned.
+pub fn load(data: &[u8], rom_crc32: u32) -> Result<Cpu, StateError> {
+    let header = StateHeader::load(data)?;
+    if header.version != VERSION {
+        return Err(StateError::UnsupportedVersion(header.version));
+    }
+    if header.rom_crc32 != rom_crc32 {
+        return Err(StateError::RomMismatch {
+            expected: rom_crc32,
+            found: header.rom_crc32,
+        });
+    }
+    postcard::from_bytes(&data[HEADER_SIZE..]).map_err(|_| StateError::Corrupted)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use super::*;
+    use crate::control_deck::ControlDeck;
+
+    /// NROM image that reads joypad one into $0001 and increments $0000 in a loop.
+    pub(crate) fn test_rom(fill: u8, prg_banks: u8) -> Vec<u8> {
+        let mut rom = vec![0x4E, 0x45, 0x53, 0x1A, prg_banks, 0x01, 0x00, 0x00];
+        rom.resize(16, 0x00);
+        let mut prg_rom = vec![fill; usize::from(prg_banks) * 0x4000];
+        #[rustfmt::skip]
+        prg_rom[..26].copy_from_slice(&[
+            0xA9, 0x01, 0x8D, 0x16, 0x40, // LDA #$01; STA $4016
+            0xA9, 0x00, 0x8D, 0x16, 0x40, // LDA #$00; STA $4016
+            0xA2, 0x08,                   // LDX #$08
+            0xAD, 0x16, 0x40,             // LDA $4016
+            0x4A, 0x26, 0x01,             // LSR A; ROL $01
+            0xCA, 0xD0, 0xF7,             // DEX; BNE -9
+            0xE6, 0x00,                   // INC $00
+            0x4C, 0x00, 0x80,             // JMP $8000
+        ]);
+        let vectors = prg_rom.len() - 4;
+        prg_rom[vectors..vectors + 2].copy_from_slice(&[0x00, 0x80]);
+        rom.extend_from_slice(&prg_rom);
+        rom.extend_from_slice(&[0x00; 0x2000]);
+        rom
+    }
+
+    pub(crate) fn load_deck(fill: u8, prg_banks: u8) -> ControlDeck {
+        let mut deck = ControlDeck::new();
+        deck.load_rom("state_test".to_string(), test_rom(fill, prg_banks))
+            .expect("valid rom");
+        deck
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let mut deck = load_deck(0xEA, 1);
+        let _ = deck.clock_frame().expect("valid frame");
+        let state = deck.save_state().expect("saved state");
+        let wram = deck.wram().to_vec();
+        let frame = deck.frame_number();
+
+        let _ = deck.clock_frame().expect("valid frame");
+        assert_ne!(deck.wram(), wram);
+
+        deck.load_state(&state).expect("loaded state");
+        assert_eq!(deck.wram(), wram);
+        assert_eq!(deck.frame_number(), frame);
+        let _ = deck.clock_frame().expect("valid frame after restore");
+    }
+
+    #[test]
+    fn state_excludes_rom() {
+        let deck = load_deck(0xEA, 1);
+        let state = deck.save_state().expect("saved state");
+        let larger_rom = load_deck(0xEA, 2).save_state().expect("saved state");
+        assert_eq!(state.len(), larger_rom.len(), "state size depends on rom size");
+        assert_eq!(
+            StateHeader::load(&state),
+            Ok(StateHeader::new(deck.rom_crc32().expect("rom loaded")))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_states() {
+        let deck = ControlDeck::new();
+        assert_eq!(deck.save_state(), Err(StateError::NoRomLoaded));
+
+        let mut deck = load_deck(0xEA, 1);
+        let other = load_deck(0xFF, 1);
+        let state = other.save_state().expect("saved state");
+        assert!(matches!(
+            deck.load_state(&state),
+            Err(StateError::RomMismatch { .. })
+        ));
+
+        assert_eq!(deck.load_state(b"NES"), Err(StateError::InvalidHeader));
+
+        let mut state = deck.save_state().expect("saved state");
+        state[4] = 0xFF;
+        assert!(matches!(
+            deck.load_state(&state),
+            Err(StateError::UnsupportedVersion(_))
+        ));
+
+        let mut state = deck.save_state().expect("saved state");
+        state.truncate(HEADER_SIZE + 8);
+        assert_eq!(deck.load_state(&state), Err(StateError::Corrupted));
+    }
+}
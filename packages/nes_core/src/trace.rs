@@ -0,0 +1,295 @@
+//! CPU instruction trace logging in the formats of reference emulators, so traces can be diffed
+//! against their logs.
+//!
+//! The [`TraceLogger`] is owned by the `ControlDeck` and logs every instruction before it
+//! executes, writing one line per instruction to a user-supplied sink.
+//!
+//! <https://www.qmtpro.com/~nes/misc/nestest.log>
+
+use crate::{
+    cpu::{
+        instr::AddrMode::{ABS, ABX, ABY, ACC, IDX, IDY, IMM, IMP, IND, REL, ZP0, ZPX, ZPY},
+        Cpu, Status,
+    },
+    debugger::Condition,
+};
+use alloc::{boxed::Box, string::String};
+use core::{
+    fmt::{self, Write},
+    ops::RangeInclusive,
+};
+
+// Column the register state starts at
+const REGS_COLUMN: usize = 48;
+
+/// Layout of each trace line.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum TraceFormat {
+    /// Nintendulator, as used by the canonical `nestest.log`.
+    ///
+    /// `C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD PPU:  0, 21 CYC:7`
+    #[default]
+    Nintendulator,
+    /// Mesen's default trace layout.
+    ///
+    /// `C000  JMP $C5F5                               A:00 X:00 Y:00 S:FD P:nvUbdIzc V:0   H:21  Fr:0 Cycle:7`
+    Mesen,
+}
+
+impl TraceFormat {
+    /// Append the trace line for the instruction about to execute, without a newline.
+    pub fn write_line(self, cpu: &Cpu, line: &mut String) -> fmt::Result {
+        // B only exists when pushed to the stack and U always reads as set
+        let status = cpu.status().difference(Status::B).union(Status::U);
+        let start = line.len();
+        match self {
+            Self::Nintendulator => Self::write_nintendulator(cpu, line)?,
+            Self::Mesen => Self::write_mesen(cpu, line)?,
+        }
+        while line.len() < start + REGS_COLUMN {
+            line.push(' ');
+        }
+        match self {
+            Self::Nintendulator => write!(
+                line,
+                "A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} PPU:{:3},{:3} CYC:{}",
+                cpu.a(),
+                cpu.x(),
+                cpu.y(),
+                status.bits(),
+                cpu.sp(),
+                cpu.ppu_scanline(),
+                cpu.ppu_cycle(),
+                cpu.cycle(),
+            ),
+            Self::Mesen => {
+                write!(
+                    line,
+                    "A:{:02X} X:{:02X} Y:{:02X} S:{:02X} P:",
+                    cpu.a(),
+                    cpu.x(),
+                    cpu.y(),
+                    cpu.sp(),
+                )?;
+                for (flag, set, clear) in [
+                    (Status::N, 'N', 'n'),
+                    (Status::V, 'V', 'v'),
+                    (Status::U, 'U', 'u'),
+                    (Status::B, 'B', 'b'),
+                    (Status::D, 'D', 'd'),
+                    (Status::I, 'I', 'i'),
+                    (Status::Z, 'Z', 'z'),
+                    (Status::C, 'C', 'c'),
+                ] {
+                    line.push(if status.contains(flag) { set } else { clear });
+                }
+                write!(
+                    line,
+                    " V:{:<3} H:{:<3} Fr:{} Cycle:{}",
+                    cpu.ppu_scanline(),
+                    cpu.ppu_cycle(),
+                    cpu.frame_number(),
+                    cpu.cycle(),
+                )
+            }
+        }
+    }
+
+    fn write_nintendulator(cpu: &Cpu, line: &mut String) -> fmt::Result {
+        let disasm = cpu.disassemble(cpu.pc(), None);
+        write!(line, "{:04X}  ", disasm.addr)?;
+        for byte in disasm.bytes() {
+            write!(line, "{byte:02X} ")?;
+        }
+        for _ in disasm.bytes().len()..3 {
+            line.push_str("   ");
+        }
+        let unofficial = if disasm.is_unofficial() { '*' } else { ' ' };
+        write!(line, "{unofficial}{} ", disasm.mnemonic())?;
+
+        let operand = disasm.operand;
+        let effective_addr = disasm.effective_addr.unwrap_or_default();
+        let value = disasm.value.unwrap_or_default();
+        match disasm.addr_mode() {
+            IMM => write!(line, "#${value:02X}"),
+            ZP0 => write!(line, "${operand:02X} = {value:02X}"),
+            ZPX => write!(
+                line,
+                "${operand:02X},X @ {effective_addr:02X} = {value:02X}"
+            ),
+            ZPY => write!(
+                line,
+                "${operand:02X},Y @ {effective_addr:02X} = {value:02X}"
+            ),
+            ABS => match disasm.value {
+                Some(value) => write!(line, "${operand:04X} = {value:02X}"),
+                None => write!(line, "${operand:04X}"),
+            },
+            ABX => write!(
+                line,
+                "${operand:04X},X @ {effective_addr:04X} = {value:02X}"
+            ),
+            ABY => write!(
+                line,
+                "${operand:04X},Y @ {effective_addr:04X} = {value:02X}"
+            ),
+            IND => write!(line, "(${operand:04X}) = {effective_addr:04X}"),
+            IDX => write!(
+                line,
+                "(${operand:02X},X) @ {:02X} = {effective_addr:04X} = {value:02X}",
+                (operand as u8).wrapping_add(cpu.x()),
+            ),
+            IDY => write!(
+                line,
+                "(${operand:02X}),Y = {:04X} @ {effective_addr:04X} = {value:02X}",
+                effective_addr.wrapping_sub(cpu.y().into()),
+            ),
+            REL => write!(line, "${effective_addr:04X}"),
+            ACC => write!(line, "A"),
+            IMP => Ok(()),
+        }
+    }
+
+    fn write_mesen(cpu: &Cpu, line: &mut String) -> fmt::Result {
+        let disasm = cpu.disassemble(cpu.pc(), None);
+        write!(line, "{:04X}  {} ", disasm.addr, disasm.mnemonic())?;
+
+        let operand = disasm.operand;
+        let effective_addr = disasm.effective_addr.unwrap_or_default();
+        match disasm.addr_mode() {
+            IMM => write!(line, "#${operand:02X}")?,
+            ZP0 => write!(line, "${operand:02X}")?,
+            ZPX => write!(line, "${operand:02X},X")?,
+            ZPY => write!(line, "${operand:02X},Y")?,
+            ABS => write!(line, "${operand:04X}")?,
+            ABX => write!(line, "${operand:04X},X")?,
+            ABY => write!(line, "${operand:04X},Y")?,
+            IND => write!(line, "(${operand:04X})")?,
+            IDX => write!(line, "(${operand:02X},X)")?,
+            IDY => write!(line, "(${operand:02X}),Y")?,
+            REL => write!(line, "${effective_addr:04X}")?,
+            ACC => line.push('A'),
+            IMP => (),
+        }
+        if matches!(disasm.addr_mode(), ZPX | ZPY | ABX | ABY | IND | IDX | IDY) {
+            write!(line, " [${effective_addr:04X}]")?;
+        }
+        match disasm.value {
+            Some(value) if disasm.addr_mode() != IMM => write!(line, " = ${value:02X}"),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Writes a trace line for each executed instruction to a sink.
+#[must_use]
+pub struct TraceLogger {
+    format: TraceFormat,
+    pc_range: Option<RangeInclusive<u16>>,
+    start: Option<Condition>,
+    stop: Option<Condition>,
+    // Whether instructions are currently logged, toggled by the start/stop conditions
+    active: bool,
+    line: String,
+    sink: Box<dyn Write + Send>,
+}
+
+impl TraceLogger {
+    /// Create a logger writing lines in the default format to `sink`.
+    pub fn new(sink: impl Write + Send + 'static) -> Self {
+        Self {
+            format: TraceFormat::default(),
+            pc_range: None,
+            start: None,
+            stop: None,
+            active: true,
+            line: String::with_capacity(128),
+            sink: Box::new(sink),
+        }
+    }
+
+    #[inline]
+    pub const fn format(&self) -> TraceFormat {
+        self.format
+    }
+
+    #[inline]
+    pub fn set_format(&mut self, format: TraceFormat) {
+        self.format = format;
+    }
+
+    #[inline]
+    #[must_use]
+    pub const fn pc_range(&self) -> Option<&RangeInclusive<u16>> {
+        self.pc_range.as_ref()
+    }
+
+    /// Only log instructions with a PC in `range`, or every instruction if `None`.
+    #[inline]
+    pub fn set_pc_range(&mut self, range: Option<RangeInclusive<u16>>) {
+        self.pc_range = range;
+    }
+
+    /// Wait to log until `condition` is met before an instruction, or log right away if `None`.
+    pub fn set_start(&mut self, condition: Option<Condition>) {
+        self.active = condition.is_none();
+        self.start = condition;
+    }
+
+    /// Stop logging once `condition` is met before an instruction. Logging resumes if the start
+    /// condition is met again.
+    pub fn set_stop(&mut self, condition: Option<Condition>) {
+        self.stop = condition;
+    }
+
+    /// Whether instructions are currently being logged, ignoring the PC range.
+    #[inline]
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Log the instruction about to execute, if it passes the filters.
+    ///
+    /// # Errors
+    ///
+    /// If the sink fails to write, an error is returned.
+    pub(crate) fn log(&mut self, cpu: &Cpu) -> fmt::Result {
+        if self.active {
+            if self.stop.as_ref().is_some_and(|stop| stop.eval(cpu, None)) {
+                self.active = false;
+            }
+        } else if self
+            .start
+            .as_ref()
+            .is_some_and(|start| start.eval(cpu, None))
+        {
+            self.active = true;
+        }
+        if !self.active
+            || !self
+                .pc_range
+                .as_ref()
+                .is_none_or(|range| range.contains(&cpu.pc()))
+        {
+            return Ok(());
+        }
+        self.line.clear();
+        self.format.write_line(cpu, &mut self.line)?;
+        self.line.push('\n');
+        self.sink.write_str(&self.line)
+    }
+}
+
+impl fmt::Debug for TraceLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceLogger")
+            .field("format", &self.format)
+            .field("pc_range", &self.pc_range)
+            .field("start", &self.start)
+            .field("stop", &self.stop)
+            .field("active", &self.active)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,242 @@
+//! Universal NES Image Format (UNIF) ROMs.
+//!
+//! A UNIF file is a 32-byte header followed by chunks, each a 4-byte ID, a little-endian 32-bit
+//! length and data. Instead of a mapper number, the `MAPR` chunk names the board.
+//!
+//! <https://www.nesdev.org/wiki/UNIF>
+
+use crate::{common::NesRegion, ppu::Mirroring};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Magic bytes at the start of every UNIF file.
+pub const MAGIC: [u8; 4] = *b"UNIF";
+/// Size of the UNIF header in bytes.
+pub const HEADER_SIZE: usize = 32;
+
+/// Errors returned when loading a UNIF file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub enum UnifError {
+    /// The data does not start with a valid UNIF header.
+    InvalidHeader,
+    /// A chunk is longer than the remaining data.
+    TruncatedChunk([u8; 4]),
+    /// There is no `MAPR` chunk naming the board.
+    MissingBoard,
+    /// The board isn't supported by any mapper.
+    UnknownBoard(String),
+    /// There are no `PRG0`-`PRGF` chunks.
+    MissingPrgRom,
+}
+
+impl fmt::Display for UnifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "unif header signature not found"),
+            Self::TruncatedChunk(id) => {
+                write!(f, "truncated unif chunk: {}", String::from_utf8_lossy(id))
+            }
+            Self::MissingBoard => write!(f, "unif board name not found"),
+            Self::UnknownBoard(board) => write!(f, "unsupported unif board: {board}"),
+            Self::MissingPrgRom => write!(f, "unif prg-rom not found"),
+        }
+    }
+}
+
+impl core::error::Error for UnifError {}
+
+/// A parsed UNIF file.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Unif {
+    pub board: String,
+    pub mapper_num: u16,
+    pub submapper_num: u8,
+    pub prg_rom: Vec<u8>,
+    pub chr_rom: Vec<u8>,
+    // `None` if mapper controlled
+    pub mirroring: Option<Mirroring>,
+    pub battery: bool,
+    pub region: NesRegion,
+}
+
+impl Unif {
+    /// Load a UNIF file.
+    ///
+    /// # Errors
+    ///
+    /// If the header is invalid, a chunk is truncated, PRG-ROM is missing, or the board is
+    /// missing or unsupported, then an error is returned.
+    pub fn load(data: &[u8]) -> Result<Self, UnifError> {
+        if data.len() < HEADER_SIZE || data[0..4] != MAGIC {
+            return Err(UnifError::InvalidHeader);
+        }
+        let mut unif = Self::default();
+        let mut board = None;
+        // PRG and CHR chunks are concatenated in numeric order, regardless of file order
+        let mut prg_chunks: [&[u8]; 16] = [&[]; 16];
+        let mut chr_chunks: [&[u8]; 16] = [&[]; 16];
+        let mut chunks = &data[HEADER_SIZE..];
+        while chunks.len() >= 8 {
+            let id = [chunks[0], chunks[1], chunks[2], chunks[3]];
+            let len = u32::from_le_bytes([chunks[4], chunks[5], chunks[6], chunks[7]]) as usize;
+            let chunk = chunks
+                .get(8..8usize.saturating_add(len))
+                .ok_or(UnifError::TruncatedChunk(id))?;
+            chunks = &chunks[8 + len..];
+            let index = char::from(id[3]).to_digit(16).map(|i| i as usize);
+            match (&id[..3], index) {
+                (b"PRG", Some(i)) => prg_chunks[i] = chunk,
+                (b"CHR", Some(i)) => chr_chunks[i] = chunk,
+                _ => match &id {
+                    b"MAPR" => {
+                        let len = chunk.iter().position(|&b| b == 0x00).unwrap_or(chunk.len());
+                        board = Some(String::from_utf8_lossy(&chunk[..len]).into_owned());
+                    }
+                    b"MIRR" => {
+                        unif.mirroring = match chunk.first() {
+                            Some(0x00) => Some(Mirroring::Horizontal),
+                            Some(0x01) => Some(Mirroring::Vertical),
+                            Some(0x02) => Some(Mirroring::SingleScreenA),
+                            Some(0x03) => Some(Mirroring::SingleScreenB),
+                            Some(0x04) => Some(Mirroring::FourScreen),
+                            _ => None,
+                        };
+                    }
+                    b"BATR" => unif.battery = chunk.first().is_some_and(|&b| b != 0x00),
+                    // 0: NTSC, 1: PAL, 2: Dual compatible
+                    b"TVCI" if chunk.first() == Some(&0x01) => unif.region = NesRegion::Pal,
+                    _ => (),
+                },
+            }
+        }
+
+        unif.prg_rom = prg_chunks.concat();
+        unif.chr_rom = chr_chunks.concat();
+        if unif.prg_rom.is_empty() {
+            return Err(UnifError::MissingPrgRom);
+        }
+        let board = board.ok_or(UnifError::MissingBoard)?;
+        let (mapper_num, submapper_num) =
+            Self::board_mapper(&board).ok_or_else(|| UnifError::UnknownBoard(board.clone()))?;
+        unif.board = board;
+        unif.mapper_num = mapper_num;
+        unif.submapper_num = submapper_num;
+        Ok(unif)
+    }
+
+    /// Returns the mapper and submapper number implementing a UNIF board name, if supported.
+    #[must_use]
+    pub fn board_mapper(board: &str) -> Option<(u16, u8)> {
+        let name = ["NES-", "HVC-", "UNL-", "BTL-", "BMC-"]
+            .iter()
+            .find_map(|prefix| board.strip_prefix(prefix))
+            .unwrap_or(board);
+        let mapper = match name {
+            "NROM" | "NROM-128" | "NROM-256" | "RROM" | "RROM-128" => (0, 0),
+            "SAROM" | "SBROM" | "SCROM" | "SEROM" | "SFROM" | "SGROM" | "SHROM" | "SJROM"
+            | "SKROM" | "SLROM" | "SL1ROM" | "SL2ROM" | "SL3ROM" | "SLRROM" | "SNROM" | "SOROM"
+            | "SUROM" | "SXROM" => (1, 0),
+            "UNROM" | "UOROM" => (2, 0),
+            "CNROM" => (3, 0),
+            "TBROM" | "TEROM" | "TFROM" | "TGROM" | "TKROM" | "TLROM" | "TL1ROM" | "TL2ROM"
+            | "TNROM" | "TR1ROM" | "TSROM" | "TVROM" | "B4" => (4, 0),
+            "EKROM" | "ELROM" | "ETROM" | "EWROM" => (5, 0),
+            "AMROM" | "ANROM" | "AN1ROM" | "AOROM" => (7, 0),
+            "PEEOROM" | "PNROM" => (9, 0),
+            "FJROM" | "FKROM" => (10, 0),
+            "GNROM" | "MHROM" => (66, 0),
+            "BF9093" | "CAMERICA-BF9093" => (71, 0),
+            "BF9097" | "CAMERICA-BF9097" => (71, 1),
+            // Konami boards, with the NES 2.0 submapper selecting the register address lines
+            "KONAMI-VRC-2A" => (22, 0),
+            "KONAMI-VRC-2B" => (23, 3),
+            "KONAMI-VRC-2C" => (25, 3),
+            "KONAMI-VRC-4A" => (21, 1),
+            "KONAMI-VRC-4B" => (25, 1),
+            "KONAMI-VRC-4C" => (21, 2),
+            "KONAMI-VRC-4D" => (25, 2),
+            "KONAMI-VRC-4E" => (23, 2),
+            "KONAMI-VRC-4F" => (23, 1),
+            "KONAMI-VRC-6A" => (24, 0),
+            "KONAMI-VRC-6B" => (26, 0),
+            "KONAMI-VRC-7" => (85, 0),
+            "KONAMI-VRC-7A" => (85, 2),
+            "KONAMI-VRC-7B" => (85, 1),
+            _ => return None,
+        };
+        Some(mapper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = id.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        chunk
+    }
+
+    fn unif(board: &[u8]) -> Vec<u8> {
+        let mut data = MAGIC.to_vec();
+        data.extend_from_slice(&7u32.to_le_bytes());
+        data.resize(HEADER_SIZE, 0x00);
+        data.extend(chunk(b"MAPR", board));
+        data.extend(chunk(b"PRG1", &[0x02; 0x4000]));
+        data.extend(chunk(b"PRG0", &[0x01; 0x4000]));
+        data.extend(chunk(b"CHR0", &[0x03; 0x2000]));
+        data.extend(chunk(b"MIRR", &[0x01]));
+        data.extend(chunk(b"BATR", &[0x01]));
+        data.extend(chunk(b"TVCI", &[0x01]));
+        data
+    }
+
+    #[test]
+    fn load_unif() {
+        let unif = Unif::load(&unif(b"NES-SNROM\0")).expect("valid unif");
+        assert_eq!(unif.board, "NES-SNROM");
+        assert_eq!((unif.mapper_num, unif.submapper_num), (1, 0));
+        assert_eq!(unif.prg_rom.len(), 0x8000);
+        assert_eq!(unif.prg_rom[0], 0x01, "PRG0 first");
+        assert_eq!(unif.prg_rom[0x4000], 0x02);
+        assert_eq!(unif.chr_rom, vec![0x03; 0x2000]);
+        assert_eq!(unif.mirroring, Some(Mirroring::Vertical));
+        assert!(unif.battery);
+        assert_eq!(unif.region, NesRegion::Pal);
+    }
+
+    #[test]
+    fn konami_boards() {
+        assert_eq!(Unif::board_mapper("KONAMI-VRC-2B"), Some((23, 3)));
+        assert_eq!(Unif::board_mapper("KONAMI-VRC-4C"), Some((21, 2)));
+        assert_eq!(Unif::board_mapper("KONAMI-VRC-6B"), Some((26, 0)));
+        assert_eq!(Unif::board_mapper("KONAMI-VRC-7A"), Some((85, 2)));
+    }
+
+    #[test]
+    fn load_errors() {
+        assert_eq!(Unif::load(b"NES\x1A"), Err(UnifError::InvalidHeader));
+        assert_eq!(
+            Unif::load(&unif(b"UNL-SACHEN-8259A\0")),
+            Err(UnifError::UnknownBoard("UNL-SACHEN-8259A".into()))
+        );
+
+        let mut data = unif(b"NES-NROM-256\0");
+        data.truncate(data.len() - 1);
+        assert_eq!(Unif::load(&data), Err(UnifError::TruncatedChunk(*b"TVCI")));
+
+        let mut data = MAGIC.to_vec();
+        data.resize(HEADER_SIZE, 0x00);
+        data.extend(chunk(b"PRG0", &[0x00; 0x4000]));
+        assert_eq!(Unif::load(&data), Err(UnifError::MissingBoard));
+        data.truncate(HEADER_SIZE);
+        data.extend(chunk(b"MAPR", b"NES-NROM-256\0"));
+        assert_eq!(Unif::load(&data), Err(UnifError::MissingPrgRom));
+    }
+}
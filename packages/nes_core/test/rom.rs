@@ -323,6 +323,7 @@ mod cpu_tests {
 mod mapper_tests {
     use crate::test_rom;
 
+    test_roms!("test_roms/mapper/m001_sxrom", mmc1a);
     test_roms!(
         "test_roms/mapper/m004_txrom",
         a12_clocking,
@@ -334,4 +335,124 @@ mod mapper_tests {
         rev_a,
     );
     test_roms!("test_roms/mapper/m005_exrom", exram, basics);
-}
\ No newline at end of file
+    test_roms!("test_roms/mapper/m010_fxrom", fxrom);
+    test_roms!("test_roms/mapper/m019_namco163", namco163);
+    test_roms!(
+        "test_roms/mapper/m021_m022_m023_m025_vrc4",
+        vrc2a,
+        vrc2b,
+        vrc2b_ines,
+        vrc4b,
+        vrc4c,
+        vrc4e,
+    );
+    test_roms!("test_roms/mapper/m069_fme7", fme7);
+    test_roms!("test_roms/mapper/m085_vrc7", vrc7a, vrc7b);
+}
+mod trace_tests {
+    use nes_core::{
+        control_deck::ControlDeck,
+        debugger::Condition,
+        trace::{TraceFormat, TraceLogger},
+    };
+    use std::{
+        fmt, fs,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Default, Clone)]
+    struct SharedLog(Arc<Mutex<String>>);
+
+    impl fmt::Write for SharedLog {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.0.lock().map_err(|_| fmt::Error)?.push_str(s);
+            Ok(())
+        }
+    }
+
+    // Blank out the PPU column, since the reference log counts dots from a different CPU/PPU
+    // alignment. Everything else has to match exactly.
+    fn mask_ppu(line: &str) -> String {
+        match (line.find(" PPU:"), line.find(" CYC:")) {
+            (Some(start), Some(end)) => format!("{} PPU:---,---{}", &line[..start], &line[end..]),
+            _ => line.to_string(),
+        }
+    }
+
+    fn load_nestest() -> ControlDeck {
+        let mut rom = fs::read("test_roms/cpu/nestest.nes").expect("nestest rom");
+        // Start at $C000 to run every test without a PPU, like the reference log
+        let reset_vector = 16 + 0x3FFC;
+        rom[reset_vector..reset_vector + 2].copy_from_slice(&[0x00, 0xC0]);
+        let mut deck = ControlDeck::new();
+        deck.load_rom("nestest.nes".to_string(), rom)
+            .expect("loaded nestest");
+        deck
+    }
+
+    fn trace(
+        deck: &mut ControlDeck,
+        logger: TraceLogger,
+        log: &SharedLog,
+        instrs: usize,
+    ) -> String {
+        deck.set_trace_logger(logger);
+        for _ in 0..instrs {
+            let _ = deck.clock_instr().expect("valid clock");
+        }
+        let _ = deck.take_trace_logger();
+        let log = log.0.lock().expect("trace log");
+        log.clone()
+    }
+
+    #[test]
+    fn nestest_trace() {
+        let expected = fs::read_to_string("test_roms/cpu/nestest.log").expect("nestest log");
+        let mut deck = load_nestest();
+        let log = SharedLog::default();
+        let logger = TraceLogger::new(log.clone());
+        let log = trace(&mut deck, logger, &log, expected.lines().count());
+
+        for (line_num, (line, expected)) in log.lines().zip(expected.lines()).enumerate() {
+            assert_eq!(
+                mask_ppu(line),
+                mask_ppu(expected),
+                "nestest trace differs on line {}",
+                line_num + 1,
+            );
+        }
+        assert_eq!(log.lines().count(), expected.lines().count());
+    }
+
+    #[test]
+    fn trace_filters() {
+        let pcs = |log: &str| {
+            log.lines()
+                .map(|line| line[..4].to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let log = SharedLog::default();
+        let mut logger = TraceLogger::new(log.clone());
+        logger.set_pc_range(Some(0xC72D..=0xC736));
+        let log = trace(&mut load_nestest(), logger, &log, 12);
+        assert_eq!(pcs(&log), ["C72D", "C72E", "C72F", "C735", "C736"]);
+
+        let log = SharedLog::default();
+        let mut logger = TraceLogger::new(log.clone());
+        logger.set_start(Some(Condition::parse("PC == $C5FD").expect("valid condition")));
+        logger.set_stop(Some(Condition::parse("PC == $C735").expect("valid condition")));
+        assert!(!logger.is_active());
+        let log = trace(&mut load_nestest(), logger, &log, 12);
+        assert_eq!(pcs(&log), ["C5FD", "C72D", "C72E", "C72F"]);
+
+        let log = SharedLog::default();
+        let mut logger = TraceLogger::new(log.clone());
+        logger.set_format(TraceFormat::Mesen);
+        let log = trace(&mut load_nestest(), logger, &log, 3);
+        let lines = log.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with("C000  JMP $C5F5 "), "{}", lines[0]);
+        assert!(lines[0].contains("A:00 X:00 Y:00 S:FD P:nvUbdIzc"), "{}", lines[0]);
+        assert!(lines[2].starts_with("C5F7  STX $00 = $00 "), "{}", lines[2]);
+    }
+}
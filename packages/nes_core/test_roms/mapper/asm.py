@@ -0,0 +1,97 @@
+"""Tiny two-pass 6502 assembler helpers for building mapper test ROMs."""
+OPS = {
+    'sei': (0x78, 0), 'cld': (0xD8, 0), 'txs': (0x9A, 0), 'inx': (0xE8, 0), 'dex': (0xCA, 0),
+    'iny': (0xC8, 0), 'dey': (0x88, 0), 'tax': (0xAA, 0), 'txa': (0x8A, 0), 'tay': (0xA8, 0),
+    'tya': (0x98, 0), 'pha': (0x48, 0), 'pla': (0x68, 0), 'cli': (0x58, 0), 'clc': (0x18, 0),
+    'sec': (0x38, 0), 'rts': (0x60, 0), 'rti': (0x40, 0), 'nop': (0xEA, 0),
+    'lda#': (0xA9, 1), 'ldx#': (0xA2, 1), 'ldy#': (0xA0, 1), 'cmp#': (0xC9, 1), 'cpx#': (0xE0, 1),
+    'cpy#': (0xC0, 1), 'and#': (0x29, 1), 'ora#': (0x09, 1), 'eor#': (0x49, 1), 'adc#': (0x69, 1),
+    'sbc#': (0xE9, 1),
+    'lda': (0xAD, 2), 'ldx': (0xAE, 2), 'ldy': (0xAC, 2), 'sta': (0x8D, 2), 'stx': (0x8E, 2),
+    'sty': (0x8C, 2), 'bit': (0x2C, 2), 'cmp': (0xCD, 2), 'cpx': (0xEC, 2), 'jsr': (0x20, 2),
+    'jmp': (0x4C, 2), 'inc': (0xEE, 2), 'dec': (0xCE, 2), 'lda,x': (0xBD, 2), 'sta,x': (0x9D, 2),
+    'lda,y': (0xB9, 2), 'sta,y': (0x99, 2), 'cmp,x': (0xDD, 2), 'cmp,y': (0xD9, 2),
+    'ldaz': (0xA5, 1), 'staz': (0x85, 1), 'cmpz': (0xC5, 1), 'incz': (0xE6, 1),
+    'lda(),y': (0xB1, 1), 'sta(),y': (0x91, 1),
+    'bpl': (0x10, 'r'), 'bmi': (0x30, 'r'), 'bne': (0xD0, 'r'), 'beq': (0xF0, 'r'),
+    'bcc': (0x90, 'r'), 'bcs': (0xB0, 'r'), 'bvc': (0x50, 'r'), 'bvs': (0x70, 'r'),
+}
+
+
+def assemble(origin, program):
+    """`program` is a list of label strings, (op, arg) tuples, or ('db', bytes)."""
+    for final in (False, True):
+        labels = {} if not final else labels_pass
+        pc = origin
+        out = bytearray()
+        for item in program:
+            if isinstance(item, str):
+                labels[item] = pc
+                continue
+            op, *args = item
+            if op == 'db':
+                data = bytes(args[0])
+                out += data
+                pc += len(data)
+                continue
+            code, size = OPS[op]
+            arg = args[0] if args else None
+            if isinstance(arg, str):
+                arg = labels.get(arg, 0) if not final else labels[arg]
+            out.append(code)
+            if size == 1:
+                out.append(arg & 0xFF)
+            elif size == 2:
+                out += bytes([arg & 0xFF, arg >> 8])
+            elif size == 'r':
+                off = arg - (pc + 2)
+                if final:
+                    assert -128 <= off <= 127, (item, off)
+                out.append(off & 0xFF)
+            pc += 1 if size == 0 else (2 if size in (1, 'r') else 3)
+        labels_pass = labels
+    return bytes(out), labels_pass
+
+
+def ines(mapper, prg, chr_, battery=False, vertical=True, prg_ram_banks=0):
+    flags6 = ((mapper & 0x0F) << 4) | (0x02 if battery else 0) | (0x01 if vertical else 0)
+    flags7 = mapper & 0xF0
+    header = b'NES\x1a' + bytes([len(prg) // 0x4000, len(chr_) // 0x2000, flags6, flags7,
+                                 prg_ram_banks, 0, 0, 0, 0, 0, 0, 0])
+    return header + bytes(prg) + bytes(chr_)
+
+
+# Shared PPU helpers, assembled into every test ROM
+def ppu_helpers():
+    return [
+        # A = hi, X = lo: read PPU address through $2007 so the mapper sees it
+        'touch', ('bit', 0x2002), ('sta', 0x2006), ('stx', 0x2006), ('lda', 0x2007), ('rts',),
+        # A = hi, X = lo: return the byte at PPU address in A
+        'ppu_read', ('bit', 0x2002), ('sta', 0x2006), ('stx', 0x2006), ('lda', 0x2007),
+        ('lda', 0x2007), ('rts',),
+        # Fill the palette with color A, then enable background rendering forever
+        'show', ('staz', 0x11), ('bit', 0x2002), ('lda#', 0x3F), ('sta', 0x2006), ('lda#', 0x00),
+        ('sta', 0x2006), ('ldx#', 32), 'show_pal', ('ldaz', 0x11), ('sta', 0x2007), ('dex',),
+        ('bne', 'show_pal'), ('lda#', 0), ('sta', 0x2006), ('sta', 0x2006), ('sta', 0x2005),
+        ('sta', 0x2005), ('lda#', 0x0A), ('sta', 0x2001), 'forever', ('jmp', 'forever'),
+        'nmi', ('rti',),
+    ]
+
+
+def reset_prologue():
+    return [
+        'reset', ('sei',), ('cld',), ('ldx#', 0xFF), ('txs',), ('lda#', 0), ('sta', 0x2000),
+        ('sta', 0x2001), 'vb1', ('bit', 0x2002), ('bpl', 'vb1'), 'vb2', ('bit', 0x2002),
+        ('bpl', 'vb2'),
+    ]
+
+PASS = 0x2A
+
+_far = [0]
+
+
+def fail_if_ne(label):
+    """Jump to `label` when the zero flag is clear, without the range limit of branches."""
+    _far[0] += 1
+    skip = f'_skip{_far[0]}'
+    return [('beq', skip), ('jmp', label), skip]
@@ -0,0 +1,47 @@
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+sys.path.insert(0, os.path.dirname(HERE))
+from asm import *  # noqa: E402,F403
+
+FAIL = {'fail_prg': 0x16, 'fail_ram': 0x27, 'fail_rev': 0x12}
+
+
+def mmc1_write(addr, val):
+    """Serially write the 5 bits of `val` to the MMC1 register at `addr`, LSB first."""
+    return [op for bit in range(5) for op in (('lda#', (val >> bit) & 1), ('sta', addr))]
+
+
+program = reset_prologue() + [
+    # Reset the shift register, which also selects PRG mode 3 with $C000 fixed to the last bank
+    ('lda#', 0x80), ('sta', 0x8000),
+    # PRG-ROM: 16K bank at $8000, bank number stored in its first byte
+    *mmc1_write(0xE000, 0x01), ('lda', 0x8000), ('cmp#', 1), *fail_if_ne('fail_prg'),
+    *mmc1_write(0xE000, 0x00), ('lda', 0x8000), ('cmp#', 0), *fail_if_ne('fail_prg'),
+    # PRG-RAM, enabled while $E000 bit 4 is clear
+    ('lda#', 0x5A), ('sta', 0x6000), ('lda', 0x6000), ('cmp#', 0x5A), *fail_if_ne('fail_ram'),
+    # MMC1A ignores the PRG-RAM disable bit, so RAM stays writable
+    *mmc1_write(0xE000, 0x10),
+    ('lda#', 0xA5), ('sta', 0x6000), ('lda', 0x6000), ('cmp#', 0xA5), *fail_if_ne('fail_rev'),
+    ('lda#', PASS), ('jmp', 'show'),
+]
+for name, color in FAIL.items():
+    program += [name, ('lda#', color), ('jmp', 'show')]
+program += ppu_helpers()
+
+code, labels = assemble(0xC010, program)
+prg = bytearray()
+for bank in range(8):
+    b = bytearray(0x4000)
+    b[0] = bank
+    prg += b
+last = 7 * 0x4000
+prg[last + 0x10:last + 0x10 + len(code)] = code
+def vec(off, addr):
+    prg[last + off] = addr & 0xFF
+    prg[last + off + 1] = addr >> 8
+vec(0x3FFA, labels['nmi']); vec(0x3FFC, labels['reset']); vec(0x3FFE, labels['nmi'])
+chr_ = bytes(0x2000)
+# iNES 1.0 can't tell MMC1 revisions apart, so this only passes with the game database entry
+open(f'{HERE}/mmc1a.nes', 'wb').write(ines(1, prg, chr_))
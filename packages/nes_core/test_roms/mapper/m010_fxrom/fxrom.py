@@ -0,0 +1,53 @@
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+sys.path.insert(0, os.path.dirname(HERE))
+from asm import *  # noqa: E402,F403
+
+FAIL = {'fail_prg': 0x16, 'fail_ram': 0x27, 'fail_latch': 0x12, 'fail_range': 0x24, 'fail_latch1': 0x05}
+program = reset_prologue() + [
+    # PRG-ROM: 16K bank at $8000, bank number stored in its first byte
+    ('ldx#', 0), 'prg_loop', ('stx', 0xA000), ('cpx', 0x8000), *fail_if_ne('fail_prg'), ('inx',),
+    ('cpx#', 8), ('bne', 'prg_loop'), ('lda', 0xC000), ('cmp#', 7), *fail_if_ne('fail_prg'),
+    # PRG-RAM
+    ('lda#', 0x5A), ('sta', 0x6000), ('lda#', 0xA5), ('sta', 0x7FFF), ('lda', 0x6000),
+    ('cmp#', 0x5A), *fail_if_ne('fail_ram'), ('lda', 0x7FFF), ('cmp#', 0xA5), *fail_if_ne('fail_ram'),
+    # CHR latch banks, each 4K CHR bank is filled with its bank number
+    ('lda#', 1), ('sta', 0xB000), ('lda#', 2), ('sta', 0xC000), ('lda#', 3), ('sta', 0xD000),
+    ('lda#', 4), ('sta', 0xE000),
+    ('lda#', 0x00), ('ldx#', 0x00), ('jsr', 'ppu_read'), ('cmp#', 1), *fail_if_ne('fail_latch'),
+    ('lda#', 0x0F), ('ldx#', 0xE8), ('jsr', 'touch'),
+    ('lda#', 0x00), ('ldx#', 0x00), ('jsr', 'ppu_read'), ('cmp#', 2), *fail_if_ne('fail_latch'),
+    # MMC4 latch 0 triggers on the whole $0FD8-$0FDF/$0FE8-$0FEF range, unlike MMC2
+    ('lda#', 0x0F), ('ldx#', 0xDC), ('jsr', 'touch'),
+    ('lda#', 0x00), ('ldx#', 0x00), ('jsr', 'ppu_read'), ('cmp#', 1), *fail_if_ne('fail_range'),
+    ('lda#', 0x0F), ('ldx#', 0xEF), ('jsr', 'touch'),
+    ('lda#', 0x00), ('ldx#', 0x00), ('jsr', 'ppu_read'), ('cmp#', 2), *fail_if_ne('fail_range'),
+    # Latch 1
+    ('lda#', 0x1F), ('ldx#', 0xD8), ('jsr', 'touch'),
+    ('lda#', 0x10), ('ldx#', 0x00), ('jsr', 'ppu_read'), ('cmp#', 3), *fail_if_ne('fail_latch1'),
+    ('lda#', 0x1F), ('ldx#', 0xEA), ('jsr', 'touch'),
+    ('lda#', 0x10), ('ldx#', 0x00), ('jsr', 'ppu_read'), ('cmp#', 4), *fail_if_ne('fail_latch1'),
+    ('lda#', PASS), ('jmp', 'show'),
+]
+for name, color in FAIL.items():
+    program += [name, ('lda#', color), ('jmp', 'show')]
+program += ppu_helpers()
+
+code, labels = assemble(0xC010, program)
+prg = bytearray()
+for bank in range(8):
+    b = bytearray(0x4000)
+    b[0] = bank
+    prg += b
+last = 7 * 0x4000
+prg[last + 0x10:last + 0x10 + len(code)] = code
+def vec(off, addr):
+    prg[last + off] = addr & 0xFF
+    prg[last + off + 1] = addr >> 8
+vec(0x3FFA, labels['nmi']); vec(0x3FFC, labels['reset']); vec(0x3FFE, labels['nmi'])
+chr_ = bytearray()
+for bank in range(16):
+    chr_ += bytes([bank]) * 0x1000
+open(f'{HERE}/fxrom.nes', 'wb').write(ines(10, prg, chr_, battery=True))
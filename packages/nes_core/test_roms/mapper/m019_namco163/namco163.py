@@ -0,0 +1,94 @@
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+sys.path.insert(0, os.path.dirname(HERE))
+from asm import *  # noqa: E402,F403
+
+FAIL = {'fail_prg': 0x16, 'fail_chr': 0x12, 'fail_ram': 0x27, 'fail_mirror': 0x24,
+        'fail_irq': 0x05, 'fail_sound': 0x1C}
+
+
+def build():
+    def ppu_read(addr, expect, fail):
+        return [('lda#', addr >> 8), ('ldx#', addr & 0xFF), ('jsr', 'ppu_read'), ('cmp#', expect),
+                *fail_if_ne(fail)]
+
+    def ppu_write(addr, val):
+        return [('bit', 0x2002), ('lda#', addr >> 8), ('sta', 0x2006), ('lda#', addr & 0xFF),
+                ('sta', 0x2006), ('lda#', val), ('sta', 0x2007)]
+
+    def reg(addr, val):
+        return [('lda#', val), ('sta', addr)]
+
+    p = reset_prologue() + [('lda#', 0x40), ('sta', 0x4017), ('lda#', 0), ('staz', 0x12)]
+    # PRG-ROM: 8K banks, bank number stored in the first byte
+    p += [('ldx#', 0), 'prg_loop', ('stx', 0xE000), ('cpx', 0x8000), *fail_if_ne('fail_prg'),
+          ('inx',), ('cpx#', 15), ('bne', 'prg_loop')]
+    p += reg(0xE800, 5) + [('lda', 0xA000), ('cmp#', 5), *fail_if_ne('fail_prg')]
+    p += reg(0xF000, 9) + [('lda', 0xC000), ('cmp#', 9), *fail_if_ne('fail_prg')]
+    p += [('lda', 0xE000), ('cmp#', 15), *fail_if_ne('fail_prg')]
+    # CHR-ROM: 1K banks, bank number stored in every byte
+    p += reg(0x8000, 0x25) + reg(0x9800, 0x13) + reg(0xA000, 0x31) + reg(0xB800, 0xCA)
+    p += ppu_read(0x0000, 0x25, 'fail_chr')
+    p += ppu_read(0x0C00, 0x13, 'fail_chr')
+    p += ppu_read(0x1000, 0x31, 'fail_chr')
+    p += ppu_read(0x1C00, 0xCA, 'fail_chr')
+    # Nametables: CIRAM pages and CHR-ROM banks
+    p += reg(0xC000, 0xE0) + reg(0xC800, 0xE1) + reg(0xD000, 0xE1) + reg(0xD800, 0x42)
+    p += ppu_write(0x2000, 0x11) + ppu_write(0x2400, 0x22)
+    p += ppu_read(0x2000, 0x11, 'fail_mirror')
+    p += ppu_read(0x2800, 0x22, 'fail_mirror')
+    p += ppu_read(0x2C00, 0x42, 'fail_mirror')
+    # CIRAM in the pattern tables, unless disabled by $E800
+    p += reg(0x8000, 0xE1) + ppu_read(0x0000, 0x22, 'fail_chr')
+    p += reg(0xE800, 0x45) + ppu_read(0x0000, 0xE1, 'fail_chr') + reg(0xE800, 5)
+    # PRG-RAM: writable with $F800 = $4x, per 2K write protect
+    p += reg(0xF800, 0x40) + reg(0x6000, 0x5A) + reg(0x6800, 0x5A)
+    p += reg(0xF800, 0x42) + reg(0x6000, 0xA5) + reg(0x6800, 0xA5)
+    p += [('lda', 0x6000), ('cmp#', 0xA5), *fail_if_ne('fail_ram'),
+          ('lda', 0x6800), ('cmp#', 0x5A), *fail_if_ne('fail_ram')]
+    p += reg(0xF800, 0x00) + reg(0x6000, 0x33)
+    p += [('lda', 0x6000), ('cmp#', 0xA5), *fail_if_ne('fail_ram')]
+    # Sound RAM with auto-increment
+    p += reg(0xF800, 0xFE) + reg(0x4800, 0x12) + reg(0x4800, 0x34) + reg(0x4800, 0x56)
+    p += reg(0xF800, 0x7E) + [('lda', 0x4800), ('cmp#', 0x12), *fail_if_ne('fail_sound'),
+                              ('lda', 0x4800), ('cmp#', 0x12), *fail_if_ne('fail_sound')]
+    p += reg(0xF800, 0xFF) + [('lda', 0x4800), ('cmp#', 0x34), *fail_if_ne('fail_sound'),
+                              ('lda', 0x4800), ('cmp#', 0x56), *fail_if_ne('fail_sound')]
+    # IRQ counter reads back
+    p += reg(0x5000, 0x34) + reg(0x5800, 0x12)
+    p += [('lda', 0x5000), ('cmp#', 0x34), *fail_if_ne('fail_irq'),
+          ('lda', 0x5800), ('cmp#', 0x12), *fail_if_ne('fail_irq')]
+    # IRQ fires when the counter reaches $7FFF
+    p += reg(0x5000, 0xF0) + reg(0x5800, 0xFF)
+    p += [('cli',), ('ldx#', 0), 'irq_wait', ('ldaz', 0x12), ('bne', 'irq_done'), ('dex',),
+          ('bne', 'irq_wait'), ('jmp', 'fail_irq'), 'irq_done', ('sei',)]
+    p += [('lda', 0x5800), ('cmp#', 0x7F), *fail_if_ne('fail_irq')]
+    p += [('lda#', PASS), ('jmp', 'show')]
+    for label, color in FAIL.items():
+        p += [label, ('lda#', color), ('jmp', 'show')]
+    p += ppu_helpers()
+    # Acknowledge by disabling the IRQ
+    p += ['irq', ('pha',), ('lda#', 0x7F), ('sta', 0x5800), ('incz', 0x12), ('pla',), ('rti',)]
+
+    code, labels = assemble(0xE010, p)
+    prg = bytearray()
+    for bank in range(16):
+        b = bytearray(0x2000)
+        b[0] = bank
+        prg += b
+    last = 15 * 0x2000
+    prg[last + 0x10:last + 0x10 + len(code)] = code
+
+    def vec(off, addr):
+        prg[last + off] = addr & 0xFF
+        prg[last + off + 1] = addr >> 8
+    vec(0x1FFA, labels['nmi']); vec(0x1FFC, labels['reset']); vec(0x1FFE, labels['irq'])
+    chr_ = bytearray()
+    for bank in range(256):
+        chr_ += bytes([bank]) * 0x400
+    return ines(19, prg, chr_)
+
+
+open(f'{HERE}/namco163.nes', 'wb').write(build())
@@ -0,0 +1,120 @@
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+sys.path.insert(0, os.path.dirname(HERE))
+from asm import *  # noqa: E402,F403
+
+A = [1 << i for i in range(8)]
+BOARDS = {
+    # name: (mapper, submapper or None for iNES 1.0, (a0 line, a1 line), vrc2)
+    'vrc2a': (22, None, (A[1], A[0]), True),
+    'vrc2b': (23, 3, (A[0], A[1]), True),
+    'vrc2b_ines': (23, None, (A[0], A[1]), True),
+    'vrc4b': (25, None, (A[1], A[0]), False),
+    'vrc4c': (21, None, (A[6], A[7]), False),
+    'vrc4e': (23, 2, (A[2], A[3]), False),
+}
+FAIL = {'fail_prg': 0x16, 'fail_chr': 0x12, 'fail_ram': 0x27, 'fail_mirror': 0x24,
+        'fail_irq': 0x05, 'fail_swap': 0x1C}
+
+
+def build(name):
+    mapper, submapper, (a0, a1), vrc2 = BOARDS[name]
+    chr_shift = 1 if name == 'vrc2a' else 0
+
+    def reg(base, r):
+        return base | (a0 if r & 1 else 0) | (a1 if r & 2 else 0)
+
+    def ppu_read(addr, expect, fail):
+        return [('lda#', addr >> 8), ('ldx#', addr & 0xFF), ('jsr', 'ppu_read'), ('cmp#', expect),
+                *fail_if_ne(fail)]
+
+    def ppu_write(addr, val):
+        return [('bit', 0x2002), ('lda#', addr >> 8), ('sta', 0x2006), ('lda#', addr & 0xFF),
+                ('sta', 0x2006), ('lda#', val), ('sta', 0x2007)]
+
+    def set_chr(slot, bank):
+        group, hi = divmod(slot, 2)
+        base = 0xB000 + 0x1000 * group
+        return [('lda#', bank & 0x0F), ('sta', reg(base, hi * 2)),
+                ('lda#', bank >> 4), ('sta', reg(base, hi * 2 + 1))]
+
+    # Inhibit APU frame IRQs so only the mapper IRQ fires
+    p = reset_prologue() + [('lda#', 0x40), ('sta', 0x4017), ('lda#', 0), ('staz', 0x12)]
+    # PRG-ROM: 8K bank at $8000, bank number stored in its first byte
+    p += [('ldx#', 0), 'prg_loop', ('stx', reg(0x8000, 0)), ('cpx', 0x8000), *fail_if_ne('fail_prg'),
+          ('inx',), ('cpx#', 14), ('bne', 'prg_loop'),
+          ('lda#', 5), ('sta', reg(0xA000, 0)), ('lda', 0xA000), ('cmp#', 5), *fail_if_ne('fail_prg'),
+          ('lda', 0xC000), ('cmp#', 14), *fail_if_ne('fail_prg'),
+          ('lda', 0xE000), ('cmp#', 15), *fail_if_ne('fail_prg')]
+    if not vrc2:
+        # PRG swap mode fixes $8000 to the second-last bank and makes $C000 switchable
+        p += [('lda#', 3), ('sta', reg(0x8000, 0)), ('lda#', 0x03), ('sta', reg(0x9000, 2)),
+              ('lda', 0x8000), ('cmp#', 14), *fail_if_ne('fail_swap'),
+              ('lda', 0xC000), ('cmp#', 3), *fail_if_ne('fail_swap'),
+              ('lda#', 0x01), ('sta', reg(0x9000, 2)),
+              ('lda', 0x8000), ('cmp#', 3), *fail_if_ne('fail_swap')]
+    # CHR-ROM: 1K banks, bank number stored in every byte
+    p += set_chr(0, 0x25) + set_chr(3, 0x13) + set_chr(4, 0x31) + set_chr(7, 0x4A)
+    p += ppu_read(0x0000, 0x25 >> chr_shift, 'fail_chr')
+    p += ppu_read(0x0C00, 0x13 >> chr_shift, 'fail_chr')
+    p += ppu_read(0x1000, 0x31 >> chr_shift, 'fail_chr')
+    p += ppu_read(0x1C00, 0x4A >> chr_shift, 'fail_chr')
+    if vrc2:
+        # Microwire latch, upper bits are open bus
+        p += [('lda#', 0x01), ('sta', 0x6000), ('lda', 0x6000), ('and#', 0x01), ('cmp#', 0x01),
+              *fail_if_ne('fail_ram'), ('lda#', 0x00), ('sta', 0x6000), ('lda', 0x6000),
+              ('and#', 0x01), *fail_if_ne('fail_ram')]
+    else:
+        # PRG-RAM, enabled by $9002
+        p += [('lda#', 0x5A), ('sta', 0x6000), ('lda', 0x6000), ('cmp#', 0x5A),
+              *fail_if_ne('fail_ram'), ('lda#', 0x00), ('sta', reg(0x9000, 2)), ('lda', 0x6000),
+              ('cmp#', 0x5A), ('beq', 'fail_ram_near'), ('lda#', 0x01), ('sta', reg(0x9000, 2))]
+    # Mirroring: horizontal shares $2000/$2400, vertical shares $2000/$2800
+    p += [('lda#', 0x01), ('sta', reg(0x9000, 0))] + ppu_write(0x2000, 0x11)
+    p += ppu_read(0x2400, 0x11, 'fail_mirror')
+    p += [('lda#', 0x00), ('sta', reg(0x9000, 0))] + ppu_write(0x2000, 0x22)
+    p += ppu_read(0x2800, 0x22, 'fail_mirror')
+    if not vrc2:
+        # IRQ in cycle mode, reloaded with $F0 so it fires after 16 CPU cycles
+        p += [('lda#', 0x00), ('sta', reg(0xF000, 0)), ('lda#', 0x0F), ('sta', reg(0xF000, 1)),
+              ('lda#', 0x06), ('sta', reg(0xF000, 2)), ('cli',), ('ldx#', 0),
+              'irq_wait', ('ldaz', 0x12), ('bne', 'irq_done'), ('dex',), ('bne', 'irq_wait'),
+              ('jmp', 'fail_irq'), 'irq_done', ('sei',)]
+    p += [('lda#', PASS), ('jmp', 'show')]
+    if not vrc2:
+        p += ['fail_ram_near', ('jmp', 'fail_ram')]
+    for label, color in FAIL.items():
+        p += [label, ('lda#', color), ('jmp', 'show')]
+    p += ppu_helpers()
+    p += ['irq', ('pha',), ('lda#', 0), ('sta', reg(0xF000, 3)), ('incz', 0x12), ('pla',), ('rti',)]
+
+    code, labels = assemble(0xE010, p)
+    prg = bytearray()
+    for bank in range(16):
+        b = bytearray(0x2000)
+        b[0] = bank
+        prg += b
+    last = 15 * 0x2000
+    prg[last + 0x10:last + 0x10 + len(code)] = code
+
+    def vec(off, addr):
+        prg[last + off] = addr & 0xFF
+        prg[last + off + 1] = addr >> 8
+    vec(0x1FFA, labels['nmi']); vec(0x1FFC, labels['reset']); vec(0x1FFE, labels['irq'])
+    chr_ = bytearray()
+    for bank in range(128):
+        chr_ += bytes([bank]) * 0x400
+    # iNES 1.0 VRC4 boards on mappers 023/025 with 128K PRG-ROM need a battery to not be
+    # taken for VRC2
+    battery = not vrc2 and submapper is None and mapper in (23, 25)
+    rom = bytearray(ines(mapper, prg, chr_, battery=battery))
+    if submapper is not None:
+        rom[7] |= 0x08
+        rom[8] = (submapper << 4) | (mapper >> 8)
+    return bytes(rom)
+
+
+for name in BOARDS:
+    open(f'{HERE}/{name}.nes', 'wb').write(build(name))
@@ -0,0 +1,87 @@
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+sys.path.insert(0, os.path.dirname(HERE))
+from asm import *  # noqa: E402,F403
+
+FAIL = {'fail_prg': 0x16, 'fail_chr': 0x12, 'fail_ram': 0x27, 'fail_mirror': 0x24,
+        'fail_irq': 0x05}
+
+
+def build():
+    def cmd(c, val):
+        return [('lda#', c), ('sta', 0x8000), ('lda#', val), ('sta', 0xA000)]
+
+    def ppu_read(addr, expect, fail):
+        return [('lda#', addr >> 8), ('ldx#', addr & 0xFF), ('jsr', 'ppu_read'), ('cmp#', expect),
+                *fail_if_ne(fail)]
+
+    def ppu_write(addr, val):
+        return [('bit', 0x2002), ('lda#', addr >> 8), ('sta', 0x2006), ('lda#', addr & 0xFF),
+                ('sta', 0x2006), ('lda#', val), ('sta', 0x2007)]
+
+    p = reset_prologue() + [('lda#', 0x40), ('sta', 0x4017), ('lda#', 0), ('staz', 0x12)]
+    # PRG-ROM: 8K banks, bank number stored in the first byte
+    p += [('ldx#', 0), 'prg_loop', ('lda#', 0x09), ('sta', 0x8000), ('stx', 0xA000),
+          ('cpx', 0x8000), *fail_if_ne('fail_prg'), ('inx',), ('cpx#', 15), ('bne', 'prg_loop')]
+    p += cmd(0x0A, 5) + [('lda', 0xA000), ('cmp#', 5), *fail_if_ne('fail_prg')]
+    p += cmd(0x0B, 9) + [('lda', 0xC000), ('cmp#', 9), *fail_if_ne('fail_prg')]
+    p += [('lda', 0xE000), ('cmp#', 15), *fail_if_ne('fail_prg')]
+    # PRG-ROM at $6000
+    p += cmd(0x08, 7) + [('lda', 0x6000), ('cmp#', 7), *fail_if_ne('fail_prg')]
+    # PRG-RAM at $6000, open bus when selected but disabled
+    p += cmd(0x08, 0xC0) + [('lda#', 0x5A), ('sta', 0x6000), ('lda', 0x6000), ('cmp#', 0x5A),
+                            *fail_if_ne('fail_ram')]
+    p += cmd(0x08, 0x40) + [('lda#', 0x00), ('sta', 0x6000)]
+    p += cmd(0x08, 0xC0) + [('lda', 0x6000), ('cmp#', 0x5A), *fail_if_ne('fail_ram')]
+    # Audio registers don't affect banking
+    p += [('lda#', 0x08), ('sta', 0xC000), ('lda#', 0x0F), ('sta', 0xE000),
+          ('lda', 0xC000), ('cmp#', 9), *fail_if_ne('fail_prg')]
+    # CHR-ROM: 1K banks, bank number stored in every byte
+    p += cmd(0, 0x25) + cmd(3, 0x13) + cmd(4, 0x31) + cmd(7, 0xCA)
+    p += ppu_read(0x0000, 0x25, 'fail_chr')
+    p += ppu_read(0x0C00, 0x13, 'fail_chr')
+    p += ppu_read(0x1000, 0x31, 'fail_chr')
+    p += ppu_read(0x1C00, 0xCA, 'fail_chr')
+    # Mirroring: horizontal shares $2000/$2400, vertical $2000/$2800, single screen all four
+    p += cmd(0x0C, 1) + ppu_write(0x2000, 0x11) + ppu_read(0x2400, 0x11, 'fail_mirror')
+    p += cmd(0x0C, 0) + ppu_write(0x2000, 0x22) + ppu_read(0x2800, 0x22, 'fail_mirror')
+    p += cmd(0x0C, 3) + ppu_write(0x2000, 0x33) + ppu_read(0x2C00, 0x33, 'fail_mirror')
+    p += cmd(0x0C, 0)
+    # No IRQ while only the counter is enabled
+    p += cmd(0x0E, 0x10) + cmd(0x0F, 0x00) + cmd(0x0D, 0x80)
+    p += [('cli',), ('ldx#', 0), 'no_irq_wait', ('dex',), ('bne', 'no_irq_wait'),
+          ('ldaz', 0x12), ('cmp#', 0), *fail_if_ne('fail_irq')]
+    # IRQ fires when the counter wraps from $0000 to $FFFF
+    p += cmd(0x0D, 0x00) + cmd(0x0E, 0x10) + cmd(0x0F, 0x00) + cmd(0x0D, 0x81)
+    p += [('ldx#', 0), 'irq_wait', ('ldaz', 0x12), ('bne', 'irq_done'), ('dex',),
+          ('bne', 'irq_wait'), ('jmp', 'fail_irq'), 'irq_done', ('sei',)]
+    p += [('lda#', PASS), ('jmp', 'show')]
+    for label, color in FAIL.items():
+        p += [label, ('lda#', color), ('jmp', 'show')]
+    p += ppu_helpers()
+    # Acknowledge by disabling the IRQ
+    p += ['irq', ('pha',), ('lda#', 0x0D), ('sta', 0x8000), ('lda#', 0x00), ('sta', 0xA000),
+          ('incz', 0x12), ('pla',), ('rti',)]
+
+    code, labels = assemble(0xE010, p)
+    prg = bytearray()
+    for bank in range(16):
+        b = bytearray(0x2000)
+        b[0] = bank
+        prg += b
+    last = 15 * 0x2000
+    prg[last + 0x10:last + 0x10 + len(code)] = code
+
+    def vec(off, addr):
+        prg[last + off] = addr & 0xFF
+        prg[last + off + 1] = addr >> 8
+    vec(0x1FFA, labels['nmi']); vec(0x1FFC, labels['reset']); vec(0x1FFE, labels['irq'])
+    chr_ = bytearray()
+    for bank in range(256):
+        chr_ += bytes([bank]) * 0x400
+    return ines(69, prg, chr_)
+
+
+open(f'{HERE}/fme7.nes', 'wb').write(build())
@@ -0,0 +1,96 @@
+import os
+import sys
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+sys.path.insert(0, os.path.dirname(HERE))
+from asm import *  # noqa: E402,F403
+
+BOARDS = {
+    # name: (submapper, register select line)
+    'vrc7a': (2, 0x10),
+    'vrc7b': (1, 0x08),
+}
+FAIL = {'fail_prg': 0x16, 'fail_chr': 0x12, 'fail_ram': 0x27, 'fail_mirror': 0x24,
+        'fail_irq': 0x05}
+
+
+def build(name):
+    submapper, sel = BOARDS[name]
+
+    def reg(base, hi):
+        return base | (sel if hi else 0)
+
+    def ppu_read(addr, expect, fail):
+        return [('lda#', addr >> 8), ('ldx#', addr & 0xFF), ('jsr', 'ppu_read'), ('cmp#', expect),
+                *fail_if_ne(fail)]
+
+    def ppu_write(addr, val):
+        return [('bit', 0x2002), ('lda#', addr >> 8), ('sta', 0x2006), ('lda#', addr & 0xFF),
+                ('sta', 0x2006), ('lda#', val), ('sta', 0x2007)]
+
+    def set_chr(slot, bank):
+        group, hi = divmod(slot, 2)
+        return [('lda#', bank), ('sta', reg(0xA000 + 0x1000 * group, hi))]
+
+    p = reset_prologue() + [('lda#', 0x40), ('sta', 0x4017), ('lda#', 0), ('staz', 0x12)]
+    # PRG-ROM: 8K banks at $8000/$A000/$C000, bank number stored in the first byte
+    p += [('ldx#', 0), 'prg_loop', ('stx', reg(0x8000, 0)), ('cpx', 0x8000), *fail_if_ne('fail_prg'),
+          ('inx',), ('cpx#', 15), ('bne', 'prg_loop'),
+          ('lda#', 5), ('sta', reg(0x8000, 1)), ('lda', 0xA000), ('cmp#', 5), *fail_if_ne('fail_prg'),
+          ('lda#', 9), ('sta', 0x9000), ('lda', 0xC000), ('cmp#', 9), *fail_if_ne('fail_prg'),
+          ('lda', 0xE000), ('cmp#', 15), *fail_if_ne('fail_prg')]
+    # Audio registers don't affect banking
+    p += [('lda#', 0x30), ('sta', reg(0x9000, 1)), ('lda#', 0x10), ('sta', reg(0x9020, 1)),
+          ('lda', 0xC000), ('cmp#', 9), *fail_if_ne('fail_prg')]
+    # CHR-ROM: 1K banks, bank number stored in every byte
+    p += set_chr(0, 0x25) + set_chr(3, 0x13) + set_chr(4, 0x31) + set_chr(7, 0xCA)
+    p += ppu_read(0x0000, 0x25, 'fail_chr')
+    p += ppu_read(0x0C00, 0x13, 'fail_chr')
+    p += ppu_read(0x1000, 0x31, 'fail_chr')
+    p += ppu_read(0x1C00, 0xCA, 'fail_chr')
+    # PRG-RAM, enabled by $E000 bit 7
+    p += [('lda#', 0x80), ('sta', 0xE000), ('lda#', 0x5A), ('sta', 0x6000), ('lda', 0x6000),
+          ('cmp#', 0x5A), *fail_if_ne('fail_ram'), ('lda#', 0x00), ('sta', 0xE000),
+          ('lda', 0x6000), ('cmp#', 0x5A), ('bne', 'ram_ok'), ('jmp', 'fail_ram'), 'ram_ok']
+    # Mirroring: horizontal shares $2000/$2400, vertical $2000/$2800, single screen all four
+    p += [('lda#', 0x01), ('sta', 0xE000)] + ppu_write(0x2000, 0x11)
+    p += ppu_read(0x2400, 0x11, 'fail_mirror')
+    p += [('lda#', 0x00), ('sta', 0xE000)] + ppu_write(0x2000, 0x22)
+    p += ppu_read(0x2800, 0x22, 'fail_mirror')
+    p += [('lda#', 0x03), ('sta', 0xE000)] + ppu_write(0x2000, 0x33)
+    p += ppu_read(0x2C00, 0x33, 'fail_mirror')
+    p += [('lda#', 0x00), ('sta', 0xE000)]
+    # IRQ in cycle mode, reloaded with $F0 so it fires after 16 CPU cycles
+    p += [('lda#', 0xF0), ('sta', reg(0xE000, 1)), ('lda#', 0x06), ('sta', 0xF000), ('cli',),
+          ('ldx#', 0), 'irq_wait', ('ldaz', 0x12), ('bne', 'irq_done'), ('dex',),
+          ('bne', 'irq_wait'), ('jmp', 'fail_irq'), 'irq_done', ('sei',)]
+    p += [('lda#', PASS), ('jmp', 'show')]
+    for label, color in FAIL.items():
+        p += [label, ('lda#', color), ('jmp', 'show')]
+    p += ppu_helpers()
+    p += ['irq', ('pha',), ('lda#', 0), ('sta', reg(0xF000, 1)), ('incz', 0x12), ('pla',), ('rti',)]
+
+    code, labels = assemble(0xE010, p)
+    prg = bytearray()
+    for bank in range(16):
+        b = bytearray(0x2000)
+        b[0] = bank
+        prg += b
+    last = 15 * 0x2000
+    prg[last + 0x10:last + 0x10 + len(code)] = code
+
+    def vec(off, addr):
+        prg[last + off] = addr & 0xFF
+        prg[last + off + 1] = addr >> 8
+    vec(0x1FFA, labels['nmi']); vec(0x1FFC, labels['reset']); vec(0x1FFE, labels['irq'])
+    chr_ = bytearray()
+    for bank in range(256):
+        chr_ += bytes([bank]) * 0x400
+    rom = bytearray(ines(85, prg, chr_))
+    rom[7] |= 0x08
+    rom[8] = (submapper << 4) | (85 >> 8)
+    return bytes(rom)
+
+
+for name in BOARDS:
+    open(f'{HERE}/{name}.nes', 'wb').write(build(name))
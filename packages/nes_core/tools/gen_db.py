@@ -0,0 +1,157 @@
+#!/usr/bin/env python3
+"""Regenerate the `GAMES` table in src/db.rs.
+
+Usage: gen_db.py [nes20db.xml [NstDatabase.xml]]
+
+Hardware details come from the NES 2.0 header database. Publisher, release year and MMC1
+revisions aren't part of it, so they're merged in from Nestopia's NstDatabase.xml when given,
+matched by the CRC-32 and SHA-1 of PRG-ROM followed by CHR-ROM.
+
+Entries in db_extra.xml are always merged in last, replacing any with the same CRC-32.
+"""
+
+import os
+import re
+import subprocess
+import sys
+import xml.etree.ElementTree as ET
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+DB_RS = os.path.join(HERE, '..', 'src', 'db.rs')
+DB_EXTRA = os.path.join(HERE, 'db_extra.xml')
+
+MIRRORING = {'H': 'Horizontal', 'V': 'Vertical', '4': 'FourScreen'}
+REGION = {'1': 'Pal', '3': 'Dendy'}
+
+
+def parse(path):
+    parser = ET.XMLParser(target=ET.TreeBuilder(insert_comments=True))
+    return ET.parse(path, parser).getroot()
+
+
+def shift(size):
+    """NES 2.0 shift count for a RAM size, where a size of 64 << n is encoded as n."""
+    size = int(size or 0)
+    if size == 0:
+        return 0
+    n = size.bit_length() - 7
+    if 64 << n != size:
+        raise ValueError(f'unsupported ram size {size}')
+    return n
+
+
+def title(game):
+    for node in game:
+        if node.tag is ET.Comment:
+            name = os.path.splitext(re.split(r'[\\/]', node.text.strip())[-1])[0]
+            return re.sub(r'\s*[(\[].*$', '', name)
+    return ''
+
+
+def mmc1_revision(node):
+    """MMC1 revision of the first MMC1 <chip> under `node`, if any."""
+    for chip in node.iter('chip'):
+        chip_type = chip.get('type', '')
+        if chip_type == 'MMC1A':
+            return 'A'
+        if chip_type.startswith(('MMC1B', 'MMC1C')):
+            return 'BC'
+    return None
+
+
+def nestopia(path):
+    """Publisher, year and MMC1 revision keyed by (crc32, sha1)."""
+    extra = {}
+    if path is None:
+        return extra
+    for game in parse(path).iter('game'):
+        publisher = game.get('publisher', '')
+        date = game.get('date', '')
+        year = int(date[:4]) if date[:4].isdigit() else 0
+        for cart in game.iter('cartridge'):
+            key = (int(cart.get('crc'), 16), cart.get('sha1', '').upper())
+            extra[key] = (publisher, year, mmc1_revision(cart))
+    return extra
+
+
+def rust_str(s):
+    return '"' + s.replace('\\', '\\\\').replace('"', '\\"') + '"'
+
+
+def entry(game, extra):
+    rom = game.find('rom')
+    pcb = game.find('pcb')
+    console = game.find('console')
+    crc32 = int(rom.get('crc32'), 16)
+    sha1 = rom.get('sha1').upper()
+    publisher, year, mmc1 = extra.get((crc32, sha1), ('', 0, None))
+    # db_extra.xml entries carry these themselves
+    publisher = game.get('publisher', publisher)
+    year = int(game.get('year', year))
+    mmc1 = mmc1_revision(game) or mmc1
+
+    def size(tag):
+        node = game.find(tag)
+        return None if node is None else node.get('size')
+
+    fields = []
+    mapper_num = int(pcb.get('mapper'))
+    submapper_num = int(pcb.get('submapper', '0'))
+    if mapper_num:
+        fields.append(f'mapper_num: {mapper_num}')
+    if submapper_num:
+        fields.append(f'submapper_num: {submapper_num}')
+    mirroring = MIRRORING.get(pcb.get('mirroring'))
+    if mirroring:
+        fields.append(f'mirroring: Some(Mirroring::{mirroring})')
+    region = REGION.get(console.get('region', '0') if console is not None else '0')
+    if region:
+        fields.append(f'region: NesRegion::{region}')
+    prg_ram_shift = shift(size('prgram')) | shift(size('prgnvram')) << 4
+    chr_ram_shift = shift(size('chrram')) | shift(size('chrnvram')) << 4
+    if prg_ram_shift:
+        fields.append(f'prg_ram_shift: 0x{prg_ram_shift:02X}')
+    if chr_ram_shift:
+        fields.append(f'chr_ram_shift: 0x{chr_ram_shift:02X}')
+    if pcb.get('battery') == '1':
+        fields.append('battery: true')
+    expansion = game.find('expansion')
+    if expansion is not None and int(expansion.get('type', '0')) > 1:
+        fields.append(f'expansion_device: 0x{int(expansion.get("type")):02X}')
+    if mmc1:
+        fields.append(f'mmc1_revision: Some(Mmc1Revision::{mmc1})')
+    new = (f'GameInfo::new(0x{crc32 >> 16:04X}_{crc32 & 0xFFFF:04X}, "{sha1}", {rust_str(title(game))}, '
+           f'{rust_str(publisher)}, {year})')
+    return crc32, 'GameInfo { ' + ''.join(f'{field}, ' for field in fields) + f'..{new} }}'
+
+
+def main():
+    if len(sys.argv) > 3:
+        sys.exit(__doc__)
+    extra = nestopia(sys.argv[2] if len(sys.argv) == 3 else None)
+
+    games = {}
+    if len(sys.argv) > 1:
+        for game in parse(sys.argv[1]).iter('game'):
+            if game.find('rom') is None or game.find('pcb') is None:
+                continue
+            crc32, rust = entry(game, extra)
+            # Keep the first of any CRC-32 collisions, lookups confirm the SHA-1 anyway
+            games.setdefault(crc32, rust)
+    for game in parse(DB_EXTRA).iter('game'):
+        crc32, rust = entry(game, extra)
+        games[crc32] = rust
+
+    table = (f'static GAMES: [GameInfo; {len(games)}] = [\n'
+             + ''.join(f'    {games[crc32]},\n' for crc32 in sorted(games)) + '];\n')
+    with open(DB_RS) as f:
+        src = f.read()
+    src = re.sub(r'(// BEGIN GAMES\n).*?(// END GAMES\n)', lambda m: m.group(1) + table + m.group(2),
+                 src, flags=re.S)
+    with open(DB_RS, 'w') as f:
+        f.write(src)
+    subprocess.run(['rustfmt', '--edition', '2021', DB_RS], check=False)
+
+
+if __name__ == '__main__':
+    main()